@@ -0,0 +1,161 @@
+//! Copy secret fields to the system clipboard and clear them again after a
+//! timeout, so passwords don't linger after a paste.
+
+use passman_types::CredentialSecret;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Seconds before a copied secret is cleared, unless the caller overrides it.
+pub const DEFAULT_CLEAR_AFTER_SECS: u64 = 30;
+
+/// Minimal clipboard interface, so the clear-after logic can be tested
+/// without a display server.
+pub trait ClipboardBackend: Send + Sync + 'static {
+    fn get_text(&self) -> Option<String>;
+    fn set_text(&self, text: &str) -> Result<(), String>;
+    fn clear(&self) -> Result<(), String>;
+}
+
+/// The OS clipboard via `arboard`.
+pub struct SystemClipboard(Mutex<arboard::Clipboard>);
+
+impl SystemClipboard {
+    pub fn new() -> Result<Self, String> {
+        arboard::Clipboard::new()
+            .map(|c| Self(Mutex::new(c)))
+            .map_err(|e| format!("clipboard unavailable: {e}"))
+    }
+}
+
+impl ClipboardBackend for SystemClipboard {
+    fn get_text(&self) -> Option<String> {
+        self.0.lock().unwrap().get_text().ok()
+    }
+
+    fn set_text(&self, text: &str) -> Result<(), String> {
+        self.0
+            .lock()
+            .unwrap()
+            .set_text(text)
+            .map_err(|e| format!("failed to copy to clipboard: {e}"))
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        self.0
+            .lock()
+            .unwrap()
+            .clear()
+            .map_err(|e| format!("failed to clear clipboard: {e}"))
+    }
+}
+
+/// Extract one field of a secret as text, e.g. `password` or `token`.
+/// Custom credentials are looked up by their field name.
+pub fn secret_field(secret: &CredentialSecret, field: &str) -> Option<String> {
+    if field == "type" {
+        return None;
+    }
+    let value = serde_json::to_value(secret).ok()?;
+    let found = value
+        .get("fields")
+        .and_then(|f| f.get(field))
+        .or_else(|| value.get(field))?;
+    match found {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Copy `value` and clear the clipboard after `clear_after`, but only if it
+/// still holds `value` — anything the user copied since is left alone.
+pub fn copy_with_clear<C: ClipboardBackend>(
+    clipboard: Arc<C>,
+    value: String,
+    clear_after: Duration,
+) -> Result<tokio::task::JoinHandle<()>, String> {
+    clipboard.set_text(&value)?;
+    Ok(tokio::spawn(async move {
+        tokio::time::sleep(clear_after).await;
+        if clipboard.get_text().as_deref() == Some(value.as_str()) {
+            let _ = clipboard.clear();
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct FakeClipboard(Mutex<Option<String>>);
+
+    impl ClipboardBackend for FakeClipboard {
+        fn get_text(&self) -> Option<String> {
+            self.0.lock().unwrap().clone()
+        }
+
+        fn set_text(&self, text: &str) -> Result<(), String> {
+            *self.0.lock().unwrap() = Some(text.to_string());
+            Ok(())
+        }
+
+        fn clear(&self) -> Result<(), String> {
+            *self.0.lock().unwrap() = None;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clears_after_timeout() {
+        let clipboard = Arc::new(FakeClipboard::default());
+        let task = copy_with_clear(
+            clipboard.clone(),
+            "hunter2".into(),
+            Duration::from_millis(20),
+        )
+        .unwrap();
+        assert_eq!(clipboard.get_text().as_deref(), Some("hunter2"));
+
+        task.await.unwrap();
+        assert_eq!(clipboard.get_text(), None);
+    }
+
+    #[tokio::test]
+    async fn test_leaves_newer_contents() {
+        let clipboard = Arc::new(FakeClipboard::default());
+        let task = copy_with_clear(
+            clipboard.clone(),
+            "hunter2".into(),
+            Duration::from_millis(20),
+        )
+        .unwrap();
+        clipboard.set_text("something else").unwrap();
+
+        task.await.unwrap();
+        assert_eq!(clipboard.get_text().as_deref(), Some("something else"));
+    }
+
+    #[test]
+    fn test_secret_field() {
+        let secret = CredentialSecret::SmtpAccount {
+            host: "smtp.example.com".into(),
+            port: 587,
+            username: "mailer".into(),
+            password: "smtp-pw".into(),
+            encryption: passman_types::SmtpEncryption::StartTls,
+        };
+        assert_eq!(
+            secret_field(&secret, "password").as_deref(),
+            Some("smtp-pw")
+        );
+        assert_eq!(secret_field(&secret, "port").as_deref(), Some("587"));
+        assert_eq!(secret_field(&secret, "type"), None);
+
+        let custom = CredentialSecret::Custom {
+            fields: [("api_key".to_string(), "k-123".to_string())].into(),
+        };
+        assert_eq!(secret_field(&custom, "api_key").as_deref(), Some("k-123"));
+        assert_eq!(secret_field(&custom, "missing"), None);
+    }
+}
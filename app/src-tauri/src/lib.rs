@@ -1,9 +1,16 @@
+mod clipboard;
+
 use passman_types::{
-    AuditEntry, CredentialKind, CredentialMeta, CredentialSecret, Environment, PolicyRule,
+    AuditAction, AuditEntry, AuditSummary, Category, CredentialInfoResponse, CredentialKind,
+    CredentialMeta, CredentialSecret, DbDriver, DuressAction, Environment, PendingApproval,
+    PolicyRule, PolicyTemplate, TimeWindow, VaultProfile, VaultSecurityInfo, VaultStats,
+    WebhookRule,
 };
-use passman_vault::Vault;
+use passman_vault::strength::{self, StrengthReport};
 use passman_vault::watcher;
+use passman_vault::{AuditFilter, ProfileRegistry, Vault};
 use serde::{Deserialize, Serialize};
+use tauri::Emitter;
 use uuid::Uuid;
 
 // ── Serializable error for Tauri commands ───────────────────────
@@ -31,14 +38,79 @@ async fn vault_exists(vault: tauri::State<'_, Vault>) -> CmdResult<bool> {
 }
 
 #[tauri::command]
-async fn vault_create(vault: tauri::State<'_, Vault>, password: String) -> CmdResult<()> {
-    vault.create(&password).await?;
+async fn vault_create(
+    vault: tauri::State<'_, Vault>,
+    password: String,
+    allow_weak: Option<bool>,
+) -> CmdResult<()> {
+    vault.create(&password, allow_weak.unwrap_or(false)).await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn vault_unlock(
+    vault: tauri::State<'_, Vault>,
+    password: String,
+) -> CmdResult<UnlockResponse> {
+    let credential_count = vault.unlock(&password).await?;
+    Ok(UnlockResponse {
+        credential_count,
+        recovered_from_backup: vault.take_recovery_warning().map(|w| w.to_string()),
+    })
+}
+
+#[derive(Serialize)]
+struct UnlockResponse {
+    credential_count: usize,
+    /// Set when the vault file was corrupt and a backup was restored.
+    recovered_from_backup: Option<String>,
+}
+
+/// Event carrying a `ReencryptProgress` while every secret is re-encrypted.
+const REENCRYPT_PROGRESS_EVENT: &str = "vault-reencrypt-progress";
+
+#[tauri::command]
+async fn vault_change_password(
+    app: tauri::AppHandle,
+    vault: tauri::State<'_, Vault>,
+    current_password: String,
+    new_password: String,
+    allow_weak: Option<bool>,
+) -> CmdResult<()> {
+    vault
+        .change_password_with_progress(
+            &current_password,
+            &new_password,
+            allow_weak.unwrap_or(false),
+            |progress| {
+                let _ = app.emit(REENCRYPT_PROGRESS_EVENT, progress);
+            },
+        )
+        .await?;
+    Ok(())
+}
+
+/// Set a second password that opens an empty decoy (or shreds the vault).
+#[tauri::command]
+async fn vault_set_duress(
+    vault: tauri::State<'_, Vault>,
+    password: String,
+    action: DuressAction,
+) -> CmdResult<()> {
+    vault.set_duress_password(&password, action).await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn vault_clear_duress(vault: tauri::State<'_, Vault>) -> CmdResult<()> {
+    vault.clear_duress_password().await?;
     Ok(())
 }
 
+/// Strength estimate for a master or credential password, shown while typing.
 #[tauri::command]
-async fn vault_unlock(vault: tauri::State<'_, Vault>, password: String) -> CmdResult<usize> {
-    Ok(vault.unlock(&password).await?)
+async fn password_strength(password: String) -> CmdResult<StrengthReport> {
+    Ok(strength::estimate(&password))
 }
 
 #[tauri::command]
@@ -61,10 +133,17 @@ async fn vault_status(vault: tauri::State<'_, Vault>) -> CmdResult<VaultStatusRe
         vec![]
     };
 
+    let security = if unlocked {
+        vault.security_info().await.ok()
+    } else {
+        None
+    };
+
     Ok(VaultStatusResponse {
         unlocked,
         credential_count,
         environments,
+        security,
     })
 }
 
@@ -73,6 +152,91 @@ struct VaultStatusResponse {
     unlocked: bool,
     credential_count: usize,
     environments: Vec<String>,
+    /// Format version, KDF settings and counts by kind (unlocked only).
+    #[serde(flatten)]
+    security: Option<VaultSecurityInfo>,
+}
+
+#[tauri::command]
+async fn vault_stats(vault: tauri::State<'_, Vault>) -> CmdResult<VaultStats> {
+    Ok(vault.stats().await?)
+}
+
+/// Which files the vault handle has open, for display.
+#[tauri::command]
+async fn vault_info(vault: tauri::State<'_, Vault>) -> CmdResult<VaultInfoResponse> {
+    Ok(VaultInfoResponse::read(&vault).await)
+}
+
+#[derive(Debug, Serialize)]
+struct VaultInfoResponse {
+    vault_path: String,
+    audit_path: String,
+    /// `None` for a handle opened on bare paths rather than a profile.
+    profile: Option<String>,
+    exists: bool,
+}
+
+impl VaultInfoResponse {
+    async fn read(vault: &Vault) -> Self {
+        Self {
+            vault_path: vault.vault_path().await.display().to_string(),
+            audit_path: vault.audit_path().await.display().to_string(),
+            profile: vault.profile().await,
+            exists: vault.exists().await,
+        }
+    }
+}
+
+// ── Profiles ────────────────────────────────────────────────────
+
+/// Watcher for the active profile's vault file, restarted on switch.
+#[derive(Clone, Default)]
+struct VaultWatcher(std::sync::Arc<tokio::sync::Mutex<Option<watcher::WatchHandle>>>);
+
+impl VaultWatcher {
+    /// Stop any running watcher and start one for `vault`'s current file.
+    async fn restart(&self, vault: &Vault) {
+        let mut slot = self.0.lock().await;
+        if let Some(handle) = slot.take() {
+            handle.stop().await;
+        }
+        let vault_path = vault.vault_path().await;
+        *slot = Some(watcher::watch_vault(vault.clone(), vault_path));
+    }
+}
+
+#[derive(Serialize)]
+struct ProfileListResponse {
+    active: String,
+    profiles: Vec<VaultProfile>,
+}
+
+#[tauri::command]
+async fn profile_list() -> CmdResult<ProfileListResponse> {
+    let registry = ProfileRegistry::open_default()?;
+    Ok(ProfileListResponse {
+        active: registry.active()?.name,
+        profiles: registry.list(),
+    })
+}
+
+#[tauri::command]
+async fn profile_create(name: String) -> CmdResult<VaultProfile> {
+    Ok(ProfileRegistry::open_default()?.create(&name)?)
+}
+
+/// Lock the current vault and open another profile's (still locked).
+#[tauri::command]
+async fn profile_switch(
+    vault: tauri::State<'_, Vault>,
+    watcher: tauri::State<'_, VaultWatcher>,
+    name: String,
+) -> CmdResult<VaultProfile> {
+    let profile = ProfileRegistry::open_default()?.set_active(&name)?;
+    vault.switch_profile(&profile).await;
+    watcher.restart(&vault).await;
+    Ok(profile)
 }
 
 // ── Credential CRUD ─────────────────────────────────────────────
@@ -83,10 +247,11 @@ async fn credential_list(
     kind: Option<String>,
     environment: Option<String>,
     tag: Option<String>,
+    category: Option<String>,
 ) -> CmdResult<Vec<CredentialMeta>> {
     let kind = kind.and_then(|k| serde_json::from_value(serde_json::Value::String(k)).ok());
-    let env = environment.and_then(|e| parse_environment(&e));
-    Ok(vault.list_credentials(kind, env, tag).await?)
+    let env = environment.and_then(|e| Environment::parse(&e));
+    Ok(vault.list_credentials(kind, env, tag, category).await?)
 }
 
 #[tauri::command]
@@ -101,18 +266,95 @@ async fn credential_search(
 async fn credential_info(
     vault: tauri::State<'_, Vault>,
     id: String,
-) -> CmdResult<CredentialMeta> {
+) -> CmdResult<CredentialInfoResponse> {
     let uuid = parse_uuid(&id)?;
-    Ok(vault.get_credential_meta(uuid).await?)
+    let mut info: CredentialInfoResponse = vault.get_credential_meta(uuid).await?.into();
+    info.linked = vault.get_linked(uuid).await?;
+    Ok(info)
 }
 
 #[tauri::command]
 async fn credential_get_secret(
     vault: tauri::State<'_, Vault>,
     id: String,
-) -> CmdResult<CredentialSecret> {
+) -> CmdResult<zeroize::Zeroizing<CredentialSecret>> {
+    let uuid = parse_uuid(&id)?;
+    Ok(vault
+        .reveal_credential_secret(uuid, "credential_get_secret")
+        .await?)
+}
+
+/// Copy one field of a secret to the clipboard and clear it after a timeout.
+/// The value never reaches the frontend.
+#[tauri::command]
+async fn clipboard_copy_secret(
+    vault: tauri::State<'_, Vault>,
+    id: String,
+    field: String,
+    clear_after_secs: Option<u64>,
+) -> CmdResult<()> {
+    let uuid = parse_uuid(&id)?;
+    let secret = vault.get_credential_secret(uuid).await?;
+    let value = clipboard::secret_field(&secret, &field).ok_or_else(|| CommandError {
+        message: format!("credential has no field '{field}'"),
+    })?;
+
+    let backend = clipboard::SystemClipboard::new().map_err(|message| CommandError { message })?;
+    let clear_after = std::time::Duration::from_secs(
+        clear_after_secs.unwrap_or(clipboard::DEFAULT_CLEAR_AFTER_SECS),
+    );
+    clipboard::copy_with_clear(std::sync::Arc::new(backend), value, clear_after)
+        .map_err(|message| CommandError { message })?;
+
+    let meta = vault.get_credential_meta(uuid).await.ok();
+    let _ = vault
+        .log_audit(&AuditEntry {
+            timestamp: chrono::Utc::now(),
+            credential_id: Some(uuid),
+            credential_name: meta.map(|m| m.name),
+            action: AuditAction::ClipboardCopy,
+            tool: "clipboard_copy_secret".to_string(),
+            success: true,
+            details: Some(format!("field: {field}")),
+        })
+        .await;
+    Ok(())
+}
+
+/// PNG QR code for a credential, for scanning into a phone: an `otpauth://`
+/// URI for a TOTP seed (see `passman_vault::qr`), otherwise the text of
+/// `field`, defaulting to the main secret. Anyone who can see the screen can
+/// read it, so the GUI must ask first and pass `confirmed`. Logged as a
+/// secret reveal; the encoded text never is.
+#[tauri::command]
+async fn credential_qr(
+    vault: tauri::State<'_, Vault>,
+    id: String,
+    field: Option<String>,
+    confirmed: bool,
+) -> CmdResult<Vec<u8>> {
+    if !confirmed {
+        return Err(CommandError {
+            message: "a QR code shows the secret; confirm before exporting it".to_string(),
+        });
+    }
     let uuid = parse_uuid(&id)?;
-    Ok(vault.get_credential_secret(uuid).await?)
+    let meta = vault.get_credential_meta(uuid).await?;
+    let secret = vault
+        .reveal_credential_secret(uuid, "credential_qr")
+        .await?;
+
+    let text = match field {
+        Some(field) => clipboard::secret_field(&secret, &field).ok_or_else(|| CommandError {
+            message: format!("credential has no field '{field}'"),
+        })?,
+        None => passman_vault::qr::totp_uri(&meta.name, &secret)
+            .or_else(|| secret.secret_strings().into_iter().next())
+            .ok_or_else(|| CommandError {
+                message: "credential has no secret to encode".to_string(),
+            })?,
+    };
+    Ok(passman_vault::qr::encode(&text)?)
 }
 
 #[derive(Deserialize)]
@@ -124,6 +366,9 @@ pub struct StoreCredentialInput {
     pub tags: Vec<String>,
     pub notes: Option<String>,
     pub secret: serde_json::Value,
+    /// Register the environment if it's a new custom one (strict mode).
+    #[serde(default)]
+    pub allow_new_env: bool,
 }
 
 #[tauri::command]
@@ -135,13 +380,14 @@ async fn credential_store(
         .map_err(|e| CommandError {
             message: format!("invalid kind: {e}"),
         })?;
-    let env = parse_environment(&input.environment).ok_or_else(|| CommandError {
+    let env = Environment::parse(&input.environment).ok_or_else(|| CommandError {
         message: format!("invalid environment: {}", input.environment),
     })?;
-    let secret: CredentialSecret =
-        serde_json::from_value(input.secret).map_err(|e| CommandError {
-            message: format!("invalid secret: {e}"),
-        })?;
+    let secret = parse_secret(input.secret)?;
+
+    if let (true, Environment::Custom(name)) = (input.allow_new_env, &env) {
+        vault.register_environment(name).await?;
+    }
 
     let id = vault
         .store_credential(input.name, kind, env, input.tags, input.notes, &secret)
@@ -166,17 +412,14 @@ async fn credential_update(
     input: UpdateCredentialInput,
 ) -> CmdResult<String> {
     let uuid = parse_uuid(&input.id)?;
-    let env = parse_environment(&input.environment).ok_or_else(|| CommandError {
+    let env = Environment::parse(&input.environment).ok_or_else(|| CommandError {
         message: format!("invalid environment: {}", input.environment),
     })?;
-    let secret: CredentialSecret =
-        serde_json::from_value(input.secret).map_err(|e| CommandError {
-            message: format!("invalid secret: {e}"),
-        })?;
+    let secret = parse_secret(input.secret)?;
 
-    let notes_opt = input.notes.map(|n| {
-        if n.is_empty() { None } else { Some(n) }
-    });
+    let notes_opt = input
+        .notes
+        .map(|n| if n.is_empty() { None } else { Some(n) });
 
     vault
         .update_credential(
@@ -192,9 +435,71 @@ async fn credential_update(
 }
 
 #[tauri::command]
-async fn credential_delete(vault: tauri::State<'_, Vault>, id: String) -> CmdResult<bool> {
+async fn credential_clone(
+    vault: tauri::State<'_, Vault>,
+    id: String,
+    new_name: String,
+    new_environment: Option<String>,
+) -> CmdResult<String> {
+    let uuid = parse_uuid(&id)?;
+    let env = match new_environment {
+        Some(e) => Some(Environment::parse(&e).ok_or_else(|| CommandError {
+            message: format!("invalid environment: {e}"),
+        })?),
+        None => None,
+    };
+    let new_id = vault.clone_credential(uuid, new_name, env).await?;
+    Ok(new_id.to_string())
+}
+
+/// Delete a credential. Fails naming the credentials that link to it unless
+/// `force` is set; the GUI lists them and asks before forcing.
+#[tauri::command]
+async fn credential_delete(
+    vault: tauri::State<'_, Vault>,
+    id: String,
+    force: Option<bool>,
+) -> CmdResult<bool> {
     let uuid = parse_uuid(&id)?;
-    Ok(vault.delete_credential(uuid).await?)
+    Ok(vault
+        .delete_credential(uuid, force.unwrap_or(false))
+        .await?)
+}
+
+#[tauri::command]
+async fn credential_import_ssh_config(
+    vault: tauri::State<'_, Vault>,
+    config_path: Option<String>,
+    keys_dir: Option<String>,
+) -> CmdResult<Vec<String>> {
+    let ssh_dir = || {
+        dirs_next()
+            .map(|home| std::path::PathBuf::from(home).join(".ssh"))
+            .ok_or_else(|| CommandError {
+                message: "Could not determine home directory".to_string(),
+            })
+    };
+    let keys_dir = match keys_dir {
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => ssh_dir()?,
+    };
+    let config_path = match config_path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => ssh_dir()?.join("config"),
+    };
+    let ids = vault.import_ssh_config(&config_path, &keys_dir).await?;
+    Ok(ids.iter().map(Uuid::to_string).collect())
+}
+
+// ── Tags ────────────────────────────────────────────────────────
+
+#[tauri::command]
+async fn tags_rename(
+    vault: tauri::State<'_, Vault>,
+    old_tag: String,
+    new_tag: String,
+) -> CmdResult<usize> {
+    Ok(vault.rename_tag(&old_tag, &new_tag).await?)
 }
 
 // ── Audit ───────────────────────────────────────────────────────
@@ -205,10 +510,30 @@ async fn audit_log(
     credential_id: Option<String>,
     limit: Option<usize>,
 ) -> CmdResult<Vec<AuditEntry>> {
-    let cred_id = credential_id
-        .map(|id| parse_uuid(&id))
-        .transpose()?;
-    Ok(vault.read_audit(cred_id, limit, None).await?)
+    let cred_id = credential_id.map(|id| parse_uuid(&id)).transpose()?;
+    let filter = AuditFilter {
+        credential_id: cred_id,
+        ..Default::default()
+    };
+    Ok(vault.read_audit(&filter, limit).await?)
+}
+
+#[tauri::command]
+async fn audit_summary(
+    vault: tauri::State<'_, Vault>,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+) -> CmdResult<AuditSummary> {
+    Ok(vault.audit_summary(since).await?)
+}
+
+/// Drop audit entries older than `before`, or the whole log when omitted.
+/// Returns how many entries were removed.
+#[tauri::command]
+async fn audit_clear(
+    vault: tauri::State<'_, Vault>,
+    before: Option<chrono::DateTime<chrono::Utc>>,
+) -> CmdResult<usize> {
+    Ok(vault.clear_audit(before).await?)
 }
 
 // ── Policy ──────────────────────────────────────────────────────
@@ -225,13 +550,31 @@ async fn policy_get(
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SavePolicyInput {
+    /// Ignored for policy templates.
+    #[serde(default)]
     pub credential_id: String,
     pub allowed_tools: Vec<String>,
     pub http_url_patterns: Vec<String>,
+    #[serde(default)]
+    pub http_default_deny: bool,
     pub ssh_command_patterns: Vec<String>,
+    #[serde(default)]
+    pub ssh_default_deny: bool,
     pub sql_allow_write: bool,
+    #[serde(default)]
+    pub forbidden_sql_patterns: Vec<String>,
     pub smtp_allowed_recipients: Vec<String>,
+    #[serde(default)]
+    pub mongo_allow_write: bool,
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    #[serde(default)]
+    pub time_windows: Vec<TimeWindow>,
+    #[serde(default)]
+    pub require_approval: bool,
     pub rate_limit: Option<RateLimitInput>,
+    #[serde(default)]
+    pub per_tool_limits: std::collections::HashMap<String, RateLimitInput>,
 }
 
 #[derive(Deserialize)]
@@ -241,37 +584,150 @@ pub struct RateLimitInput {
     pub window_secs: u64,
 }
 
+impl SavePolicyInput {
+    fn into_rule(self, credential_id: Uuid) -> PolicyRule {
+        PolicyRule {
+            credential_id,
+            allowed_tools: self.allowed_tools,
+            http_url_patterns: self.http_url_patterns,
+            http_default_deny: self.http_default_deny,
+            ssh_command_patterns: self.ssh_command_patterns,
+            ssh_default_deny: self.ssh_default_deny,
+            sql_allow_write: self.sql_allow_write,
+            forbidden_sql_patterns: self.forbidden_sql_patterns,
+            smtp_allowed_recipients: self.smtp_allowed_recipients,
+            mongo_allow_write: self.mongo_allow_write,
+            allowed_hosts: self.allowed_hosts,
+            time_windows: self.time_windows,
+            require_approval: self.require_approval,
+            rate_limit: self.rate_limit.map(|r| passman_types::RateLimit {
+                max_requests: r.max_requests,
+                window_secs: r.window_secs,
+            }),
+            per_tool_limits: self
+                .per_tool_limits
+                .into_iter()
+                .map(|(tool, r)| {
+                    (
+                        tool,
+                        passman_types::RateLimit {
+                            max_requests: r.max_requests,
+                            window_secs: r.window_secs,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
 #[tauri::command]
-async fn policy_save(
-    vault: tauri::State<'_, Vault>,
-    input: SavePolicyInput,
-) -> CmdResult<()> {
+async fn policy_save(vault: tauri::State<'_, Vault>, input: SavePolicyInput) -> CmdResult<()> {
     let uuid = parse_uuid(&input.credential_id)?;
-    let policy = PolicyRule {
-        credential_id: uuid,
-        allowed_tools: input.allowed_tools,
-        http_url_patterns: input.http_url_patterns,
-        ssh_command_patterns: input.ssh_command_patterns,
-        sql_allow_write: input.sql_allow_write,
-        smtp_allowed_recipients: input.smtp_allowed_recipients,
-        rate_limit: input.rate_limit.map(|r| passman_types::RateLimit {
-            max_requests: r.max_requests,
-            window_secs: r.window_secs,
-        }),
-    };
-    vault.save_policy(policy).await?;
+    vault.save_policy(input.into_rule(uuid)).await?;
     Ok(())
 }
 
 #[tauri::command]
-async fn policy_delete(
-    vault: tauri::State<'_, Vault>,
-    credential_id: String,
-) -> CmdResult<bool> {
+async fn policy_delete(vault: tauri::State<'_, Vault>, credential_id: String) -> CmdResult<bool> {
     let uuid = parse_uuid(&credential_id)?;
     Ok(vault.delete_policy(uuid).await?)
 }
 
+// ── Policy templates ────────────────────────────────────────────
+
+#[tauri::command]
+async fn policy_templates_list(vault: tauri::State<'_, Vault>) -> CmdResult<Vec<PolicyTemplate>> {
+    Ok(vault.list_policy_templates().await?)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavePolicyTemplateInput {
+    pub name: String,
+    pub environment: Option<String>,
+    pub tag: Option<String>,
+    pub rule: SavePolicyInput,
+}
+
+#[tauri::command]
+async fn policy_template_save(
+    vault: tauri::State<'_, Vault>,
+    input: SavePolicyTemplateInput,
+) -> CmdResult<()> {
+    let template = PolicyTemplate {
+        name: input.name,
+        environment: input.environment.and_then(|e| Environment::parse(&e)),
+        tag: input.tag,
+        rule: input.rule.into_rule(Uuid::nil()),
+    };
+    vault.save_policy_template(template).await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn policy_template_delete(vault: tauri::State<'_, Vault>, name: String) -> CmdResult<bool> {
+    Ok(vault.delete_policy_template(&name).await?)
+}
+
+// ── Webhooks ────────────────────────────────────────────────────
+
+#[tauri::command]
+async fn webhooks_list(vault: tauri::State<'_, Vault>) -> CmdResult<Vec<WebhookRule>> {
+    Ok(vault.list_webhooks().await?)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveWebhookInput {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub actions: Vec<AuditAction>,
+    #[serde(default)]
+    pub environments: Vec<String>,
+}
+
+#[tauri::command]
+async fn webhook_save(vault: tauri::State<'_, Vault>, input: SaveWebhookInput) -> CmdResult<()> {
+    let rule = WebhookRule {
+        name: input.name,
+        url: input.url,
+        actions: input.actions,
+        environments: input
+            .environments
+            .iter()
+            .filter_map(|e| Environment::parse(e))
+            .collect(),
+    };
+    vault.save_webhook(rule).await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn webhook_delete(vault: tauri::State<'_, Vault>, name: String) -> CmdResult<bool> {
+    Ok(vault.delete_webhook(&name).await?)
+}
+
+// ── Approvals ───────────────────────────────────────────────────
+
+#[tauri::command]
+async fn approvals_list(vault: tauri::State<'_, Vault>) -> CmdResult<Vec<PendingApproval>> {
+    Ok(vault.list_approvals().await?)
+}
+
+#[tauri::command]
+async fn approvals_approve(vault: tauri::State<'_, Vault>, id: String) -> CmdResult<bool> {
+    let uuid = parse_uuid(&id)?;
+    Ok(vault.approve(uuid).await?)
+}
+
+#[tauri::command]
+async fn approvals_deny(vault: tauri::State<'_, Vault>, id: String) -> CmdResult<bool> {
+    let uuid = parse_uuid(&id)?;
+    Ok(vault.deny(uuid).await?)
+}
+
 // ── MCP Server management ──────────────────────────────────────
 
 #[derive(Serialize)]
@@ -373,10 +829,11 @@ async fn install_mcp_server() -> CmdResult<String> {
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        std::fs::set_permissions(&install_path, std::fs::Permissions::from_mode(0o755))
-            .map_err(|e| CommandError {
+        std::fs::set_permissions(&install_path, std::fs::Permissions::from_mode(0o755)).map_err(
+            |e| CommandError {
                 message: format!("Failed to set permissions: {e}"),
-            })?;
+            },
+        )?;
     }
 
     Ok(install_path)
@@ -411,36 +868,126 @@ fn parse_uuid(s: &str) -> Result<Uuid, CommandError> {
     })
 }
 
-fn parse_environment(s: &str) -> Option<Environment> {
-    match s.to_lowercase().as_str() {
-        "local" => Some(Environment::Local),
-        "development" => Some(Environment::Development),
-        "staging" => Some(Environment::Staging),
-        "production" => Some(Environment::Production),
-        other => Some(Environment::Custom(other.to_string())),
+/// Deserialize a secret from the editor. A database connection without a
+/// port gets its driver's default.
+fn parse_secret(mut value: serde_json::Value) -> Result<CredentialSecret, CommandError> {
+    if let Some(obj) = value.as_object_mut() {
+        let driver = obj
+            .get("type")
+            .filter(|t| *t == "database_connection")
+            .and_then(|_| obj.get("driver"))
+            .and_then(|d| serde_json::from_value::<DbDriver>(d.clone()).ok());
+        if let Some(driver) = driver {
+            if matches!(obj.get("port"), None | Some(serde_json::Value::Null)) {
+                obj.insert("port".into(), driver.default_port().into());
+            }
+        }
     }
+    serde_json::from_value(value).map_err(|e| CommandError {
+        message: format!("invalid secret: {e}"),
+    })
+}
+
+// ── Categories ──────────────────────────────────────────────────
+
+#[tauri::command]
+async fn category_list(vault: tauri::State<'_, Vault>) -> CmdResult<Vec<Category>> {
+    Ok(vault.list_categories().await?)
+}
+
+#[tauri::command]
+async fn category_add(
+    vault: tauri::State<'_, Vault>,
+    name: String,
+    description: Option<String>,
+) -> CmdResult<()> {
+    vault.add_category(&name, description).await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn category_delete(vault: tauri::State<'_, Vault>, name: String) -> CmdResult<bool> {
+    Ok(vault.delete_category(&name).await?)
+}
+
+#[tauri::command]
+async fn credential_set_category(
+    vault: tauri::State<'_, Vault>,
+    id: String,
+    category: Option<String>,
+) -> CmdResult<()> {
+    let uuid = parse_uuid(&id)?;
+    vault.set_credential_category(uuid, category).await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn credential_set_rotation_interval(
+    vault: tauri::State<'_, Vault>,
+    id: String,
+    days: Option<u32>,
+) -> CmdResult<()> {
+    let uuid = parse_uuid(&id)?;
+    vault.set_rotation_interval(uuid, days).await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn rotation_due(vault: tauri::State<'_, Vault>) -> CmdResult<Vec<CredentialMeta>> {
+    Ok(vault.rotation_due().await?)
+}
+
+// ── Environments ────────────────────────────────────────────────
+
+#[tauri::command]
+async fn environments_known(vault: tauri::State<'_, Vault>) -> CmdResult<Vec<String>> {
+    let envs = vault.known_environments().await?;
+    Ok(envs.iter().map(|e| e.to_string()).collect())
+}
+
+/// Built-in environment that `name` looks like a typo of, for a warning in the editor.
+#[tauri::command]
+async fn environment_suggest(name: String) -> CmdResult<Option<String>> {
+    Ok(Environment::suggest_builtin(&name).map(|e| e.to_string()))
+}
+
+#[tauri::command]
+async fn environments_set_strict(vault: tauri::State<'_, Vault>, strict: bool) -> CmdResult<()> {
+    vault.set_strict_environments(strict).await?;
+    Ok(())
+}
+
+#[tauri::command]
+async fn vault_set_unique_names(vault: tauri::State<'_, Vault>, unique: bool) -> CmdResult<()> {
+    vault.set_unique_names(unique).await?;
+    Ok(())
 }
 
 // ── App entry ───────────────────────────────────────────────────
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let vault = Vault::with_defaults();
+    // Open the profile that was active last time
+    let vault = match ProfileRegistry::open_default().and_then(|r| r.active()) {
+        Ok(profile) => Vault::from_profile(&profile),
+        Err(e) => {
+            eprintln!("Failed to read profiles, using the default vault: {e}");
+            Vault::with_defaults()
+        }
+    };
 
     // Start vault file watcher so GUI stays in sync when MCP server modifies the vault
     let vault_for_watch = vault.clone();
-    let vault_path = passman_vault::storage::default_vault_path();
+    let vault_watcher = VaultWatcher::default();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .manage(vault)
+        .manage(vault_watcher.clone())
         .setup(move |_app| {
+            // The watcher lives in managed state for the lifetime of the app
             tauri::async_runtime::spawn(async move {
-                let _handle = watcher::watch_vault(vault_for_watch, vault_path);
-                // Keep the watcher alive for the lifetime of the app
-                loop {
-                    tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
-                }
+                vault_watcher.restart(&vault_for_watch).await;
             });
             Ok(())
         })
@@ -449,21 +996,143 @@ pub fn run() {
             vault_create,
             vault_unlock,
             vault_lock,
+            vault_change_password,
+            vault_set_duress,
+            vault_clear_duress,
+            password_strength,
             vault_status,
+            vault_stats,
+            vault_info,
+            profile_list,
+            profile_create,
+            profile_switch,
             credential_list,
             credential_search,
             credential_info,
             credential_get_secret,
+            clipboard_copy_secret,
+            credential_qr,
             credential_store,
             credential_update,
+            credential_clone,
             credential_delete,
+            credential_import_ssh_config,
+            tags_rename,
+            category_list,
+            category_add,
+            category_delete,
+            credential_set_category,
+            credential_set_rotation_interval,
+            rotation_due,
+            environments_known,
+            environment_suggest,
+            environments_set_strict,
+            vault_set_unique_names,
             audit_log,
+            audit_summary,
+            audit_clear,
             policy_get,
             policy_save,
             policy_delete,
+            policy_templates_list,
+            policy_template_save,
+            policy_template_delete,
+            webhooks_list,
+            webhook_save,
+            webhook_delete,
+            approvals_list,
+            approvals_approve,
+            approvals_deny,
             check_mcp_installed,
             install_mcp_server,
         ])
         .run(tauri::generate_context!())
         .expect("error while running Passman");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_port_defaults_by_driver() {
+        let stored_port = |driver: &str, port: serde_json::Value| {
+            let secret = serde_json::json!({
+                "type": "database_connection",
+                "driver": driver,
+                "host": "db.example.com",
+                "port": port,
+                "database": "app",
+                "username": "app",
+                "password": "pw",
+            });
+            match parse_secret(secret).unwrap() {
+                CredentialSecret::DatabaseConnection { port, .. } => port,
+                other => panic!("wrong variant: {other:?}"),
+            }
+        };
+        assert_eq!(stored_port("postgres", serde_json::Value::Null), 5432);
+        assert_eq!(stored_port("mysql", serde_json::Value::Null), 3306);
+        assert_eq!(stored_port("sqlite", serde_json::Value::Null), 0);
+        assert_eq!(stored_port("mysql", 3307.into()), 3307);
+    }
+
+    #[tokio::test]
+    async fn test_custom_environment_round_trips() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let vault = Vault::new(
+            dir.path().join("vault.json"),
+            dir.path().join("audit.jsonl"),
+        );
+        vault.create("env-test-pw-2024", true).await.unwrap();
+
+        let env = Environment::parse("my-env").unwrap();
+        assert_eq!(env, Environment::Custom("my-env".into()));
+        let id = vault
+            .store_credential(
+                "Env API".into(),
+                CredentialKind::ApiToken,
+                env,
+                vec![],
+                None,
+                &CredentialSecret::ApiToken {
+                    token: "tok_env".into(),
+                    header_name: None,
+                    prefix: None,
+                },
+            )
+            .await
+            .unwrap();
+
+        // The GUI filters with the name it displays
+        let meta = vault.get_credential_meta(id).await.unwrap();
+        let shown = Environment::parse(&meta.environment.to_string());
+        let listed = vault
+            .list_credentials(None, shown, None, None)
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(Environment::parse(" Staging "), Some(Environment::Staging));
+        assert_eq!(Environment::parse("  "), None);
+    }
+
+    #[tokio::test]
+    async fn test_vault_info_reports_profile_paths() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let profile = VaultProfile {
+            name: "work".into(),
+            vault_path: dir.path().join("work.json"),
+            audit_path: dir.path().join("work-audit.jsonl"),
+        };
+        let vault = Vault::from_profile(&profile);
+
+        let info = VaultInfoResponse::read(&vault).await;
+        assert_eq!(info.vault_path, profile.vault_path.display().to_string());
+        assert_eq!(info.audit_path, profile.audit_path.display().to_string());
+        assert_eq!(info.profile.as_deref(), Some("work"));
+        assert!(!info.exists);
+
+        vault.create("info-test-pw-2024", true).await.unwrap();
+        assert!(VaultInfoResponse::read(&vault).await.exists);
+    }
+}
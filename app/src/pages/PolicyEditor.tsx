@@ -1,13 +1,18 @@
 import { useState, useEffect } from "react";
 import { useParams, useNavigate } from "react-router-dom";
 import { invoke } from "@tauri-apps/api/core";
-import type { CredentialMeta, PolicyRule } from "../types";
+import type { CredentialMeta, PolicyRule, TimeWindow } from "../types";
 
 const ALL_TOOLS = [
   "http_request",
   "ssh_exec",
+  "ssh_tunnel",
   "sql_query",
+  "mongo_find",
   "send_email",
+  "smtp_verify",
+  "credential_ping",
+  "blob_fetch",
 ];
 
 export function PolicyEditor() {
@@ -17,9 +22,17 @@ export function PolicyEditor() {
   const [meta, setMeta] = useState<CredentialMeta | null>(null);
   const [allowedTools, setAllowedTools] = useState<string[]>([...ALL_TOOLS]);
   const [httpPatterns, setHttpPatterns] = useState<string[]>([]);
+  const [httpDefaultDeny, setHttpDefaultDeny] = useState(false);
   const [sshPatterns, setSshPatterns] = useState<string[]>([]);
+  const [sshDefaultDeny, setSshDefaultDeny] = useState(false);
   const [sqlAllowWrite, setSqlAllowWrite] = useState(false);
+  const [forbiddenSql, setForbiddenSql] = useState<string[]>([]);
+  const [mongoAllowWrite, setMongoAllowWrite] = useState(false);
   const [smtpRecipients, setSmtpRecipients] = useState<string[]>([]);
+  const [allowedHosts, setAllowedHosts] = useState<string[]>([]);
+  const [requireApproval, setRequireApproval] = useState(false);
+  const [timeWindows, setTimeWindows] = useState<TimeWindow[]>([]);
+  const [perToolLimits, setPerToolLimits] = useState<PolicyRule["per_tool_limits"]>({});
   const [rateLimitEnabled, setRateLimitEnabled] = useState(false);
   const [maxRequests, setMaxRequests] = useState("60");
   const [windowSecs, setWindowSecs] = useState("3600");
@@ -41,9 +54,17 @@ export function PolicyEditor() {
         if (policy) {
           setAllowedTools(policy.allowed_tools);
           setHttpPatterns(policy.http_url_patterns);
+          setHttpDefaultDeny(policy.http_default_deny ?? false);
           setSshPatterns(policy.ssh_command_patterns);
+          setSshDefaultDeny(policy.ssh_default_deny ?? false);
           setSqlAllowWrite(policy.sql_allow_write);
+          setForbiddenSql(policy.forbidden_sql_patterns ?? []);
           setSmtpRecipients(policy.smtp_allowed_recipients);
+          setMongoAllowWrite(policy.mongo_allow_write);
+          setAllowedHosts(policy.allowed_hosts ?? []);
+          setTimeWindows(policy.time_windows ?? []);
+          setRequireApproval(policy.require_approval ?? false);
+          setPerToolLimits(policy.per_tool_limits ?? {});
           if (policy.rate_limit) {
             setRateLimitEnabled(true);
             setMaxRequests(String(policy.rate_limit.max_requests));
@@ -105,6 +126,17 @@ export function PolicyEditor() {
             onChange={setHttpPatterns}
             placeholder="https://api.example.com/*"
           />
+          <label style={{ display: "flex", alignItems: "center", gap: 8, cursor: "pointer", marginTop: 8 }}>
+            <input
+              type="checkbox"
+              checked={httpDefaultDeny}
+              onChange={(e) => setHttpDefaultDeny(e.target.checked)}
+              style={{ accentColor: "var(--accent)" }}
+            />
+            <span style={{ fontSize: 14, color: "var(--text-secondary)" }}>
+              Deny all URLs when the list is empty
+            </span>
+          </label>
         </div>
 
         <div className="policy-section">
@@ -117,6 +149,17 @@ export function PolicyEditor() {
             onChange={setSshPatterns}
             placeholder="ls *"
           />
+          <label style={{ display: "flex", alignItems: "center", gap: 8, cursor: "pointer", marginTop: 8 }}>
+            <input
+              type="checkbox"
+              checked={sshDefaultDeny}
+              onChange={(e) => setSshDefaultDeny(e.target.checked)}
+              style={{ accentColor: "var(--accent)" }}
+            />
+            <span style={{ fontSize: 14, color: "var(--text-secondary)" }}>
+              Deny all commands when the list is empty
+            </span>
+          </label>
         </div>
 
         <div className="policy-section">
@@ -132,6 +175,29 @@ export function PolicyEditor() {
               Allow write operations (INSERT, UPDATE, DELETE)
             </span>
           </label>
+          <p style={{ fontSize: 13, color: "var(--text-secondary)", margin: "12px 0 8px" }}>
+            Forbidden tables and keywords, denied even when writes are allowed
+          </p>
+          <PatternList
+            patterns={forbiddenSql}
+            onChange={setForbiddenSql}
+            placeholder="schema_migrations"
+          />
+        </div>
+
+        <div className="policy-section">
+          <h3>MongoDB Access</h3>
+          <label style={{ display: "flex", alignItems: "center", gap: 8, cursor: "pointer" }}>
+            <input
+              type="checkbox"
+              checked={mongoAllowWrite}
+              onChange={(e) => setMongoAllowWrite(e.target.checked)}
+              style={{ accentColor: "var(--accent)" }}
+            />
+            <span style={{ fontSize: 14, color: "var(--text-secondary)" }}>
+              Allow write operations (insert, update, delete)
+            </span>
+          </label>
         </div>
 
         <div className="policy-section">
@@ -143,6 +209,30 @@ export function PolicyEditor() {
           />
         </div>
 
+        <div className="policy-section">
+          <h3>Ping Allowed Hosts</h3>
+          <PatternList
+            patterns={allowedHosts}
+            onChange={setAllowedHosts}
+            placeholder="*.internal"
+          />
+        </div>
+
+        <div className="policy-section">
+          <h3>Approval</h3>
+          <label style={{ display: "flex", alignItems: "center", gap: 8, cursor: "pointer" }}>
+            <input
+              type="checkbox"
+              checked={requireApproval}
+              onChange={(e) => setRequireApproval(e.target.checked)}
+              style={{ accentColor: "var(--accent)" }}
+            />
+            <span style={{ fontSize: 14, color: "var(--text-secondary)" }}>
+              Require approval in this app before each operation runs
+            </span>
+          </label>
+        </div>
+
         <div className="policy-section">
           <h3>Rate Limiting</h3>
           <label style={{ display: "flex", alignItems: "center", gap: 8, cursor: "pointer", marginBottom: 12 }}>
@@ -195,12 +285,25 @@ export function PolicyEditor() {
                     credentialId: id,
                     allowedTools,
                     httpUrlPatterns: httpPatterns,
+                    httpDefaultDeny,
                     sshCommandPatterns: sshPatterns,
+                    sshDefaultDeny,
                     sqlAllowWrite,
+                    forbiddenSqlPatterns: forbiddenSql,
                     smtpAllowedRecipients: smtpRecipients,
+                    mongoAllowWrite,
+                    allowedHosts,
+                    timeWindows,
+                    requireApproval,
                     rateLimit: rateLimitEnabled
                       ? { maxRequests: parseInt(maxRequests, 10), windowSecs: parseInt(windowSecs, 10) }
                       : null,
+                    perToolLimits: Object.fromEntries(
+                      Object.entries(perToolLimits).map(([tool, rl]) => [
+                        tool,
+                        { maxRequests: rl.max_requests, windowSecs: rl.window_secs },
+                      ])
+                    ),
                   },
                 });
                 setSaved(true);
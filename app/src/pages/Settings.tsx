@@ -1,6 +1,6 @@
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import type { VaultStatus } from "../types";
+import type { VaultInfo, VaultStatus } from "../types";
 
 interface McpStatus {
   installed: boolean;
@@ -15,9 +15,11 @@ export function Settings({ status }: SettingsProps) {
   const [mcpStatus, setMcpStatus] = useState<McpStatus | null>(null);
   const [installing, setInstalling] = useState(false);
   const [installError, setInstallError] = useState<string | null>(null);
+  const [vaultInfo, setVaultInfo] = useState<VaultInfo | null>(null);
 
   useEffect(() => {
     invoke<McpStatus>("check_mcp_installed").then(setMcpStatus);
+    invoke<VaultInfo>("vault_info").then(setVaultInfo);
   }, []);
 
   const handleInstall = async () => {
@@ -63,13 +65,20 @@ export function Settings({ status }: SettingsProps) {
 
       <div className="settings-section">
         <h3>Vault Path</h3>
+        <div className="settings-row">
+          <span className="label">Profile</span>
+          <span className="value">{vaultInfo?.profile ?? "\u2014"}</span>
+        </div>
         <div className="settings-row">
           <span className="label">Location</span>
-          <span className="value">~/.passman/vault.json</span>
+          <span className="value">
+            {vaultInfo ? vaultInfo.vault_path : "\u2014"}
+            {vaultInfo && !vaultInfo.exists && " (not created yet)"}
+          </span>
         </div>
         <div className="settings-row">
           <span className="label">Audit Log</span>
-          <span className="value">~/.passman/audit.jsonl</span>
+          <span className="value">{vaultInfo ? vaultInfo.audit_path : "\u2014"}</span>
         </div>
       </div>
 
@@ -161,7 +170,9 @@ export function Settings({ status }: SettingsProps) {
         <h3>Security</h3>
         <div className="settings-row">
           <span className="label">Encryption</span>
-          <span className="value">AES-256-GCM</span>
+          <span className="value">
+            {status.cipher === "xchacha20-poly1305" ? "XChaCha20-Poly1305" : "AES-256-GCM"}
+          </span>
         </div>
         <div className="settings-row">
           <span className="label">Key Derivation</span>
@@ -1,5 +1,6 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import type { UnlockResponse } from "../types";
 
 interface UnlockPageProps {
   onUnlocked: () => void;
@@ -12,6 +13,7 @@ export function UnlockPage({ onUnlocked }: UnlockPageProps) {
   const [loading, setLoading] = useState(true);
   const [submitting, setSubmitting] = useState(false);
   const [error, setError] = useState("");
+  const [recovered, setRecovered] = useState("");
 
   useEffect(() => {
     invoke<boolean>("vault_exists").then((exists) => {
@@ -40,7 +42,12 @@ export function UnlockPage({ onUnlocked }: UnlockPageProps) {
       if (isNewVault) {
         await invoke("vault_create", { password });
       } else {
-        await invoke("vault_unlock", { password });
+        const result = await invoke<UnlockResponse>("vault_unlock", { password });
+        // Make sure the user sees that recent changes may be gone
+        if (result.recovered_from_backup) {
+          setRecovered(result.recovered_from_backup);
+          return;
+        }
       }
       onUnlocked();
     } catch (err: any) {
@@ -59,6 +66,28 @@ export function UnlockPage({ onUnlocked }: UnlockPageProps) {
     );
   }
 
+  if (recovered) {
+    return (
+      <div className="unlock-page">
+        <div className="unlock-card">
+          <h1>Passman</h1>
+          <p className="subtitle">Vault unlocked from a backup</p>
+          <div className="form-warning">{recovered}</div>
+          <p className="subtitle">
+            Changes saved after that backup was made are missing.
+          </p>
+          <button
+            className="btn btn-primary"
+            style={{ width: "100%", marginTop: 16 }}
+            onClick={onUnlocked}
+          >
+            Continue
+          </button>
+        </div>
+      </div>
+    );
+  }
+
   return (
     <div className="unlock-page">
       <div className="unlock-card">
@@ -1,7 +1,7 @@
 import { useEffect, useState } from "react";
 import { Link } from "react-router-dom";
 import { invoke } from "@tauri-apps/api/core";
-import type { CredentialMeta, CredentialKind } from "../types";
+import type { CredentialMeta, CredentialKind, CredentialInfoResponse } from "../types";
 import { kindLabel, environmentToString, CREDENTIAL_KINDS, ENVIRONMENTS } from "../types";
 
 interface VaultBrowserProps {
@@ -14,7 +14,12 @@ export function VaultBrowser({ onRefresh }: VaultBrowserProps) {
   const [kindFilter, setKindFilter] = useState("");
   const [envFilter, setEnvFilter] = useState("");
   const [loading, setLoading] = useState(true);
-  const [deleteConfirm, setDeleteConfirm] = useState<{ id: string; name: string } | null>(null);
+  const [deleteConfirm, setDeleteConfirm] = useState<{
+    id: string;
+    name: string;
+    dependents: string[];
+  } | null>(null);
+  const [deleteError, setDeleteError] = useState<string | null>(null);
 
   const fetchCredentials = async () => {
     setLoading(true);
@@ -48,14 +53,28 @@ export function VaultBrowser({ onRefresh }: VaultBrowserProps) {
     fetchCredentials();
   };
 
-  const handleDelete = async (id: string) => {
-    setDeleteConfirm(null);
+  // Look up which credentials link to this one so the dialog can name them
+  const confirmDelete = async (id: string, name: string) => {
+    setDeleteError(null);
+    let dependents: string[] = [];
     try {
-      await invoke("credential_delete", { id });
+      const info = await invoke<CredentialInfoResponse>("credential_info", { id });
+      dependents = info.linked.filter((l) => l.direction === "incoming").map((l) => l.name);
+    } catch (err) {
+      console.error("Failed to load links:", err);
+    }
+    setDeleteConfirm({ id, name, dependents });
+  };
+
+  const handleDelete = async (id: string, force: boolean) => {
+    try {
+      await invoke("credential_delete", { id, force });
+      setDeleteConfirm(null);
       fetchCredentials();
       onRefresh();
-    } catch (err) {
+    } catch (err: any) {
       console.error("Failed to delete:", err);
+      setDeleteError(typeof err === "string" ? err : err?.message || "Failed to delete");
     }
   };
 
@@ -130,7 +149,7 @@ export function VaultBrowser({ onRefresh }: VaultBrowserProps) {
               <button
                 className="btn btn-danger btn-sm"
                 style={{ marginLeft: 8 }}
-                onClick={() => setDeleteConfirm({ id: cred.id, name: cred.name })}
+                onClick={() => confirmDelete(cred.id, cred.name)}
               >
                 Delete
               </button>
@@ -167,6 +186,15 @@ export function VaultBrowser({ onRefresh }: VaultBrowserProps) {
             <p style={{ margin: "0 0 20px", opacity: 0.8 }}>
               Delete <strong>{deleteConfirm.name}</strong>? This cannot be undone.
             </p>
+            {deleteConfirm.dependents.length > 0 && (
+              <p style={{ margin: "0 0 20px", color: "var(--warning)" }}>
+                Linked from <strong>{deleteConfirm.dependents.join(", ")}</strong>. Their links
+                to it will be removed.
+              </p>
+            )}
+            {deleteError && (
+              <p style={{ margin: "0 0 20px", color: "var(--danger)" }}>{deleteError}</p>
+            )}
             <div style={{ display: "flex", gap: 8, justifyContent: "flex-end" }}>
               <button
                 className="btn btn-secondary btn-sm"
@@ -176,9 +204,11 @@ export function VaultBrowser({ onRefresh }: VaultBrowserProps) {
               </button>
               <button
                 className="btn btn-danger btn-sm"
-                onClick={() => handleDelete(deleteConfirm.id)}
+                onClick={() =>
+                  handleDelete(deleteConfirm.id, deleteConfirm.dependents.length > 0)
+                }
               >
-                Delete
+                {deleteConfirm.dependents.length > 0 ? "Delete and Unlink" : "Delete"}
               </button>
             </div>
           </div>
@@ -3,9 +3,13 @@ export type CredentialKind =
   | "api_token"
   | "ssh_key"
   | "ssh_password"
+  | "ssh_agent"
   | "database_connection"
   | "certificate"
   | "smtp_account"
+  | "mongodb"
+  | "jwt_signer"
+  | "opaque_blob"
   | "custom";
 
 export type Environment =
@@ -24,12 +28,66 @@ export interface CredentialMeta {
   created_at: string;
   updated_at: string;
   notes: string | null;
+  category: string | null;
+  use_count: number;
+  last_used_at: string | null;
+  links: CredentialLink[];
+  rotation_interval_days: number | null;
+  last_rotated_at: string | null;
+}
+
+export interface CredentialLink {
+  target_id: string;
+  relation: string;
+}
+
+export interface LinkedCredential {
+  id: string;
+  name: string;
+  relation: string;
+  direction: "outgoing" | "incoming";
+}
+
+/** Returned by `credential_info`: `CredentialMeta` fields plus links in both directions. */
+export type CredentialInfoResponse = Omit<CredentialMeta, "links"> & {
+  linked: LinkedCredential[];
+};
+
+export interface Category {
+  name: string;
+  description: string | null;
+}
+
+export interface StrengthReport {
+  score: 0 | 1 | 2 | 3 | 4;
+  guesses: number;
+  warnings: string[];
+}
+
+export interface KdfParams {
+  memory_kib: number;
+  iterations: number;
+  parallelism: number;
 }
 
 export interface VaultStatus {
   unlocked: boolean;
   credential_count: number;
   environments: string[];
+  // Present only while unlocked
+  format_version?: number;
+  kdf_algorithm?: string;
+  kdf_params?: KdfParams;
+  cipher?: "aes-256-gcm" | "xchacha20-poly1305";
+  storage_format?: "monolithic" | "directory";
+  compressed?: boolean;
+  credentials_by_kind?: Partial<Record<CredentialKind, number>>;
+}
+
+export interface UnlockResponse {
+  credential_count: number;
+  // Set when the vault file was corrupt and a backup was restored
+  recovered_from_backup: string | null;
 }
 
 export interface AuditEntry {
@@ -42,14 +100,105 @@ export interface AuditEntry {
   details: string | null;
 }
 
+export type DuressAction = "decoy" | "wipe";
+
+export interface VaultProfile {
+  name: string;
+  vault_path: string;
+  audit_path: string;
+}
+
+export interface VaultInfo {
+  vault_path: string;
+  audit_path: string;
+  profile: string | null;
+  exists: boolean;
+}
+
+export interface ProfileList {
+  active: string;
+  profiles: VaultProfile[];
+}
+
+export interface VaultStats {
+  total: number;
+  by_kind: Partial<Record<CredentialKind, number>>;
+  by_environment: Record<string, number>;
+  total_tags: number;
+  with_policy: number;
+}
+
+/** Payload of the `vault-reencrypt-progress` event during a password change. */
+export interface ReencryptProgress {
+  processed: number;
+  total: number;
+}
+
+export interface AuditSummary {
+  total: number;
+  succeeded: number;
+  failed: number;
+  by_action: { action: string; total: number; failed: number }[];
+  top_credentials: {
+    credential_id: string;
+    credential_name: string | null;
+    count: number;
+  }[];
+  first_entry: string | null;
+  last_entry: string | null;
+}
+
 export interface PolicyRule {
   credential_id: string;
   allowed_tools: string[];
   http_url_patterns: string[];
+  http_default_deny: boolean;
   ssh_command_patterns: string[];
+  ssh_default_deny: boolean;
   sql_allow_write: boolean;
+  forbidden_sql_patterns: string[];
   smtp_allowed_recipients: string[];
+  mongo_allow_write: boolean;
+  allowed_hosts: string[];
+  time_windows: TimeWindow[];
+  require_approval: boolean;
   rate_limit: { max_requests: number; window_secs: number } | null;
+  per_tool_limits: Record<string, { max_requests: number; window_secs: number }>;
+}
+
+export interface PolicyTemplate {
+  name: string;
+  environment: Environment | null;
+  tag: string | null;
+  rule: PolicyRule;
+}
+
+export interface WebhookRule {
+  name: string;
+  url: string;
+  actions: string[];
+  environments: Environment[];
+}
+
+export interface TimeWindow {
+  days: string[];
+  start: string;
+  end: string;
+  timezone: string;
+}
+
+export interface PendingApproval {
+  id: string;
+  credential_id: string;
+  credential_name: string | null;
+  tool: string;
+  summary: string;
+  /** The full request as it will run; this is what the user approves. */
+  request: unknown;
+  request_hash: string;
+  status: "pending" | "approved" | "denied";
+  created_at: string;
+  expires_at: string;
 }
 
 // Secret types for the credential editor forms
@@ -84,6 +233,14 @@ export interface SshPasswordSecret {
   password: string;
 }
 
+export interface SshAgentSecret {
+  type: "ssh_agent";
+  username: string;
+  host: string;
+  port: number;
+  public_key: string;
+}
+
 export interface DatabaseConnectionSecret {
   type: "database_connection";
   driver: "postgres" | "mysql" | "sqlite";
@@ -100,6 +257,7 @@ export interface CertificateSecret {
   cert_pem: string;
   key_pem: string;
   ca_pem?: string;
+  pin_ca?: boolean;
 }
 
 export interface SmtpAccountSecret {
@@ -111,6 +269,26 @@ export interface SmtpAccountSecret {
   encryption: "none" | "start_tls" | "tls";
 }
 
+export interface MongoDbSecret {
+  type: "mongodb";
+  uri: string;
+}
+
+export interface JwtSignerSecret {
+  type: "jwt_signer";
+  private_key_pem: string;
+  algorithm: "RS256" | "RS384" | "RS512" | "ES256" | "ES384" | "EdDSA";
+  issuer: string;
+  audience: string;
+  ttl_secs: number;
+}
+
+export interface OpaqueBlobSecret {
+  type: "opaque_blob";
+  data_base64: string;
+  content_type?: string;
+}
+
 export interface CustomSecret {
   type: "custom";
   fields: Record<string, string>;
@@ -121,9 +299,13 @@ export type CredentialSecret =
   | ApiTokenSecret
   | SshKeySecret
   | SshPasswordSecret
+  | SshAgentSecret
   | DatabaseConnectionSecret
   | CertificateSecret
   | SmtpAccountSecret
+  | MongoDbSecret
+  | JwtSignerSecret
+  | OpaqueBlobSecret
   | CustomSecret;
 
 export const CREDENTIAL_KINDS: { value: CredentialKind; label: string }[] = [
@@ -131,9 +313,13 @@ export const CREDENTIAL_KINDS: { value: CredentialKind; label: string }[] = [
   { value: "api_token", label: "API Token" },
   { value: "ssh_key", label: "SSH Key" },
   { value: "ssh_password", label: "SSH Password" },
+  { value: "ssh_agent", label: "SSH Agent" },
   { value: "database_connection", label: "Database" },
   { value: "certificate", label: "Certificate" },
   { value: "smtp_account", label: "SMTP Account" },
+  { value: "mongodb", label: "MongoDB" },
+  { value: "jwt_signer", label: "JWT Signer" },
+  { value: "opaque_blob", label: "Opaque Blob" },
   { value: "custom", label: "Custom" },
 ];
 
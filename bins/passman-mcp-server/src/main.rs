@@ -1,13 +1,135 @@
-use anyhow::Result;
-use passman_mcp::PassmanServer;
+use anyhow::{bail, Context, Result};
+use passman_mcp::concurrency::{DEFAULT_MAX_CONCURRENT, DEFAULT_MAX_PER_CREDENTIAL};
+use passman_mcp::tools::{OutputLimits, DEFAULT_MAX_OUTPUT_BYTES};
+use passman_mcp::{PassmanServer, Scope};
+use passman_proxy::http::{ClientCache, HttpConfig};
+use passman_proxy::redact::RedactingWriter;
 use passman_vault::{watcher, Vault};
+use rmcp::transport::streamable_http_server::{
+    session::local::LocalSessionManager, StreamableHttpService,
+};
 use rmcp::{transport::stdio, ServiceExt};
 use tracing_subscriber::{self, EnvFilter};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
+const DEFAULT_HTTP_PORT: u16 = 8787;
 
-#[tokio::main]
-async fn main() -> Result<()> {
+enum Transport {
+    Stdio,
+    Http { port: u16 },
+}
+
+/// The value following `flag`, if it was given.
+fn value_of<'a>(args: &'a [String], flag: &str) -> Result<Option<&'a String>> {
+    args.iter()
+        .position(|a| a == flag)
+        .map(|i| {
+            args.get(i + 1)
+                .with_context(|| format!("{flag} requires a value"))
+        })
+        .transpose()
+}
+
+enum LogFormat {
+    Text,
+    Json,
+}
+
+/// `--log-format`, falling back to `PASSMAN_LOG_FORMAT`, then text.
+fn parse_log_format(args: &[String]) -> Result<LogFormat> {
+    let env = std::env::var("PASSMAN_LOG_FORMAT").ok();
+    let format = value_of(args, "--log-format")?.or(env.as_ref());
+    match format.map(String::as_str) {
+        None | Some("text") => Ok(LogFormat::Text),
+        Some("json") => Ok(LogFormat::Json),
+        Some(other) => bail!("unknown log format: {other} (expected text or json)"),
+    }
+}
+
+fn parse_transport(args: &[String]) -> Result<Transport> {
+    let port = value_of(args, "--port")?
+        .map(|p| {
+            p.parse::<u16>()
+                .with_context(|| format!("invalid port: {p}"))
+        })
+        .transpose()?;
+
+    match value_of(args, "--transport")?.map(String::as_str) {
+        None | Some("stdio") => {
+            if port.is_some() {
+                bail!("--port requires --transport http");
+            }
+            Ok(Transport::Stdio)
+        }
+        Some("http") => Ok(Transport::Http {
+            port: port.unwrap_or(DEFAULT_HTTP_PORT),
+        }),
+        Some(other) => bail!("unknown transport: {other} (expected stdio or http)"),
+    }
+}
+
+/// Every value given for a repeatable flag, also splitting on commas:
+/// `--scope-tag a --scope-tag b,c` gives `[a, b, c]`.
+fn values_of(args: &[String], flag: &str) -> Result<Vec<String>> {
+    let mut values = vec![];
+    for (i, arg) in args.iter().enumerate() {
+        if arg == flag {
+            let value = args
+                .get(i + 1)
+                .with_context(|| format!("{flag} requires a value"))?;
+            values.extend(
+                value
+                    .split(',')
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty()),
+            );
+        }
+    }
+    Ok(values)
+}
+
+/// `--max-output-bytes` and any `--tool-max-output-bytes <TOOL>=<BYTES>`
+/// overrides.
+fn parse_output_limits(args: &[String]) -> Result<OutputLimits> {
+    let mut limits = OutputLimits::default();
+    if let Some(bytes) = value_of(args, "--max-output-bytes")? {
+        limits.max_output_bytes = bytes
+            .parse()
+            .with_context(|| format!("invalid --max-output-bytes: {bytes}"))?;
+    }
+    for entry in values_of(args, "--tool-max-output-bytes")? {
+        let (tool, bytes) = entry
+            .split_once('=')
+            .with_context(|| format!("expected <TOOL>=<BYTES>, got: {entry}"))?;
+        let bytes = bytes
+            .parse()
+            .with_context(|| format!("invalid byte count for {tool}: {bytes}"))?;
+        limits.per_tool.insert(tool.to_string(), bytes);
+    }
+    Ok(limits)
+}
+
+/// A positive count given with `flag`, or `default`.
+fn count_of(args: &[String], flag: &str, default: usize) -> Result<usize> {
+    match value_of(args, flag)? {
+        None => Ok(default),
+        Some(n) => match n.parse::<usize>() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => bail!("{flag} must be a positive number, got: {n}"),
+        },
+    }
+}
+
+fn main() -> Result<()> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    let result = runtime.block_on(run());
+    // The stdio transport reads stdin on a blocking thread that can't be
+    // cancelled, so don't wait for it once the server has shut down
+    runtime.shutdown_background();
+    result
+}
+
+async fn run() -> Result<()> {
     // Handle --version / --help
     let args: Vec<String> = std::env::args().collect();
     if args.iter().any(|a| a == "--version" || a == "-V") {
@@ -19,45 +141,242 @@ async fn main() -> Result<()> {
         println!("Secure credential proxy MCP server\n");
         println!("USAGE: passman-mcp-server [OPTIONS]\n");
         println!("OPTIONS:");
-        println!("  -h, --help       Print help");
-        println!("  -V, --version    Print version");
-        println!("\nCommunicates via JSON-RPC over stdio (MCP transport).");
+        println!("  --transport <stdio|http>  MCP transport (default: stdio)");
+        println!(
+            "  --port <PORT>             HTTP port on 127.0.0.1 (default: {DEFAULT_HTTP_PORT})"
+        );
+        println!("  --read-only               Refuse to store, update or delete credentials");
+        println!("  --syslog                  Also send audit entries to the local syslog (Unix)");
+        println!(
+            "  --profile <NAME>          Use a named vault profile (default: the default vault)"
+        );
+        println!(
+            "  --keyring                 Keep the key in the OS keychain; unlock from it at start"
+        );
+        println!("  --scope-tag <TAG>         Only expose credentials with this tag (repeatable)");
+        println!(
+            "  --scope-env <ENV>         Only expose credentials in this environment (repeatable)"
+        );
+        println!("  --log-format <text|json>  Log format on stderr (default: text, or $PASSMAN_LOG_FORMAT)");
+        println!("  --max-output-bytes <N>    Truncate proxy tool output beyond N bytes (default: {DEFAULT_MAX_OUTPUT_BYTES})");
+        println!("  --tool-max-output-bytes <TOOL>=<N>  Per-tool override, e.g. ssh_exec=1048576 (repeatable)");
+        println!("  --max-concurrent <N>      Proxy operations run at once; extra calls wait (default: {DEFAULT_MAX_CONCURRENT})");
+        println!("  --max-concurrent-per-credential <N>  Same, per credential (default: {DEFAULT_MAX_PER_CREDENTIAL})");
+        println!("  -h, --help                Print help");
+        println!("  -V, --version             Print version");
+        println!("\nBy default communicates via JSON-RPC over stdio (MCP transport).");
         println!("Configure in your MCP client as:");
         println!("  {{ \"command\": \"passman-mcp-server\", \"args\": [] }}");
+        println!("\nHTTP requests go through $PASSMAN_HTTP_PROXY (or $HTTPS_PROXY) and also trust");
+        println!("the PEM roots in the file named by $PASSMAN_EXTRA_CA_PEM.");
+        println!("\nWith --transport http, serves streamable HTTP at http://127.0.0.1:<PORT>/mcp");
+        println!("and Prometheus metrics at http://127.0.0.1:<PORT>/metrics");
         return Ok(());
     }
+    let transport = parse_transport(&args)?;
+    let log_format = parse_log_format(&args)?;
+    let read_only = args.iter().any(|a| a == "--read-only");
+    let syslog = args.iter().any(|a| a == "--syslog");
+    let profile = value_of(&args, "--profile")?.cloned();
+    let keyring = args.iter().any(|a| a == "--keyring");
+    let scope = Scope::from_args(
+        values_of(&args, "--scope-tag")?,
+        &values_of(&args, "--scope-env")?,
+    );
+    let output_limits = parse_output_limits(&args)?;
+    let max_concurrent = count_of(&args, "--max-concurrent", DEFAULT_MAX_CONCURRENT)?;
+    let max_per_credential = count_of(
+        &args,
+        "--max-concurrent-per-credential",
+        DEFAULT_MAX_PER_CREDENTIAL,
+    )?;
+    let http_clients = ClientCache::with_config(HttpConfig::from_env()?)?;
 
-    // All logging goes to stderr (stdout is the MCP JSON-RPC transport)
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::from_default_env()
-                .add_directive(tracing::Level::INFO.into()),
-        )
-        .with_writer(std::io::stderr)
-        .with_ansi(false)
-        .init();
+    // All logging goes to stderr (stdout is the MCP JSON-RPC transport), with
+    // the secrets of the proxy call in progress redacted
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env().add_directive(tracing::Level::INFO.into()))
+        .with_writer(RedactingWriter::new(std::io::stderr))
+        .with_ansi(false);
+    match log_format {
+        LogFormat::Text => subscriber.init(),
+        LogFormat::Json => subscriber
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .init(),
+    }
 
     tracing::info!("Passman MCP server v{VERSION} starting");
 
-    let vault = Vault::with_defaults();
+    let mut vault = match &profile {
+        Some(name) => {
+            tracing::info!("Using vault profile '{name}'");
+            Vault::open_profile(name)?
+        }
+        None => Vault::with_defaults(),
+    };
+    if syslog {
+        add_syslog_sink(&mut vault)?;
+        tracing::info!("Mirroring audit entries to syslog");
+    }
+    if keyring {
+        vault = use_keyring(vault)?;
+        match vault.unlock_from_keyring().await {
+            Ok(count) => tracing::info!("Unlocked from the OS keychain ({count} credentials)"),
+            Err(e) => tracing::info!("Not unlocked from the OS keychain: {e}"),
+        }
+    }
 
     // Start file watcher for cross-process vault sync
     let vault_path = vault.vault_path().await;
-    let _watch_handle = watcher::watch_vault(vault.clone(), vault_path);
+    let watch_handle = watcher::watch_vault(vault.clone(), vault_path);
 
-    let server = PassmanServer::new(vault);
+    // Proxy tools count credential uses in memory; write them out periodically
+    let flush_vault = vault.clone();
+    let usage_flush = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(passman_vault::USAGE_FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = flush_vault.flush_usage().await {
+                tracing::warn!("Failed to save credential usage: {e}");
+            }
+        }
+    });
 
-    let service = server
-        .serve(stdio())
-        .await
-        .inspect_err(|e| {
-            tracing::error!("Failed to start MCP service: {:?}", e);
-        })?;
+    let server = if read_only {
+        tracing::info!("Read-only mode: credential mutations are disabled");
+        PassmanServer::new(vault.read_only_view())
+    } else {
+        PassmanServer::new(vault.clone())
+    };
+    if !scope.is_unrestricted() {
+        tracing::info!("Session scoped to {}", scope.describe());
+    }
+    let server = server
+        .with_scope(scope)
+        .with_output_limits(output_limits)
+        .with_concurrency_limits(max_concurrent, max_per_credential)
+        .with_http_clients(http_clients);
+
+    let serve = async {
+        match transport {
+            Transport::Stdio => serve_stdio(server).await,
+            Transport::Http { port } => serve_http(server, port).await,
+        }
+    };
+
+    let result = tokio::select! {
+        result = serve => result,
+        _ = shutdown_signal() => {
+            tracing::info!("Shutdown signal received");
+            Ok(())
+        }
+    };
+
+    // Drop the derived key before exiting rather than leaving it to process
+    // teardown. Only ours: the GUI and other servers stay unlocked
+    tracing::info!("Passman MCP server shutting down");
+    usage_flush.abort();
+    vault.lock_local().await;
+    watch_handle.stop().await;
+
+    result
+}
+
+#[cfg(unix)]
+fn add_syslog_sink(vault: &mut Vault) -> Result<()> {
+    let sink = passman_vault::sink::SyslogSink::connect()?;
+    vault.add_audit_sink(std::sync::Arc::new(sink));
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn add_syslog_sink(_vault: &mut Vault) -> Result<()> {
+    bail!("--syslog is only supported on Unix")
+}
+
+#[cfg(feature = "keyring")]
+fn use_keyring(vault: Vault) -> Result<Vault> {
+    Ok(vault.with_key_store(std::sync::Arc::new(passman_vault::keystore::OsKeyring)))
+}
+
+#[cfg(not(feature = "keyring"))]
+fn use_keyring(_vault: Vault) -> Result<Vault> {
+    bail!("--keyring requires building with the keyring feature")
+}
+
+/// Resolves on SIGINT, or SIGTERM on Unix.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl-C: {e}");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+async fn serve_stdio(server: PassmanServer) -> Result<()> {
+    let service = server.serve(stdio()).await.inspect_err(|e| {
+        tracing::error!("Failed to start MCP service: {:?}", e);
+    })?;
 
     tracing::info!("Passman MCP server running on stdio");
 
     service.waiting().await?;
+    Ok(())
+}
 
-    tracing::info!("Passman MCP server shutting down");
+async fn serve_http(server: PassmanServer, port: u16) -> Result<()> {
+    let metrics = server.metrics.clone();
+
+    // Every HTTP session shares one vault handle, policy engine and metrics
+    let service = StreamableHttpService::new(
+        move || Ok(server.clone()),
+        LocalSessionManager::default().into(),
+        Default::default(),
+    );
+    let router = axum::Router::new().nest_service("/mcp", service).route(
+        "/metrics",
+        axum::routing::get(move || async move {
+            (
+                [(
+                    axum::http::header::CONTENT_TYPE,
+                    "text/plain; version=0.0.4",
+                )],
+                metrics.render(),
+            )
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("failed to bind 127.0.0.1:{port}"))?;
+
+    tracing::info!("Passman MCP server running on http://127.0.0.1:{port}/mcp");
+    tracing::info!("Prometheus metrics at http://127.0.0.1:{port}/metrics");
+
+    axum::serve(listener, router).await?;
     Ok(())
 }
@@ -0,0 +1,184 @@
+//! Smoke tests for the streamable HTTP transport: the server binary completes
+//! an MCP initialize handshake and serves metrics for the tool calls it handled.
+
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Kills the spawned server when the test ends, pass or fail.
+struct ServerProcess(Child);
+
+impl Drop for ServerProcess {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn free_port() -> u16 {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+fn spawn_server(home: &std::path::Path, port: u16) -> ServerProcess {
+    ServerProcess(
+        Command::new(env!("CARGO_BIN_EXE_passman-mcp-server"))
+            .args(["--transport", "http", "--port", &port.to_string()])
+            .env("HOME", home)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap(),
+    )
+}
+
+fn initialize_request() -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2025-03-26",
+            "capabilities": {},
+            "clientInfo": { "name": "smoke-test", "version": "0.0.0" }
+        }
+    })
+}
+
+fn post(
+    client: &reqwest::Client,
+    port: u16,
+    session: Option<&str>,
+    message: &serde_json::Value,
+) -> reqwest::RequestBuilder {
+    let mut request = client
+        .post(format!("http://127.0.0.1:{port}/mcp"))
+        .header("Accept", "application/json, text/event-stream")
+        .json(message);
+    if let Some(session) = session {
+        request = request.header("Mcp-Session-Id", session);
+    }
+    request
+}
+
+/// Send the initialize request, retrying until the server is listening.
+async fn initialize(client: &reqwest::Client, port: u16) -> reqwest::Response {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        match post(client, port, None, &initialize_request()).send().await {
+            Ok(response) => return response,
+            Err(_) if Instant::now() < deadline => {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            Err(e) => panic!("server never came up: {e}"),
+        }
+    }
+}
+
+/// Read the body until it contains `needle`. The reply may arrive as an SSE
+/// event on a stream that stays open.
+async fn read_until(response: &mut reqwest::Response, needle: &str) -> String {
+    let mut body = String::new();
+    while !body.contains(needle) {
+        let chunk = tokio::time::timeout(Duration::from_secs(5), response.chunk())
+            .await
+            .unwrap_or_else(|_| panic!("timed out waiting for {needle}"))
+            .unwrap()
+            .unwrap_or_else(|| panic!("stream ended before {needle}"));
+        body.push_str(&String::from_utf8_lossy(&chunk));
+    }
+    body
+}
+
+#[tokio::test]
+async fn test_http_initialize_handshake() {
+    let home = tempfile::tempdir().unwrap();
+    let port = free_port();
+    let _server = spawn_server(home.path(), port);
+
+    let client = reqwest::Client::new();
+    let mut response = initialize(&client, port).await;
+    assert!(
+        response.status().is_success(),
+        "status {}",
+        response.status()
+    );
+
+    let body = read_until(&mut response, "serverInfo").await;
+    assert!(body.contains("passman") || body.contains("protocolVersion"));
+}
+
+#[tokio::test]
+async fn test_metrics_count_tool_calls() {
+    let home = tempfile::tempdir().unwrap();
+    let port = free_port();
+    let _server = spawn_server(home.path(), port);
+
+    let client = reqwest::Client::new();
+    let mut response = initialize(&client, port).await;
+    let session = response
+        .headers()
+        .get("mcp-session-id")
+        .expect("no session id")
+        .to_str()
+        .unwrap()
+        .to_string();
+    read_until(&mut response, "serverInfo").await;
+
+    let initialized = serde_json::json!({
+        "jsonrpc": "2.0",
+        "method": "notifications/initialized"
+    });
+    let response = post(&client, port, Some(&session), &initialized)
+        .send()
+        .await
+        .unwrap();
+    assert!(
+        response.status().is_success(),
+        "status {}",
+        response.status()
+    );
+
+    // Two successful calls, and one that fails because the vault is locked
+    for (id, tool) in [(2, "vault_status"), (3, "vault_status"), (4, "vault_stats")] {
+        let call = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "tools/call",
+            "params": { "name": tool, "arguments": {} }
+        });
+        let mut response = post(&client, port, Some(&session), &call)
+            .send()
+            .await
+            .unwrap();
+        read_until(&mut response, &format!("\"id\":{id}")).await;
+    }
+
+    let response = client
+        .get(format!("http://127.0.0.1:{port}/metrics"))
+        .send()
+        .await
+        .unwrap();
+    assert!(
+        response.status().is_success(),
+        "status {}",
+        response.status()
+    );
+    let text = response.text().await.unwrap();
+    assert!(
+        text.contains("passman_tool_invocations_total{tool=\"vault_status\"} 2\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("passman_tool_successes_total{tool=\"vault_status\"} 2\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("passman_tool_invocations_total{tool=\"vault_stats\"} 1\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("passman_tool_failures_total{tool=\"vault_stats\"} 1\n"),
+        "{text}"
+    );
+    assert!(text.contains("passman_tool_duration_seconds_count{tool=\"vault_status\"} 2\n"));
+}
@@ -0,0 +1,78 @@
+//! With JSON log format, every stderr line is a JSON object.
+
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Kills the spawned server when the test ends, pass or fail.
+struct ServerProcess(Child);
+
+impl Drop for ServerProcess {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Start the server on stdio and return its first stderr line.
+fn first_log_line(command: &mut Command) -> String {
+    let home = tempfile::tempdir().unwrap();
+    // Keep stdin open so the stdio transport doesn't end on its own
+    let mut server = ServerProcess(
+        command
+            .env("HOME", home.path())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap(),
+    );
+
+    let stderr = server.0.stderr.take().unwrap();
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut line = String::new();
+        let _ = BufReader::new(stderr).read_line(&mut line);
+        let _ = tx.send(line);
+    });
+    rx.recv_timeout(Duration::from_secs(10))
+        .expect("server logged nothing within 10s")
+}
+
+fn assert_json_startup_line(line: &str) {
+    let entry: serde_json::Value =
+        serde_json::from_str(line).unwrap_or_else(|e| panic!("not JSON ({e}): {line}"));
+    assert_eq!(entry["level"], "INFO");
+    assert!(entry["timestamp"].is_string());
+    assert!(entry["target"].is_string());
+    let message = entry["fields"]["message"].as_str().unwrap();
+    assert!(message.contains("starting"), "{message}");
+}
+
+#[test]
+fn test_log_format_flag() {
+    let line = first_log_line(
+        Command::new(env!("CARGO_BIN_EXE_passman-mcp-server"))
+            .args(["--log-format", "json"])
+            .env_remove("PASSMAN_LOG_FORMAT"),
+    );
+    assert_json_startup_line(&line);
+}
+
+#[test]
+fn test_log_format_env() {
+    let line = first_log_line(
+        Command::new(env!("CARGO_BIN_EXE_passman-mcp-server")).env("PASSMAN_LOG_FORMAT", "json"),
+    );
+    assert_json_startup_line(&line);
+
+    let line = first_log_line(
+        Command::new(env!("CARGO_BIN_EXE_passman-mcp-server")).env_remove("PASSMAN_LOG_FORMAT"),
+    );
+    assert!(
+        serde_json::from_str::<serde_json::Value>(&line).is_err(),
+        "{line}"
+    );
+    assert!(line.contains("starting"), "{line}");
+}
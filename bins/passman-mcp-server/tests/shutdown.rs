@@ -0,0 +1,41 @@
+//! The server exits cleanly (locking the vault first) when sent SIGTERM.
+#![cfg(unix)]
+
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_sigterm_exits_cleanly() {
+    let home = tempfile::tempdir().unwrap();
+
+    // Keep stdin open so the stdio transport doesn't end on its own
+    let mut child = Command::new(env!("CARGO_BIN_EXE_passman-mcp-server"))
+        .env("HOME", home.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // Give the runtime time to install its signal handlers
+    std::thread::sleep(Duration::from_millis(500));
+
+    let status = Command::new("kill")
+        .args(["-TERM", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let exit = loop {
+        if let Some(exit) = child.try_wait().unwrap() {
+            break exit;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            panic!("server did not exit within 5s of SIGTERM");
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    };
+    assert!(exit.success(), "server exited with {exit}");
+}
@@ -0,0 +1,121 @@
+//! Human-in-the-loop gate for policies with `require_approval` set.
+//!
+//! The first call for a gated operation queues it and returns a pending
+//! approval ID. Once the user approves it in the GUI, the agent repeats the
+//! same call with `approval_id`; that call waits briefly for a decision,
+//! consumes the approval, and lets the operation run exactly once.
+//!
+//! Each tool passes the full request it is about to run (every parameter,
+//! body, header, recipient and stdin). The user sees that request, and the
+//! approval is bound to its hash, so a changed request needs a new approval.
+
+use crate::server::PassmanServer;
+use passman_types::{ApprovalStatus, PolicyRule};
+use rmcp::{model::CallToolResult, model::Content, ErrorData as McpError};
+use std::time::Duration;
+
+/// How long a queued operation stays eligible for approval.
+const APPROVAL_TTL_MINUTES: i64 = 15;
+
+/// How long a call carrying an `approval_id` blocks waiting for a decision.
+const APPROVAL_WAIT: Duration = Duration::from_secs(10);
+const APPROVAL_POLL: Duration = Duration::from_millis(500);
+
+/// Returns `Some(result)` if the tool should respond without executing
+/// (pending, denied, or mismatched), `None` if it may proceed.
+pub async fn check_approval(
+    server: &PassmanServer,
+    policy: &PolicyRule,
+    tool: &str,
+    summary: &str,
+    request: serde_json::Value,
+    approval_id: Option<&str>,
+) -> Result<Option<CallToolResult>, McpError> {
+    if !policy.require_approval {
+        return Ok(None);
+    }
+
+    let Some(approval_id) = approval_id else {
+        let approval = server
+            .vault
+            .request_approval(
+                policy.credential_id,
+                tool,
+                summary.to_string(),
+                request,
+                chrono::Duration::minutes(APPROVAL_TTL_MINUTES),
+            )
+            .await
+            .map_err(|e| McpError::internal_error(format!("{e}"), None))?;
+        return Ok(Some(awaiting(approval.id, &approval.request)));
+    };
+    let request_hash = passman_vault::approvals::request_hash(&request);
+
+    let id: uuid::Uuid = approval_id
+        .parse()
+        .map_err(|_| McpError::invalid_params("invalid approval_id", None))?;
+
+    let deadline = tokio::time::Instant::now() + APPROVAL_WAIT;
+    loop {
+        let approval = server
+            .vault
+            .get_approval(id)
+            .await
+            .map_err(|e| McpError::internal_error(format!("{e}"), None))?;
+
+        let Some(approval) = approval else {
+            return Ok(Some(CallToolResult::error(vec![Content::text(
+                "approval not found or expired",
+            )])));
+        };
+
+        if approval.credential_id != policy.credential_id
+            || approval.tool != tool
+            || approval.request_hash != request_hash
+        {
+            return Ok(Some(CallToolResult::error(vec![Content::text(
+                "approval does not match this request",
+            )])));
+        }
+
+        match approval.status {
+            ApprovalStatus::Denied => {
+                return Ok(Some(CallToolResult::error(vec![Content::text(
+                    "operation denied by the user",
+                )])));
+            }
+            ApprovalStatus::Approved => {
+                // Consuming is what authorizes the run; a concurrent caller may have won
+                let consumed = server
+                    .vault
+                    .consume_approval(id)
+                    .await
+                    .map_err(|e| McpError::internal_error(format!("{e}"), None))?;
+                return Ok(match consumed {
+                    Some(_) => None,
+                    None => Some(CallToolResult::error(vec![Content::text(
+                        "approval already used",
+                    )])),
+                });
+            }
+            ApprovalStatus::Pending => {
+                if tokio::time::Instant::now() >= deadline {
+                    return Ok(Some(awaiting(id, &approval.request)));
+                }
+                tokio::time::sleep(APPROVAL_POLL).await;
+            }
+        }
+    }
+}
+
+fn awaiting(id: uuid::Uuid, request: &serde_json::Value) -> CallToolResult {
+    CallToolResult::success(vec![Content::text(
+        serde_json::json!({
+            "status": "awaiting_approval",
+            "approval_id": id.to_string(),
+            "request": request,
+            "message": "This operation requires approval in the Passman app. Repeat the same call with approval_id once approved.",
+        })
+        .to_string(),
+    )])
+}
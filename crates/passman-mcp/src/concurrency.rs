@@ -0,0 +1,120 @@
+//! Caps on how many proxy operations run at once, so a burst of parallel
+//! tool calls queues up instead of exhausting sockets. Rate limits count
+//! calls over time; this bounds the calls in flight.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use uuid::Uuid;
+
+/// Default cap on proxy operations running at once, across all credentials.
+pub const DEFAULT_MAX_CONCURRENT: usize = 16;
+
+/// Default cap on proxy operations running at once with one credential.
+pub const DEFAULT_MAX_PER_CREDENTIAL: usize = 4;
+
+/// Global and per-credential semaphores shared by every session.
+pub struct ConcurrencyLimiter {
+    global: Arc<Semaphore>,
+    max_per_credential: usize,
+    per_credential: Mutex<HashMap<Uuid, Arc<Semaphore>>>,
+}
+
+/// Slots held for one proxy operation; released on drop.
+pub struct ConcurrencyPermit {
+    _credential: OwnedSemaphorePermit,
+    _global: OwnedSemaphorePermit,
+}
+
+impl Default for ConcurrencyLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONCURRENT, DEFAULT_MAX_PER_CREDENTIAL)
+    }
+}
+
+impl ConcurrencyLimiter {
+    /// Limits below 1 are raised to 1.
+    pub fn new(max_concurrent: usize, max_per_credential: usize) -> Self {
+        Self {
+            global: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            max_per_credential: max_per_credential.max(1),
+            per_credential: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait for a free slot for `credential_id`, then for a global one.
+    /// Waiting on the credential first keeps a queued call from holding a
+    /// global slot that other credentials could use.
+    pub async fn acquire(&self, credential_id: Uuid) -> ConcurrencyPermit {
+        let credential = self
+            .per_credential
+            .lock()
+            .unwrap()
+            .entry(credential_id)
+            .or_insert_with(|| Arc::new(Semaphore::new(self.max_per_credential)))
+            .clone();
+
+        // The semaphores are never closed, so acquiring can't fail
+        let credential = credential.acquire_owned().await.unwrap();
+        let global = self.global.clone().acquire_owned().await.unwrap();
+        ConcurrencyPermit {
+            _credential: credential,
+            _global: global,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Run one task per credential in `credentials` and return the most
+    /// that were ever inside the limiter at once.
+    async fn peak_in_flight(limiter: Arc<ConcurrencyLimiter>, credentials: Vec<Uuid>) -> usize {
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = credentials
+            .into_iter()
+            .map(|id| {
+                let (limiter, active, peak) = (limiter.clone(), active.clone(), peak.clone());
+                tokio::spawn(async move {
+                    let _permit = limiter.acquire(id).await;
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        peak.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_global_limit_queues_extra_operations() {
+        let limiter = Arc::new(ConcurrencyLimiter::new(2, 10));
+        let credentials = (0..6).map(|_| Uuid::new_v4()).collect();
+        assert_eq!(peak_in_flight(limiter, credentials).await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_per_credential_limit() {
+        let limiter = Arc::new(ConcurrencyLimiter::new(10, 1));
+        let id = Uuid::new_v4();
+
+        let start = std::time::Instant::now();
+        assert_eq!(peak_in_flight(limiter.clone(), vec![id; 4]).await, 1);
+        // Serialized: four 20ms operations back to back
+        assert!(start.elapsed() >= Duration::from_millis(80));
+
+        // Different credentials don't wait on each other
+        let others = (0..4).map(|_| Uuid::new_v4()).collect();
+        assert_eq!(peak_in_flight(limiter, others).await, 4);
+    }
+}
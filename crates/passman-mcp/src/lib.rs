@@ -1,5 +1,12 @@
+pub mod approval;
+pub mod concurrency;
+pub mod metrics;
 pub mod policy;
+pub mod prompts;
+pub mod resources;
+pub mod scope;
 pub mod server;
 pub mod tools;
 
+pub use scope::Scope;
 pub use server::PassmanServer;
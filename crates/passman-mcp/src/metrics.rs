@@ -0,0 +1,181 @@
+//! Operational metrics for the MCP server: per-tool call counters and
+//! latency histograms, rendered in the Prometheus text exposition format.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds, in seconds, of the tool latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Default)]
+struct ToolStats {
+    invocations: u64,
+    successes: u64,
+    failures: u64,
+    policy_denials: u64,
+    rate_limit_hits: u64,
+    /// Calls at or under each `LATENCY_BUCKETS` bound (not cumulative).
+    latency_buckets: [u64; LATENCY_BUCKETS.len()],
+    latency_sum: f64,
+}
+
+/// Counters shared by every session of a server.
+#[derive(Default)]
+pub struct Metrics {
+    tools: Mutex<BTreeMap<String, ToolStats>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_tool(&self, tool: &str, f: impl FnOnce(&mut ToolStats)) {
+        let mut tools = self.tools.lock().unwrap();
+        f(tools.entry(tool.to_string()).or_default());
+    }
+
+    /// Count a finished tool call. A call that returned a tool error
+    /// (including a policy denial) counts as a failure.
+    pub fn record_call(&self, tool: &str, success: bool, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        self.with_tool(tool, |stats| {
+            stats.invocations += 1;
+            if success {
+                stats.successes += 1;
+            } else {
+                stats.failures += 1;
+            }
+            if let Some(i) = LATENCY_BUCKETS.iter().position(|bound| secs <= *bound) {
+                stats.latency_buckets[i] += 1;
+            }
+            stats.latency_sum += secs;
+        });
+    }
+
+    /// Count a call refused by the credential's policy.
+    pub fn record_policy_denial(&self, tool: &str) {
+        self.with_tool(tool, |stats| stats.policy_denials += 1);
+    }
+
+    /// Count a call refused by a rate limit.
+    pub fn record_rate_limit_hit(&self, tool: &str) {
+        self.with_tool(tool, |stats| stats.rate_limit_hits += 1);
+    }
+
+    /// All metrics in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let tools = self.tools.lock().unwrap();
+        let mut out = String::new();
+
+        write_counter(
+            &mut out,
+            &tools,
+            "passman_tool_invocations_total",
+            "Tool calls",
+            |s| s.invocations,
+        );
+        write_counter(
+            &mut out,
+            &tools,
+            "passman_tool_successes_total",
+            "Tool calls that succeeded",
+            |s| s.successes,
+        );
+        write_counter(
+            &mut out,
+            &tools,
+            "passman_tool_failures_total",
+            "Tool calls that returned an error",
+            |s| s.failures,
+        );
+        write_counter(
+            &mut out,
+            &tools,
+            "passman_policy_denials_total",
+            "Tool calls denied by policy",
+            |s| s.policy_denials,
+        );
+        write_counter(
+            &mut out,
+            &tools,
+            "passman_rate_limit_hits_total",
+            "Tool calls refused by a rate limit",
+            |s| s.rate_limit_hits,
+        );
+
+        let name = "passman_tool_duration_seconds";
+        let _ = writeln!(out, "# HELP {name} Tool call latency.");
+        let _ = writeln!(out, "# TYPE {name} histogram");
+        for (tool, stats) in tools.iter() {
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(stats.latency_buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "{name}_bucket{{tool=\"{tool}\",le=\"{bound}\"}} {cumulative}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "{name}_bucket{{tool=\"{tool}\",le=\"+Inf\"}} {}",
+                stats.invocations
+            );
+            let _ = writeln!(out, "{name}_sum{{tool=\"{tool}\"}} {}", stats.latency_sum);
+            let _ = writeln!(out, "{name}_count{{tool=\"{tool}\"}} {}", stats.invocations);
+        }
+
+        out
+    }
+}
+
+fn write_counter(
+    out: &mut String,
+    tools: &BTreeMap<String, ToolStats>,
+    name: &str,
+    help: &str,
+    value: fn(&ToolStats) -> u64,
+) {
+    let _ = writeln!(out, "# HELP {name} {help}.");
+    let _ = writeln!(out, "# TYPE {name} counter");
+    for (tool, stats) in tools {
+        let _ = writeln!(out, "{name}{{tool=\"{tool}\"}} {}", value(stats));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counts_and_buckets() {
+        let metrics = Metrics::new();
+        metrics.record_call("sql_query", true, Duration::from_millis(20));
+        metrics.record_call("sql_query", false, Duration::from_secs(30));
+        metrics.record_policy_denial("sql_query");
+        metrics.record_rate_limit_hit("http_request");
+
+        let text = metrics.render();
+        assert!(text.contains("passman_tool_invocations_total{tool=\"sql_query\"} 2\n"));
+        assert!(text.contains("passman_tool_successes_total{tool=\"sql_query\"} 1\n"));
+        assert!(text.contains("passman_tool_failures_total{tool=\"sql_query\"} 1\n"));
+        assert!(text.contains("passman_policy_denials_total{tool=\"sql_query\"} 1\n"));
+        assert!(text.contains("passman_rate_limit_hits_total{tool=\"http_request\"} 1\n"));
+        assert!(text.contains("# TYPE passman_tool_duration_seconds histogram\n"));
+        let bucket = |le: &str, count: u64| {
+            format!(
+                "passman_tool_duration_seconds_bucket{{tool=\"sql_query\",le=\"{le}\"}} {count}\n"
+            )
+        };
+        // 20ms lands in the 0.025 bucket; the 30s call only in +Inf
+        assert!(text.contains(&bucket("0.01", 0)));
+        assert!(text.contains(&bucket("0.025", 1)));
+        assert!(text.contains(&bucket("10", 1)));
+        assert!(text.contains(&bucket("+Inf", 2)));
+        assert!(text.contains("passman_tool_duration_seconds_count{tool=\"sql_query\"} 2\n"));
+    }
+}
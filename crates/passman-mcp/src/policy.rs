@@ -1,4 +1,5 @@
-use passman_types::PolicyRule;
+use chrono::{DateTime, Datelike, NaiveTime, Utc};
+use passman_types::{PolicyRule, TimeWindow};
 use std::collections::HashMap;
 use std::time::Instant;
 use tokio::sync::Mutex;
@@ -6,7 +7,14 @@ use uuid::Uuid;
 
 /// Policy engine that evaluates per-credential rules and rate limits.
 pub struct PolicyEngine {
-    rate_counters: Mutex<HashMap<Uuid, Vec<Instant>>>,
+    /// Request timestamps per (credential, tool), so tools don't share a window.
+    rate_counters: Mutex<HashMap<(Uuid, String), Vec<Instant>>>,
+}
+
+impl Default for PolicyEngine {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl PolicyEngine {
@@ -18,8 +26,7 @@ impl PolicyEngine {
 
     /// Check if a tool is allowed for the given credential.
     pub fn check_tool(&self, policy: &PolicyRule, tool_name: &str) -> Result<(), PolicyDenied> {
-        if !policy.allowed_tools.is_empty()
-            && !policy.allowed_tools.iter().any(|t| t == tool_name)
+        if !policy.allowed_tools.is_empty() && !policy.allowed_tools.iter().any(|t| t == tool_name)
         {
             return Err(PolicyDenied(format!(
                 "tool '{}' not allowed for this credential",
@@ -32,6 +39,11 @@ impl PolicyEngine {
     /// Check if a URL matches the HTTP URL patterns.
     pub fn check_http_url(&self, policy: &PolicyRule, url: &str) -> Result<(), PolicyDenied> {
         if policy.http_url_patterns.is_empty() {
+            if policy.http_default_deny {
+                return Err(PolicyDenied(
+                    "no URLs are allowed for this credential".to_string(),
+                ));
+            }
             return Ok(());
         }
 
@@ -41,10 +53,7 @@ impl PolicyEngine {
             }
         }
 
-        Err(PolicyDenied(format!(
-            "URL '{}' not allowed by policy",
-            url
-        )))
+        Err(PolicyDenied(format!("URL '{}' not allowed by policy", url)))
     }
 
     /// Check if an SSH command matches allowed patterns.
@@ -54,6 +63,11 @@ impl PolicyEngine {
         command: &str,
     ) -> Result<(), PolicyDenied> {
         if policy.ssh_command_patterns.is_empty() {
+            if policy.ssh_default_deny {
+                return Err(PolicyDenied(
+                    "no SSH commands are allowed for this credential".to_string(),
+                ));
+            }
             return Ok(());
         }
 
@@ -63,19 +77,41 @@ impl PolicyEngine {
             }
         }
 
-        Err(PolicyDenied(format!(
-            "SSH command not allowed by policy"
-        )))
+        Err(PolicyDenied(
+            "SSH command not allowed by policy".to_string(),
+        ))
     }
 
-    /// Check if a SQL query is allowed (read-only enforcement).
+    /// Check if a SQL query is allowed: it must not touch a forbidden table or
+    /// keyword, and must be read-only unless the policy allows writes.
     pub fn check_sql_query(&self, policy: &PolicyRule, query: &str) -> Result<(), PolicyDenied> {
+        if !policy.forbidden_sql_patterns.is_empty() {
+            // MySQL and Postgres `E'...'` strings treat `\` as an escape and
+            // standard SQL doesn't, so a match under either reading counts
+            for backslash_escapes in [false, true] {
+                let words = sql_words(query, backslash_escapes).ok_or_else(|| {
+                    PolicyDenied(
+                        "query has an unterminated string or quoted identifier".to_string(),
+                    )
+                })?;
+                for pattern in &policy.forbidden_sql_patterns {
+                    if sql_matches_pattern(&words, pattern) {
+                        return Err(PolicyDenied(format!(
+                            "query touches '{pattern}', which is forbidden for this credential"
+                        )));
+                    }
+                }
+            }
+        }
+
         if policy.sql_allow_write {
             return Ok(());
         }
 
         let trimmed = query.trim().to_uppercase();
-        let write_keywords = ["INSERT", "UPDATE", "DELETE", "DROP", "ALTER", "CREATE", "TRUNCATE", "REPLACE", "MERGE"];
+        let write_keywords = [
+            "INSERT", "UPDATE", "DELETE", "DROP", "ALTER", "CREATE", "TRUNCATE", "REPLACE", "MERGE",
+        ];
 
         for keyword in &write_keywords {
             if trimmed.starts_with(keyword) {
@@ -89,6 +125,26 @@ impl PolicyEngine {
         Ok(())
     }
 
+    /// Check if a MongoDB operation is allowed (read-only enforcement).
+    pub fn check_mongo_operation(
+        &self,
+        policy: &PolicyRule,
+        operation: &str,
+    ) -> Result<(), PolicyDenied> {
+        if policy.mongo_allow_write {
+            return Ok(());
+        }
+
+        let read_operations = ["find", "count", "aggregate", "distinct"];
+        if !read_operations.contains(&operation) {
+            return Err(PolicyDenied(format!(
+                "write operations not allowed for this credential ({operation})"
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Check if an email recipient is allowed.
     pub fn check_smtp_recipient(
         &self,
@@ -111,16 +167,60 @@ impl PolicyEngine {
         )))
     }
 
-    /// Check and increment the rate limit counter.
-    pub async fn check_rate_limit(&self, policy: &PolicyRule) -> Result<(), PolicyDenied> {
-        let rate_limit = match &policy.rate_limit {
+    /// Check if a host may be connected to by `credential_ping`.
+    pub fn check_host(&self, policy: &PolicyRule, host: &str) -> Result<(), PolicyDenied> {
+        if policy.allowed_hosts.is_empty() {
+            return Ok(());
+        }
+
+        let host = host.to_lowercase();
+        for pattern in &policy.allowed_hosts {
+            if glob_match(&host, &pattern.to_lowercase()) {
+                return Ok(());
+            }
+        }
+
+        Err(PolicyDenied(format!("host '{host}' not allowed by policy")))
+    }
+
+    /// Check that `now` falls inside at least one of the policy's time windows.
+    pub fn check_time_window(
+        &self,
+        policy: &PolicyRule,
+        now: DateTime<Utc>,
+    ) -> Result<(), PolicyDenied> {
+        if policy.time_windows.is_empty() {
+            return Ok(());
+        }
+
+        if policy.time_windows.iter().any(|w| window_contains(w, now)) {
+            return Ok(());
+        }
+
+        Err(PolicyDenied(
+            "access not allowed at this time (outside all policy time windows)".to_string(),
+        ))
+    }
+
+    /// Check and increment the rate limit counter for a tool. Uses the tool's
+    /// entry in `per_tool_limits` if present, otherwise the policy-wide `rate_limit`.
+    pub async fn check_rate_limit(
+        &self,
+        policy: &PolicyRule,
+        tool_name: &str,
+    ) -> Result<(), PolicyDenied> {
+        let rate_limit = match policy
+            .per_tool_limits
+            .get(tool_name)
+            .or(policy.rate_limit.as_ref())
+        {
             Some(rl) => rl,
             None => return Ok(()),
         };
 
         let mut counters = self.rate_counters.lock().await;
         let entries = counters
-            .entry(policy.credential_id)
+            .entry((policy.credential_id, tool_name.to_string()))
             .or_insert_with(Vec::new);
 
         let window = std::time::Duration::from_secs(rate_limit.window_secs);
@@ -152,6 +252,31 @@ impl std::fmt::Display for PolicyDenied {
     }
 }
 
+/// Whether `now` falls inside the window. Malformed windows never match.
+fn window_contains(window: &TimeWindow, now: DateTime<Utc>) -> bool {
+    let Ok(tz) = window.timezone.parse::<chrono_tz::Tz>() else {
+        return false;
+    };
+    let (Ok(start), Ok(end)) = (
+        NaiveTime::parse_from_str(&window.start, "%H:%M"),
+        NaiveTime::parse_from_str(&window.end, "%H:%M"),
+    ) else {
+        return false;
+    };
+
+    let local = now.with_timezone(&tz);
+    let time = local.time();
+    let today = local.weekday();
+    let on_day = |d: chrono::Weekday| window.days.is_empty() || window.days.contains(&d);
+
+    if start <= end {
+        on_day(today) && time >= start && time < end
+    } else {
+        // Crosses midnight: the late part belongs to today, the early part to yesterday
+        (on_day(today) && time >= start) || (on_day(today.pred()) && time < end)
+    }
+}
+
 /// Simple wildcard pattern matching for URLs (supports * as glob).
 fn url_matches_pattern(url: &str, pattern: &str) -> bool {
     glob_match(url, pattern)
@@ -167,6 +292,86 @@ fn email_matches_pattern(email: &str, pattern: &str) -> bool {
     glob_match(email, pattern)
 }
 
+/// The identifiers and keywords of a SQL query, lowercased, in order. String
+/// literals and comments are skipped, quoted identifiers are unquoted, and
+/// qualified names are split into their parts (`public.users` → `public`, `users`).
+/// With `backslash_escapes`, `\` inside a literal escapes the next character.
+/// `None` if a literal or quoted identifier is never closed, since the tail
+/// it would swallow can't be checked.
+fn sql_words(query: &str, backslash_escapes: bool) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                // Literal; '' is an escaped quote and just reopens it
+                loop {
+                    match chars.next()? {
+                        '\\' if backslash_escapes => {
+                            chars.next()?;
+                        }
+                        '\'' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '"' | '`' | '[' => {
+                let close = if c == '[' { ']' } else { c };
+                let mut word = String::new();
+                loop {
+                    match chars.next()? {
+                        c if c == close => break,
+                        c => word.push(c),
+                    }
+                }
+                words.push(word.to_lowercase());
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                chars.find(|&c| c == '\n');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut word = c.to_lowercase().to_string();
+                while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_') {
+                    word.extend(c.to_lowercase());
+                    chars.next();
+                }
+                words.push(word);
+            }
+            _ => {}
+        }
+    }
+
+    Some(words)
+}
+
+/// Whether `words` contains the pattern's words consecutively, e.g.
+/// `drop database` or `public.schema_migrations`. Each word may use `*`.
+fn sql_matches_pattern(words: &[String], pattern: &str) -> bool {
+    let pattern: Vec<String> = pattern
+        .split(|c: char| c.is_whitespace() || c == '.')
+        .filter(|p| !p.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    if pattern.is_empty() {
+        return false;
+    }
+
+    words
+        .windows(pattern.len())
+        .any(|w| w.iter().zip(&pattern).all(|(word, p)| glob_match(word, p)))
+}
+
 /// Basic glob matching with * wildcard support.
 fn glob_match(text: &str, pattern: &str) -> bool {
     let parts: Vec<&str> = pattern.split('*').collect();
@@ -214,7 +419,10 @@ mod tests {
 
     #[test]
     fn test_glob_match() {
-        assert!(glob_match("https://api.github.com/repos", "https://api.github.com/*"));
+        assert!(glob_match(
+            "https://api.github.com/repos",
+            "https://api.github.com/*"
+        ));
         assert!(glob_match("ls -la /tmp", "ls *"));
         assert!(glob_match("user@company.com", "*@company.com"));
         assert!(!glob_match("user@other.com", "*@company.com"));
@@ -230,19 +438,233 @@ mod tests {
             credential_id: Uuid::new_v4(),
             allowed_tools: vec![],
             http_url_patterns: vec![],
+            http_default_deny: false,
             ssh_command_patterns: vec![],
+            ssh_default_deny: false,
             sql_allow_write: false,
+            forbidden_sql_patterns: vec![],
             smtp_allowed_recipients: vec![],
+            mongo_allow_write: false,
+            allowed_hosts: vec![],
+            time_windows: vec![],
+            require_approval: false,
             rate_limit: None,
+            per_tool_limits: Default::default(),
         };
 
-        assert!(engine.check_sql_query(&policy, "SELECT * FROM users").is_ok());
-        assert!(engine.check_sql_query(&policy, "select count(*) from users").is_ok());
-        assert!(engine.check_sql_query(&policy, "INSERT INTO users VALUES (1)").is_err());
-        assert!(engine.check_sql_query(&policy, "DELETE FROM users").is_err());
+        assert!(engine
+            .check_sql_query(&policy, "SELECT * FROM users")
+            .is_ok());
+        assert!(engine
+            .check_sql_query(&policy, "select count(*) from users")
+            .is_ok());
+        assert!(engine
+            .check_sql_query(&policy, "INSERT INTO users VALUES (1)")
+            .is_err());
+        assert!(engine
+            .check_sql_query(&policy, "DELETE FROM users")
+            .is_err());
         assert!(engine.check_sql_query(&policy, "DROP TABLE users").is_err());
     }
 
+    #[test]
+    fn test_forbidden_sql_patterns() {
+        let engine = PolicyEngine::new();
+        let mut policy = policy_with_windows(vec![]);
+        policy.sql_allow_write = true;
+        policy.forbidden_sql_patterns =
+            vec!["schema_migrations".to_string(), "DROP DATABASE".to_string()];
+
+        // Denied even for a read, and through quoting or a schema prefix
+        for query in [
+            "SELECT * FROM schema_migrations",
+            "select version from public.\"Schema_Migrations\"",
+            "DELETE FROM `schema_migrations` WHERE 1=1",
+            "drop  database app",
+        ] {
+            assert!(engine.check_sql_query(&policy, query).is_err(), "{query}");
+        }
+
+        // Literals, comments and longer identifiers don't match
+        for query in [
+            "SELECT * FROM users",
+            "SELECT 'schema_migrations' AS name",
+            "SELECT 1 -- schema_migrations\n",
+            "SELECT * FROM schema_migrations_backup",
+            "DROP TABLE sessions /* not the database */",
+        ] {
+            assert!(engine.check_sql_query(&policy, query).is_ok(), "{query}");
+        }
+
+        // Backslash escapes (MySQL) and standard literals (Postgres) can't
+        // hide a table; nor can a literal or identifier that never closes
+        for query in [
+            "SELECT 'a\\'', * FROM schema_migrations",
+            "SELECT 'a\\' , * FROM schema_migrations -- '",
+            "SELECT * FROM users WHERE name = 'x",
+            "SELECT * FROM \"users",
+        ] {
+            assert!(engine.check_sql_query(&policy, query).is_err(), "{query}");
+        }
+        assert!(engine
+            .check_sql_query(&policy, "SELECT 'it''s', 'C:\\\\dir' FROM users")
+            .is_ok());
+
+        policy.forbidden_sql_patterns = vec!["audit_*".to_string()];
+        assert!(engine
+            .check_sql_query(&policy, "SELECT * FROM audit_log")
+            .is_err());
+    }
+
+    #[test]
+    fn test_mongo_read_only() {
+        let engine = PolicyEngine::new();
+        let mut policy = PolicyRule {
+            credential_id: Uuid::new_v4(),
+            allowed_tools: vec![],
+            http_url_patterns: vec![],
+            http_default_deny: false,
+            ssh_command_patterns: vec![],
+            ssh_default_deny: false,
+            sql_allow_write: false,
+            forbidden_sql_patterns: vec![],
+            smtp_allowed_recipients: vec![],
+            mongo_allow_write: false,
+            allowed_hosts: vec![],
+            time_windows: vec![],
+            require_approval: false,
+            rate_limit: None,
+            per_tool_limits: Default::default(),
+        };
+
+        assert!(engine.check_mongo_operation(&policy, "find").is_ok());
+        assert!(engine.check_mongo_operation(&policy, "delete").is_err());
+
+        policy.mongo_allow_write = true;
+        assert!(engine.check_mongo_operation(&policy, "delete").is_ok());
+    }
+
+    #[test]
+    fn test_empty_patterns_default_deny() {
+        let engine = PolicyEngine::new();
+        let mut policy = policy_with_windows(vec![]);
+
+        // Empty lists allow everything unless default-deny is on
+        assert!(engine.check_ssh_command(&policy, "rm -rf /tmp/x").is_ok());
+        assert!(engine
+            .check_http_url(&policy, "https://example.com/")
+            .is_ok());
+
+        policy.ssh_default_deny = true;
+        policy.http_default_deny = true;
+        assert!(engine.check_ssh_command(&policy, "ls").is_err());
+        assert!(engine
+            .check_http_url(&policy, "https://example.com/")
+            .is_err());
+
+        // Listed entries are still allowed
+        policy.ssh_command_patterns = vec!["uptime".into()];
+        policy.http_url_patterns = vec!["https://example.com/*".into()];
+        assert!(engine.check_ssh_command(&policy, "uptime").is_ok());
+        assert!(engine.check_ssh_command(&policy, "ls").is_err());
+        assert!(engine
+            .check_http_url(&policy, "https://example.com/health")
+            .is_ok());
+    }
+
+    fn policy_with_windows(time_windows: Vec<TimeWindow>) -> PolicyRule {
+        PolicyRule {
+            credential_id: Uuid::new_v4(),
+            allowed_tools: vec![],
+            http_url_patterns: vec![],
+            http_default_deny: false,
+            ssh_command_patterns: vec![],
+            ssh_default_deny: false,
+            sql_allow_write: false,
+            forbidden_sql_patterns: vec![],
+            smtp_allowed_recipients: vec![],
+            mongo_allow_write: false,
+            allowed_hosts: vec![],
+            time_windows,
+            require_approval: false,
+            rate_limit: None,
+            per_tool_limits: Default::default(),
+        }
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_time_window_business_hours() {
+        use chrono::Weekday::*;
+        let engine = PolicyEngine::new();
+        let policy = policy_with_windows(vec![TimeWindow {
+            days: vec![Mon, Tue, Wed, Thu, Fri],
+            start: "09:00".into(),
+            end: "17:00".into(),
+            timezone: "Europe/Berlin".into(),
+        }]);
+
+        // 2026-03-04 is a Wednesday; Berlin is UTC+1 in March
+        assert!(engine
+            .check_time_window(&policy, at("2026-03-04T10:00:00Z"))
+            .is_ok());
+        assert!(engine
+            .check_time_window(&policy, at("2026-03-04T17:30:00Z"))
+            .is_err());
+        // Saturday
+        assert!(engine
+            .check_time_window(&policy, at("2026-03-07T10:00:00Z"))
+            .is_err());
+        // No windows = always allowed
+        let open = policy_with_windows(vec![]);
+        assert!(engine
+            .check_time_window(&open, at("2026-03-07T03:00:00Z"))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_time_window_cross_midnight() {
+        use chrono::Weekday::*;
+        let engine = PolicyEngine::new();
+        let policy = policy_with_windows(vec![TimeWindow {
+            days: vec![Fri],
+            start: "22:00".into(),
+            end: "02:00".into(),
+            timezone: "UTC".into(),
+        }]);
+
+        // Friday 23:00 and the following Saturday 01:00 are inside
+        assert!(engine
+            .check_time_window(&policy, at("2026-03-06T23:00:00Z"))
+            .is_ok());
+        assert!(engine
+            .check_time_window(&policy, at("2026-03-07T01:00:00Z"))
+            .is_ok());
+        // Saturday 23:00 and Friday 01:00 are not
+        assert!(engine
+            .check_time_window(&policy, at("2026-03-07T23:00:00Z"))
+            .is_err());
+        assert!(engine
+            .check_time_window(&policy, at("2026-03-06T01:00:00Z"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_allowed_hosts() {
+        let engine = PolicyEngine::new();
+        let mut policy = policy_with_windows(vec![]);
+        assert!(engine.check_host(&policy, "db.example.com").is_ok());
+
+        policy.allowed_hosts = vec!["*.internal".to_string(), "10.0.0.5".to_string()];
+        assert!(engine.check_host(&policy, "db.internal").is_ok());
+        assert!(engine.check_host(&policy, "DB.Internal").is_ok());
+        assert!(engine.check_host(&policy, "10.0.0.5").is_ok());
+        assert!(engine.check_host(&policy, "db.example.com").is_err());
+    }
+
     #[tokio::test]
     async fn test_rate_limit() {
         let engine = PolicyEngine::new();
@@ -250,17 +672,67 @@ mod tests {
             credential_id: Uuid::new_v4(),
             allowed_tools: vec![],
             http_url_patterns: vec![],
+            http_default_deny: false,
             ssh_command_patterns: vec![],
+            ssh_default_deny: false,
             sql_allow_write: false,
+            forbidden_sql_patterns: vec![],
             smtp_allowed_recipients: vec![],
+            mongo_allow_write: false,
+            allowed_hosts: vec![],
+            time_windows: vec![],
+            require_approval: false,
             rate_limit: Some(passman_types::RateLimit {
                 max_requests: 2,
                 window_secs: 3600,
             }),
+            per_tool_limits: Default::default(),
         };
 
-        assert!(engine.check_rate_limit(&policy).await.is_ok());
-        assert!(engine.check_rate_limit(&policy).await.is_ok());
-        assert!(engine.check_rate_limit(&policy).await.is_err()); // exceeded
+        assert!(engine
+            .check_rate_limit(&policy, "http_request")
+            .await
+            .is_ok());
+        assert!(engine
+            .check_rate_limit(&policy, "http_request")
+            .await
+            .is_ok());
+        assert!(engine
+            .check_rate_limit(&policy, "http_request")
+            .await
+            .is_err()); // exceeded
+    }
+
+    #[tokio::test]
+    async fn test_per_tool_rate_limit() {
+        let engine = PolicyEngine::new();
+        let mut policy = policy_with_windows(vec![]);
+        policy.rate_limit = Some(passman_types::RateLimit {
+            max_requests: 3,
+            window_secs: 3600,
+        });
+        policy.per_tool_limits.insert(
+            "http_request".to_string(),
+            passman_types::RateLimit {
+                max_requests: 1,
+                window_secs: 3600,
+            },
+        );
+
+        // HTTP uses its own limit of 1
+        assert!(engine
+            .check_rate_limit(&policy, "http_request")
+            .await
+            .is_ok());
+        assert!(engine
+            .check_rate_limit(&policy, "http_request")
+            .await
+            .is_err());
+
+        // SQL still passes under the same credential, using the global fallback
+        for _ in 0..3 {
+            assert!(engine.check_rate_limit(&policy, "sql_query").await.is_ok());
+        }
+        assert!(engine.check_rate_limit(&policy, "sql_query").await.is_err());
     }
 }
@@ -0,0 +1,147 @@
+//! Curated MCP prompts for common credential workflows. Each prompt expands
+//! into step-by-step instructions that reference the right Passman tools.
+
+use rmcp::model::{
+    GetPromptResult, JsonObject, Prompt, PromptArgument, PromptMessage, PromptMessageRole,
+};
+use rmcp::ErrorData as McpError;
+
+struct PromptTemplate {
+    name: &'static str,
+    description: &'static str,
+    /// (name, description, required)
+    arguments: &'static [(&'static str, &'static str, bool)],
+    template: &'static str,
+}
+
+const PROMPTS: &[PromptTemplate] = &[
+    PromptTemplate {
+        name: "rotate_credential",
+        description: "Rotate a stored credential and verify the new secret works",
+        arguments: &[
+            ("credential_id", "UUID of the credential to rotate", true),
+            ("reason", "Why the credential is being rotated", false),
+        ],
+        template: "Rotate the credential {credential_id}. Reason: {reason}.\n\n\
+                   1. Call credential_info with id {credential_id} to confirm its kind, environment and host.\n\
+                   2. Create the replacement secret at the provider. Use the matching proxy tool \
+                   (http_request, ssh_exec, sql_query) with credential {credential_id} so the current \
+                   secret is never exposed.\n\
+                   3. Call credential_update on {credential_id} with the new secret. Do not use \
+                   credential_store; it would create a duplicate.\n\
+                   4. Verify the new secret with a harmless read through the same proxy tool.\n\
+                   5. Revoke the old secret at the provider, then review audit_log for {credential_id}.",
+    },
+    PromptTemplate {
+        name: "read_only_report",
+        description: "Safely run a read-only report against a database credential",
+        arguments: &[
+            ("credential_id", "UUID of the database_connection credential", true),
+            ("question", "What the report should answer", true),
+        ],
+        template: "Answer this question from the database behind credential {credential_id}: {question}\n\n\
+                   1. Call credential_info with id {credential_id} and check its environment. Be extra \
+                   careful if it is production.\n\
+                   2. Explore the schema with sql_query using only SELECT statements (for example \
+                   information_schema queries).\n\
+                   3. Run SELECT queries only. Never issue INSERT, UPDATE, DELETE, DDL or other writes; \
+                   the policy may reject them and they are out of scope.\n\
+                   4. Add LIMIT clauses to keep result sets small, and aggregate in SQL where possible.\n\
+                   5. Summarize the answer and list the queries you ran.",
+    },
+    PromptTemplate {
+        name: "audit_review",
+        description: "Review recent usage of a credential for anything unexpected",
+        arguments: &[("credential_id", "UUID of the credential to review", true)],
+        template: "Review recent usage of credential {credential_id}.\n\n\
+                   1. Call credential_info with id {credential_id} for context.\n\
+                   2. Call audit_log with credential_id {credential_id} and a limit of 100.\n\
+                   3. Flag failed operations, unusual tools, and activity at unusual times.\n\
+                   4. Summarize your findings and suggest policy changes if anything looks wrong.",
+    },
+];
+
+/// All available prompts.
+pub fn list() -> Vec<Prompt> {
+    PROMPTS
+        .iter()
+        .map(|p| {
+            let arguments = p
+                .arguments
+                .iter()
+                .map(|(name, description, required)| PromptArgument {
+                    name: name.to_string(),
+                    title: None,
+                    description: Some(description.to_string()),
+                    required: Some(*required),
+                })
+                .collect();
+            Prompt::new(p.name, Some(p.description), Some(arguments))
+        })
+        .collect()
+}
+
+/// Expand a prompt by name, substituting its `{argument}` placeholders.
+pub fn get(name: &str, arguments: Option<&JsonObject>) -> Result<GetPromptResult, McpError> {
+    let prompt = PROMPTS
+        .iter()
+        .find(|p| p.name == name)
+        .ok_or_else(|| McpError::invalid_params(format!("unknown prompt: {name}"), None))?;
+
+    let mut text = prompt.template.to_string();
+    for (arg, _, required) in prompt.arguments {
+        let value = arguments
+            .and_then(|args| args.get(*arg))
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty());
+        let value = match value {
+            Some(v) => v,
+            None if *required => {
+                return Err(McpError::invalid_params(
+                    format!("missing required argument: {arg}"),
+                    None,
+                ));
+            }
+            None => "not given",
+        };
+        text = text.replace(&format!("{{{arg}}}"), value);
+    }
+
+    Ok(GetPromptResult {
+        description: Some(prompt.description.to_string()),
+        messages: vec![PromptMessage::new_text(PromptMessageRole::User, text)],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::PromptMessageContent;
+
+    #[test]
+    fn test_list_prompts() {
+        let prompts = list();
+        assert!(!prompts.is_empty());
+        assert!(prompts.iter().any(|p| p.name == "rotate_credential"));
+    }
+
+    #[test]
+    fn test_get_prompt_substitutes_arguments() {
+        let mut args = JsonObject::new();
+        args.insert(
+            "credential_id".into(),
+            "6f1c2a7e-0000-4000-8000-000000000001".into(),
+        );
+
+        let result = get("rotate_credential", Some(&args)).unwrap();
+        let PromptMessageContent::Text { text } = &result.messages[0].content else {
+            panic!("expected text message");
+        };
+        assert!(text.contains("6f1c2a7e-0000-4000-8000-000000000001"));
+        assert!(text.contains("Reason: not given"));
+        assert!(!text.contains('{'));
+
+        assert!(get("rotate_credential", None).is_err());
+        assert!(get("no_such_prompt", Some(&args)).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! Read-only MCP resources: one per credential (metadata only, never
+//! secrets) plus the recent audit log. Nothing is listed while locked.
+
+use crate::server::PassmanServer;
+use rmcp::model::{AnnotateAble, RawResource, ReadResourceResult, Resource, ResourceContents};
+use rmcp::ErrorData as McpError;
+
+const CREDENTIAL_PREFIX: &str = "passman://credential/";
+const AUDIT_URI: &str = "passman://audit";
+
+/// Number of audit entries served by the audit resource.
+const AUDIT_RESOURCE_LIMIT: usize = 100;
+
+pub fn credential_uri(id: uuid::Uuid) -> String {
+    format!("{CREDENTIAL_PREFIX}{id}")
+}
+
+/// All resources currently available. Empty while the vault is locked.
+pub async fn list(server: &PassmanServer) -> Result<Vec<Resource>, McpError> {
+    if !server.vault.is_unlocked().await {
+        return Ok(vec![]);
+    }
+
+    let creds = server
+        .vault
+        .list_credentials(None, None, None, None)
+        .await
+        .map_err(|e| McpError::internal_error(format!("{e}"), None))?;
+
+    let mut resources: Vec<Resource> = creds
+        .iter()
+        .filter(|c| server.scope.allows(c))
+        .map(|c| {
+            let mut raw = RawResource::new(credential_uri(c.id), c.name.clone());
+            raw.description = Some(format!("{} credential ({})", c.kind, c.environment));
+            raw.mime_type = Some("application/json".to_string());
+            raw.no_annotation()
+        })
+        .collect();
+
+    let mut audit = RawResource::new(AUDIT_URI, "Audit log");
+    audit.description = Some(format!(
+        "The {AUDIT_RESOURCE_LIMIT} most recent audit entries"
+    ));
+    audit.mime_type = Some("application/json".to_string());
+    resources.push(audit.no_annotation());
+
+    Ok(resources)
+}
+
+/// Read a single resource by URI.
+pub async fn read(server: &PassmanServer, uri: &str) -> Result<ReadResourceResult, McpError> {
+    if !server.vault.is_unlocked().await {
+        return Err(McpError::invalid_request(
+            crate::tools::VAULT_LOCKED_MESSAGE,
+            None,
+        ));
+    }
+
+    let json = if uri == AUDIT_URI {
+        let internal = |e| McpError::internal_error(format!("{e}"), None);
+        let visible = crate::tools::visible_ids(server).await.map_err(internal)?;
+        let limit = if visible.is_some() {
+            None
+        } else {
+            Some(AUDIT_RESOURCE_LIMIT)
+        };
+        let mut entries = server
+            .vault
+            .read_audit(&Default::default(), limit)
+            .await
+            .map_err(internal)?;
+        if let Some(ids) = visible {
+            entries.retain(|e| e.credential_id.is_none_or(|id| ids.contains(&id)));
+            entries.truncate(AUDIT_RESOURCE_LIMIT);
+        }
+        serde_json::to_string(&entries).unwrap()
+    } else if let Some(id) = uri.strip_prefix(CREDENTIAL_PREFIX) {
+        let id: uuid::Uuid = id
+            .parse()
+            .map_err(|_| McpError::invalid_params("invalid UUID", None))?;
+        let meta = server
+            .vault
+            .get_credential_meta(id)
+            .await
+            .ok()
+            .filter(|m| server.scope.allows(m))
+            .ok_or_else(|| {
+                McpError::resource_not_found(
+                    format!("{}", passman_vault::VaultError::NotFound(id)),
+                    None,
+                )
+            })?;
+        serde_json::to_string(&meta).unwrap()
+    } else {
+        return Err(McpError::resource_not_found(
+            format!("unknown resource: {uri}"),
+            None,
+        ));
+    };
+
+    Ok(ReadResourceResult {
+        contents: vec![ResourceContents::text(json, uri)],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use passman_types::{CredentialKind, CredentialSecret, Environment};
+    use passman_vault::Vault;
+
+    async fn store_token(vault: &Vault, name: &str, token: &str) -> uuid::Uuid {
+        vault
+            .store_credential(
+                name.into(),
+                CredentialKind::ApiToken,
+                Environment::Local,
+                vec![],
+                None,
+                &CredentialSecret::ApiToken {
+                    token: token.into(),
+                    header_name: None,
+                    prefix: None,
+                },
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_list_and_read_resources() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault = Vault::new(
+            dir.path().join("vault.json"),
+            dir.path().join("audit.jsonl"),
+        );
+        vault.create("resources-test-pw", false).await.unwrap();
+
+        let first = store_token(&vault, "GitHub", "ghp_resource_secret_1").await;
+        store_token(&vault, "Stripe", "sk_resource_secret_2").await;
+
+        let server = PassmanServer::new(vault.clone());
+        let resources = list(&server).await.unwrap();
+        // Two credentials plus the audit log
+        assert_eq!(resources.len(), 3);
+        assert!(resources.iter().any(|r| r.uri == credential_uri(first)));
+        assert!(resources.iter().any(|r| r.uri == AUDIT_URI));
+
+        let result = read(&server, &credential_uri(first)).await.unwrap();
+        let ResourceContents::TextResourceContents { text, .. } = &result.contents[0] else {
+            panic!("expected text contents");
+        };
+        assert!(text.contains("GitHub"));
+        assert!(!text.contains("ghp_resource_secret_1"));
+
+        vault.lock().await;
+        assert!(list(&server).await.unwrap().is_empty());
+        assert!(read(&server, &credential_uri(first)).await.is_err());
+    }
+}
@@ -0,0 +1,93 @@
+//! Limit an MCP session to a subset of the vault's credentials.
+//!
+//! Credentials outside the scope are left out of discovery results, links,
+//! audit views and resources, and proxy tools refuse to use them.
+
+use passman_types::{CredentialMeta, Environment};
+
+#[derive(Debug, Clone, Default)]
+pub struct Scope {
+    /// Credential must carry at least one of these tags (if any are set).
+    pub tags: Vec<String>,
+    /// Credential must be in one of these environments (if any are set).
+    pub environments: Vec<Environment>,
+}
+
+impl Scope {
+    /// Build a scope from command-line values. Environment names that
+    /// aren't built in are treated as custom environments.
+    pub fn from_args(tags: Vec<String>, environments: &[String]) -> Self {
+        let environments = environments
+            .iter()
+            .filter_map(|name| Environment::parse(name))
+            .collect();
+        Self { tags, environments }
+    }
+
+    /// True when the session may see every credential.
+    pub fn is_unrestricted(&self) -> bool {
+        self.tags.is_empty() && self.environments.is_empty()
+    }
+
+    pub fn allows(&self, meta: &CredentialMeta) -> bool {
+        let tag_ok = self.tags.is_empty() || meta.tags.iter().any(|t| self.tags.contains(t));
+        let env_ok = self.environments.is_empty() || self.environments.contains(&meta.environment);
+        tag_ok && env_ok
+    }
+
+    /// Human-readable summary for denial messages, e.g. `tag: billing; environment: staging`.
+    pub fn describe(&self) -> String {
+        let mut parts = vec![];
+        if !self.tags.is_empty() {
+            parts.push(format!("tag: {}", self.tags.join(", ")));
+        }
+        if !self.environments.is_empty() {
+            let envs: Vec<String> = self.environments.iter().map(|e| e.to_string()).collect();
+            parts.push(format!("environment: {}", envs.join(", ")));
+        }
+        if parts.is_empty() {
+            return "unrestricted".to_string();
+        }
+        parts.join("; ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use passman_types::CredentialKind;
+
+    fn meta(tags: &[&str], environment: Environment) -> CredentialMeta {
+        let now = chrono::Utc::now();
+        CredentialMeta {
+            id: uuid::Uuid::new_v4(),
+            name: "test".into(),
+            kind: CredentialKind::ApiToken,
+            environment,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            created_at: now,
+            updated_at: now,
+            notes: None,
+            category: None,
+            use_count: 0,
+            last_used_at: None,
+            links: Vec::new(),
+            rotation_interval_days: None,
+            last_rotated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_scope_matching() {
+        assert!(Scope::default().allows(&meta(&[], Environment::Production)));
+
+        let scope = Scope {
+            tags: vec!["billing".into()],
+            environments: vec![Environment::Staging],
+        };
+        assert!(scope.allows(&meta(&["billing", "db"], Environment::Staging)));
+        assert!(!scope.allows(&meta(&["billing"], Environment::Production)));
+        assert!(!scope.allows(&meta(&["db"], Environment::Staging)));
+        assert_eq!(scope.describe(), "tag: billing; environment: staging");
+    }
+}
@@ -1,199 +1,397 @@
+use crate::concurrency::ConcurrencyLimiter;
+use crate::metrics::Metrics;
 use crate::policy::PolicyEngine;
+use crate::scope::Scope;
 use crate::tools;
 use passman_vault::Vault;
 use rmcp::{
     handler::server::router::tool::ToolRouter,
     handler::server::wrapper::Parameters,
-    model::{CallToolResult, Content, ServerCapabilities, ServerInfo},
-    schemars, tool, tool_handler, tool_router, ErrorData as McpError, ServerHandler,
+    model::{
+        CallToolResult, GetPromptRequestParams, GetPromptResult, ListPromptsResult,
+        ListResourcesResult, PaginatedRequestParams, ReadResourceRequestParams, ReadResourceResult,
+        ServerCapabilities, ServerInfo,
+    },
+    service::RequestContext,
+    tool, tool_handler, tool_router, ErrorData as McpError, RoleServer, ServerHandler,
 };
 
+/// Tools hidden when the server runs on a read-only vault handle.
+pub const READ_ONLY_EXCLUDED_TOOLS: [&str; 4] = [
+    "credential_store",
+    "credential_update",
+    "credential_update_field",
+    "credential_delete",
+];
+
 /// The Passman MCP server. Holds the vault handle and policy engine.
 #[derive(Clone)]
 pub struct PassmanServer {
     pub vault: Vault,
     pub policy: std::sync::Arc<PolicyEngine>,
+    /// Credentials this session may see and use.
+    pub scope: Scope,
+    /// mTLS clients reused across http_request calls.
+    pub http_clients: std::sync::Arc<passman_proxy::http::ClientCache>,
+    /// Tool call counters and latencies, shared by every session.
+    pub metrics: std::sync::Arc<Metrics>,
+    /// Size caps on proxy tool output.
+    pub output_limits: tools::OutputLimits,
+    /// Caps on proxy operations in flight, shared by every session.
+    pub concurrency: std::sync::Arc<ConcurrencyLimiter>,
     tool_router: ToolRouter<Self>,
 }
 
 #[tool_router]
 impl PassmanServer {
+    /// Create the server. With a read-only vault handle the credential
+    /// mutation tools are left out of the tool list entirely.
     pub fn new(vault: Vault) -> Self {
+        let mut tool_router = Self::tool_router();
+        if vault.is_read_only() {
+            for name in READ_ONLY_EXCLUDED_TOOLS {
+                tool_router.remove_route(name);
+            }
+        }
         Self {
             vault,
             policy: std::sync::Arc::new(PolicyEngine::new()),
-            tool_router: Self::tool_router(),
+            scope: Scope::default(),
+            http_clients: Default::default(),
+            metrics: Default::default(),
+            output_limits: Default::default(),
+            concurrency: Default::default(),
+            tool_router,
         }
     }
 
+    /// Restrict the session to credentials matching `scope`.
+    pub fn with_scope(mut self, scope: Scope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Run at most `max_concurrent` proxy operations at once, and at most
+    /// `max_per_credential` with any one credential. Extra calls wait.
+    pub fn with_concurrency_limits(
+        mut self,
+        max_concurrent: usize,
+        max_per_credential: usize,
+    ) -> Self {
+        self.concurrency =
+            std::sync::Arc::new(ConcurrencyLimiter::new(max_concurrent, max_per_credential));
+        self
+    }
+
+    /// Make HTTP requests with `clients`, e.g. one built from the server's
+    /// proxy and CA settings.
+    pub fn with_http_clients(mut self, clients: passman_proxy::http::ClientCache) -> Self {
+        self.http_clients = std::sync::Arc::new(clients);
+        self
+    }
+
+    /// Cap proxy tool output at `limits`.
+    pub fn with_output_limits(mut self, limits: tools::OutputLimits) -> Self {
+        self.output_limits = limits;
+        self
+    }
+
+    /// Run a tool handler, recording the call and its latency in `metrics`.
+    async fn observe(
+        &self,
+        tool: &str,
+        call: impl std::future::Future<Output = Result<CallToolResult, McpError>>,
+    ) -> Result<CallToolResult, McpError> {
+        let start = std::time::Instant::now();
+        let result = call.await;
+        let success = matches!(&result, Ok(r) if r.is_error != Some(true));
+        self.metrics.record_call(tool, success, start.elapsed());
+        result
+    }
+
     // ── Vault Management ─────────────────────────────────────
 
-    #[tool(description = "Unlock the vault with the master password. MUST be called before any other tool. Creates a new vault if none exists. Returns credential count on success.")]
+    #[tool(
+        description = "Unlock the vault with the master password. MUST be called before any other tool. Creates a new vault if none exists. Returns credential count on success."
+    )]
     async fn vault_unlock(
         &self,
         Parameters(params): Parameters<tools::vault::VaultUnlockRequest>,
     ) -> Result<CallToolResult, McpError> {
-        tools::vault::vault_unlock(self, params).await
+        self.observe("vault_unlock", tools::vault::vault_unlock(self, params))
+            .await
     }
 
     #[tool(description = "Lock the vault, clearing the encryption key from memory.")]
     async fn vault_lock(&self) -> Result<CallToolResult, McpError> {
-        tools::vault::vault_lock(self).await
+        self.observe("vault_lock", tools::vault::vault_lock(self))
+            .await
     }
 
     #[tool(description = "Check vault status: locked/unlocked, credential count, environments.")]
     async fn vault_status(&self) -> Result<CallToolResult, McpError> {
-        tools::vault::vault_status(self).await
+        self.observe("vault_status", tools::vault::vault_status(self))
+            .await
+    }
+
+    #[tool(
+        description = "Count credentials by kind and by environment, plus the number of distinct tags and of credentials with their own policy. Reads metadata only."
+    )]
+    async fn vault_stats(&self) -> Result<CallToolResult, McpError> {
+        self.observe("vault_stats", tools::vault::vault_stats(self))
+            .await
     }
 
     // ── Credential Discovery ─────────────────────────────────
 
-    #[tool(description = "List credentials with optional filters by kind, environment, or tag. Returns id, name, kind, environment, tags for each credential. Never returns secret values. Use this to find credential UUIDs for proxy tools.")]
+    #[tool(
+        description = "List credentials with optional filters by kind, environment, or tag. Returns id, name, kind, environment, tags for each credential. Never returns secret values. Use this to find credential UUIDs for proxy tools."
+    )]
     async fn credential_list(
         &self,
         Parameters(params): Parameters<tools::discovery::CredentialListRequest>,
     ) -> Result<CallToolResult, McpError> {
-        tools::discovery::credential_list(self, params).await
+        self.observe(
+            "credential_list",
+            tools::discovery::credential_list(self, params),
+        )
+        .await
     }
 
-    #[tool(description = "Search credentials by name, tags, or notes. Never returns secret values.")]
+    #[tool(
+        description = "Search credentials by name, tags, or notes. Never returns secret values."
+    )]
     async fn credential_search(
         &self,
         Parameters(params): Parameters<tools::discovery::CredentialSearchRequest>,
     ) -> Result<CallToolResult, McpError> {
-        tools::discovery::credential_search(self, params).await
+        self.observe(
+            "credential_search",
+            tools::discovery::credential_search(self, params),
+        )
+        .await
     }
 
-    #[tool(description = "Get detailed metadata for a credential (name, kind, environment, tags, notes). Never returns secret values.")]
+    #[tool(
+        description = "Get detailed metadata for a credential (name, kind, environment, tags, notes) and the credentials linked to it in either direction. Never returns secret values."
+    )]
     async fn credential_info(
         &self,
         Parameters(params): Parameters<tools::discovery::CredentialInfoRequest>,
     ) -> Result<CallToolResult, McpError> {
-        tools::discovery::credential_info(self, params).await
+        self.observe(
+            "credential_info",
+            tools::discovery::credential_info(self, params),
+        )
+        .await
+    }
+
+    #[tool(
+        description = "List credentials past their rotation interval (set with credential_update's rotation_interval_days), most overdue first. Reads metadata only."
+    )]
+    async fn rotation_due(&self) -> Result<CallToolResult, McpError> {
+        self.observe("rotation_due", tools::discovery::rotation_due(self))
+            .await
     }
 
     // ── Credential Storage ───────────────────────────────────
 
-    #[tool(description = "Store a NEW credential in the vault. ALWAYS creates a new entry with a new UUID. To modify an existing credential, use credential_update instead. Supports kinds: password, api_token, ssh_key, ssh_password, database_connection, certificate, smtp_account, custom. The 'secret' field structure depends on the kind (see server instructions for field details).")]
+    #[tool(
+        description = "Store a NEW credential in the vault. ALWAYS creates a new entry with a new UUID. To modify an existing credential, use credential_update instead. Supports kinds: password, api_token, ssh_key, ssh_password, ssh_agent, database_connection, certificate, smtp_account, mongodb, jwt_signer, opaque_blob, custom. The 'secret' field structure depends on the kind (see server instructions for field details)."
+    )]
     async fn credential_store(
         &self,
         Parameters(params): Parameters<tools::storage::CredentialStoreRequest>,
     ) -> Result<CallToolResult, McpError> {
-        tools::storage::credential_store(self, params).await
+        self.observe(
+            "credential_store",
+            tools::storage::credential_store(self, params),
+        )
+        .await
     }
 
-    #[tool(description = "Update an EXISTING credential by UUID. Pass only the fields you want to change; omitted fields keep their current values. Use this instead of credential_store when modifying credentials to avoid creating duplicates. The secret field structure must match the credential's kind.")]
+    #[tool(
+        description = "Update an EXISTING credential by UUID. Pass only the fields you want to change; omitted fields keep their current values. Use this instead of credential_store when modifying credentials to avoid creating duplicates. The secret field structure must match the credential's kind."
+    )]
     async fn credential_update(
         &self,
         Parameters(params): Parameters<tools::storage::CredentialUpdateRequest>,
     ) -> Result<CallToolResult, McpError> {
-        tools::storage::credential_update(self, params).await
+        self.observe(
+            "credential_update",
+            tools::storage::credential_update(self, params),
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Set or remove a single field of an EXISTING custom credential by UUID, leaving its other fields unchanged. Omit value to remove the field. Only works on custom credentials; use credential_update for other kinds."
+    )]
+    async fn credential_update_field(
+        &self,
+        Parameters(params): Parameters<tools::storage::CredentialUpdateFieldRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        self.observe(
+            "credential_update_field",
+            tools::storage::credential_update_field(self, params),
+        )
+        .await
     }
 
-    #[tool(description = "Delete a credential from the vault. Requires confirm=true.")]
+    #[tool(
+        description = "Delete a credential from the vault. Requires confirm=true. Refused while other credentials link to it unless force=true, which removes their links."
+    )]
     async fn credential_delete(
         &self,
         Parameters(params): Parameters<tools::storage::CredentialDeleteRequest>,
     ) -> Result<CallToolResult, McpError> {
-        tools::storage::credential_delete(self, params).await
+        self.observe(
+            "credential_delete",
+            tools::storage::credential_delete(self, params),
+        )
+        .await
     }
 
     // ── Protocol Proxies ─────────────────────────────────────
 
-    #[tool(description = "Make an HTTP request using a stored credential for authentication. Supports credential types: api_token (Bearer/custom header), password (Basic auth), certificate (mTLS), and custom (with auth_strategy: basic/bearer/headers). The credential's secret is injected as auth headers and NEVER exposed to you. Response body and headers are sanitized to remove any secret values.")]
+    #[tool(
+        description = "Make an HTTP request using a stored credential for authentication. Supports credential types: api_token (Bearer/custom header), password (Basic auth), certificate (mTLS), jwt_signer (a freshly signed JWT as Bearer), and custom (with auth_strategy: basic/bearer/headers). The credential's secret is injected as auth headers and NEVER exposed to you. Response body and headers are sanitized to remove any secret values."
+    )]
     async fn http_request(
         &self,
         Parameters(params): Parameters<tools::http::HttpRequestParams>,
     ) -> Result<CallToolResult, McpError> {
-        tools::http::http_request(self, params).await
+        self.observe("http_request", tools::http::http_request(self, params))
+            .await
     }
 
-    #[tool(description = "Execute a command on a remote host via SSH using a stored ssh_key or ssh_password credential. Host and port are read from the credential. Output is sanitized. Commands with no output for 120s are timed out. For background processes, redirect ALL file descriptors: nohup cmd > /tmp/out.log 2>&1 < /dev/null & disown")]
+    #[tool(
+        description = "Execute a command on a remote host via SSH using a stored ssh_key, ssh_password or ssh_agent credential. Host and port are read from the credential. Pass either a raw shell command, or program plus args to have each argument shell-escaped. Output is sanitized. Commands with no output for 120s are timed out (inactivity_timeout_secs); max_total_secs sets an overall limit. For background processes, redirect ALL file descriptors: nohup cmd > /tmp/out.log 2>&1 < /dev/null & disown"
+    )]
     async fn ssh_exec(
         &self,
         Parameters(params): Parameters<tools::ssh::SshExecParams>,
     ) -> Result<CallToolResult, McpError> {
-        tools::ssh::ssh_exec(self, params).await
+        self.observe("ssh_exec", tools::ssh::ssh_exec(self, params))
+            .await
+    }
+
+    #[tool(
+        description = "Send a one-shot TCP request to a host reachable only from an SSH bastion, using a stored ssh_key, ssh_password or ssh_agent credential. Opens a direct-tcpip tunnel, writes the payload, and returns the response once the remote side closes or goes quiet for 30s. Response is sanitized."
+    )]
+    async fn ssh_tunnel(
+        &self,
+        Parameters(params): Parameters<tools::ssh::SshTunnelParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.observe("ssh_tunnel", tools::ssh::ssh_tunnel(self, params))
+            .await
     }
 
-    #[tool(description = "Execute a SQL query using a stored database_connection credential. Connects using the credential's driver/host/port/database. Returns columns, rows, and rows_affected. Results are sanitized. Supports parameterized queries via the params array. Policy can enforce read-only mode.")]
+    #[tool(
+        description = "Execute a SQL query using a stored database_connection credential. Connects using the credential's driver/host/port/database. Returns columns, rows, and rows_affected. A null value is SQL NULL; a value of a type Passman can't decode is returned as {\"_unsupported_type\": \"<type>\"}. Results are sanitized. Supports parameterized queries via the params array. Policy can enforce read-only mode."
+    )]
     async fn sql_query(
         &self,
         Parameters(params): Parameters<tools::sql::SqlQueryParams>,
     ) -> Result<CallToolResult, McpError> {
-        tools::sql::sql_query(self, params).await
+        self.observe("sql_query", tools::sql::sql_query(self, params))
+            .await
     }
 
-    #[tool(description = "Send an email using a stored smtp_account credential. Supports to, cc, bcc recipients. Email body is plain text. The sender address is taken from the credential's username. Recipients can be restricted by policy.")]
+    #[tool(
+        description = "Find documents in a MongoDB collection using a stored mongodb credential. Accepts a filter document and limit (default 100, max 1000). Returns documents as relaxed extended JSON. Results are sanitized. Policy can enforce read-only mode."
+    )]
+    async fn mongo_find(
+        &self,
+        Parameters(params): Parameters<tools::mongo::MongoFindParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.observe("mongo_find", tools::mongo::mongo_find(self, params))
+            .await
+    }
+
+    #[tool(
+        description = "Send an email using a stored smtp_account credential. Supports to, cc, bcc recipients. Email body is plain text. The sender address is taken from the credential's username. Recipients can be restricted by policy."
+    )]
     async fn send_email(
         &self,
         Parameters(params): Parameters<tools::smtp::SendEmailParams>,
     ) -> Result<CallToolResult, McpError> {
-        tools::smtp::send_email(self, params).await
+        self.observe("send_email", tools::smtp::send_email(self, params))
+            .await
+    }
+
+    #[tool(
+        description = "Check that a stored smtp_account credential can connect and log in, without sending an email. Authenticates with the server and disconnects before any message is started."
+    )]
+    async fn smtp_verify(
+        &self,
+        Parameters(params): Parameters<tools::smtp::SmtpVerifyParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.observe("smtp_verify", tools::smtp::smtp_verify(self, params))
+            .await
+    }
+
+    #[tool(
+        description = "Check whether the host of a stored SSH, database or SMTP credential accepts TCP connections, without authenticating. Returns reachable and the connect latency; useful to tell network problems from bad credentials. The host can be restricted by policy."
+    )]
+    async fn credential_ping(
+        &self,
+        Parameters(params): Parameters<tools::ping::CredentialPingParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.observe(
+            "credential_ping",
+            tools::ping::credential_ping(self, params),
+        )
+        .await
+    }
+
+    #[tool(
+        description = "Return the raw contents of a stored opaque_blob credential (e.g. an age- or GPG-encrypted file) as base64, for a tool that expects the ciphertext itself. Only allowed when the credential's policy explicitly lists blob_fetch in its allowed tools; every fetch is audited."
+    )]
+    async fn blob_fetch(
+        &self,
+        Parameters(params): Parameters<tools::blob::BlobFetchParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.observe("blob_fetch", tools::blob::blob_fetch(self, params))
+            .await
+    }
+
+    // ── Policy ───────────────────────────────────────────────
+
+    #[tool(
+        description = "List policy templates in precedence order. A credential's own policy always wins; otherwise the first template matching its environment and/or tag applies. Templates are managed in the Passman app."
+    )]
+    async fn policy_template_list(&self) -> Result<CallToolResult, McpError> {
+        self.observe(
+            "policy_template_list",
+            tools::policy::policy_template_list(self),
+        )
+        .await
     }
 
     // ── Audit ────────────────────────────────────────────────
 
-    #[tool(description = "View the audit log of proxy operations. Filter by credential_id, limit, or time range.")]
+    #[tool(
+        description = "View the audit log of proxy operations. Filter by credential_id, action, tool, success, or time range (since/until), and cap results with limit."
+    )]
     async fn audit_log(
         &self,
-        Parameters(params): Parameters<AuditLogParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let credential_id = params
-            .credential_id
-            .map(|id| {
-                id.parse::<uuid::Uuid>()
-                    .map_err(|_| McpError::invalid_params("invalid UUID", None))
-            })
-            .transpose()?;
-
-        let since = params
-            .since
-            .map(|s| {
-                chrono::DateTime::parse_from_rfc3339(&s)
-                    .map(|dt| dt.with_timezone(&chrono::Utc))
-                    .map_err(|_| McpError::invalid_params("invalid datetime (use RFC3339)", None))
-            })
-            .transpose()?;
-
-        match self
-            .vault
-            .read_audit(credential_id, params.limit.map(|l| l as usize), since)
+        Parameters(params): Parameters<tools::audit::AuditLogParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.observe("audit_log", tools::audit::audit_log(self, params))
             .await
-        {
-            Ok(entries) => {
-                let items: Vec<serde_json::Value> = entries
-                    .iter()
-                    .map(|e| {
-                        serde_json::json!({
-                            "timestamp": e.timestamp.to_rfc3339(),
-                            "credential_id": e.credential_id.map(|id| id.to_string()),
-                            "credential_name": e.credential_name,
-                            "action": e.action,
-                            "tool": e.tool,
-                            "success": e.success,
-                            "details": e.details,
-                        })
-                    })
-                    .collect();
-
-                Ok(CallToolResult::success(vec![Content::text(
-                    serde_json::to_string(&items).unwrap(),
-                )]))
-            }
-            Err(e) => Ok(CallToolResult::error(vec![Content::text(format!("{e}"))])),
-        }
     }
-}
 
-#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
-pub struct AuditLogParams {
-    #[schemars(description = "Filter by credential UUID")]
-    pub credential_id: Option<String>,
-    #[schemars(description = "Maximum number of entries to return")]
-    pub limit: Option<u32>,
-    #[schemars(description = "Only return entries after this RFC3339 datetime")]
-    pub since: Option<String>,
+    #[tool(
+        description = "Summarize the audit log: counts per action, successes and failures, the most used credentials, and the time range covered. Optionally only count entries since an RFC3339 datetime."
+    )]
+    async fn audit_summary(
+        &self,
+        Parameters(params): Parameters<tools::audit::AuditSummaryParams>,
+    ) -> Result<CallToolResult, McpError> {
+        self.observe("audit_summary", tools::audit::audit_summary(self, params))
+            .await
+    }
 }
 
 #[tool_handler]
@@ -213,9 +411,13 @@ impl ServerHandler for PassmanServer {
                  - api_token: {token, header_name?, prefix?} - header_name defaults to 'Authorization', prefix to 'Bearer '\n\
                  - ssh_key: {username, host, port?, private_key, passphrase?}\n\
                  - ssh_password: {username, host, port?, password}\n\
+                 - ssh_agent: {username, host, port?, public_key} - signs with the matching key in the local ssh-agent; no private key is stored\n\
                  - database_connection: {driver, host, port?, database, username, password} - driver: postgres/mysql/sqlite\n\
-                 - certificate: {cert_pem, key_pem, ca_pem?} - for mTLS\n\
+                 - certificate: {cert_pem, key_pem, ca_pem?, pin_ca?} - for mTLS; pin_ca trusts only ca_pem for the server\n\
                  - smtp_account: {host, port?, username, password, encryption?} - encryption: tls/start_tls/none\n\
+                 - mongodb: {uri} - standard mongodb:// or mongodb+srv:// connection string\n\
+                 - jwt_signer: {private_key_pem, algorithm?, issuer, audience, ttl_secs?} - algorithm: RS256 (default)/RS384/RS512/ES256/ES384/EdDSA; http_request sends a JWT signed with the key as the bearer\n\
+                 - opaque_blob: {data_base64, content_type?} - arbitrary secret material (e.g. an age-encrypted file), returned as-is only by blob_fetch\n\
                  - custom: {fields: {key: value, ...}} - see Custom Auth below\n\n\
                  ## Custom Auth Strategies (for http_request)\n\
                  Store a 'custom' credential with an 'auth_strategy' field in the fields map:\n\
@@ -226,7 +428,11 @@ impl ServerHandler for PassmanServer {
                  ## Updating vs Storing Credentials\n\
                  - credential_store: ALWAYS creates a NEW credential with a new UUID\n\
                  - credential_update: modifies an EXISTING credential by UUID - use this for edits\n\
+                 - credential_update_field: sets or removes ONE field of a custom credential, keeping the rest\n\
                  Never use credential_store to update - it will create duplicates.\n\n\
+                 ## Policies\n\
+                 Each proxy call is checked against the credential's effective policy: its own policy if one \
+                 is set, otherwise the first policy template matching its environment and/or tag.\n\n\
                  ## SSH Tips\n\
                  - Background processes (nohup, &) will HANG unless you redirect ALL file descriptors: \
                    nohup <cmd> > /tmp/out.log 2>&1 < /dev/null & disown\n\
@@ -234,8 +440,45 @@ impl ServerHandler for PassmanServer {
                  - To check a background process: pgrep -f <pattern> or cat /tmp/out.log"
                     .to_string(),
             ),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_prompts()
+                .build(),
             ..Default::default()
         }
     }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let resources = crate::resources::list(self).await?;
+        Ok(ListResourcesResult::with_all_items(resources))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        crate::resources::read(self, &request.uri).await
+    }
+
+    async fn list_prompts(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListPromptsResult, McpError> {
+        Ok(ListPromptsResult::with_all_items(crate::prompts::list()))
+    }
+
+    async fn get_prompt(
+        &self,
+        request: GetPromptRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<GetPromptResult, McpError> {
+        crate::prompts::get(&request.name, request.arguments.as_ref())
+    }
 }
@@ -0,0 +1,136 @@
+use crate::server::PassmanServer;
+use rmcp::{model::CallToolResult, model::Content, schemars, ErrorData as McpError};
+use serde::Deserialize;
+
+// ── audit_log ────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AuditLogParams {
+    #[schemars(description = "Filter by credential UUID")]
+    pub credential_id: Option<String>,
+    #[schemars(description = "Maximum number of entries to return")]
+    pub limit: Option<u32>,
+    #[schemars(description = "Only return entries after this RFC3339 datetime")]
+    pub since: Option<String>,
+    #[schemars(description = "Only return entries before this RFC3339 datetime")]
+    pub until: Option<String>,
+    #[schemars(description = "Filter by action, e.g. sql_query, http_request, credential_store")]
+    pub action: Option<String>,
+    #[schemars(description = "Filter by the tool that wrote the entry, e.g. sql_query")]
+    pub tool: Option<String>,
+    #[schemars(description = "Only successful (true) or failed (false) operations")]
+    pub success: Option<bool>,
+}
+
+pub async fn audit_log(
+    server: &PassmanServer,
+    params: AuditLogParams,
+) -> Result<CallToolResult, McpError> {
+    let credential_id = params
+        .credential_id
+        .map(|id| {
+            id.parse::<uuid::Uuid>()
+                .map_err(|_| McpError::invalid_params("invalid UUID", None))
+        })
+        .transpose()?;
+
+    let parse_time = |s: String| {
+        chrono::DateTime::parse_from_rfc3339(&s)
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+            .map_err(|_| McpError::invalid_params("invalid datetime (use RFC3339)", None))
+    };
+
+    let action = params
+        .action
+        .map(|a| {
+            serde_json::from_value(a.clone().into())
+                .map_err(|_| McpError::invalid_params(format!("unknown audit action: {a}"), None))
+        })
+        .transpose()?;
+
+    let filter = passman_vault::AuditFilter {
+        credential_id,
+        action,
+        tool: params.tool,
+        success: params.success,
+        since: params.since.map(parse_time).transpose()?,
+        until: params.until.map(parse_time).transpose()?,
+    };
+
+    let visible = match super::visible_ids(server).await {
+        Ok(visible) => visible,
+        Err(e) => return super::vault_error_result(e),
+    };
+    let limit = params.limit.map(|l| l as usize);
+    // Scope filtering happens after reading, so the limit has to wait too
+    let read_limit = if visible.is_some() { None } else { limit };
+
+    match server.vault.read_audit(&filter, read_limit).await {
+        Ok(mut entries) => {
+            if let Some(ids) = &visible {
+                entries.retain(|e| e.credential_id.is_none_or(|id| ids.contains(&id)));
+                entries.truncate(limit.unwrap_or(usize::MAX));
+            }
+            let items: Vec<serde_json::Value> = entries
+                .iter()
+                .map(|e| {
+                    serde_json::json!({
+                        "timestamp": e.timestamp.to_rfc3339(),
+                        "credential_id": e.credential_id.map(|id| id.to_string()),
+                        "credential_name": e.credential_name,
+                        "action": e.action,
+                        "tool": e.tool,
+                        "success": e.success,
+                        "details": e.details,
+                    })
+                })
+                .collect();
+
+            Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string(&items).unwrap(),
+            )]))
+        }
+        Err(e) => Ok(CallToolResult::error(vec![Content::text(format!("{e}"))])),
+    }
+}
+
+// ── audit_summary ────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct AuditSummaryParams {
+    #[schemars(description = "Only count entries after this RFC3339 datetime")]
+    pub since: Option<String>,
+}
+
+pub async fn audit_summary(
+    server: &PassmanServer,
+    params: AuditSummaryParams,
+) -> Result<CallToolResult, McpError> {
+    let since = params
+        .since
+        .map(|s| {
+            chrono::DateTime::parse_from_rfc3339(&s)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .map_err(|_| McpError::invalid_params("invalid datetime (use RFC3339)", None))
+        })
+        .transpose()?;
+
+    let summary = match super::visible_ids(server).await {
+        Ok(None) => server.vault.audit_summary(since).await,
+        Ok(Some(ids)) => {
+            server
+                .vault
+                .audit_summary_matching(since, |e| {
+                    e.credential_id.is_none_or(|id| ids.contains(&id))
+                })
+                .await
+        }
+        Err(e) => return super::vault_error_result(e),
+    };
+    match summary {
+        Ok(summary) => Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string(&summary).unwrap(),
+        )])),
+        Err(e) => Ok(CallToolResult::error(vec![Content::text(format!("{e}"))])),
+    }
+}
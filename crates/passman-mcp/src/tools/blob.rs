@@ -0,0 +1,100 @@
+use crate::policy::PolicyDenied;
+use crate::server::PassmanServer;
+use passman_types::{AuditAction, AuditEntry, CredentialSecret};
+use rmcp::{model::CallToolResult, model::Content, schemars, ErrorData as McpError};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct BlobFetchParams {
+    #[schemars(description = "Credential UUID or name@environment (opaque_blob)")]
+    #[serde(alias = "credential_ref")]
+    pub credential_id: String,
+    #[schemars(
+        description = "Approval ID returned by a previous call when the credential's policy requires approval"
+    )]
+    pub approval_id: Option<String>,
+}
+
+pub async fn blob_fetch(
+    server: &PassmanServer,
+    params: BlobFetchParams,
+) -> Result<CallToolResult, McpError> {
+    let cred_id = match super::resolve_credential(server, &params.credential_id).await {
+        Ok(id) => id,
+        Err(result) => return Ok(result),
+    };
+
+    if let Some(denied) = super::scope_denial(server, cred_id).await {
+        return Ok(denied);
+    }
+
+    let secret = match server.vault.get_credential_secret(cred_id).await {
+        Ok(secret) => secret,
+        Err(e) => return super::vault_error_result(e),
+    };
+    let CredentialSecret::OpaqueBlob {
+        data_base64,
+        content_type,
+    } = &*secret
+    else {
+        return Ok(CallToolResult::error(vec![Content::text(
+            "blob_fetch only returns opaque_blob credentials",
+        )]));
+    };
+
+    // The blob is handed out as-is, so the policy must allow it explicitly
+    let policy = match server.vault.get_effective_policy(cred_id).await {
+        Ok(Some(policy)) if policy.allowed_tools.iter().any(|t| t == "blob_fetch") => policy,
+        _ => {
+            let e = PolicyDenied(
+                "blob_fetch must be listed in the credential's allowed tools".to_string(),
+            );
+            return Ok(super::policy_denial(server, "blob_fetch", e));
+        }
+    };
+    if let Err(e) = server.policy.check_time_window(&policy, chrono::Utc::now()) {
+        return Ok(super::policy_denial(server, "blob_fetch", e));
+    }
+    if let Err(e) = server.policy.check_rate_limit(&policy, "blob_fetch").await {
+        return Ok(super::rate_limit_denial(server, "blob_fetch", e));
+    }
+    let meta = server.vault.get_credential_meta(cred_id).await.ok();
+    let summary = format!(
+        "fetch blob '{}'",
+        meta.as_ref().map(|m| m.name.as_str()).unwrap_or_default()
+    );
+    if let Some(result) = crate::approval::check_approval(
+        server,
+        &policy,
+        "blob_fetch",
+        &summary,
+        serde_json::json!({ "credential_id": cred_id }),
+        params.approval_id.as_deref(),
+    )
+    .await?
+    {
+        return Ok(result);
+    }
+
+    let _ = server
+        .vault
+        .log_audit(&AuditEntry {
+            timestamp: chrono::Utc::now(),
+            credential_id: Some(cred_id),
+            credential_name: meta.map(|m| m.name),
+            action: AuditAction::BlobFetch,
+            tool: "blob_fetch".to_string(),
+            success: true,
+            details: content_type.clone(),
+        })
+        .await;
+    let _ = server.vault.record_usage(cred_id).await;
+
+    Ok(CallToolResult::success(vec![Content::text(
+        serde_json::json!({
+            "data_base64": data_base64,
+            "content_type": content_type,
+        })
+        .to_string(),
+    )]))
+}
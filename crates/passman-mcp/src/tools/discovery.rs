@@ -1,5 +1,5 @@
 use crate::server::PassmanServer;
-use passman_types::{AuditAction, AuditEntry, CredentialKind, Environment};
+use passman_types::{AuditAction, AuditEntry, CredentialInfoResponse, CredentialKind, Environment};
 use rmcp::{model::CallToolResult, model::Content, schemars, ErrorData as McpError};
 use serde::Deserialize;
 
@@ -7,12 +7,18 @@ use serde::Deserialize;
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct CredentialListRequest {
-    #[schemars(description = "Filter by credential kind: password, api_token, ssh_key, database_connection, certificate, smtp_account, custom")]
+    #[schemars(
+        description = "Filter by credential kind: password, api_token, ssh_key, ssh_password, ssh_agent, database_connection, certificate, smtp_account, mongodb, jwt_signer, opaque_blob, custom"
+    )]
     pub kind: Option<String>,
-    #[schemars(description = "Filter by environment: local, development, staging, production")]
+    #[schemars(
+        description = "Filter by environment: local, development, staging, production, or a custom environment name"
+    )]
     pub environment: Option<String>,
     #[schemars(description = "Filter by tag")]
     pub tag: Option<String>,
+    #[schemars(description = "Filter by category name")]
+    pub category: Option<String>,
 }
 
 pub async fn credential_list(
@@ -29,25 +35,31 @@ pub async fn credential_list(
     let environment: Option<Environment> = params
         .environment
         .as_deref()
-        .map(|e| serde_json::from_value(serde_json::Value::String(e.to_string())))
-        .transpose()
-        .map_err(|_| McpError::invalid_params("invalid environment", None))?;
+        .map(|e| {
+            Environment::parse(e)
+                .ok_or_else(|| McpError::invalid_params("invalid environment", None))
+        })
+        .transpose()?;
 
     match server
         .vault
-        .list_credentials(kind, environment, params.tag)
+        .list_credentials(kind, environment, params.tag, params.category)
         .await
     {
-        Ok(creds) => {
-            let _ = server.vault.log_audit(&AuditEntry {
-                timestamp: chrono::Utc::now(),
-                credential_id: None,
-                credential_name: None,
-                action: AuditAction::CredentialList,
-                tool: "credential_list".to_string(),
-                success: true,
-                details: None,
-            }).await;
+        Ok(mut creds) => {
+            creds.retain(|c| server.scope.allows(c));
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: None,
+                    credential_name: None,
+                    action: AuditAction::CredentialList,
+                    tool: "credential_list".to_string(),
+                    success: true,
+                    details: None,
+                })
+                .await;
 
             let items: Vec<serde_json::Value> = creds
                 .iter()
@@ -58,6 +70,7 @@ pub async fn credential_list(
                         "kind": c.kind,
                         "environment": c.environment,
                         "tags": c.tags,
+                        "category": c.category,
                     })
                 })
                 .collect();
@@ -83,16 +96,20 @@ pub async fn credential_search(
     params: CredentialSearchRequest,
 ) -> Result<CallToolResult, McpError> {
     match server.vault.search_credentials(&params.query).await {
-        Ok(creds) => {
-            let _ = server.vault.log_audit(&AuditEntry {
-                timestamp: chrono::Utc::now(),
-                credential_id: None,
-                credential_name: None,
-                action: AuditAction::CredentialSearch,
-                tool: "credential_search".to_string(),
-                success: true,
-                details: Some(format!("query: {}", params.query)),
-            }).await;
+        Ok(mut creds) => {
+            creds.retain(|c| server.scope.allows(c));
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: None,
+                    credential_name: None,
+                    action: AuditAction::CredentialSearch,
+                    tool: "credential_search".to_string(),
+                    success: true,
+                    details: Some(format!("query: {}", params.query)),
+                })
+                .await;
 
             let items: Vec<serde_json::Value> = creds
                 .iter()
@@ -132,29 +149,75 @@ pub async fn credential_info(
         .map_err(|_| McpError::invalid_params("invalid UUID", None))?;
 
     match server.vault.get_credential_meta(id).await {
+        // Out-of-scope credentials look the same as missing ones
+        Ok(meta) if !server.scope.allows(&meta) => Ok(CallToolResult::error(vec![Content::text(
+            format!("{}", passman_vault::VaultError::NotFound(id)),
+        )])),
         Ok(meta) => {
-            let _ = server.vault.log_audit(&AuditEntry {
-                timestamp: chrono::Utc::now(),
-                credential_id: Some(id),
-                credential_name: Some(meta.name.clone()),
-                action: AuditAction::CredentialInfo,
-                tool: "credential_info".to_string(),
-                success: true,
-                details: None,
-            }).await;
-
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(id),
+                    credential_name: Some(meta.name.clone()),
+                    action: AuditAction::CredentialInfo,
+                    tool: "credential_info".to_string(),
+                    success: true,
+                    details: None,
+                })
+                .await;
+
+            let mut info = CredentialInfoResponse::from(meta);
+            info.linked = server.vault.get_linked(id).await.unwrap_or_default();
+            match super::visible_ids(server).await {
+                Ok(None) => {}
+                Ok(Some(ids)) => info.linked.retain(|l| ids.contains(&l.id)),
+                Err(_) => info.linked.clear(),
+            }
             Ok(CallToolResult::success(vec![Content::text(
-                serde_json::json!({
-                    "id": meta.id.to_string(),
-                    "name": meta.name,
-                    "kind": meta.kind,
-                    "environment": meta.environment,
-                    "tags": meta.tags,
-                    "notes": meta.notes,
-                    "created_at": meta.created_at.to_rfc3339(),
-                    "updated_at": meta.updated_at.to_rfc3339(),
+                serde_json::to_string(&info).unwrap(),
+            )]))
+        }
+        Err(e) => Ok(CallToolResult::error(vec![Content::text(format!("{e}"))])),
+    }
+}
+
+// ── rotation_due ─────────────────────────────────────────────────
+
+pub async fn rotation_due(server: &PassmanServer) -> Result<CallToolResult, McpError> {
+    match server.vault.rotation_due().await {
+        Ok(mut creds) => {
+            creds.retain(|c| server.scope.allows(c));
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: None,
+                    credential_name: None,
+                    action: AuditAction::RotationDue,
+                    tool: "rotation_due".to_string(),
+                    success: true,
+                    details: Some(format!("{} due", creds.len())),
+                })
+                .await;
+
+            let items: Vec<serde_json::Value> = creds
+                .iter()
+                .map(|c| {
+                    serde_json::json!({
+                        "id": c.id.to_string(),
+                        "name": c.name,
+                        "kind": c.kind,
+                        "environment": c.environment,
+                        "rotation_interval_days": c.rotation_interval_days,
+                        "last_rotated_at": c.last_rotated_at.map(|t| t.to_rfc3339()),
+                        "due_at": c.rotation_due_at().map(|t| t.to_rfc3339()),
+                    })
                 })
-                .to_string(),
+                .collect();
+
+            Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string(&items).unwrap(),
             )]))
         }
         Err(e) => Ok(CallToolResult::error(vec![Content::text(format!("{e}"))])),
@@ -1,49 +1,107 @@
 use crate::server::PassmanServer;
-use passman_types::{AuditAction, AuditEntry};
+use passman_types::{AuditAction, AuditEntry, AuthScheme, FormPart};
 use rmcp::{model::CallToolResult, model::Content, schemars, ErrorData as McpError};
 use serde::Deserialize;
 use std::collections::HashMap;
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct HttpRequestParams {
-    #[schemars(description = "Credential UUID for authentication. Supports: api_token (Bearer/custom header), password (Basic auth), certificate (mTLS), custom (with auth_strategy field)")]
+    #[schemars(
+        description = "Credential UUID or name@environment for authentication. Supports: api_token (Bearer/custom header), password (Basic auth), certificate (mTLS), jwt_signer (signed JWT as Bearer), custom (with auth_strategy field)"
+    )]
+    #[serde(alias = "credential_ref")]
     pub credential_id: String,
     #[schemars(description = "HTTP method: GET, POST, PUT, PATCH, DELETE, HEAD")]
     pub method: String,
-    #[schemars(description = "Full target URL including scheme (e.g., https://api.example.com/v1/resource)")]
+    #[schemars(
+        description = "Full target URL including scheme (e.g., https://api.example.com/v1/resource)"
+    )]
     pub url: String,
-    #[schemars(description = "Additional HTTP headers as key-value pairs. Auth headers are injected automatically from the credential - do NOT set Authorization manually.")]
+    #[schemars(
+        description = "Additional HTTP headers as key-value pairs. Auth headers are injected automatically from the credential - do NOT set Authorization manually."
+    )]
     pub headers: Option<HashMap<String, String>>,
-    #[schemars(description = "Request body (typically JSON string for POST/PUT/PATCH). Set Content-Type header if needed.")]
+    #[schemars(
+        description = "Request body (typically JSON string for POST/PUT/PATCH). Set Content-Type header if needed."
+    )]
     pub body: Option<String>,
+    #[schemars(
+        description = "Override where the secret goes, for password and api_token credentials: 'basic', 'bearer', {header_name: 'X-Api-Key'} or {query_param: 'api_key'}"
+    )]
+    pub auth_scheme: Option<AuthScheme>,
+    #[schemars(
+        description = "multipart/form-data parts, instead of body. Each part is {type: 'text', name, value} or {type: 'file', name, filename, content_base64, mime?}. Content-Type is set automatically."
+    )]
+    pub form: Option<Vec<FormPart>>,
+    #[schemars(
+        description = "Only return these response headers (case-insensitive). Default: all headers"
+    )]
+    pub include_headers: Option<Vec<String>>,
+    #[schemars(
+        description = "Drop these response headers (case-insensitive), e.g. ['set-cookie']"
+    )]
+    pub exclude_headers: Option<Vec<String>>,
+    #[schemars(
+        description = "Approval ID returned by a previous call when the credential's policy requires approval"
+    )]
+    pub approval_id: Option<String>,
 }
 
 pub async fn http_request(
     server: &PassmanServer,
     params: HttpRequestParams,
 ) -> Result<CallToolResult, McpError> {
-    let cred_id: uuid::Uuid = params
-        .credential_id
-        .parse()
-        .map_err(|_| McpError::invalid_params("invalid UUID", None))?;
+    let cred_id = match super::resolve_credential(server, &params.credential_id).await {
+        Ok(id) => id,
+        Err(result) => return Ok(result),
+    };
+
+    if let Some(denied) = super::scope_denial(server, cred_id).await {
+        return Ok(denied);
+    }
 
     // Get the credential secret (never exposed to LLM)
-    let secret = server
-        .vault
-        .get_credential_secret(cred_id)
-        .await
-        .map_err(|e| McpError::internal_error(format!("{e}"), None))?;
+    let secret = match server.vault.get_credential_secret(cred_id).await {
+        Ok(secret) => secret,
+        Err(e) => return super::vault_error_result(e),
+    };
 
     // Check policy
-    if let Ok(Some(policy)) = server.vault.get_policy(cred_id).await {
+    if let Ok(Some(policy)) = server.vault.get_effective_policy(cred_id).await {
         if let Err(e) = server.policy.check_tool(&policy, "http_request") {
-            return Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]));
+            return Ok(super::policy_denial(server, "http_request", e));
+        }
+        if let Err(e) = server.policy.check_time_window(&policy, chrono::Utc::now()) {
+            return Ok(super::policy_denial(server, "http_request", e));
         }
         if let Err(e) = server.policy.check_http_url(&policy, &params.url) {
-            return Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]));
+            return Ok(super::policy_denial(server, "http_request", e));
+        }
+        if let Err(e) = server
+            .policy
+            .check_rate_limit(&policy, "http_request")
+            .await
+        {
+            return Ok(super::rate_limit_denial(server, "http_request", e));
         }
-        if let Err(e) = server.policy.check_rate_limit(&policy).await {
-            return Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]));
+        if let Some(result) = crate::approval::check_approval(
+            server,
+            &policy,
+            "http_request",
+            &format!("{} {}", params.method, params.url),
+            serde_json::json!({
+                "method": params.method,
+                "url": params.url,
+                "headers": params.headers,
+                "body": params.body,
+                "auth_scheme": params.auth_scheme,
+                "form": params.form,
+            }),
+            params.approval_id.as_deref(),
+        )
+        .await?
+        {
+            return Ok(result);
         }
     }
 
@@ -52,43 +110,64 @@ pub async fn http_request(
         url: params.url.clone(),
         headers: params.headers,
         body: params.body,
+        auth_scheme: params.auth_scheme,
+        form: params.form,
+        include_headers: params.include_headers,
+        exclude_headers: params.exclude_headers,
     };
 
     let meta = server.vault.get_credential_meta(cred_id).await.ok();
 
-    match passman_proxy::http::execute(&secret, &input).await {
-        Ok(response) => {
-            let _ = server.vault.log_audit(&AuditEntry {
-                timestamp: chrono::Utc::now(),
-                credential_id: Some(cred_id),
-                credential_name: meta.map(|m| m.name),
-                action: AuditAction::HttpRequest,
-                tool: "http_request".to_string(),
-                success: true,
-                details: Some(format!("{} {}", input.method, params.url)),
-            }).await;
+    let _permit = server.concurrency.acquire(cred_id).await;
+    let result = passman_proxy::redact::scope(
+        secret.secret_strings(),
+        passman_proxy::http::execute_cached(&server.http_clients, cred_id, &secret, &input),
+    )
+    .await;
+    match result {
+        Ok(mut response) => {
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::HttpRequest,
+                    tool: "http_request".to_string(),
+                    success: true,
+                    details: Some(format!("{} {}", input.method, params.url)),
+                })
+                .await;
+            let _ = server.vault.record_usage(cred_id).await;
 
+            let max_bytes = server.output_limits.for_tool("http_request");
+            let truncated = super::truncate_output(&mut response.body, max_bytes);
             Ok(CallToolResult::success(vec![Content::text(
                 serde_json::json!({
                     "status": response.status,
                     "headers": response.headers,
                     "body": response.body,
+                    "truncated": truncated,
+                    "timing": response.timing,
                 })
                 .to_string(),
             )]))
         }
         Err(e) => {
-            let _ = server.vault.log_audit(&AuditEntry {
-                timestamp: chrono::Utc::now(),
-                credential_id: Some(cred_id),
-                credential_name: meta.map(|m| m.name),
-                action: AuditAction::HttpRequest,
-                tool: "http_request".to_string(),
-                success: false,
-                details: Some(format!("{e}")),
-            }).await;
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::HttpRequest,
+                    tool: "http_request".to_string(),
+                    success: false,
+                    details: Some(format!("{e}")),
+                })
+                .await;
 
-            Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]))
+            Ok(super::proxy_error_result(&e))
         }
     }
 }
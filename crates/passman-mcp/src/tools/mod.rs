@@ -1,7 +1,322 @@
+pub mod audit;
+pub mod blob;
 pub mod discovery;
 pub mod http;
+pub mod mongo;
+pub mod ping;
+pub mod policy;
 pub mod smtp;
 pub mod sql;
 pub mod ssh;
 pub mod storage;
 pub mod vault;
+
+use passman_proxy::ProxyError;
+use passman_types::Environment;
+use passman_vault::VaultError;
+use rmcp::{model::CallToolResult, model::Content, ErrorData as McpError};
+use std::collections::HashMap;
+
+/// Default cap on the output a proxy tool returns.
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 256 * 1024;
+
+/// Caps on proxy tool output, applied after sanitization so a chatty
+/// command or a wide result can't swamp the agent's context.
+#[derive(Debug, Clone)]
+pub struct OutputLimits {
+    /// Cap for every tool without an entry in `per_tool`.
+    pub max_output_bytes: usize,
+    /// Overrides keyed by tool name (e.g. "ssh_exec").
+    pub per_tool: HashMap<String, usize>,
+}
+
+impl Default for OutputLimits {
+    fn default() -> Self {
+        Self {
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            per_tool: HashMap::new(),
+        }
+    }
+}
+
+impl OutputLimits {
+    /// The cap for `tool`.
+    pub fn for_tool(&self, tool: &str) -> usize {
+        self.per_tool
+            .get(tool)
+            .copied()
+            .unwrap_or(self.max_output_bytes)
+    }
+}
+
+/// Cut `text` to at most `max_bytes` and append a marker saying how much was
+/// dropped. Returns whether anything was cut.
+pub(crate) fn truncate_output(text: &mut String, max_bytes: usize) -> bool {
+    if text.len() <= max_bytes {
+        return false;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    let omitted = text.len() - end;
+    text.truncate(end);
+    text.push_str(&format!("\n[output truncated: {omitted} bytes omitted]"));
+    true
+}
+
+/// Drop rows from the end until the rest serialize to at most `budget`
+/// bytes, and take what they use out of `budget`. Returns the truncation
+/// marker when rows were dropped.
+pub(crate) fn truncate_rows(
+    rows: &mut Vec<Vec<serde_json::Value>>,
+    budget: &mut usize,
+) -> Option<String> {
+    let sizes: Vec<usize> = rows
+        .iter()
+        .map(|row| serde_json::to_string(row).map_or(0, |s| s.len() + 1))
+        .collect();
+    let mut total = 0;
+    let keep = sizes
+        .iter()
+        .take_while(|size| {
+            total += *size;
+            total <= *budget
+        })
+        .count();
+    *budget -= sizes[..keep].iter().sum::<usize>();
+    if keep == rows.len() {
+        return None;
+    }
+    let omitted: usize = sizes[keep..].iter().sum();
+    rows.truncate(keep);
+    Some(format!("[output truncated: {omitted} bytes omitted]"))
+}
+
+/// Message returned to the agent when a proxy tool needs the vault unlocked.
+pub(crate) const VAULT_LOCKED_MESSAGE: &str = "vault is locked — call vault_unlock first";
+
+/// Map a vault error hit while preparing a proxy call. A locked vault becomes
+/// an actionable tool error; anything else stays an internal error.
+pub(crate) fn vault_error_result(e: VaultError) -> Result<CallToolResult, McpError> {
+    match e {
+        VaultError::Locked => Ok(CallToolResult::error(vec![Content::text(
+            VAULT_LOCKED_MESSAGE,
+        )])),
+        e => Err(McpError::internal_error(format!("{e}"), None)),
+    }
+}
+
+/// Resolve a proxy tool's `credential_id`: a UUID, or a credential name with
+/// an optional `@environment` (e.g. `GitHub@production`). A name must match
+/// exactly one in-scope credential.
+pub(crate) async fn resolve_credential(
+    server: &crate::server::PassmanServer,
+    reference: &str,
+) -> Result<uuid::Uuid, CallToolResult> {
+    if let Ok(id) = reference.trim().parse() {
+        return Ok(id);
+    }
+
+    let mut matches = match lookup_name(server, reference).await {
+        Ok(found) => found,
+        Err(e) => return Err(lookup_error(e)),
+    };
+    // Names may contain '@' themselves, e.g. an email address
+    if matches.is_empty() && reference.contains('@') {
+        matches = match server.vault.find_by_name(reference, None).await {
+            Ok(found) => found,
+            Err(e) => return Err(lookup_error(e)),
+        };
+    }
+    matches.retain(|m| server.scope.allows(m));
+
+    match matches.as_slice() {
+        [meta] => Ok(meta.id),
+        [] => Err(CallToolResult::error(vec![Content::text(format!(
+            "no credential matches '{reference}'; pass a UUID or name@environment from credential_list"
+        ))])),
+        _ => {
+            let candidates: Vec<String> = matches
+                .iter()
+                .map(|m| format!("{}@{} ({})", m.name, m.environment, m.id))
+                .collect();
+            Err(CallToolResult::error(vec![Content::text(format!(
+                "'{reference}' is ambiguous, it matches: {}; pass the UUID or add @environment",
+                candidates.join(", ")
+            ))]))
+        }
+    }
+}
+
+/// Credentials named by `reference`, split as `name@environment` at the last '@'.
+async fn lookup_name(
+    server: &crate::server::PassmanServer,
+    reference: &str,
+) -> Result<Vec<passman_types::CredentialMeta>, VaultError> {
+    let Some((name, environment)) = reference
+        .rsplit_once('@')
+        .and_then(|(name, env)| Some((name, Environment::parse(env)?)))
+    else {
+        return server.vault.find_by_name(reference, None).await;
+    };
+    server.vault.find_by_name(name, Some(&environment)).await
+}
+
+fn lookup_error(e: VaultError) -> CallToolResult {
+    match e {
+        VaultError::Locked => CallToolResult::error(vec![Content::text(VAULT_LOCKED_MESSAGE)]),
+        e => CallToolResult::error(vec![Content::text(format!("{e}"))]),
+    }
+}
+
+/// Denial for a credential outside the session's scope. Unknown IDs pass
+/// through so the tool reports its usual not-found error.
+pub(crate) async fn scope_denial(
+    server: &crate::server::PassmanServer,
+    id: uuid::Uuid,
+) -> Option<CallToolResult> {
+    if server.scope.is_unrestricted() {
+        return None;
+    }
+    match server.vault.get_credential_meta(id).await {
+        Ok(meta) if !server.scope.allows(&meta) => {
+            Some(CallToolResult::error(vec![Content::text(format!(
+                "credential {id} is outside this session's scope ({})",
+                server.scope.describe()
+            ))]))
+        }
+        _ => None,
+    }
+}
+
+/// IDs of the credentials a restricted session may see, or `None` when it
+/// sees every credential. Audit entries and links naming any other
+/// credential, including deleted ones, are left out of its results.
+pub(crate) async fn visible_ids(
+    server: &crate::server::PassmanServer,
+) -> Result<Option<std::collections::HashSet<uuid::Uuid>>, VaultError> {
+    if server.scope.is_unrestricted() {
+        return Ok(None);
+    }
+    let creds = server
+        .vault
+        .list_credentials(None, None, None, None)
+        .await?;
+    Ok(Some(
+        creds
+            .into_iter()
+            .filter(|c| server.scope.allows(c))
+            .map(|c| c.id)
+            .collect(),
+    ))
+}
+
+/// Tool error for a call the credential's policy refused.
+pub(crate) fn policy_denial(
+    server: &crate::server::PassmanServer,
+    tool: &str,
+    e: crate::policy::PolicyDenied,
+) -> CallToolResult {
+    server.metrics.record_policy_denial(tool);
+    CallToolResult::error(vec![Content::text(format!("{e}"))])
+}
+
+/// Tool error for a call refused by the policy's rate limit.
+pub(crate) fn rate_limit_denial(
+    server: &crate::server::PassmanServer,
+    tool: &str,
+    e: crate::policy::PolicyDenied,
+) -> CallToolResult {
+    server.metrics.record_rate_limit_hit(tool);
+    CallToolResult::error(vec![Content::text(format!("{e}"))])
+}
+
+/// Tool error for a failed proxy operation, with a hint on what the agent
+/// can do about it.
+pub(crate) fn proxy_error_result(e: &ProxyError) -> CallToolResult {
+    let hint = match e {
+        ProxyError::Connection(_) => {
+            "Check the host and port, and that the service is reachable. Retrying may succeed."
+        }
+        ProxyError::Auth(_) => {
+            "The stored credential was rejected. It may be expired or need rotating (credential_update)."
+        }
+        ProxyError::Timeout(_) => "The remote did not respond in time. Retrying may succeed.",
+        ProxyError::Tls(_) => "Check the server certificate and the credential's TLS settings.",
+        ProxyError::Remote { status, .. } if e.is_retryable() => {
+            if *status == 429 {
+                "The remote is rate limiting. Wait before retrying."
+            } else {
+                "The remote service failed. Retrying may succeed."
+            }
+        }
+        ProxyError::Remote { .. } => "The remote service rejected the request.",
+        ProxyError::InvalidInput(_) | ProxyError::Protocol(_) | ProxyError::PolicyDenied(_) => {
+            return CallToolResult::error(vec![Content::text(format!("{e}"))]);
+        }
+    };
+
+    CallToolResult::error(vec![Content::text(format!("{e}\n{hint}"))])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(result: &CallToolResult) -> String {
+        result
+            .content
+            .iter()
+            .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_proxy_error_hints() {
+        let result = proxy_error_result(&ProxyError::Auth("SSH authentication rejected".into()));
+        assert_eq!(result.is_error, Some(true));
+        assert!(text(&result).contains("credential_update"));
+
+        let result = proxy_error_result(&ProxyError::Remote {
+            status: 503,
+            message: "Service Unavailable".into(),
+        });
+        assert!(text(&result).contains("Retrying may succeed"));
+
+        let result = proxy_error_result(&ProxyError::InvalidInput("bad header".into()));
+        assert_eq!(text(&result), "invalid input: bad header");
+    }
+
+    #[test]
+    fn test_truncate_output() {
+        let mut short = "ok".to_string();
+        assert!(!truncate_output(&mut short, 10));
+        assert_eq!(short, "ok");
+
+        // Cut on a character boundary: 'é' is two bytes
+        let mut text = "aé".repeat(10);
+        assert!(truncate_output(&mut text, 4));
+        assert_eq!(text, "aéa\n[output truncated: 26 bytes omitted]");
+    }
+
+    #[test]
+    fn test_truncate_rows() {
+        let mut rows: Vec<Vec<serde_json::Value>> =
+            (0..100).map(|n| vec![serde_json::json!(n)]).collect();
+        let mut budget = 1_000_000;
+        assert!(truncate_rows(&mut rows, &mut budget).is_none());
+        assert!(budget < 1_000_000);
+
+        // "[0]" plus a separator is 4 bytes a row for the first ten
+        let mut budget = 42;
+        let marker = truncate_rows(&mut rows, &mut budget).unwrap();
+        assert_eq!(rows.len(), 10);
+        assert_eq!(budget, 2);
+        assert!(marker.starts_with("[output truncated: "), "{marker}");
+
+        // A spent budget keeps nothing
+        assert!(truncate_rows(&mut rows, &mut budget).is_some());
+        assert!(rows.is_empty());
+    }
+}
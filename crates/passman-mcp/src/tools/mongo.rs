@@ -0,0 +1,153 @@
+use crate::server::PassmanServer;
+use rmcp::{model::CallToolResult, schemars, ErrorData as McpError};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct MongoFindParams {
+    #[schemars(description = "Credential UUID or name@environment (mongodb)")]
+    #[serde(alias = "credential_ref")]
+    pub credential_id: String,
+    #[schemars(description = "Database name")]
+    pub database: String,
+    #[schemars(description = "Collection name")]
+    pub collection: String,
+    #[schemars(
+        description = "Query filter document (e.g., {\"status\": \"active\"}). Omit to match all documents."
+    )]
+    pub filter: Option<serde_json::Value>,
+    #[schemars(description = "Maximum number of documents to return (default 100, max 1000)")]
+    pub limit: Option<u32>,
+    #[schemars(
+        description = "Approval ID returned by a previous call when the credential's policy requires approval"
+    )]
+    pub approval_id: Option<String>,
+}
+
+#[cfg(feature = "mongo")]
+pub async fn mongo_find(
+    server: &PassmanServer,
+    params: MongoFindParams,
+) -> Result<CallToolResult, McpError> {
+    use passman_types::{AuditAction, AuditEntry};
+    use rmcp::model::Content;
+
+    let cred_id = match super::resolve_credential(server, &params.credential_id).await {
+        Ok(id) => id,
+        Err(result) => return Ok(result),
+    };
+
+    if let Some(denied) = super::scope_denial(server, cred_id).await {
+        return Ok(denied);
+    }
+
+    let secret = match server.vault.get_credential_secret(cred_id).await {
+        Ok(secret) => secret,
+        Err(e) => return super::vault_error_result(e),
+    };
+
+    // Check policy
+    if let Ok(Some(policy)) = server.vault.get_effective_policy(cred_id).await {
+        if let Err(e) = server.policy.check_tool(&policy, "mongo_find") {
+            return Ok(super::policy_denial(server, "mongo_find", e));
+        }
+        if let Err(e) = server.policy.check_time_window(&policy, chrono::Utc::now()) {
+            return Ok(super::policy_denial(server, "mongo_find", e));
+        }
+        if let Err(e) = server.policy.check_mongo_operation(&policy, "find") {
+            return Ok(super::policy_denial(server, "mongo_find", e));
+        }
+        if let Err(e) = server.policy.check_rate_limit(&policy, "mongo_find").await {
+            return Ok(super::rate_limit_denial(server, "mongo_find", e));
+        }
+        if let Some(result) = crate::approval::check_approval(
+            server,
+            &policy,
+            "mongo_find",
+            &format!(
+                "find {}.{} {}",
+                params.database,
+                params.collection,
+                params.filter.clone().unwrap_or_default()
+            ),
+            serde_json::json!({
+                "database": params.database,
+                "collection": params.collection,
+                "filter": params.filter,
+                "limit": params.limit,
+            }),
+            params.approval_id.as_deref(),
+        )
+        .await?
+        {
+            return Ok(result);
+        }
+    }
+
+    let input = passman_proxy::mongo::MongoFindInput {
+        database: params.database,
+        collection: params.collection,
+        filter: params.filter,
+        limit: params.limit,
+    };
+    let target = format!("{}.{}", input.database, input.collection);
+
+    let meta = server.vault.get_credential_meta(cred_id).await.ok();
+
+    let _permit = server.concurrency.acquire(cred_id).await;
+    let result = passman_proxy::redact::scope(
+        secret.secret_strings(),
+        passman_proxy::mongo::find(&secret, &input),
+    )
+    .await;
+    match result {
+        Ok(documents) => {
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::MongoFind,
+                    tool: "mongo_find".to_string(),
+                    success: true,
+                    details: Some(target),
+                })
+                .await;
+            let _ = server.vault.record_usage(cred_id).await;
+
+            Ok(CallToolResult::success(vec![Content::text(
+                serde_json::json!({
+                    "count": documents.len(),
+                    "documents": documents,
+                })
+                .to_string(),
+            )]))
+        }
+        Err(e) => {
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::MongoFind,
+                    tool: "mongo_find".to_string(),
+                    success: false,
+                    details: Some(format!("{target}: {e}")),
+                })
+                .await;
+
+            Ok(super::proxy_error_result(&e))
+        }
+    }
+}
+
+#[cfg(not(feature = "mongo"))]
+pub async fn mongo_find(
+    _server: &PassmanServer,
+    _params: MongoFindParams,
+) -> Result<CallToolResult, McpError> {
+    Ok(CallToolResult::error(vec![rmcp::model::Content::text(
+        "MongoDB support is not enabled in this build (rebuild with the 'mongo' feature)",
+    )]))
+}
@@ -0,0 +1,97 @@
+use crate::server::PassmanServer;
+use passman_types::{AuditAction, AuditEntry};
+use rmcp::{model::CallToolResult, model::Content, schemars, ErrorData as McpError};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CredentialPingParams {
+    #[schemars(
+        description = "Credential UUID or name@environment (SSH, database or SMTP credential)"
+    )]
+    #[serde(alias = "credential_ref")]
+    pub credential_id: String,
+    #[schemars(description = "Connect timeout in seconds (default: 5)")]
+    pub timeout_secs: Option<u64>,
+}
+
+pub async fn credential_ping(
+    server: &PassmanServer,
+    params: CredentialPingParams,
+) -> Result<CallToolResult, McpError> {
+    let cred_id = match super::resolve_credential(server, &params.credential_id).await {
+        Ok(id) => id,
+        Err(result) => return Ok(result),
+    };
+
+    if let Some(denied) = super::scope_denial(server, cred_id).await {
+        return Ok(denied);
+    }
+
+    let secret = match server.vault.get_credential_secret(cred_id).await {
+        Ok(secret) => secret,
+        Err(e) => return super::vault_error_result(e),
+    };
+
+    let host = match passman_proxy::ping::target(&secret) {
+        Ok((host, _)) => host.to_string(),
+        Err(e) => return Ok(super::proxy_error_result(&e)),
+    };
+
+    // Check policy
+    if let Ok(Some(policy)) = server.vault.get_effective_policy(cred_id).await {
+        if let Err(e) = server.policy.check_tool(&policy, "credential_ping") {
+            return Ok(super::policy_denial(server, "credential_ping", e));
+        }
+        if let Err(e) = server.policy.check_host(&policy, &host) {
+            return Ok(super::policy_denial(server, "credential_ping", e));
+        }
+        if let Err(e) = server.policy.check_time_window(&policy, chrono::Utc::now()) {
+            return Ok(super::policy_denial(server, "credential_ping", e));
+        }
+        if let Err(e) = server
+            .policy
+            .check_rate_limit(&policy, "credential_ping")
+            .await
+        {
+            return Ok(super::rate_limit_denial(server, "credential_ping", e));
+        }
+    }
+
+    let meta = server.vault.get_credential_meta(cred_id).await.ok();
+
+    let _permit = server.concurrency.acquire(cred_id).await;
+    let result = passman_proxy::redact::scope(
+        secret.secret_strings(),
+        passman_proxy::ping::execute(&secret, params.timeout_secs),
+    )
+    .await;
+    match result {
+        Ok(output) => {
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::CredentialPing,
+                    tool: "credential_ping".to_string(),
+                    success: output.reachable,
+                    details: Some(format!("{}:{}", output.host, output.port)),
+                })
+                .await;
+            let _ = server.vault.record_usage(cred_id).await;
+
+            Ok(CallToolResult::success(vec![Content::text(
+                serde_json::json!({
+                    "host": output.host,
+                    "port": output.port,
+                    "reachable": output.reachable,
+                    "latency_ms": output.latency_ms,
+                    "error": output.error,
+                })
+                .to_string(),
+            )]))
+        }
+        Err(e) => Ok(super::proxy_error_result(&e)),
+    }
+}
@@ -0,0 +1,30 @@
+use crate::server::PassmanServer;
+use rmcp::{model::CallToolResult, model::Content, ErrorData as McpError};
+
+// ── policy_template_list ─────────────────────────────────────────
+
+pub async fn policy_template_list(server: &PassmanServer) -> Result<CallToolResult, McpError> {
+    match server.vault.list_policy_templates().await {
+        Ok(templates) => {
+            let items: Vec<serde_json::Value> = templates
+                .iter()
+                .map(|t| {
+                    serde_json::json!({
+                        "name": t.name,
+                        "environment": t.environment,
+                        "tag": t.tag,
+                        "allowed_tools": t.rule.allowed_tools,
+                        "sql_allow_write": t.rule.sql_allow_write,
+                        "mongo_allow_write": t.rule.mongo_allow_write,
+                        "require_approval": t.rule.require_approval,
+                    })
+                })
+                .collect();
+
+            Ok(CallToolResult::success(vec![Content::text(
+                serde_json::to_string(&items).unwrap(),
+            )]))
+        }
+        Err(e) => Ok(CallToolResult::error(vec![Content::text(format!("{e}"))])),
+    }
+}
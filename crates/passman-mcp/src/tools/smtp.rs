@@ -5,7 +5,8 @@ use serde::Deserialize;
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct SendEmailParams {
-    #[schemars(description = "Credential UUID (SMTP account)")]
+    #[schemars(description = "Credential UUID or name@environment (SMTP account)")]
+    #[serde(alias = "credential_ref")]
     pub credential_id: String,
     #[schemars(description = "Recipient email addresses")]
     pub to: Vec<String>,
@@ -17,36 +18,79 @@ pub struct SendEmailParams {
     pub cc: Option<Vec<String>>,
     #[schemars(description = "BCC recipients")]
     pub bcc: Option<Vec<String>>,
+    #[schemars(
+        description = "SMTP envelope sender (MAIL FROM), e.g. a bounce address, when it must differ from the From header"
+    )]
+    pub envelope_from: Option<String>,
+    #[schemars(
+        description = "Additional headers as [name, value] pairs, e.g. [[\"List-Unsubscribe\", \"<mailto:unsub@example.com>\"]]"
+    )]
+    pub extra_headers: Option<Vec<(String, String)>>,
+    #[schemars(
+        description = "Approval ID returned by a previous call when the credential's policy requires approval"
+    )]
+    pub approval_id: Option<String>,
 }
 
 pub async fn send_email(
     server: &PassmanServer,
     params: SendEmailParams,
 ) -> Result<CallToolResult, McpError> {
-    let cred_id: uuid::Uuid = params
-        .credential_id
-        .parse()
-        .map_err(|_| McpError::invalid_params("invalid UUID", None))?;
+    let cred_id = match super::resolve_credential(server, &params.credential_id).await {
+        Ok(id) => id,
+        Err(result) => return Ok(result),
+    };
+
+    if let Some(denied) = super::scope_denial(server, cred_id).await {
+        return Ok(denied);
+    }
 
-    let secret = server
-        .vault
-        .get_credential_secret(cred_id)
-        .await
-        .map_err(|e| McpError::internal_error(format!("{e}"), None))?;
+    let secret = match server.vault.get_credential_secret(cred_id).await {
+        Ok(secret) => secret,
+        Err(e) => return super::vault_error_result(e),
+    };
 
     // Check policy
-    if let Ok(Some(policy)) = server.vault.get_policy(cred_id).await {
+    if let Ok(Some(policy)) = server.vault.get_effective_policy(cred_id).await {
         if let Err(e) = server.policy.check_tool(&policy, "send_email") {
-            return Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]));
+            return Ok(super::policy_denial(server, "send_email", e));
+        }
+        if let Err(e) = server.policy.check_time_window(&policy, chrono::Utc::now()) {
+            return Ok(super::policy_denial(server, "send_email", e));
         }
         // Check each recipient
-        for recipient in params.to.iter().chain(params.cc.iter().flatten()).chain(params.bcc.iter().flatten()) {
+        for recipient in params
+            .to
+            .iter()
+            .chain(params.cc.iter().flatten())
+            .chain(params.bcc.iter().flatten())
+        {
             if let Err(e) = server.policy.check_smtp_recipient(&policy, recipient) {
-                return Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]));
+                return Ok(super::policy_denial(server, "send_email", e));
             }
         }
-        if let Err(e) = server.policy.check_rate_limit(&policy).await {
-            return Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]));
+        if let Err(e) = server.policy.check_rate_limit(&policy, "send_email").await {
+            return Ok(super::rate_limit_denial(server, "send_email", e));
+        }
+        if let Some(result) = crate::approval::check_approval(
+            server,
+            &policy,
+            "send_email",
+            &format!("to: {}; subject: {}", params.to.join(", "), params.subject),
+            serde_json::json!({
+                "to": params.to,
+                "cc": params.cc,
+                "bcc": params.bcc,
+                "subject": params.subject,
+                "body": params.body,
+                "envelope_from": params.envelope_from,
+                "extra_headers": params.extra_headers,
+            }),
+            params.approval_id.as_deref(),
+        )
+        .await?
+        {
+            return Ok(result);
         }
     }
 
@@ -57,21 +101,33 @@ pub async fn send_email(
         cc: params.cc,
         bcc: params.bcc,
         from: None,
+        envelope_from: params.envelope_from,
+        extra_headers: params.extra_headers.unwrap_or_default(),
     };
 
     let meta = server.vault.get_credential_meta(cred_id).await.ok();
 
-    match passman_proxy::smtp::execute(&secret, &input).await {
+    let _permit = server.concurrency.acquire(cred_id).await;
+    let result = passman_proxy::redact::scope(
+        secret.secret_strings(),
+        passman_proxy::smtp::execute(&secret, &input),
+    )
+    .await;
+    match result {
         Ok(output) => {
-            let _ = server.vault.log_audit(&AuditEntry {
-                timestamp: chrono::Utc::now(),
-                credential_id: Some(cred_id),
-                credential_name: meta.map(|m| m.name),
-                action: AuditAction::SendEmail,
-                tool: "send_email".to_string(),
-                success: output.success,
-                details: Some(format!("to: {}", params.to.join(", "))),
-            }).await;
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::SendEmail,
+                    tool: "send_email".to_string(),
+                    success: output.success,
+                    details: Some(format!("to: {}", params.to.join(", "))),
+                })
+                .await;
+            let _ = server.vault.record_usage(cred_id).await;
 
             Ok(CallToolResult::success(vec![Content::text(
                 serde_json::json!({
@@ -82,17 +138,105 @@ pub async fn send_email(
             )]))
         }
         Err(e) => {
-            let _ = server.vault.log_audit(&AuditEntry {
-                timestamp: chrono::Utc::now(),
-                credential_id: Some(cred_id),
-                credential_name: meta.map(|m| m.name),
-                action: AuditAction::SendEmail,
-                tool: "send_email".to_string(),
-                success: false,
-                details: Some(format!("{e}")),
-            }).await;
-
-            Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]))
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::SendEmail,
+                    tool: "send_email".to_string(),
+                    success: false,
+                    details: Some(format!("{e}")),
+                })
+                .await;
+
+            Ok(super::proxy_error_result(&e))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SmtpVerifyParams {
+    #[schemars(description = "Credential UUID or name@environment (SMTP account)")]
+    #[serde(alias = "credential_ref")]
+    pub credential_id: String,
+}
+
+pub async fn smtp_verify(
+    server: &PassmanServer,
+    params: SmtpVerifyParams,
+) -> Result<CallToolResult, McpError> {
+    let cred_id = match super::resolve_credential(server, &params.credential_id).await {
+        Ok(id) => id,
+        Err(result) => return Ok(result),
+    };
+
+    if let Some(denied) = super::scope_denial(server, cred_id).await {
+        return Ok(denied);
+    }
+
+    let secret = match server.vault.get_credential_secret(cred_id).await {
+        Ok(secret) => secret,
+        Err(e) => return super::vault_error_result(e),
+    };
+
+    // Check policy
+    if let Ok(Some(policy)) = server.vault.get_effective_policy(cred_id).await {
+        if let Err(e) = server.policy.check_tool(&policy, "smtp_verify") {
+            return Ok(super::policy_denial(server, "smtp_verify", e));
+        }
+        if let Err(e) = server.policy.check_time_window(&policy, chrono::Utc::now()) {
+            return Ok(super::policy_denial(server, "smtp_verify", e));
+        }
+        if let Err(e) = server.policy.check_rate_limit(&policy, "smtp_verify").await {
+            return Ok(super::rate_limit_denial(server, "smtp_verify", e));
+        }
+    }
+
+    let meta = server.vault.get_credential_meta(cred_id).await.ok();
+
+    let _permit = server.concurrency.acquire(cred_id).await;
+    let result = passman_proxy::redact::scope(
+        secret.secret_strings(),
+        passman_proxy::smtp::verify(&secret),
+    )
+    .await;
+    match result {
+        Ok(()) => {
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::SmtpVerify,
+                    tool: "smtp_verify".to_string(),
+                    success: true,
+                    details: None,
+                })
+                .await;
+            let _ = server.vault.record_usage(cred_id).await;
+
+            Ok(CallToolResult::success(vec![Content::text(
+                serde_json::json!({ "success": true }).to_string(),
+            )]))
+        }
+        Err(e) => {
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::SmtpVerify,
+                    tool: "smtp_verify".to_string(),
+                    success: false,
+                    details: Some(format!("{e}")),
+                })
+                .await;
+
+            Ok(super::proxy_error_result(&e))
         }
     }
 }
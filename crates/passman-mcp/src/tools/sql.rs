@@ -1,86 +1,223 @@
+use crate::policy::PolicyDenied;
 use crate::server::PassmanServer;
+use passman_proxy::sql::SqlQueryOutput;
 use passman_types::{AuditAction, AuditEntry};
 use rmcp::{model::CallToolResult, model::Content, schemars, ErrorData as McpError};
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct SqlQueryParams {
-    #[schemars(description = "Credential UUID (database connection)")]
+    #[schemars(description = "Credential UUID or name@environment (database connection)")]
+    #[serde(alias = "credential_ref")]
     pub credential_id: String,
     #[schemars(description = "SQL query to execute")]
     pub query: String,
     #[schemars(description = "Query parameters (positional)")]
     pub params: Option<Vec<serde_json::Value>>,
+    #[schemars(
+        description = "Maximum number of rows to return (default 1000). The result reports truncated=true if more rows were available."
+    )]
+    pub max_rows: Option<usize>,
+    #[schemars(
+        description = "Abort the query after this many seconds (default 30). PostgreSQL and MySQL queries are cancelled on the server; a SQLite query can't be interrupted and finishes in the background."
+    )]
+    pub timeout_secs: Option<u64>,
+    #[schemars(
+        description = "Run a script of several ';'-separated statements and return one result per statement. Requires a policy that allows writes."
+    )]
+    pub multi: Option<bool>,
+    #[schemars(
+        description = "Approval ID returned by a previous call when the credential's policy requires approval"
+    )]
+    pub approval_id: Option<String>,
 }
 
 pub async fn sql_query(
     server: &PassmanServer,
     params: SqlQueryParams,
 ) -> Result<CallToolResult, McpError> {
-    let cred_id: uuid::Uuid = params
-        .credential_id
-        .parse()
-        .map_err(|_| McpError::invalid_params("invalid UUID", None))?;
+    let cred_id = match super::resolve_credential(server, &params.credential_id).await {
+        Ok(id) => id,
+        Err(result) => return Ok(result),
+    };
 
-    let secret = server
-        .vault
-        .get_credential_secret(cred_id)
-        .await
-        .map_err(|e| McpError::internal_error(format!("{e}"), None))?;
+    if let Some(denied) = super::scope_denial(server, cred_id).await {
+        return Ok(denied);
+    }
+
+    let secret = match server.vault.get_credential_secret(cred_id).await {
+        Ok(secret) => secret,
+        Err(e) => return super::vault_error_result(e),
+    };
+
+    let multi = params.multi.unwrap_or(false);
 
     // Check policy
-    if let Ok(Some(policy)) = server.vault.get_policy(cred_id).await {
-        if let Err(e) = server.policy.check_tool(&policy, "sql_query") {
-            return Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]));
+    let policy = server
+        .vault
+        .get_effective_policy(cred_id)
+        .await
+        .ok()
+        .flatten();
+    if let Some(policy) = &policy {
+        if let Err(e) = server.policy.check_tool(policy, "sql_query") {
+            return Ok(super::policy_denial(server, "sql_query", e));
+        }
+        if let Err(e) = server.policy.check_time_window(policy, chrono::Utc::now()) {
+            return Ok(super::policy_denial(server, "sql_query", e));
         }
-        if let Err(e) = server.policy.check_sql_query(&policy, &params.query) {
-            return Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]));
+        if multi && !policy.sql_allow_write {
+            let e = PolicyDenied(
+                "multi-statement scripts need a policy that allows writes".to_string(),
+            );
+            return Ok(super::policy_denial(server, "sql_query", e));
         }
-        if let Err(e) = server.policy.check_rate_limit(&policy).await {
-            return Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]));
+        let statements = match multi {
+            true => passman_proxy::sql::split_statements(&params.query),
+            false => vec![params.query.as_str()],
+        };
+        for statement in statements {
+            if let Err(e) = server.policy.check_sql_query(policy, statement) {
+                return Ok(super::policy_denial(server, "sql_query", e));
+            }
+        }
+        if let Err(e) = server.policy.check_rate_limit(policy, "sql_query").await {
+            return Ok(super::rate_limit_denial(server, "sql_query", e));
+        }
+        if let Some(result) = crate::approval::check_approval(
+            server,
+            policy,
+            "sql_query",
+            &params.query,
+            serde_json::json!({
+                "query": params.query,
+                "params": params.params,
+                "max_rows": params.max_rows,
+                "timeout_secs": params.timeout_secs,
+                "multi": multi,
+            }),
+            params.approval_id.as_deref(),
+        )
+        .await?
+        {
+            return Ok(result);
         }
     }
 
     let input = passman_proxy::sql::SqlQueryInput {
         query: params.query.clone(),
         params: params.params,
+        max_rows: params.max_rows,
+        timeout_secs: params.timeout_secs,
+        // Backs up the keyword check: the database itself refuses writes
+        read_only: policy.as_ref().is_some_and(|p| !p.sql_allow_write),
+        multi,
     };
 
     let meta = server.vault.get_credential_meta(cred_id).await.ok();
 
-    match passman_proxy::sql::execute(&secret, &input).await {
-        Ok(output) => {
-            let _ = server.vault.log_audit(&AuditEntry {
-                timestamp: chrono::Utc::now(),
-                credential_id: Some(cred_id),
-                credential_name: meta.map(|m| m.name),
-                action: AuditAction::SqlQuery,
-                tool: "sql_query".to_string(),
-                success: true,
-                details: Some(params.query),
-            }).await;
+    let _permit = server.concurrency.acquire(cred_id).await;
+    let result = passman_proxy::redact::scope(
+        secret.secret_strings(),
+        passman_proxy::sql::execute_multi(&secret, &input),
+    )
+    .await;
+    match result {
+        Ok(mut outputs) => {
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::SqlQuery,
+                    tool: "sql_query".to_string(),
+                    success: true,
+                    details: Some(params.query),
+                })
+                .await;
+            let _ = server.vault.record_usage(cred_id).await;
 
+            // One budget for the whole response, spent statement by statement
+            let mut budget = server.output_limits.for_tool("sql_query");
+            let body = match multi {
+                true => {
+                    let results: Vec<_> = outputs
+                        .into_iter()
+                        .map(|output| output_json(output, &mut budget))
+                        .collect();
+                    let truncated = results.iter().any(|r| !r["output_truncated"].is_null());
+                    serde_json::json!({
+                        "results": results,
+                        "truncated": truncated,
+                    })
+                }
+                false => output_json(outputs.remove(0), &mut budget),
+            };
             Ok(CallToolResult::success(vec![Content::text(
-                serde_json::json!({
-                    "columns": output.columns,
-                    "rows": output.rows,
-                    "rows_affected": output.rows_affected,
-                })
-                .to_string(),
+                body.to_string(),
             )]))
         }
         Err(e) => {
-            let _ = server.vault.log_audit(&AuditEntry {
-                timestamp: chrono::Utc::now(),
-                credential_id: Some(cred_id),
-                credential_name: meta.map(|m| m.name),
-                action: AuditAction::SqlQuery,
-                tool: "sql_query".to_string(),
-                success: false,
-                details: Some(format!("{e}")),
-            }).await;
-
-            Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]))
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::SqlQuery,
+                    tool: "sql_query".to_string(),
+                    success: false,
+                    details: Some(format!("{e}")),
+                })
+                .await;
+
+            Ok(super::proxy_error_result(&e))
         }
     }
 }
+
+/// One statement's result, with rows dropped from the end to fit what's
+/// left of `budget`.
+fn output_json(mut output: SqlQueryOutput, budget: &mut usize) -> serde_json::Value {
+    let cut = super::truncate_rows(&mut output.rows, budget);
+    serde_json::json!({
+        "columns": output.columns,
+        "rows": output.rows,
+        "rows_affected": output.rows_affected,
+        "truncated": output.truncated || cut.is_some(),
+        "output_truncated": cut,
+        "timing": output.timing,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(rows: usize) -> SqlQueryOutput {
+        SqlQueryOutput {
+            columns: vec!["n".to_string()],
+            rows: (0..rows).map(|n| vec![serde_json::json!(n % 10)]).collect(),
+            rows_affected: 0,
+            truncated: false,
+            timing: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_statements_share_the_output_budget() {
+        // Each "[n]" row is 4 bytes with its separator
+        let mut budget = 60;
+        let first = output_json(output(10), &mut budget);
+        let second = output_json(output(10), &mut budget);
+        let third = output_json(output(10), &mut budget);
+
+        assert_eq!(first["rows"].as_array().unwrap().len(), 10);
+        assert_eq!(first["truncated"], false);
+        assert_eq!(second["rows"].as_array().unwrap().len(), 5);
+        assert_eq!(second["truncated"], true);
+        assert!(third["rows"].as_array().unwrap().is_empty());
+        assert!(third["output_truncated"].is_string());
+    }
+}
@@ -1,83 +1,343 @@
 use crate::server::PassmanServer;
+use passman_proxy::ssh::SshExecOutput;
 use passman_types::{AuditAction, AuditEntry};
 use rmcp::{model::CallToolResult, model::Content, schemars, ErrorData as McpError};
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
 pub struct SshExecParams {
-    #[schemars(description = "Credential UUID (SSH key or password)")]
+    #[schemars(description = "Credential UUID or name@environment (SSH key or password)")]
+    #[serde(alias = "credential_ref")]
     pub credential_id: String,
-    #[schemars(description = "Shell command to execute on the remote host")]
-    pub command: String,
+    #[schemars(
+        description = "Shell command to execute on the remote host, passed to the shell as-is. Use program and args instead to have arguments quoted for you"
+    )]
+    pub command: Option<String>,
+    #[schemars(
+        description = "Program to run, as an alternative to command; combined with args into a shell-escaped command line"
+    )]
+    pub program: Option<String>,
+    #[schemars(
+        description = "Arguments for program, each passed literally (spaces, quotes and $(...) are not interpreted)"
+    )]
+    pub args: Option<Vec<String>>,
+    #[schemars(
+        description = "Optional input written to the command's stdin (EOF is sent afterwards)"
+    )]
+    pub stdin: Option<String>,
+    #[schemars(
+        description = "Allocate a pseudo-terminal (default false). Needed for programs that check isatty; stdout and stderr are interleaved in PTY mode"
+    )]
+    pub pty: Option<bool>,
+    #[schemars(description = "Terminal type when pty is true (default xterm)")]
+    pub term: Option<String>,
+    #[schemars(description = "Stop after this many seconds without output (default 120)")]
+    pub inactivity_timeout_secs: Option<u64>,
+    #[schemars(
+        description = "Stop after this many seconds in total, even if output is still arriving (default: no limit)"
+    )]
+    pub max_total_secs: Option<u64>,
+    #[schemars(
+        description = "Approval ID returned by a previous call when the credential's policy requires approval"
+    )]
+    pub approval_id: Option<String>,
 }
 
 pub async fn ssh_exec(
     server: &PassmanServer,
     params: SshExecParams,
 ) -> Result<CallToolResult, McpError> {
-    let cred_id: uuid::Uuid = params
-        .credential_id
-        .parse()
-        .map_err(|_| McpError::invalid_params("invalid UUID", None))?;
+    let cred_id = match super::resolve_credential(server, &params.credential_id).await {
+        Ok(id) => id,
+        Err(result) => return Ok(result),
+    };
+
+    let command = match (params.command, params.program) {
+        (Some(command), None) if params.args.is_none() => command,
+        (None, Some(program)) => {
+            passman_proxy::ssh::shell_command(&program, &params.args.unwrap_or_default())
+        }
+        _ => {
+            return Err(McpError::invalid_params(
+                "provide either command, or program with optional args",
+                None,
+            ))
+        }
+    };
+
+    if let Some(denied) = super::scope_denial(server, cred_id).await {
+        return Ok(denied);
+    }
 
-    let secret = server
-        .vault
-        .get_credential_secret(cred_id)
-        .await
-        .map_err(|e| McpError::internal_error(format!("{e}"), None))?;
+    let secret = match server.vault.get_credential_secret(cred_id).await {
+        Ok(secret) => secret,
+        Err(e) => return super::vault_error_result(e),
+    };
 
     // Check policy
-    if let Ok(Some(policy)) = server.vault.get_policy(cred_id).await {
+    if let Ok(Some(policy)) = server.vault.get_effective_policy(cred_id).await {
         if let Err(e) = server.policy.check_tool(&policy, "ssh_exec") {
-            return Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]));
+            return Ok(super::policy_denial(server, "ssh_exec", e));
         }
-        if let Err(e) = server.policy.check_ssh_command(&policy, &params.command) {
-            return Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]));
+        if let Err(e) = server.policy.check_time_window(&policy, chrono::Utc::now()) {
+            return Ok(super::policy_denial(server, "ssh_exec", e));
         }
-        if let Err(e) = server.policy.check_rate_limit(&policy).await {
-            return Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]));
+        if let Err(e) = server.policy.check_ssh_command(&policy, &command) {
+            return Ok(super::policy_denial(server, "ssh_exec", e));
+        }
+        if let Err(e) = server.policy.check_rate_limit(&policy, "ssh_exec").await {
+            return Ok(super::rate_limit_denial(server, "ssh_exec", e));
+        }
+        if let Some(result) = crate::approval::check_approval(
+            server,
+            &policy,
+            "ssh_exec",
+            &command,
+            serde_json::json!({
+                "command": command,
+                "stdin": params.stdin,
+                "pty": params.pty,
+                "term": params.term,
+                "inactivity_timeout_secs": params.inactivity_timeout_secs,
+                "max_total_secs": params.max_total_secs,
+            }),
+            params.approval_id.as_deref(),
+        )
+        .await?
+        {
+            return Ok(result);
         }
     }
 
     let input = passman_proxy::ssh::SshExecInput {
-        command: params.command.clone(),
+        command: command.clone(),
+        stdin: params.stdin,
+        pty: params.pty.unwrap_or(false),
+        term: params.term,
+        inactivity_timeout_secs: params.inactivity_timeout_secs,
+        max_total_secs: params.max_total_secs,
     };
 
     let meta = server.vault.get_credential_meta(cred_id).await.ok();
 
-    match passman_proxy::ssh::execute(&secret, &input).await {
+    let _permit = server.concurrency.acquire(cred_id).await;
+    let result = passman_proxy::redact::scope(
+        secret.secret_strings(),
+        passman_proxy::ssh::execute(&secret, &input),
+    )
+    .await;
+    match result {
         Ok(output) => {
-            let _ = server.vault.log_audit(&AuditEntry {
-                timestamp: chrono::Utc::now(),
-                credential_id: Some(cred_id),
-                credential_name: meta.map(|m| m.name),
-                action: AuditAction::SshExec,
-                tool: "ssh_exec".to_string(),
-                success: output.exit_code == 0,
-                details: Some(params.command),
-            }).await;
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::SshExec,
+                    tool: "ssh_exec".to_string(),
+                    success: output.exit_code == 0,
+                    details: Some(command),
+                })
+                .await;
+            let _ = server.vault.record_usage(cred_id).await;
+
+            let max_bytes = server.output_limits.for_tool("ssh_exec");
+            Ok(CallToolResult::success(vec![Content::text(
+                exec_output_json(output, max_bytes).to_string(),
+            )]))
+        }
+        Err(e) => {
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::SshExec,
+                    tool: "ssh_exec".to_string(),
+                    success: false,
+                    details: Some(format!("{e}")),
+                })
+                .await;
+
+            Ok(super::proxy_error_result(&e))
+        }
+    }
+}
+
+// ── ssh_tunnel ───────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct SshTunnelParams {
+    #[schemars(
+        description = "Credential UUID or name@environment (SSH key or password) of the bastion host"
+    )]
+    #[serde(alias = "credential_ref")]
+    pub credential_id: String,
+    #[schemars(
+        description = "Host to reach from the bastion (e.g., an internal service hostname)"
+    )]
+    pub remote_host: String,
+    #[schemars(description = "TCP port on the remote host")]
+    pub remote_port: u16,
+    #[schemars(
+        description = "Raw request bytes to send, as text (e.g., an HTTP/1.0 request or a Redis command)"
+    )]
+    pub payload: String,
+    #[schemars(
+        description = "Approval ID returned by a previous call when the credential's policy requires approval"
+    )]
+    pub approval_id: Option<String>,
+}
+
+pub async fn ssh_tunnel(
+    server: &PassmanServer,
+    params: SshTunnelParams,
+) -> Result<CallToolResult, McpError> {
+    let cred_id = match super::resolve_credential(server, &params.credential_id).await {
+        Ok(id) => id,
+        Err(result) => return Ok(result),
+    };
+
+    if let Some(denied) = super::scope_denial(server, cred_id).await {
+        return Ok(denied);
+    }
+
+    let secret = match server.vault.get_credential_secret(cred_id).await {
+        Ok(secret) => secret,
+        Err(e) => return super::vault_error_result(e),
+    };
+
+    // Check policy
+    if let Ok(Some(policy)) = server.vault.get_effective_policy(cred_id).await {
+        if let Err(e) = server.policy.check_tool(&policy, "ssh_tunnel") {
+            return Ok(super::policy_denial(server, "ssh_tunnel", e));
+        }
+        if let Err(e) = server.policy.check_time_window(&policy, chrono::Utc::now()) {
+            return Ok(super::policy_denial(server, "ssh_tunnel", e));
+        }
+        if let Err(e) = server.policy.check_rate_limit(&policy, "ssh_tunnel").await {
+            return Ok(super::rate_limit_denial(server, "ssh_tunnel", e));
+        }
+        if let Some(result) = crate::approval::check_approval(
+            server,
+            &policy,
+            "ssh_tunnel",
+            &format!("{}:{}", params.remote_host, params.remote_port),
+            serde_json::json!({
+                "remote_host": params.remote_host,
+                "remote_port": params.remote_port,
+                "payload": params.payload,
+            }),
+            params.approval_id.as_deref(),
+        )
+        .await?
+        {
+            return Ok(result);
+        }
+    }
+
+    let target = format!("{}:{}", params.remote_host, params.remote_port);
+    let meta = server.vault.get_credential_meta(cred_id).await.ok();
+
+    let _permit = server.concurrency.acquire(cred_id).await;
+    let result = passman_proxy::redact::scope(
+        secret.secret_strings(),
+        passman_proxy::ssh::tunnel_request(
+            &secret,
+            &params.remote_host,
+            params.remote_port,
+            params.payload.into_bytes(),
+        ),
+    )
+    .await;
+    match result {
+        Ok(response) => {
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::SshTunnel,
+                    tool: "ssh_tunnel".to_string(),
+                    success: true,
+                    details: Some(target),
+                })
+                .await;
+            let _ = server.vault.record_usage(cred_id).await;
 
             Ok(CallToolResult::success(vec![Content::text(
                 serde_json::json!({
-                    "exit_code": output.exit_code,
-                    "stdout": output.stdout,
-                    "stderr": output.stderr,
+                    "bytes": response.len(),
+                    "response": String::from_utf8_lossy(&response),
                 })
                 .to_string(),
             )]))
         }
         Err(e) => {
-            let _ = server.vault.log_audit(&AuditEntry {
-                timestamp: chrono::Utc::now(),
-                credential_id: Some(cred_id),
-                credential_name: meta.map(|m| m.name),
-                action: AuditAction::SshExec,
-                tool: "ssh_exec".to_string(),
-                success: false,
-                details: Some(format!("{e}")),
-            }).await;
-
-            Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]))
+            let _ = server
+                .vault
+                .log_audit(&AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(cred_id),
+                    credential_name: meta.map(|m| m.name),
+                    action: AuditAction::SshTunnel,
+                    tool: "ssh_tunnel".to_string(),
+                    success: false,
+                    details: Some(format!("{target}: {e}")),
+                })
+                .await;
+
+            Ok(super::proxy_error_result(&e))
+        }
+    }
+}
+
+/// The `ssh_exec` result, with stdout and stderr each cut to `max_bytes`.
+fn exec_output_json(mut output: SshExecOutput, max_bytes: usize) -> serde_json::Value {
+    let stdout_cut = super::truncate_output(&mut output.stdout, max_bytes);
+    let stderr_cut = super::truncate_output(&mut output.stderr, max_bytes);
+    serde_json::json!({
+        "exit_code": output.exit_code,
+        "stdout": output.stdout,
+        "stderr": output.stderr,
+        "signal": output.signal,
+        "core_dumped": output.core_dumped,
+        "signal_message": output.signal_message,
+        "truncated": stdout_cut || stderr_cut,
+        "timing": output.timing,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(stdout: String) -> SshExecOutput {
+        SshExecOutput {
+            exit_code: 0,
+            stdout,
+            stderr: "warning".to_string(),
+            signal: None,
+            core_dumped: false,
+            signal_message: None,
+            timing: Default::default(),
         }
     }
+
+    #[test]
+    fn test_oversized_stdout_is_truncated() {
+        let json = exec_output_json(output("line\n".repeat(10_000)), 1024);
+        let stdout = json["stdout"].as_str().unwrap();
+        assert!(stdout.len() < 1100, "{} bytes", stdout.len());
+        assert!(stdout.ends_with("[output truncated: 48976 bytes omitted]"));
+        assert_eq!(json["stderr"], "warning");
+        assert_eq!(json["truncated"], true);
+
+        let json = exec_output_json(output("ok\n".to_string()), 1024);
+        assert_eq!(json["stdout"], "ok\n");
+        assert_eq!(json["truncated"], false);
+    }
 }
@@ -1,5 +1,5 @@
 use crate::server::PassmanServer;
-use passman_types::{CredentialKind, CredentialSecret, Environment};
+use passman_types::{CredentialKind, CredentialSecret, DbDriver, Environment, LinkDirection};
 use rmcp::{model::CallToolResult, model::Content, schemars, ErrorData as McpError};
 use serde::Deserialize;
 use std::collections::HashMap;
@@ -10,16 +10,26 @@ use std::collections::HashMap;
 pub struct CredentialStoreRequest {
     #[schemars(description = "Human-readable name for the credential")]
     pub name: String,
-    #[schemars(description = "Credential kind: password, api_token, ssh_key, ssh_password, database_connection, certificate, smtp_account, custom")]
+    #[schemars(
+        description = "Credential kind: password, api_token, ssh_key, ssh_password, ssh_agent, database_connection, certificate, smtp_account, mongodb, jwt_signer, opaque_blob, custom"
+    )]
     pub kind: String,
-    #[schemars(description = "Environment: local, development, staging, production, or any custom string")]
+    #[schemars(
+        description = "Environment: local, development, staging, production, or any custom string"
+    )]
     pub environment: String,
-    #[schemars(description = "Secret data object. Structure depends on kind: password={username,password,url?}, api_token={token,header_name?,prefix?}, ssh_key={username,host,port?,private_key,passphrase?}, ssh_password={username,host,port?,password}, database_connection={driver,host,port?,database,username,password}, certificate={cert_pem,key_pem,ca_pem?}, smtp_account={host,port?,username,password,encryption?}, custom={any key-value pairs, add auth_strategy for HTTP auth}")]
+    #[schemars(
+        description = "Secret data object. Structure depends on kind: password={username,password,url?}, api_token={token,header_name?,prefix?}, ssh_key={username,host,port?,private_key,passphrase?}, ssh_password={username,host,port?,password}, ssh_agent={username,host,port?,public_key}, database_connection={driver,host,port?,database,username,password}, certificate={cert_pem,key_pem,ca_pem?,pin_ca?}, smtp_account={host,port?,username,password,encryption?}, mongodb={uri}, jwt_signer={private_key_pem,algorithm?,issuer,audience,ttl_secs?}, opaque_blob={data_base64,content_type?}, custom={any key-value pairs, add auth_strategy for HTTP auth}"
+    )]
     pub secret: serde_json::Value,
     #[schemars(description = "Optional tags for categorization")]
     pub tags: Option<Vec<String>>,
     #[schemars(description = "Optional notes")]
     pub notes: Option<String>,
+    #[schemars(
+        description = "Register a new custom environment when the vault only accepts known environments (strict mode)"
+    )]
+    pub allow_new_env: Option<bool>,
 }
 
 pub async fn credential_store(
@@ -29,13 +39,23 @@ pub async fn credential_store(
     let kind: CredentialKind = serde_json::from_value(serde_json::Value::String(params.kind))
         .map_err(|_| McpError::invalid_params("invalid credential kind", None))?;
 
-    let environment: Environment =
-        serde_json::from_value(serde_json::Value::String(params.environment))
-            .map_err(|_| McpError::invalid_params("invalid environment", None))?;
+    let warning = Environment::suggest_builtin(&params.environment).map(|env| {
+        format!(
+            "environment '{}' looks like a typo of '{env}'",
+            params.environment
+        )
+    });
+    let environment = Environment::parse(&params.environment)
+        .ok_or_else(|| McpError::invalid_params("invalid environment", None))?;
+
+    let secret = parse_secret(kind, &params.secret)
+        .map_err(|e| McpError::invalid_params(format!("invalid secret: {e}"), None))?;
 
-    let secret = parse_secret(kind, &params.secret).map_err(|e| {
-        McpError::invalid_params(format!("invalid secret: {e}"), None)
-    })?;
+    if let (Some(true), Environment::Custom(name)) = (params.allow_new_env, &environment) {
+        if let Err(e) = server.vault.register_environment(name).await {
+            return super::vault_error_result(e);
+        }
+    }
 
     match server
         .vault
@@ -49,13 +69,18 @@ pub async fn credential_store(
         )
         .await
     {
-        Ok(id) => Ok(CallToolResult::success(vec![Content::text(
-            serde_json::json!({
+        Ok(id) => {
+            let mut response = serde_json::json!({
                 "id": id.to_string(),
                 "name": params.name,
-            })
-            .to_string(),
-        )])),
+            });
+            if let Some(warning) = warning {
+                response["warning"] = serde_json::Value::String(warning);
+            }
+            Ok(CallToolResult::success(vec![Content::text(
+                response.to_string(),
+            )]))
+        }
         Err(e) => Ok(CallToolResult::error(vec![Content::text(format!("{e}"))])),
     }
 }
@@ -94,7 +119,7 @@ fn parse_secret(
         CredentialKind::SshKey => {
             let username = get_str(obj, "username")?;
             let host = get_str(obj, "host")?;
-            let port = obj.get("port").and_then(|v| v.as_u64()).unwrap_or(22) as u16;
+            let port = get_port(obj, 22)?;
             let private_key = get_str(obj, "private_key")?;
             let passphrase = obj
                 .get("passphrase")
@@ -111,7 +136,7 @@ fn parse_secret(
         CredentialKind::SshPassword => {
             let username = get_str(obj, "username")?;
             let host = get_str(obj, "host")?;
-            let port = obj.get("port").and_then(|v| v.as_u64()).unwrap_or(22) as u16;
+            let port = get_port(obj, 22)?;
             let password = get_str(obj, "password")?;
             Ok(CredentialSecret::SshPassword {
                 username,
@@ -120,15 +145,24 @@ fn parse_secret(
                 password,
             })
         }
+        CredentialKind::SshAgent => {
+            let username = get_str(obj, "username")?;
+            let host = get_str(obj, "host")?;
+            let port = get_port(obj, 22)?;
+            let public_key = get_str(obj, "public_key")?;
+            Ok(CredentialSecret::SshAgent {
+                username,
+                host,
+                port,
+                public_key,
+            })
+        }
         CredentialKind::DatabaseConnection => {
             let driver_str = get_str(obj, "driver")?;
-            let driver = serde_json::from_value(serde_json::Value::String(driver_str))
+            let driver: DbDriver = serde_json::from_value(serde_json::Value::String(driver_str))
                 .map_err(|_| "invalid driver (postgres, mysql, sqlite)")?;
             let host = get_str(obj, "host")?;
-            let port = obj
-                .get("port")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(5432) as u16;
+            let port = get_port(obj, driver.default_port())?;
             let database = get_str(obj, "database")?;
             let username = get_str(obj, "username")?;
             let password = get_str(obj, "password")?;
@@ -149,22 +183,18 @@ fn parse_secret(
         CredentialKind::Certificate => {
             let cert_pem = get_str(obj, "cert_pem")?;
             let key_pem = get_str(obj, "key_pem")?;
-            let ca_pem = obj
-                .get("ca_pem")
-                .and_then(|v| v.as_str())
-                .map(String::from);
+            let ca_pem = obj.get("ca_pem").and_then(|v| v.as_str()).map(String::from);
+            let pin_ca = obj.get("pin_ca").and_then(|v| v.as_bool()).unwrap_or(false);
             Ok(CredentialSecret::Certificate {
                 cert_pem,
                 key_pem,
                 ca_pem,
+                pin_ca,
             })
         }
         CredentialKind::SmtpAccount => {
             let host = get_str(obj, "host")?;
-            let port = obj
-                .get("port")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(587) as u16;
+            let port = get_port(obj, 587)?;
             let username = get_str(obj, "username")?;
             let password = get_str(obj, "password")?;
             let encryption_str = obj
@@ -182,6 +212,41 @@ fn parse_secret(
                 encryption,
             })
         }
+        CredentialKind::MongoDb => {
+            let uri = get_str(obj, "uri")?;
+            Ok(CredentialSecret::MongoDb { uri })
+        }
+        CredentialKind::JwtSigner => {
+            let private_key_pem = get_str(obj, "private_key_pem")?;
+            let algorithm_str = obj
+                .get("algorithm")
+                .and_then(|v| v.as_str())
+                .unwrap_or("RS256");
+            let algorithm =
+                serde_json::from_value(serde_json::Value::String(algorithm_str.to_string()))
+                    .map_err(|_| "invalid algorithm (RS256, RS384, RS512, ES256, ES384, EdDSA)")?;
+            let issuer = get_str(obj, "issuer")?;
+            let audience = get_str(obj, "audience")?;
+            let ttl_secs = obj.get("ttl_secs").and_then(|v| v.as_u64()).unwrap_or(300);
+            Ok(CredentialSecret::JwtSigner {
+                private_key_pem,
+                algorithm,
+                issuer,
+                audience,
+                ttl_secs,
+            })
+        }
+        CredentialKind::OpaqueBlob => {
+            let data_base64 = get_str(obj, "data_base64")?;
+            let content_type = obj
+                .get("content_type")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            Ok(CredentialSecret::OpaqueBlob {
+                data_base64,
+                content_type,
+            })
+        }
         CredentialKind::Custom => {
             let mut fields = HashMap::new();
             for (k, v) in obj {
@@ -194,16 +259,25 @@ fn parse_secret(
     }
 }
 
-fn get_str(
-    obj: &serde_json::Map<String, serde_json::Value>,
-    key: &str,
-) -> Result<String, String> {
+fn get_str(obj: &serde_json::Map<String, serde_json::Value>, key: &str) -> Result<String, String> {
     obj.get(key)
         .and_then(|v| v.as_str())
         .map(String::from)
         .ok_or_else(|| format!("missing required field '{key}'"))
 }
 
+/// The optional `port` field, or `default` when it's absent. Out-of-range
+/// numbers are rejected rather than wrapped into a valid-looking port.
+fn get_port(obj: &serde_json::Map<String, serde_json::Value>, default: u16) -> Result<u16, String> {
+    match obj.get("port") {
+        None | Some(serde_json::Value::Null) => Ok(default),
+        Some(v) => v
+            .as_u64()
+            .and_then(|p| u16::try_from(p).ok())
+            .ok_or_else(|| format!("invalid port {v} (must be 0-65535)")),
+    }
+}
+
 // ── credential_update ────────────────────────────────────────────
 
 #[derive(Debug, Deserialize, schemars::JsonSchema)]
@@ -222,6 +296,8 @@ pub struct CredentialUpdateRequest {
         description = "New secret data (optional, structure depends on credential kind). Omit to keep current secret."
     )]
     pub secret: Option<serde_json::Value>,
+    #[schemars(description = "Rotation reminder interval in days (optional, 0 clears it)")]
+    pub rotation_interval_days: Option<u32>,
 }
 
 pub async fn credential_update(
@@ -233,11 +309,15 @@ pub async fn credential_update(
         .parse()
         .map_err(|_| McpError::invalid_params("invalid UUID", None))?;
 
+    if let Some(denied) = super::scope_denial(server, id).await {
+        return Ok(denied);
+    }
+
     let environment = params
         .environment
         .map(|e| {
-            serde_json::from_value::<Environment>(serde_json::Value::String(e))
-                .map_err(|_| McpError::invalid_params("invalid environment", None))
+            Environment::parse(&e)
+                .ok_or_else(|| McpError::invalid_params("invalid environment", None))
         })
         .transpose()?;
 
@@ -248,8 +328,7 @@ pub async fn credential_update(
             .get_credential_meta(id)
             .await
             .map_err(|e| McpError::internal_error(format!("{e}"), None))?;
-        let kind: CredentialKind =
-            meta.kind;
+        let kind: CredentialKind = meta.kind;
         let parsed = parse_secret(kind, secret_val)
             .map_err(|e| McpError::invalid_params(format!("invalid secret: {e}"), None))?;
         Some(parsed)
@@ -257,13 +336,9 @@ pub async fn credential_update(
         None
     };
 
-    let notes_opt = params.notes.map(|n| {
-        if n.is_empty() {
-            None
-        } else {
-            Some(n)
-        }
-    });
+    let notes_opt = params
+        .notes
+        .map(|n| if n.is_empty() { None } else { Some(n) });
 
     match server
         .vault
@@ -277,13 +352,67 @@ pub async fn credential_update(
         )
         .await
     {
-        Ok(id) => Ok(CallToolResult::success(vec![Content::text(
-            serde_json::json!({
-                "id": id.to_string(),
-                "updated": true,
-            })
-            .to_string(),
-        )])),
+        Ok(id) => {
+            server.http_clients.invalidate(id);
+            if let Some(days) = params.rotation_interval_days {
+                if let Err(e) = server.vault.set_rotation_interval(id, Some(days)).await {
+                    return Ok(CallToolResult::error(vec![Content::text(format!("{e}"))]));
+                }
+            }
+            Ok(CallToolResult::success(vec![Content::text(
+                serde_json::json!({
+                    "id": id.to_string(),
+                    "updated": true,
+                })
+                .to_string(),
+            )]))
+        }
+        Err(e) => Ok(CallToolResult::error(vec![Content::text(format!("{e}"))])),
+    }
+}
+
+// ── credential_update_field ──────────────────────────────────────
+
+#[derive(Debug, Deserialize, schemars::JsonSchema)]
+pub struct CredentialUpdateFieldRequest {
+    #[schemars(description = "UUID of a custom credential")]
+    pub id: String,
+    #[schemars(description = "Name of the field to set or remove")]
+    pub field: String,
+    #[schemars(description = "New value for the field. Omit (or null) to remove the field.")]
+    pub value: Option<String>,
+}
+
+pub async fn credential_update_field(
+    server: &PassmanServer,
+    params: CredentialUpdateFieldRequest,
+) -> Result<CallToolResult, McpError> {
+    let id: uuid::Uuid = params
+        .id
+        .parse()
+        .map_err(|_| McpError::invalid_params("invalid UUID", None))?;
+
+    if let Some(denied) = super::scope_denial(server, id).await {
+        return Ok(denied);
+    }
+
+    let removed = params.value.is_none();
+    match server
+        .vault
+        .update_custom_field(id, &params.field, params.value)
+        .await
+    {
+        Ok(()) => {
+            server.http_clients.invalidate(id);
+            Ok(CallToolResult::success(vec![Content::text(
+                serde_json::json!({
+                    "id": id.to_string(),
+                    "field": params.field,
+                    "removed": removed,
+                })
+                .to_string(),
+            )]))
+        }
         Err(e) => Ok(CallToolResult::error(vec![Content::text(format!("{e}"))])),
     }
 }
@@ -296,6 +425,10 @@ pub struct CredentialDeleteRequest {
     pub id: String,
     #[schemars(description = "Must be true to confirm deletion")]
     pub confirm: bool,
+    #[schemars(
+        description = "Delete even if other credentials link to this one, removing their links. Without it such a delete is refused and the dependents are listed."
+    )]
+    pub force: Option<bool>,
 }
 
 pub async fn credential_delete(
@@ -313,13 +446,106 @@ pub async fn credential_delete(
         .parse()
         .map_err(|_| McpError::invalid_params("invalid UUID", None))?;
 
-    match server.vault.delete_credential(id).await {
-        Ok(true) => Ok(CallToolResult::success(vec![Content::text(
-            serde_json::json!({ "success": true }).to_string(),
-        )])),
+    if let Some(denied) = super::scope_denial(server, id).await {
+        return Ok(denied);
+    }
+
+    // With force, credentials that depend on this one lose their link to it
+    let dependents: Vec<String> = server
+        .vault
+        .get_linked(id)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|l| l.direction == LinkDirection::Incoming)
+        .map(|l| l.name)
+        .collect();
+
+    match server
+        .vault
+        .delete_credential(id, params.force.unwrap_or(false))
+        .await
+    {
+        Ok(true) => {
+            server.http_clients.invalidate(id);
+            let mut response = serde_json::json!({ "success": true });
+            if !dependents.is_empty() {
+                response["unlinked"] = serde_json::json!(dependents);
+            }
+            Ok(CallToolResult::success(vec![Content::text(
+                response.to_string(),
+            )]))
+        }
         Ok(false) => Ok(CallToolResult::error(vec![Content::text(
             "credential not found",
         )])),
         Err(e) => Ok(CallToolResult::error(vec![Content::text(format!("{e}"))])),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn database_port(secret: serde_json::Value) -> u16 {
+        match parse_secret(CredentialKind::DatabaseConnection, &secret).unwrap() {
+            CredentialSecret::DatabaseConnection { port, .. } => port,
+            other => panic!("wrong variant: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_database_port_defaults_by_driver() {
+        let secret = |driver: &str| {
+            serde_json::json!({
+                "driver": driver,
+                "host": "db.example.com",
+                "database": "app",
+                "username": "app",
+                "password": "pw",
+            })
+        };
+        assert_eq!(database_port(secret("postgres")), 5432);
+        assert_eq!(database_port(secret("mysql")), 3306);
+        assert_eq!(database_port(secret("sqlite")), 0);
+
+        let mut explicit = secret("mysql");
+        explicit["port"] = 3307.into();
+        assert_eq!(database_port(explicit), 3307);
+    }
+
+    #[test]
+    fn test_out_of_range_port_is_rejected() {
+        // 65557 would wrap to port 21 if truncated
+        let err = parse_secret(
+            CredentialKind::DatabaseConnection,
+            &serde_json::json!({
+                "driver": "postgres",
+                "host": "db.example.com",
+                "port": 65557,
+                "database": "app",
+                "username": "app",
+                "password": "pw",
+            }),
+        )
+        .unwrap_err();
+        assert!(err.contains("invalid port"), "{err}");
+
+        for kind in [CredentialKind::SshPassword, CredentialKind::SmtpAccount] {
+            let secret = serde_json::json!({
+                "host": "example.com",
+                "port": 65557,
+                "username": "u",
+                "password": "pw",
+            });
+            assert!(parse_secret(kind, &secret).is_err());
+        }
+        let negative = serde_json::json!({
+            "host": "example.com",
+            "port": -1,
+            "username": "u",
+            "password": "pw",
+        });
+        assert!(parse_secret(CredentialKind::SshPassword, &negative).is_err());
+    }
+}
@@ -16,7 +16,7 @@ pub async fn vault_unlock(
 ) -> Result<CallToolResult, McpError> {
     // Check if vault exists; if not, create it
     if !server.vault.exists().await {
-        match server.vault.create(&params.password).await {
+        match server.vault.create(&params.password, false).await {
             Ok(()) => {
                 return Ok(CallToolResult::success(vec![Content::text(
                     serde_json::json!({
@@ -36,13 +36,18 @@ pub async fn vault_unlock(
     }
 
     match server.vault.unlock(&params.password).await {
-        Ok(count) => Ok(CallToolResult::success(vec![Content::text(
-            serde_json::json!({
+        Ok(count) => {
+            let mut response = serde_json::json!({
                 "success": true,
                 "credential_count": count
-            })
-            .to_string(),
-        )])),
+            });
+            if let Some(warning) = server.vault.take_recovery_warning() {
+                response["warning"] = serde_json::Value::String(warning.to_string());
+            }
+            Ok(CallToolResult::success(vec![Content::text(
+                response.to_string(),
+            )]))
+        }
         Err(e) => Ok(CallToolResult::error(vec![Content::text(format!(
             "Failed to unlock vault: {e}"
         ))])),
@@ -77,13 +82,34 @@ pub async fn vault_status(server: &PassmanServer) -> Result<CallToolResult, McpE
     let count = server.vault.credential_count().await.unwrap_or(0);
     let envs = server.vault.get_environments().await.unwrap_or_default();
 
+    let mut status = serde_json::json!({
+        "exists": exists,
+        "locked": false,
+        "credential_count": count,
+        "environments": envs,
+    });
+    // Format version, KDF settings and counts by kind; never the salt
+    if let Ok(info) = server.vault.security_info().await {
+        if let (Some(status), serde_json::Value::Object(info)) = (
+            status.as_object_mut(),
+            serde_json::to_value(info).unwrap_or_default(),
+        ) {
+            status.extend(info);
+        }
+    }
+
     Ok(CallToolResult::success(vec![Content::text(
-        serde_json::json!({
-            "exists": exists,
-            "locked": false,
-            "credential_count": count,
-            "environments": envs,
-        })
-        .to_string(),
+        status.to_string(),
     )]))
 }
+
+// ── vault_stats ──────────────────────────────────────────────────
+
+pub async fn vault_stats(server: &PassmanServer) -> Result<CallToolResult, McpError> {
+    match server.vault.stats().await {
+        Ok(stats) => Ok(CallToolResult::success(vec![Content::text(
+            serde_json::to_string(&stats).unwrap(),
+        )])),
+        Err(e) => Ok(CallToolResult::error(vec![Content::text(format!("{e}"))])),
+    }
+}
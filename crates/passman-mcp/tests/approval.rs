@@ -0,0 +1,268 @@
+//! Integration test: approval-gated proxy operations stay pending until
+//! approved, then run exactly once.
+
+use passman_mcp::tools::sql::{sql_query, SqlQueryParams};
+use passman_mcp::PassmanServer;
+use passman_proxy::sql::SqlQueryInput;
+use passman_types::{
+    ApprovalStatus, CredentialKind, CredentialSecret, DbDriver, Environment, PolicyRule,
+};
+use passman_vault::Vault;
+use rmcp::model::CallToolResult;
+use tempfile::TempDir;
+
+/// Table names in the SQLite database, read directly rather than through
+/// the approval-gated tool.
+async fn tables(secret: &CredentialSecret) -> Vec<String> {
+    let input = SqlQueryInput {
+        query: "SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name".into(),
+        params: None,
+        max_rows: None,
+        timeout_secs: None,
+        read_only: true,
+        multi: false,
+    };
+    passman_proxy::sql::execute(secret, &input)
+        .await
+        .unwrap()
+        .rows
+        .into_iter()
+        .map(|row| row[0].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn result_text(result: &CallToolResult) -> String {
+    result
+        .content
+        .iter()
+        .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+        .collect()
+}
+
+/// A vault with one SQLite credential whose policy requires approval.
+async fn setup(dir: &TempDir) -> (Vault, uuid::Uuid, CredentialSecret) {
+    let vault = Vault::new(
+        dir.path().join("vault.json"),
+        dir.path().join("audit.jsonl"),
+    );
+    vault.create("approval-test-pw", false).await.unwrap();
+
+    let db_path = dir.path().join("test.db");
+    let secret = CredentialSecret::DatabaseConnection {
+        driver: DbDriver::Sqlite,
+        host: String::new(),
+        port: 0,
+        database: format!("{}?mode=rwc", db_path.display()),
+        username: String::new(),
+        password: String::new(),
+        params: Default::default(),
+    };
+    let cred_id = vault
+        .store_credential(
+            "Local SQLite".into(),
+            CredentialKind::DatabaseConnection,
+            Environment::Local,
+            vec![],
+            None,
+            &secret,
+        )
+        .await
+        .unwrap();
+
+    vault
+        .save_policy(PolicyRule {
+            credential_id: cred_id,
+            allowed_tools: vec![],
+            http_url_patterns: vec![],
+            http_default_deny: false,
+            ssh_command_patterns: vec![],
+            ssh_default_deny: false,
+            sql_allow_write: true,
+            forbidden_sql_patterns: vec![],
+            smtp_allowed_recipients: vec![],
+            mongo_allow_write: false,
+            allowed_hosts: vec![],
+            time_windows: vec![],
+            require_approval: true,
+            rate_limit: None,
+            per_tool_limits: Default::default(),
+        })
+        .await
+        .unwrap();
+
+    (vault, cred_id, secret)
+}
+
+fn sql_params(
+    cred_id: uuid::Uuid,
+    query: &str,
+    params: Option<Vec<serde_json::Value>>,
+    approval_id: Option<String>,
+) -> SqlQueryParams {
+    SqlQueryParams {
+        credential_id: cred_id.to_string(),
+        query: query.to_string(),
+        params,
+        max_rows: None,
+        timeout_secs: None,
+        multi: None,
+        approval_id,
+    }
+}
+
+/// Queue `query` and approve it, returning the approval ID.
+async fn approved(
+    server: &PassmanServer,
+    vault: &Vault,
+    cred_id: uuid::Uuid,
+    query: &str,
+) -> String {
+    let first = sql_query(server, sql_params(cred_id, query, None, None))
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_str(&result_text(&first)).unwrap();
+    assert_eq!(body["status"], "awaiting_approval");
+    assert_eq!(body["request"]["query"], query);
+    let approval_id = body["approval_id"].as_str().unwrap().to_string();
+    assert!(vault.approve(approval_id.parse().unwrap()).await.unwrap());
+    approval_id
+}
+
+#[tokio::test]
+async fn test_sql_write_waits_for_approval() {
+    let dir = TempDir::new().unwrap();
+    let (vault, cred_id, secret) = setup(&dir).await;
+    let server = PassmanServer::new(vault.clone());
+    let query = "CREATE TABLE t (x INTEGER)";
+
+    // ── 1. First call is queued, not executed ───────────────
+    let first = sql_query(&server, sql_params(cred_id, query, None, None))
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_str(&result_text(&first)).unwrap();
+    assert_eq!(body["status"], "awaiting_approval");
+    let approval_id = body["approval_id"].as_str().unwrap().to_string();
+
+    let pending = vault
+        .get_approval(approval_id.parse().unwrap())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(pending.status, ApprovalStatus::Pending);
+    assert_eq!(pending.request["query"], query);
+    assert!(tables(&secret).await.is_empty());
+
+    // ── 2. Approve, then the repeated call executes ─────────
+    assert!(vault.approve(approval_id.parse().unwrap()).await.unwrap());
+    let second = sql_query(
+        &server,
+        sql_params(cred_id, query, None, Some(approval_id.clone())),
+    )
+    .await
+    .unwrap();
+    assert_ne!(second.is_error, Some(true), "{}", result_text(&second));
+    assert!(!result_text(&second).contains("awaiting_approval"));
+    assert_eq!(tables(&secret).await, ["t"]);
+    assert!(vault
+        .get_approval(approval_id.parse().unwrap())
+        .await
+        .unwrap()
+        .is_none());
+
+    // ── 3. The approval can't be reused ─────────────────────
+    let third = sql_query(&server, sql_params(cred_id, query, None, Some(approval_id)))
+        .await
+        .unwrap();
+    assert_eq!(third.is_error, Some(true));
+}
+
+#[tokio::test]
+async fn test_approval_does_not_release_a_different_query() {
+    let dir = TempDir::new().unwrap();
+    let (vault, cred_id, secret) = setup(&dir).await;
+    let server = PassmanServer::new(vault.clone());
+
+    let approval_id = approved(
+        &server,
+        &vault,
+        cred_id,
+        "CREATE TABLE harmless (x INTEGER)",
+    )
+    .await;
+    let swapped = sql_query(
+        &server,
+        sql_params(
+            cred_id,
+            "CREATE TABLE other (x INTEGER)",
+            None,
+            Some(approval_id.clone()),
+        ),
+    )
+    .await
+    .unwrap();
+    assert_eq!(swapped.is_error, Some(true));
+    assert!(result_text(&swapped).contains("does not match"));
+    assert!(tables(&secret).await.is_empty());
+
+    // The approval still releases the query it was granted for
+    let original = sql_query(
+        &server,
+        sql_params(
+            cred_id,
+            "CREATE TABLE harmless (x INTEGER)",
+            None,
+            Some(approval_id),
+        ),
+    )
+    .await
+    .unwrap();
+    assert_ne!(original.is_error, Some(true), "{}", result_text(&original));
+    assert_eq!(tables(&secret).await, ["harmless"]);
+}
+
+#[tokio::test]
+async fn test_approval_does_not_release_different_params() {
+    let dir = TempDir::new().unwrap();
+    let (vault, cred_id, secret) = setup(&dir).await;
+    let server = PassmanServer::new(vault.clone());
+
+    let setup_id = approved(&server, &vault, cred_id, "CREATE TABLE t (x INTEGER)").await;
+    sql_query(
+        &server,
+        sql_params(cred_id, "CREATE TABLE t (x INTEGER)", None, Some(setup_id)),
+    )
+    .await
+    .unwrap();
+
+    // Approve an insert of 1, then try to run it with a different value
+    let insert = "INSERT INTO t (x) VALUES (?)";
+    let first = sql_query(
+        &server,
+        sql_params(cred_id, insert, Some(vec![1.into()]), None),
+    )
+    .await
+    .unwrap();
+    let body: serde_json::Value = serde_json::from_str(&result_text(&first)).unwrap();
+    assert_eq!(body["request"]["params"], serde_json::json!([1]));
+    let approval_id = body["approval_id"].as_str().unwrap().to_string();
+    assert!(vault.approve(approval_id.parse().unwrap()).await.unwrap());
+
+    let swapped = sql_query(
+        &server,
+        sql_params(cred_id, insert, Some(vec![2.into()]), Some(approval_id)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(swapped.is_error, Some(true));
+
+    let input = SqlQueryInput {
+        query: "SELECT COUNT(*) FROM t".into(),
+        params: None,
+        max_rows: None,
+        timeout_secs: None,
+        read_only: true,
+        multi: false,
+    };
+    let count = passman_proxy::sql::execute(&secret, &input).await.unwrap();
+    assert_eq!(count.rows[0][0], 0);
+}
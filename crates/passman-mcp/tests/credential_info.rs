@@ -0,0 +1,75 @@
+//! Integration test: `credential_info` returns a fixed set of JSON keys.
+
+use passman_mcp::tools::discovery::{credential_info, CredentialInfoRequest};
+use passman_mcp::PassmanServer;
+use passman_types::{CredentialKind, CredentialSecret, Environment};
+use passman_vault::Vault;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_credential_info_json_contract() {
+    let dir = TempDir::new().unwrap();
+    let vault = Vault::new(
+        dir.path().join("vault.json"),
+        dir.path().join("audit.jsonl"),
+    );
+    vault.create("info-test-pw-2024", false).await.unwrap();
+    let id = vault
+        .store_credential(
+            "Contract API".into(),
+            CredentialKind::ApiToken,
+            Environment::Staging,
+            vec!["billing".into()],
+            Some("used by the invoicing job".into()),
+            &CredentialSecret::ApiToken {
+                token: "tok_contract".into(),
+                header_name: None,
+                prefix: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    let server = PassmanServer::new(vault);
+    let result = credential_info(&server, CredentialInfoRequest { id: id.to_string() })
+        .await
+        .unwrap();
+    let text: String = result
+        .content
+        .iter()
+        .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+        .collect();
+    let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+
+    let mut keys: Vec<&str> = json
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    keys.sort();
+    assert_eq!(
+        keys,
+        [
+            "category",
+            "created_at",
+            "environment",
+            "id",
+            "kind",
+            "last_rotated_at",
+            "last_used_at",
+            "linked",
+            "name",
+            "notes",
+            "rotation_interval_days",
+            "tags",
+            "updated_at",
+            "use_count",
+        ]
+    );
+    assert_eq!(json["id"], id.to_string());
+    assert_eq!(json["kind"], "api_token");
+    assert_eq!(json["environment"], "staging");
+    assert_eq!(json["use_count"], 0);
+    assert!(!text.contains("tok_contract"));
+}
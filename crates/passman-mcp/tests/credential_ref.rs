@@ -0,0 +1,126 @@
+//! Integration test: proxy tools accept a credential name, optionally
+//! qualified as `name@environment`, in place of its UUID.
+
+use passman_mcp::tools::ping::{credential_ping, CredentialPingParams};
+use passman_mcp::PassmanServer;
+use passman_types::{CredentialKind, CredentialSecret, Environment, SmtpEncryption};
+use passman_vault::Vault;
+use rmcp::model::CallToolResult;
+use tempfile::TempDir;
+
+fn text(result: &CallToolResult) -> String {
+    result
+        .content
+        .iter()
+        .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+        .collect()
+}
+
+async fn store(vault: &Vault, name: &str, environment: Environment, host: &str) -> uuid::Uuid {
+    vault
+        .store_credential(
+            name.into(),
+            CredentialKind::SmtpAccount,
+            environment,
+            vec![],
+            None,
+            &CredentialSecret::SmtpAccount {
+                host: host.into(),
+                // Nothing listens here; the ping just reports unreachable
+                port: 1,
+                username: "mailer@example.com".into(),
+                password: "not-used".into(),
+                encryption: SmtpEncryption::None,
+            },
+        )
+        .await
+        .unwrap()
+}
+
+async fn ping(server: &PassmanServer, reference: &str) -> CallToolResult {
+    credential_ping(
+        server,
+        CredentialPingParams {
+            credential_id: reference.into(),
+            timeout_secs: Some(1),
+        },
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_credential_reference_by_uuid_and_name() {
+    let dir = TempDir::new().unwrap();
+    let vault = Vault::new(
+        dir.path().join("vault.json"),
+        dir.path().join("audit.jsonl"),
+    );
+    vault.create("ref-test-pw", false).await.unwrap();
+
+    let mailer = store(&vault, "Mailer", Environment::Staging, "127.0.0.1").await;
+    store(&vault, "Shared", Environment::Staging, "127.0.0.2").await;
+    store(&vault, "Shared", Environment::Production, "127.0.0.3").await;
+    store(
+        &vault,
+        "ops@example.com",
+        Environment::Production,
+        "127.0.0.4",
+    )
+    .await;
+    let server = PassmanServer::new(vault.clone());
+
+    let by_id = ping(&server, &mailer.to_string()).await;
+    assert_ne!(by_id.is_error, Some(true), "got: {}", text(&by_id));
+    assert!(text(&by_id).contains("127.0.0.1"));
+
+    // Names match case-insensitively, with or without an environment
+    for reference in ["Mailer", "mailer", "Mailer@staging"] {
+        let result = ping(&server, reference).await;
+        assert_ne!(
+            result.is_error,
+            Some(true),
+            "{reference}: {}",
+            text(&result)
+        );
+        assert!(text(&result).contains("127.0.0.1"));
+    }
+
+    let qualified = ping(&server, "Shared@production").await;
+    assert_ne!(qualified.is_error, Some(true), "got: {}", text(&qualified));
+    assert!(text(&qualified).contains("127.0.0.3"));
+
+    // An '@' that isn't an environment separator is part of the name
+    let email = ping(&server, "ops@example.com").await;
+    assert_ne!(email.is_error, Some(true), "got: {}", text(&email));
+    assert!(text(&email).contains("127.0.0.4"));
+
+    let missing = ping(&server, "Mailer@production").await;
+    assert_eq!(missing.is_error, Some(true));
+    assert!(
+        text(&missing).contains("no credential matches"),
+        "got: {}",
+        text(&missing)
+    );
+}
+
+#[tokio::test]
+async fn test_ambiguous_name_is_rejected() {
+    let dir = TempDir::new().unwrap();
+    let vault = Vault::new(
+        dir.path().join("vault.json"),
+        dir.path().join("audit.jsonl"),
+    );
+    vault.create("ref-test-pw", false).await.unwrap();
+
+    let staging = store(&vault, "Shared", Environment::Staging, "127.0.0.2").await;
+    let production = store(&vault, "Shared", Environment::Production, "127.0.0.3").await;
+    let server = PassmanServer::new(vault.clone());
+
+    let result = ping(&server, "Shared").await;
+    assert_eq!(result.is_error, Some(true));
+    let message = text(&result);
+    assert!(message.contains("ambiguous"), "got: {message}");
+    assert!(message.contains(&staging.to_string()));
+    assert!(message.contains(&production.to_string()));
+}
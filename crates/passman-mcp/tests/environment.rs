@@ -0,0 +1,128 @@
+//! Integration test: custom environment names are parsed the same way as in
+//! the GUI, for storing and for filtering.
+
+use passman_mcp::tools::discovery::{credential_list, CredentialListRequest};
+use passman_mcp::tools::storage::{
+    credential_store, credential_update, CredentialStoreRequest, CredentialUpdateRequest,
+};
+use passman_mcp::PassmanServer;
+use passman_types::{CredentialKind, CredentialSecret, Environment};
+use passman_vault::Vault;
+use tempfile::TempDir;
+
+fn text(result: &rmcp::model::CallToolResult) -> String {
+    result
+        .content
+        .iter()
+        .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_custom_environment_round_trips_through_store_and_update() {
+    let dir = TempDir::new().unwrap();
+    let vault = Vault::new(
+        dir.path().join("vault.json"),
+        dir.path().join("audit.jsonl"),
+    );
+    vault.create("env-test-pw-2024", false).await.unwrap();
+    let server = PassmanServer::new(vault.clone());
+
+    let result = credential_store(
+        &server,
+        CredentialStoreRequest {
+            name: "Env API".into(),
+            kind: "api_token".into(),
+            environment: "my-env".into(),
+            secret: serde_json::json!({ "token": "tok_env" }),
+            tags: None,
+            notes: None,
+            allow_new_env: None,
+        },
+    )
+    .await
+    .unwrap();
+    assert_ne!(result.is_error, Some(true), "{}", text(&result));
+    let json: serde_json::Value = serde_json::from_str(&text(&result)).unwrap();
+    let id: uuid::Uuid = json["id"].as_str().unwrap().parse().unwrap();
+
+    let meta = vault.get_credential_meta(id).await.unwrap();
+    assert_eq!(meta.environment, Environment::Custom("my-env".into()));
+    assert_eq!(
+        Environment::parse(&meta.environment.to_string()),
+        Some(meta.environment)
+    );
+
+    // Built-in names are matched in any case; custom ones are lowercased
+    for (typed, expected) in [
+        ("Production", Environment::Production),
+        ("MY-ENV", Environment::Custom("my-env".into())),
+    ] {
+        let result = credential_update(
+            &server,
+            CredentialUpdateRequest {
+                id: id.to_string(),
+                name: None,
+                environment: Some(typed.into()),
+                tags: None,
+                notes: None,
+                secret: None,
+                rotation_interval_days: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_ne!(result.is_error, Some(true), "{}", text(&result));
+        assert_eq!(
+            vault.get_credential_meta(id).await.unwrap().environment,
+            expected
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_credential_list_filters_by_custom_environment() {
+    let dir = TempDir::new().unwrap();
+    let vault = Vault::new(
+        dir.path().join("vault.json"),
+        dir.path().join("audit.jsonl"),
+    );
+    vault.create("env-test-pw-2024", false).await.unwrap();
+    for (name, environment) in [
+        ("QA API", Environment::Custom("qa".into())),
+        ("Staging API", Environment::Staging),
+    ] {
+        vault
+            .store_credential(
+                name.into(),
+                CredentialKind::ApiToken,
+                environment,
+                vec![],
+                None,
+                &CredentialSecret::ApiToken {
+                    token: format!("tok_{name}"),
+                    header_name: None,
+                    prefix: None,
+                },
+            )
+            .await
+            .unwrap();
+    }
+    let server = PassmanServer::new(vault);
+
+    let result = credential_list(
+        &server,
+        CredentialListRequest {
+            kind: None,
+            environment: Some("qa".into()),
+            tag: None,
+            category: None,
+        },
+    )
+    .await
+    .unwrap();
+    assert_ne!(result.is_error, Some(true), "{}", text(&result));
+    let items: Vec<serde_json::Value> = serde_json::from_str(&text(&result)).unwrap();
+    let names: Vec<_> = items.iter().map(|i| i["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["QA API"]);
+}
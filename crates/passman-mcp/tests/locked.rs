@@ -0,0 +1,62 @@
+//! Integration test: proxy tools called while the vault is locked tell the
+//! agent to unlock instead of failing with an internal error.
+
+use passman_mcp::tools::http::{http_request, HttpRequestParams};
+use passman_mcp::PassmanServer;
+use passman_types::{CredentialKind, CredentialSecret, Environment};
+use passman_vault::Vault;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_proxy_tool_while_locked() {
+    let dir = TempDir::new().unwrap();
+    let vault = Vault::new(
+        dir.path().join("vault.json"),
+        dir.path().join("audit.jsonl"),
+    );
+    vault.create("locked-test-pw", false).await.unwrap();
+
+    let cred_id = vault
+        .store_credential(
+            "API".into(),
+            CredentialKind::ApiToken,
+            Environment::Local,
+            vec![],
+            None,
+            &CredentialSecret::ApiToken {
+                token: "tok_locked_test".into(),
+                header_name: None,
+                prefix: None,
+            },
+        )
+        .await
+        .unwrap();
+    vault.lock().await;
+
+    let server = PassmanServer::new(vault.clone());
+    let result = http_request(
+        &server,
+        HttpRequestParams {
+            credential_id: cred_id.to_string(),
+            method: "GET".into(),
+            url: "http://127.0.0.1:1/".into(),
+            headers: None,
+            body: None,
+            form: None,
+            include_headers: None,
+            exclude_headers: None,
+            auth_scheme: None,
+            approval_id: None,
+        },
+    )
+    .await
+    .expect("locked vault should be a tool error, not an internal error");
+
+    assert_eq!(result.is_error, Some(true));
+    let text: String = result
+        .content
+        .iter()
+        .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+        .collect();
+    assert!(text.contains("call vault_unlock first"), "got: {text}");
+}
@@ -0,0 +1,130 @@
+//! Integration test: a session scoped to a tag can't see or use credentials
+//! outside that tag.
+
+use passman_mcp::tools::audit::{audit_log, audit_summary, AuditLogParams, AuditSummaryParams};
+use passman_mcp::tools::discovery::{
+    credential_info, credential_list, CredentialInfoRequest, CredentialListRequest,
+};
+use passman_mcp::tools::http::{http_request, HttpRequestParams};
+use passman_mcp::{PassmanServer, Scope};
+use passman_types::{CredentialKind, CredentialSecret, Environment};
+use passman_vault::Vault;
+use rmcp::model::CallToolResult;
+use tempfile::TempDir;
+
+fn text(result: &CallToolResult) -> String {
+    result
+        .content
+        .iter()
+        .filter_map(|c| c.as_text().map(|t| t.text.clone()))
+        .collect()
+}
+
+async fn store(vault: &Vault, name: &str, tag: &str) -> uuid::Uuid {
+    vault
+        .store_credential(
+            name.into(),
+            CredentialKind::ApiToken,
+            Environment::Production,
+            vec![tag.into()],
+            None,
+            &CredentialSecret::ApiToken {
+                token: format!("tok_{tag}"),
+                header_name: None,
+                prefix: None,
+            },
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_out_of_scope_credential_hidden_and_denied() {
+    let dir = TempDir::new().unwrap();
+    let vault = Vault::new(
+        dir.path().join("vault.json"),
+        dir.path().join("audit.jsonl"),
+    );
+    vault.create("scope-test-pw", false).await.unwrap();
+
+    let billing = store(&vault, "Billing API", "billing").await;
+    let infra = store(&vault, "Infra API", "infra").await;
+    vault
+        .link_credentials(billing, infra, "depends_on")
+        .await
+        .unwrap();
+
+    let server =
+        PassmanServer::new(vault.clone()).with_scope(Scope::from_args(vec!["billing".into()], &[]));
+
+    let listed = credential_list(
+        &server,
+        CredentialListRequest {
+            kind: None,
+            environment: None,
+            tag: None,
+            category: None,
+        },
+    )
+    .await
+    .unwrap();
+    let listed = text(&listed);
+    assert!(listed.contains(&billing.to_string()));
+    assert!(!listed.contains(&infra.to_string()), "got: {listed}");
+
+    // Neither the audit log, its summary nor links give the other one away
+    let log = audit_log(
+        &server,
+        AuditLogParams {
+            credential_id: None,
+            limit: None,
+            since: None,
+            until: None,
+            action: None,
+            tool: None,
+            success: None,
+        },
+    )
+    .await
+    .unwrap();
+    let summary = audit_summary(&server, AuditSummaryParams { since: None })
+        .await
+        .unwrap();
+    let info = credential_info(
+        &server,
+        CredentialInfoRequest {
+            id: billing.to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    for shown in [text(&log), text(&summary), text(&info)] {
+        assert!(shown.contains(&billing.to_string()), "got: {shown}");
+        assert!(!shown.contains(&infra.to_string()), "got: {shown}");
+        assert!(!shown.contains("Infra API"), "got: {shown}");
+    }
+
+    let result = http_request(
+        &server,
+        HttpRequestParams {
+            credential_id: infra.to_string(),
+            method: "GET".into(),
+            url: "http://127.0.0.1:1/".into(),
+            headers: None,
+            body: None,
+            form: None,
+            include_headers: None,
+            exclude_headers: None,
+            auth_scheme: None,
+            approval_id: None,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.is_error, Some(true));
+    assert!(
+        text(&result).contains("outside this session's scope"),
+        "got: {}",
+        text(&result)
+    );
+}
@@ -0,0 +1,91 @@
+//! Integration test: successful proxy calls are counted on the credential.
+
+use passman_mcp::tools::http::{http_request, HttpRequestParams};
+use passman_mcp::PassmanServer;
+use passman_types::{CredentialKind, CredentialSecret, Environment};
+use passman_vault::Vault;
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// Serve `requests` plain 200 responses on a local port.
+async fn ok_server(requests: usize) -> std::net::SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        for _ in 0..requests {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).await;
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok")
+                .await
+                .unwrap();
+        }
+    });
+    addr
+}
+
+#[tokio::test]
+async fn test_http_calls_bump_use_count() {
+    let dir = TempDir::new().unwrap();
+    let vault = Vault::new(
+        dir.path().join("vault.json"),
+        dir.path().join("audit.jsonl"),
+    );
+    vault.create("usage-test-pw", false).await.unwrap();
+
+    let id = vault
+        .store_credential(
+            "Usage API".into(),
+            CredentialKind::ApiToken,
+            Environment::Local,
+            vec![],
+            None,
+            &CredentialSecret::ApiToken {
+                token: "tok_usage".into(),
+                header_name: None,
+                prefix: None,
+            },
+        )
+        .await
+        .unwrap();
+    let before = vault.get_credential_meta(id).await.unwrap();
+    assert_eq!(before.use_count, 0);
+    assert!(before.last_used_at.is_none());
+
+    let addr = ok_server(2).await;
+    let server = PassmanServer::new(vault.clone());
+    for _ in 0..2 {
+        let result = http_request(
+            &server,
+            HttpRequestParams {
+                credential_id: id.to_string(),
+                method: "GET".into(),
+                url: format!("http://{addr}/"),
+                headers: None,
+                body: None,
+                form: None,
+                include_headers: None,
+                exclude_headers: None,
+                auth_scheme: None,
+                approval_id: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_ne!(result.is_error, Some(true));
+    }
+
+    let meta = vault.get_credential_meta(id).await.unwrap();
+    assert_eq!(meta.use_count, 2);
+    assert!(meta.last_used_at.is_some());
+
+    // Uses are batched: nothing is written until a flush
+    let saves = vault.save_count();
+    vault.flush_usage().await.unwrap();
+    assert_eq!(vault.save_count(), saves + 1);
+
+    vault.lock().await;
+    vault.unlock("usage-test-pw").await.unwrap();
+    assert_eq!(vault.get_credential_meta(id).await.unwrap().use_count, 2);
+}
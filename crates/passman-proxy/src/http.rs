@@ -1,10 +1,18 @@
-use passman_types::CredentialSecret;
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use base64::Engine;
+use passman_types::{AuthScheme, CredentialSecret, FormPart};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use uuid::Uuid;
 
+use crate::jwt::{self, TokenCache};
 use crate::sanitizer;
-use crate::ProxyError;
+use crate::{ProxyError, Timing};
 
 #[derive(Debug, Deserialize)]
 pub struct HttpRequestInput {
@@ -12,6 +20,14 @@ pub struct HttpRequestInput {
     pub url: String,
     pub headers: Option<HashMap<String, String>>,
     pub body: Option<String>,
+    /// Place the secret somewhere other than the credential kind's default.
+    pub auth_scheme: Option<AuthScheme>,
+    /// Send a `multipart/form-data` body built from these parts instead of `body`.
+    pub form: Option<Vec<FormPart>>,
+    /// Return only these response headers (case-insensitive). All when unset.
+    pub include_headers: Option<Vec<String>>,
+    /// Drop these response headers (case-insensitive), e.g. `set-cookie`.
+    pub exclude_headers: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize)]
@@ -19,6 +35,325 @@ pub struct HttpResponse {
     pub status: u16,
     pub headers: HashMap<String, String>,
     pub body: String,
+    pub timing: Timing,
+}
+
+impl HttpResponse {
+    /// Turn a 4xx/5xx response into `ProxyError::Remote`, leaving the
+    /// response itself untouched for callers that want the body.
+    pub fn error_for_status(&self) -> Result<(), ProxyError> {
+        let status = reqwest::StatusCode::from_u16(self.status)
+            .map_err(|e| ProxyError::Protocol(format!("invalid HTTP status: {e}")))?;
+        if status.is_client_error() || status.is_server_error() {
+            return Err(ProxyError::Remote {
+                status: self.status,
+                message: status.canonical_reason().unwrap_or("").to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Egress settings every client is built with. These come from whoever runs
+/// the server, never from a request: a proxy and CA chosen by the caller
+/// could intercept TLS and read the injected credentials.
+#[derive(Debug, Clone, Default)]
+pub struct HttpConfig {
+    /// Send through this proxy (e.g. `http://proxy.corp:3128`), still
+    /// honouring `NO_PROXY`. Unset, the `HTTPS_PROXY`/`HTTP_PROXY`
+    /// environment applies.
+    pub http_proxy: Option<String>,
+    /// Extra PEM root certificates to trust, e.g. an internal CA.
+    pub extra_ca_pem: Option<String>,
+}
+
+/// Environment variable naming the proxy for every HTTP request.
+pub const HTTP_PROXY_ENV: &str = "PASSMAN_HTTP_PROXY";
+
+/// Environment variable naming a PEM file of extra root certificates to
+/// trust for every HTTP request.
+pub const EXTRA_CA_ENV: &str = "PASSMAN_EXTRA_CA_PEM";
+
+impl HttpConfig {
+    /// The proxy in `PASSMAN_HTTP_PROXY` and the roots in the file named by
+    /// `PASSMAN_EXTRA_CA_PEM`, read once.
+    pub fn from_env() -> Result<Self, ProxyError> {
+        let extra_ca_pem = match std::env::var_os(EXTRA_CA_ENV) {
+            Some(path) => Some(std::fs::read_to_string(&path).map_err(|e| {
+                ProxyError::InvalidInput(format!(
+                    "failed to read {EXTRA_CA_ENV} ({}): {e}",
+                    path.to_string_lossy()
+                ))
+            })?),
+            None => None,
+        };
+        Ok(Self {
+            http_proxy: std::env::var(HTTP_PROXY_ENV).ok().filter(|p| !p.is_empty()),
+            extra_ca_pem,
+        })
+    }
+}
+
+/// HTTP clients shared across requests. Plain requests use one client built
+/// from the `HttpConfig`; certificate credentials get an mTLS client each,
+/// kept per credential so repeat requests skip rebuilding the TLS
+/// configuration. An entry is rebuilt when the credential changes, and can
+/// be dropped explicitly with `invalidate`. Tokens minted for JWT signer
+/// credentials are kept here too.
+#[derive(Default)]
+pub struct ClientCache {
+    config: HttpConfig,
+    base: OnceLock<reqwest::Client>,
+    clients: Mutex<HashMap<Uuid, (u64, reqwest::Client)>>,
+    tokens: TokenCache,
+    /// Clients built so far, i.e. cache misses.
+    builds: AtomicUsize,
+}
+
+impl ClientCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A cache whose clients all use `config`. Fails if the proxy URL or
+    /// CA PEM is invalid.
+    pub fn with_config(config: HttpConfig) -> Result<Self, ProxyError> {
+        let cache = Self {
+            config,
+            ..Self::default()
+        };
+        cache.base_client()?;
+        Ok(cache)
+    }
+
+    /// The client for requests without an mTLS identity, built on first use.
+    fn base_client(&self) -> Result<reqwest::Client, ProxyError> {
+        if let Some(client) = self.base.get() {
+            return Ok(client.clone());
+        }
+        let client = build_client(&self.config, None, None)?;
+        Ok(self.base.get_or_init(|| client).clone())
+    }
+
+    /// Forget the client and token for `credential_id`, e.g. after the
+    /// credential changed.
+    pub fn invalidate(&self, credential_id: Uuid) {
+        self.clients.lock().unwrap().remove(&credential_id);
+        self.tokens.invalidate(credential_id);
+    }
+
+    fn get_or_build(
+        &self,
+        credential_id: Uuid,
+        fingerprint: u64,
+        build: impl FnOnce() -> Result<reqwest::Client, ProxyError>,
+    ) -> Result<reqwest::Client, ProxyError> {
+        if let Some((cached, client)) = self.clients.lock().unwrap().get(&credential_id) {
+            if *cached == fingerprint {
+                return Ok(client.clone());
+            }
+        }
+        let client = build()?;
+        self.builds.fetch_add(1, Ordering::Relaxed);
+        self.clients
+            .lock()
+            .unwrap()
+            .insert(credential_id, (fingerprint, client.clone()));
+        Ok(client)
+    }
+}
+
+/// Hash of the credential fields an mTLS client is built from, so a cached
+/// client is only reused while none of them has changed.
+fn client_fingerprint(secret: &CredentialSecret) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    if let CredentialSecret::Certificate {
+        cert_pem,
+        key_pem,
+        ca_pem,
+        pin_ca,
+    } = secret
+    {
+        (cert_pem, key_pem, ca_pem, pin_ca).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Build a client: the configured proxy (still honouring `NO_PROXY`) or the
+/// environment's, extra trusted roots, and the mTLS identity if any. With
+/// `pinned_ca` that CA is the only trusted root.
+fn build_client(
+    config: &HttpConfig,
+    identity: Option<reqwest::Identity>,
+    pinned_ca: Option<&str>,
+) -> Result<reqwest::Client, ProxyError> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(identity) = identity {
+        builder = builder.identity(identity);
+    }
+
+    if let Some(url) = &config.http_proxy {
+        let proxy = reqwest::Proxy::all(url)
+            .map_err(|e| ProxyError::InvalidInput(format!("invalid http_proxy '{url}': {e}")))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+
+    let roots = match pinned_ca {
+        Some(ca) => {
+            builder = builder.tls_built_in_root_certs(false);
+            vec![ca.to_string()]
+        }
+        None => config.extra_ca_pem.iter().cloned().collect(),
+    };
+    for pem in &roots {
+        let certs = reqwest::Certificate::from_pem_bundle(pem.as_bytes())
+            .map_err(|e| ProxyError::InvalidInput(format!("invalid CA PEM: {e}")))?;
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|e| ProxyError::Tls(format!("failed to build HTTP client: {e}")))
+}
+
+/// Classify a reqwest failure into the matching `ProxyError` variant.
+fn request_error(e: reqwest::Error) -> ProxyError {
+    // reqwest has no TLS predicate; look through the source chain instead
+    let mut chain = e.to_string();
+    let mut source = std::error::Error::source(&e);
+    while let Some(inner) = source {
+        chain.push_str(": ");
+        chain.push_str(&inner.to_string());
+        source = inner.source();
+    }
+
+    let lower = chain.to_lowercase();
+    if e.is_timeout() {
+        ProxyError::Timeout(format!("HTTP request timed out: {chain}"))
+    } else if lower.contains("certificate") || lower.contains("tls") {
+        ProxyError::Tls(format!("HTTP TLS handshake failed: {chain}"))
+    } else if e.is_connect() {
+        ProxyError::Connection(format!("HTTP connection failed: {chain}"))
+    } else {
+        ProxyError::Protocol(format!("HTTP request failed: {chain}"))
+    }
+}
+
+/// The response headers to hand back, filtered by the input's include and
+/// exclude lists, with every retained value sanitized.
+fn response_headers(
+    headers: &HeaderMap,
+    input: &HttpRequestInput,
+    secrets: &[String],
+) -> HashMap<String, String> {
+    let listed = |list: &Option<Vec<String>>, name: &str| {
+        list.as_ref()
+            .map(|names| names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+    };
+    headers
+        .iter()
+        .filter(|(name, _)| {
+            listed(&input.include_headers, name.as_str()).unwrap_or(true)
+                && !listed(&input.exclude_headers, name.as_str()).unwrap_or(false)
+        })
+        .map(|(name, value)| {
+            let value = sanitizer::sanitize(value.to_str().unwrap_or(""), secrets);
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// Inject the credential's secret as `scheme` asks. Only passwords and API
+/// tokens can be placed this way; the token's own header name and prefix
+/// are ignored.
+fn apply_auth_scheme(
+    request: reqwest::RequestBuilder,
+    header_map: &mut HeaderMap,
+    secret: &CredentialSecret,
+    scheme: &AuthScheme,
+) -> Result<reqwest::RequestBuilder, ProxyError> {
+    let value = match secret {
+        CredentialSecret::Password { password, .. } => password,
+        CredentialSecret::ApiToken { token, .. } => token,
+        _ => {
+            return Err(ProxyError::InvalidInput(
+                "auth_scheme only applies to password and api_token credentials".to_string(),
+            ))
+        }
+    };
+    let header_value = |v: &str| {
+        HeaderValue::try_from(v)
+            .map_err(|e| ProxyError::InvalidInput(format!("invalid header value: {e}")))
+    };
+
+    match scheme {
+        AuthScheme::Basic => match secret {
+            CredentialSecret::Password { username, .. } => {
+                Ok(request.basic_auth(username, Some(value)))
+            }
+            _ => Err(ProxyError::InvalidInput(
+                "basic auth_scheme needs a password credential".to_string(),
+            )),
+        },
+        AuthScheme::Bearer => {
+            header_map.insert(
+                HeaderName::from_static("authorization"),
+                header_value(&format!("Bearer {value}"))?,
+            );
+            Ok(request)
+        }
+        AuthScheme::HeaderName(name) => {
+            let name = HeaderName::try_from(name.as_str()).map_err(|e| {
+                ProxyError::InvalidInput(format!("invalid header name '{name}': {e}"))
+            })?;
+            header_map.insert(name, header_value(value)?);
+            Ok(request)
+        }
+        AuthScheme::QueryParam(name) => Ok(request.query(&[(name, value)])),
+    }
+}
+
+/// Build the multipart form for `input.form`, if any. Rejects requests that
+/// set both `form` and `body`.
+fn build_form(input: &HttpRequestInput) -> Result<Option<reqwest::multipart::Form>, ProxyError> {
+    let Some(parts) = &input.form else {
+        return Ok(None);
+    };
+    if input.body.is_some() {
+        return Err(ProxyError::InvalidInput(
+            "set either body or form, not both".to_string(),
+        ));
+    }
+
+    let mut form = reqwest::multipart::Form::new();
+    for part in parts {
+        form = match part {
+            FormPart::Text { name, value } => form.text(name.clone(), value.clone()),
+            FormPart::File {
+                name,
+                filename,
+                content_base64,
+                mime,
+            } => {
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(content_base64)
+                    .map_err(|e| {
+                        ProxyError::InvalidInput(format!(
+                            "invalid base64 in file '{filename}': {e}"
+                        ))
+                    })?;
+                let file = reqwest::multipart::Part::bytes(bytes)
+                    .file_name(filename.clone())
+                    .mime_str(mime.as_deref().unwrap_or("application/octet-stream"))
+                    .map_err(|e| ProxyError::InvalidInput(format!("invalid MIME type: {e}")))?;
+                form.part(name.clone(), file)
+            }
+        };
+    }
+    Ok(Some(form))
 }
 
 /// Execute an HTTP request using the credential for authentication.
@@ -26,7 +361,68 @@ pub async fn execute(
     secret: &CredentialSecret,
     input: &HttpRequestInput,
 ) -> Result<HttpResponse, ProxyError> {
-    let client = reqwest::Client::new();
+    send(secret, input, None).await
+}
+
+/// Like `execute`, reusing the mTLS client for a certificate credential,
+/// or the minted token for a JWT signer, from `cache` across calls.
+pub async fn execute_cached(
+    cache: &ClientCache,
+    credential_id: Uuid,
+    secret: &CredentialSecret,
+    input: &HttpRequestInput,
+) -> Result<HttpResponse, ProxyError> {
+    send(secret, input, Some((cache, credential_id))).await
+}
+
+async fn send(
+    secret: &CredentialSecret,
+    input: &HttpRequestInput,
+    cache: Option<(&ClientCache, Uuid)>,
+) -> Result<HttpResponse, ProxyError> {
+    let start = Instant::now();
+    let client = match (secret, &input.auth_scheme) {
+        (
+            CredentialSecret::Certificate {
+                cert_pem,
+                key_pem,
+                ca_pem,
+                pin_ca,
+            },
+            None,
+        ) => {
+            let pinned_ca = match (pin_ca, ca_pem) {
+                (false, _) => None,
+                (true, Some(ca)) => Some(ca.as_str()),
+                (true, None) => {
+                    return Err(ProxyError::InvalidInput(
+                        "pin_ca is set but the credential has no ca_pem".to_string(),
+                    ))
+                }
+            };
+
+            // mTLS: a client with the certificate identity, cached per credential
+            let build = |config: &HttpConfig| {
+                let mut pem_bundle = cert_pem.as_bytes().to_vec();
+                pem_bundle.push(b'\n');
+                pem_bundle.extend_from_slice(key_pem.as_bytes());
+                let identity = reqwest::Identity::from_pem(&pem_bundle).map_err(|e| {
+                    ProxyError::InvalidInput(format!("invalid certificate/key PEM: {e}"))
+                })?;
+                build_client(config, Some(identity), pinned_ca)
+            };
+            match cache {
+                Some((cache, id)) => {
+                    cache.get_or_build(id, client_fingerprint(secret), || build(&cache.config))?
+                }
+                None => build(&HttpConfig::default())?,
+            }
+        }
+        _ => match cache {
+            Some((cache, _)) => cache.base_client()?,
+            None => build_client(&HttpConfig::default(), None, None)?,
+        },
+    };
 
     let method: reqwest::Method = input
         .method
@@ -34,6 +430,7 @@ pub async fn execute(
         .map_err(|_| ProxyError::InvalidInput(format!("invalid HTTP method: {}", input.method)))?;
 
     let mut request = client.request(method, &input.url);
+    let form = build_form(input)?;
 
     // Build custom headers
     let mut header_map = HeaderMap::new();
@@ -46,185 +443,608 @@ pub async fn execute(
             header_map.insert(name, value);
         }
     }
+    // The multipart body sets its own Content-Type, with the boundary
+    if form.is_some() {
+        header_map.remove(CONTENT_TYPE);
+    }
 
     // Inject authentication from the credential
-    match secret {
-        CredentialSecret::ApiToken {
-            token,
-            header_name,
-            prefix,
-        } => {
-            let hdr_name = header_name.as_deref().unwrap_or("Authorization");
-            let hdr_prefix = prefix.as_deref().unwrap_or("Bearer ");
-            let value = format!("{hdr_prefix}{token}");
-
-            let name = HeaderName::try_from(hdr_name)
-                .map_err(|e| ProxyError::InvalidInput(format!("invalid header name: {e}")))?;
-            let val = HeaderValue::try_from(&value)
-                .map_err(|e| ProxyError::InvalidInput(format!("invalid header value: {e}")))?;
-            header_map.insert(name, val);
-        }
-        CredentialSecret::Password {
-            username, password, ..
-        } => {
-            request = request.basic_auth(username, Some(password));
-        }
-        CredentialSecret::Certificate {
-            cert_pem, key_pem, ..
-        } => {
-            // mTLS: build a new client with the certificate identity
-            let mut pem_bundle = cert_pem.as_bytes().to_vec();
-            pem_bundle.push(b'\n');
-            pem_bundle.extend_from_slice(key_pem.as_bytes());
-            let identity = reqwest::Identity::from_pem(&pem_bundle)
-                .map_err(|e| ProxyError::InvalidInput(format!("invalid certificate/key PEM: {e}")))?;
-
-            let tls_client = reqwest::Client::builder()
-                .identity(identity)
-                .build()
-                .map_err(|e| ProxyError::Protocol(format!("failed to build TLS client: {e}")))?;
-
-            // Re-build the request with the mTLS client
-            let method_clone: reqwest::Method = input
-                .method
-                .parse()
-                .map_err(|_| ProxyError::InvalidInput(format!("invalid HTTP method: {}", input.method)))?;
-            let mut cert_request = tls_client.request(method_clone, &input.url);
-            cert_request = cert_request.headers(header_map);
-            if let Some(body) = &input.body {
-                cert_request = cert_request.body(body.clone());
-            }
-
-            let response = cert_request
-                .send()
-                .await
-                .map_err(|e| ProxyError::Protocol(format!("HTTP request failed: {e}")))?;
+    let mut minted_token = None;
+    if let Some(scheme) = &input.auth_scheme {
+        request = apply_auth_scheme(request, &mut header_map, secret, scheme)?;
+    } else {
+        match secret {
+            CredentialSecret::ApiToken {
+                token,
+                header_name,
+                prefix,
+            } => {
+                let hdr_name = header_name.as_deref().unwrap_or("Authorization");
+                let hdr_prefix = prefix.as_deref().unwrap_or("Bearer ");
+                let value = format!("{hdr_prefix}{token}");
 
-            let status = response.status().as_u16();
-            let resp_headers: HashMap<String, String> = response
-                .headers()
-                .iter()
-                .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
-                .collect();
-            let body = response
-                .text()
-                .await
-                .map_err(|e| ProxyError::Protocol(format!("failed to read response body: {e}")))?;
-
-            let secrets = secret.secret_strings();
-            let sanitized_body = sanitizer::sanitize(&body, &secrets);
-            let sanitized_headers: HashMap<String, String> = resp_headers
-                .into_iter()
-                .map(|(k, v)| (k, sanitizer::sanitize(&v, &secrets)))
-                .collect();
-
-            return Ok(HttpResponse {
-                status,
-                headers: sanitized_headers,
-                body: sanitized_body,
-            });
-        }
-        CredentialSecret::Custom { fields } => {
-            // Custom credentials support an optional "auth_strategy" field:
-            //   "basic"   → HTTP Basic Auth using username/client_id + password/client_secret
-            //   "bearer"  → Bearer token using "token" field
-            //   "headers" → inject each field as a custom header (default)
-            let strategy = fields.get("auth_strategy").map(|s| s.as_str()).unwrap_or("headers");
-            match strategy {
-                "basic" => {
-                    let user = fields
-                        .get("username")
-                        .or_else(|| fields.get("client_id"))
-                        .ok_or_else(|| {
-                            ProxyError::InvalidInput(
-                                "basic auth_strategy requires 'username' or 'client_id' field"
-                                    .to_string(),
-                            )
-                        })?;
-                    let pass = fields
-                        .get("password")
-                        .or_else(|| fields.get("client_secret"))
-                        .ok_or_else(|| {
+                let name = HeaderName::try_from(hdr_name)
+                    .map_err(|e| ProxyError::InvalidInput(format!("invalid header name: {e}")))?;
+                let val = HeaderValue::try_from(&value)
+                    .map_err(|e| ProxyError::InvalidInput(format!("invalid header value: {e}")))?;
+                header_map.insert(name, val);
+            }
+            CredentialSecret::Password {
+                username, password, ..
+            } => {
+                request = request.basic_auth(username, Some(password));
+            }
+            // mTLS: the identity is on the client
+            CredentialSecret::Certificate { .. } => {}
+            CredentialSecret::JwtSigner { .. } => {
+                let now = jwt::unix_now();
+                let token = match cache {
+                    Some((cache, id)) => cache.tokens.get_or_mint(id, secret, now)?,
+                    None => jwt::mint(secret, now)?.token,
+                };
+                header_map.insert(
+                    HeaderName::from_static("authorization"),
+                    HeaderValue::try_from(format!("Bearer {token}")).map_err(|e| {
+                        ProxyError::InvalidInput(format!("invalid header value: {e}"))
+                    })?,
+                );
+                minted_token = Some(token);
+            }
+            CredentialSecret::Custom { fields } => {
+                // Custom credentials support an optional "auth_strategy" field:
+                //   "basic"   → HTTP Basic Auth using username/client_id + password/client_secret
+                //   "bearer"  → Bearer token using "token" field
+                //   "headers" → inject each field as a custom header (default)
+                let strategy = fields
+                    .get("auth_strategy")
+                    .map(|s| s.as_str())
+                    .unwrap_or("headers");
+                match strategy {
+                    "basic" => {
+                        let user = fields
+                            .get("username")
+                            .or_else(|| fields.get("client_id"))
+                            .ok_or_else(|| {
+                                ProxyError::InvalidInput(
+                                    "basic auth_strategy requires 'username' or 'client_id' field"
+                                        .to_string(),
+                                )
+                            })?;
+                        let pass = fields
+                            .get("password")
+                            .or_else(|| fields.get("client_secret"))
+                            .ok_or_else(|| {
+                                ProxyError::InvalidInput(
+                                    "basic auth_strategy requires 'password' or 'client_secret' field"
+                                        .to_string(),
+                                )
+                            })?;
+                        request = request.basic_auth(user, Some(pass));
+                    }
+                    "bearer" => {
+                        let token = fields.get("token").ok_or_else(|| {
                             ProxyError::InvalidInput(
-                                "basic auth_strategy requires 'password' or 'client_secret' field"
-                                    .to_string(),
+                                "bearer auth_strategy requires 'token' field".to_string(),
                             )
                         })?;
-                    request = request.basic_auth(user, Some(pass));
-                }
-                "bearer" => {
-                    let token = fields.get("token").ok_or_else(|| {
-                        ProxyError::InvalidInput(
-                            "bearer auth_strategy requires 'token' field".to_string(),
-                        )
-                    })?;
-                    let value = format!("Bearer {token}");
-                    header_map.insert(
-                        HeaderName::from_static("authorization"),
-                        HeaderValue::try_from(&value).map_err(|e| {
-                            ProxyError::InvalidInput(format!("invalid header value: {e}"))
-                        })?,
-                    );
-                }
-                _ => {
-                    // "headers" or any other value: inject each field as a custom header
-                    for (k, v) in fields {
-                        if k == "auth_strategy" {
-                            continue;
+                        let value = format!("Bearer {token}");
+                        header_map.insert(
+                            HeaderName::from_static("authorization"),
+                            HeaderValue::try_from(&value).map_err(|e| {
+                                ProxyError::InvalidInput(format!("invalid header value: {e}"))
+                            })?,
+                        );
+                    }
+                    _ => {
+                        // "headers" or any other value: inject each field as a custom header
+                        for (k, v) in fields {
+                            if k == "auth_strategy" {
+                                continue;
+                            }
+                            let name = HeaderName::try_from(k.as_str()).map_err(|e| {
+                                ProxyError::InvalidInput(format!("invalid header name '{k}': {e}"))
+                            })?;
+                            let val = HeaderValue::try_from(v.as_str()).map_err(|e| {
+                                ProxyError::InvalidInput(format!("invalid header value: {e}"))
+                            })?;
+                            header_map.insert(name, val);
                         }
-                        let name = HeaderName::try_from(k.as_str()).map_err(|e| {
-                            ProxyError::InvalidInput(format!("invalid header name '{k}': {e}"))
-                        })?;
-                        let val = HeaderValue::try_from(v.as_str()).map_err(|e| {
-                            ProxyError::InvalidInput(format!("invalid header value: {e}"))
-                        })?;
-                        header_map.insert(name, val);
                     }
                 }
             }
-        }
-        _ => {
-            return Err(ProxyError::InvalidInput(
-                "credential type not supported for HTTP requests".to_string(),
-            ));
+            _ => {
+                return Err(ProxyError::InvalidInput(
+                    "credential type not supported for HTTP requests".to_string(),
+                ));
+            }
         }
     }
 
     request = request.headers(header_map);
 
-    if let Some(body) = &input.body {
+    if let Some(form) = form {
+        request = request.multipart(form);
+    } else if let Some(body) = &input.body {
         request = request.body(body.clone());
     }
 
-    let response = request
-        .send()
-        .await
-        .map_err(|e| ProxyError::Protocol(format!("HTTP request failed: {e}")))?;
+    let response = request.send().await.map_err(request_error)?;
+    let first_byte_ms = crate::millis(start.elapsed());
 
     let status = response.status().as_u16();
 
-    let resp_headers: HashMap<String, String> = response
-        .headers()
-        .iter()
-        .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
-        .collect();
+    // Sanitize the response, including any token minted for it
+    let mut secrets = secret.secret_strings();
+    secrets.extend(minted_token);
+    let sanitized_headers = response_headers(response.headers(), input, &secrets);
 
-    let body = response
-        .text()
-        .await
-        .map_err(|e| ProxyError::Protocol(format!("failed to read response body: {e}")))?;
+    let body = response.text().await.map_err(request_error)?;
 
-    // Sanitize the response
-    let secrets = secret.secret_strings();
     let sanitized_body = sanitizer::sanitize(&body, &secrets);
-    let sanitized_headers: HashMap<String, String> = resp_headers
-        .into_iter()
-        .map(|(k, v)| (k, sanitizer::sanitize(&v, &secrets)))
-        .collect();
 
     Ok(HttpResponse {
         status,
         headers: sanitized_headers,
         body: sanitized_body,
+        timing: Timing {
+            first_byte_ms: Some(first_byte_ms),
+            ..Timing::since(start)
+        },
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn test_secret() -> CredentialSecret {
+        CredentialSecret::ApiToken {
+            token: "tok_test_123".to_string(),
+            header_name: None,
+            prefix: None,
+        }
+    }
+
+    fn get(url: String) -> HttpRequestInput {
+        HttpRequestInput {
+            method: "GET".to_string(),
+            url,
+            headers: None,
+            body: None,
+            auth_scheme: None,
+            form: None,
+            include_headers: None,
+            exclude_headers: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connection_refused() {
+        // Bind then drop to get a port nothing is listening on
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let err = execute(&test_secret(), &get(format!("http://{addr}/")))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProxyError::Connection(_)), "got {err:?}");
+        assert!(err.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_server_error_status() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).await;
+            stream
+                .write_all(b"HTTP/1.1 500 Internal Server Error\r\ncontent-length: 4\r\nconnection: close\r\n\r\noops")
+                .await
+                .unwrap();
+        });
+
+        let response = execute(&test_secret(), &get(format!("http://{addr}/")))
+            .await
+            .unwrap();
+        assert_eq!(response.status, 500);
+        assert_eq!(response.body, "oops");
+        assert!(matches!(
+            response.error_for_status(),
+            Err(ProxyError::Remote { status: 500, .. })
+        ));
+    }
+
+    /// Read one HTTP request: headers plus a Content-Length or chunked body.
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> String {
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        loop {
+            let n = stream.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+            let text = String::from_utf8_lossy(&buf).to_string();
+            let Some(end) = text.find("\r\n\r\n") else {
+                continue;
+            };
+            let head = text[..end].to_lowercase();
+            let complete = if head.contains("transfer-encoding: chunked") {
+                text.ends_with("0\r\n\r\n")
+            } else {
+                let length = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length:"))
+                    .and_then(|v| v.trim().parse::<usize>().ok())
+                    .unwrap_or(0);
+                buf.len() >= end + 4 + length
+            };
+            if complete || n == 0 {
+                return text;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multipart_form() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut stream).await;
+            // Echo the boundary and the names of the parts that arrived
+            let boundary = request
+                .lines()
+                .find_map(|l| l.split_once("boundary=").map(|(_, b)| b.trim().to_string()))
+                .unwrap_or_default();
+            let names: Vec<&str> = request
+                .split("; name=\"")
+                .skip(1)
+                .filter_map(|rest| rest.split('"').next())
+                .collect();
+            let delimiters = request.matches(&format!("--{boundary}")).count();
+            let body = format!("{boundary}|{}|{delimiters}", names.join(","));
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let mut input = get(format!("http://{addr}/upload"));
+        input.method = "POST".to_string();
+        input.headers = Some(HashMap::from([(
+            "Content-Type".to_string(),
+            "application/json".to_string(),
+        )]));
+        input.form = Some(vec![
+            FormPart::Text {
+                name: "note".to_string(),
+                value: "quarterly report".to_string(),
+            },
+            FormPart::File {
+                name: "upload".to_string(),
+                filename: "report.txt".to_string(),
+                content_base64: "aGVsbG8=".to_string(),
+                mime: Some("text/plain".to_string()),
+            },
+        ]);
+
+        let response = execute(&test_secret(), &input).await.unwrap();
+        let echoed: Vec<&str> = response.body.split('|').collect();
+        assert!(
+            !echoed[0].is_empty(),
+            "no multipart boundary in {response:?}"
+        );
+        assert_eq!(echoed[1], "note,upload");
+        // One delimiter per part plus the closing one
+        assert_eq!(echoed[2], "3");
+    }
+
+    #[tokio::test]
+    async fn test_form_and_body_are_exclusive() {
+        let mut input = get("http://127.0.0.1:9/".to_string());
+        input.body = Some("{}".to_string());
+        input.form = Some(vec![]);
+        let err = execute(&test_secret(), &input).await.unwrap_err();
+        assert!(matches!(err, ProxyError::InvalidInput(_)), "got {err:?}");
+    }
+
+    #[tokio::test]
+    async fn test_jwt_signer_sends_cached_bearer() {
+        // Echo the bearer token back, and hand it to the test for checking
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let request = read_request(&mut stream).await;
+                let token = request
+                    .lines()
+                    .find_map(|l| l.strip_prefix("authorization: Bearer "))
+                    .unwrap_or_default()
+                    .to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{token}",
+                    token.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+                tx.send(token).unwrap();
+            }
+        });
+
+        let secret = CredentialSecret::JwtSigner {
+            private_key_pem: include_str!("../tests/fixtures/jwt_es256.key").to_string(),
+            algorithm: passman_types::JwtAlgorithm::ES256,
+            issuer: "passman-test".to_string(),
+            audience: format!("http://{addr}"),
+            ttl_secs: 300,
+        };
+        let cache = ClientCache::new();
+        let id = Uuid::new_v4();
+        let input = get(format!("http://{addr}/"));
+        for _ in 0..2 {
+            let response = execute_cached(&cache, id, &secret, &input).await.unwrap();
+            assert_eq!(response.body, "[REDACTED]");
+        }
+
+        let first = rx.recv().await.unwrap();
+        assert_eq!(first.split('.').count(), 3, "not a JWT: {first}");
+        assert_eq!(rx.recv().await.unwrap(), first);
+    }
+
+    /// Serve one response carrying a cookie, an internal header and the
+    /// credential's token, to `count` sequential requests.
+    async fn header_server(count: usize) -> std::net::SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for _ in 0..count {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                read_request(&mut stream).await;
+                stream
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\n\
+                          set-cookie: session=abc123; Path=/\r\n\
+                          x-internal-trace: tok_test_123\r\n\
+                          content-length: 2\r\nconnection: close\r\n\r\nok",
+                    )
+                    .await
+                    .unwrap();
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_response_header_filters() {
+        let addr = header_server(3).await;
+
+        let all = execute(&test_secret(), &get(format!("http://{addr}/")))
+            .await
+            .unwrap();
+        assert!(all.headers.contains_key("set-cookie"));
+        // Retained header values are still sanitized
+        assert_eq!(all.headers["x-internal-trace"], "[REDACTED]");
+
+        let mut excluding = get(format!("http://{addr}/"));
+        excluding.exclude_headers = Some(vec!["Set-Cookie".to_string()]);
+        let response = execute(&test_secret(), &excluding).await.unwrap();
+        assert!(!response.headers.contains_key("set-cookie"));
+        assert!(response.headers.contains_key("content-type"));
+
+        let mut including = get(format!("http://{addr}/"));
+        including.include_headers = Some(vec!["Content-Type".to_string()]);
+        let response = execute(&test_secret(), &including).await.unwrap();
+        let names: Vec<_> = response.headers.keys().collect();
+        assert_eq!(names, vec!["content-type"]);
+        assert_eq!(response.body, "ok");
+    }
+
+    /// Answer one request with the raw request text as the body.
+    async fn echo_server() -> std::net::SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let request = read_request(&mut stream).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{request}",
+                request.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_response_timing() {
+        let addr = echo_server().await;
+        let response = execute(&test_secret(), &get(format!("http://{addr}/")))
+            .await
+            .unwrap();
+
+        let first_byte = response.timing.first_byte_ms.unwrap();
+        assert!(first_byte <= response.timing.total_ms);
+        let json = serde_json::to_value(&response).unwrap();
+        assert!(json["timing"]["total_ms"].as_u64().is_some());
+        assert!(json["timing"]["first_byte_ms"].as_u64().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_auth_scheme_query_param() {
+        let addr = echo_server().await;
+        let mut input = get(format!("http://{addr}/search"));
+        input.auth_scheme = Some(AuthScheme::QueryParam("api_key".to_string()));
+
+        let response = execute(&test_secret(), &input).await.unwrap();
+        // The token was sent in the query string and scrubbed from the echo
+        assert!(response.body.starts_with("GET /search?api_key=[REDACTED] "));
+        assert!(!response.body.to_lowercase().contains("authorization"));
+        assert!(!response.body.contains("tok_test_123"));
+    }
+
+    #[tokio::test]
+    async fn test_auth_scheme_custom_header() {
+        let addr = echo_server().await;
+        let secret = CredentialSecret::Password {
+            username: "svc".to_string(),
+            password: "hunter2-secret".to_string(),
+            url: None,
+        };
+        let mut input = get(format!("http://{addr}/"));
+        input.auth_scheme = Some(AuthScheme::HeaderName("X-Api-Key".to_string()));
+
+        let response = execute(&secret, &input).await.unwrap();
+        assert!(response.body.contains("x-api-key: [REDACTED]"));
+        assert!(!response.body.to_lowercase().contains("authorization"));
+        assert!(!response.body.contains("hunter2-secret"));
+    }
+
+    #[tokio::test]
+    async fn test_auth_scheme_must_fit_credential() {
+        let mut input = get("http://127.0.0.1:9/".to_string());
+        input.auth_scheme = Some(AuthScheme::Basic);
+        let err = execute(&test_secret(), &input).await.unwrap_err();
+        assert!(matches!(err, ProxyError::InvalidInput(_)), "got {err:?}");
+
+        let cert = CredentialSecret::Certificate {
+            cert_pem: String::new(),
+            key_pem: String::new(),
+            ca_pem: None,
+            pin_ca: false,
+        };
+        input.auth_scheme = Some(AuthScheme::Bearer);
+        let err = execute(&cert, &input).await.unwrap_err();
+        assert!(matches!(err, ProxyError::InvalidInput(_)), "got {err:?}");
+    }
+
+    #[tokio::test]
+    async fn test_requests_go_through_http_proxy() {
+        // A forward proxy sees the absolute URL in the request line
+        let proxy = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = proxy.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = proxy.accept().await.unwrap();
+            let request = read_request(&mut stream).await;
+            let line = request.lines().next().unwrap_or_default().to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{line}",
+                line.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        let cache = ClientCache::with_config(HttpConfig {
+            http_proxy: Some(format!("http://{addr}")),
+            extra_ca_pem: None,
+        })
+        .unwrap();
+        let input = get("http://upstream.invalid/hello".to_string());
+        let response = execute_cached(&cache, Uuid::new_v4(), &test_secret(), &input)
+            .await
+            .unwrap();
+        assert_eq!(response.body, "GET http://upstream.invalid/hello HTTP/1.1");
+    }
+
+    #[test]
+    fn test_extra_ca_is_loaded() {
+        let mut config = HttpConfig {
+            http_proxy: None,
+            extra_ca_pem: Some(include_str!("../tests/fixtures/ca.pem").to_string()),
+        };
+        assert!(ClientCache::with_config(config.clone()).is_ok());
+
+        config.http_proxy = Some("not a url".to_string());
+        assert!(matches!(
+            ClientCache::with_config(config),
+            Err(ProxyError::InvalidInput(_))
+        ));
+    }
+
+    /// HTTPS server presenting `fixtures/server.pem` (issued by
+    /// `fixtures/ca.pem`) for `connections` connections.
+    async fn tls_server(connections: usize) -> std::net::SocketAddr {
+        use tokio_rustls::rustls;
+
+        let cert_pem = include_str!("../tests/fixtures/server.pem");
+        let key_pem = include_str!("../tests/fixtures/server.key");
+        let certs = rustls_pemfile::certs(&mut cert_pem.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let key = rustls_pemfile::private_key(&mut key_pem.as_bytes())
+            .unwrap()
+            .unwrap();
+        let config = rustls::ServerConfig::builder_with_provider(std::sync::Arc::new(
+            rustls::crypto::ring::default_provider(),
+        ))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .unwrap();
+        let acceptor = tokio_rustls::TlsAcceptor::from(std::sync::Arc::new(config));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            for _ in 0..connections {
+                let (stream, _) = listener.accept().await.unwrap();
+                // A client that doesn't trust the certificate aborts the handshake
+                let Ok(mut tls) = acceptor.accept(stream).await else {
+                    continue;
+                };
+                let mut buf = [0u8; 4096];
+                let _ = tls.read(&mut buf).await;
+                let response =
+                    b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok";
+                let _ = tls.write_all(response).await;
+                let _ = tls.shutdown().await;
+            }
+        });
+        addr
+    }
+
+    fn client_certificate(ca_pem: &str) -> CredentialSecret {
+        CredentialSecret::Certificate {
+            cert_pem: include_str!("../tests/fixtures/client.pem").to_string(),
+            key_pem: include_str!("../tests/fixtures/client.key").to_string(),
+            ca_pem: Some(ca_pem.to_string()),
+            pin_ca: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mtls_client_is_reused() {
+        let addr = tls_server(3).await;
+        let cache = ClientCache::new();
+        let id = Uuid::new_v4();
+        let secret = client_certificate(include_str!("../tests/fixtures/ca.pem"));
+        let input = get(format!("https://{addr}/"));
+
+        for _ in 0..2 {
+            let response = execute_cached(&cache, id, &secret, &input).await.unwrap();
+            assert_eq!(response.body, "ok");
+        }
+        assert_eq!(cache.builds.load(Ordering::Relaxed), 1);
+
+        // An updated credential gets a fresh client
+        cache.invalidate(id);
+        execute_cached(&cache, id, &secret, &input).await.unwrap();
+        assert_eq!(cache.builds.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_ca_rejects_untrusted_server() {
+        let addr = tls_server(1).await;
+        // The server's certificate comes from ca.pem, not the pinned CA
+        let secret = client_certificate(include_str!("../tests/fixtures/other_ca.pem"));
+
+        let err = execute(&secret, &get(format!("https://{addr}/")))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ProxyError::Tls(_)), "got {err:?}");
+    }
+}
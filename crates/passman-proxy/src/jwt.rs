@@ -0,0 +1,235 @@
+//! Bearer tokens minted from `JwtSigner` credentials. Each token carries the
+//! standard iss/aud/iat/exp claims and is signed with the stored key; the key
+//! itself never leaves this module.
+
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use passman_types::{CredentialSecret, JwtAlgorithm};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::ProxyError;
+
+/// A cached token is replaced once it's this close to expiring (or halfway
+/// through its lifetime, for shorter TTLs).
+pub const REFRESH_MARGIN_SECS: u64 = 30;
+
+/// Claims of every minted token.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    pub iss: String,
+    pub aud: String,
+    pub iat: u64,
+    pub exp: u64,
+}
+
+/// A signed token and when it expires (Unix seconds).
+#[derive(Clone)]
+pub struct MintedToken {
+    pub token: String,
+    pub expires_at: u64,
+}
+
+/// Current Unix time in seconds.
+pub fn unix_now() -> u64 {
+    u64::try_from(chrono::Utc::now().timestamp()).unwrap_or(0)
+}
+
+/// Sign a token valid from `now` for the credential's `ttl_secs`.
+pub fn mint(secret: &CredentialSecret, now: u64) -> Result<MintedToken, ProxyError> {
+    let CredentialSecret::JwtSigner {
+        private_key_pem,
+        algorithm,
+        issuer,
+        audience,
+        ttl_secs,
+    } = secret
+    else {
+        return Err(ProxyError::InvalidInput(
+            "credential is not a jwt_signer".to_string(),
+        ));
+    };
+
+    let pem = private_key_pem.as_bytes();
+    let (algorithm, key) = match algorithm {
+        JwtAlgorithm::RS256 => (Algorithm::RS256, EncodingKey::from_rsa_pem(pem)),
+        JwtAlgorithm::RS384 => (Algorithm::RS384, EncodingKey::from_rsa_pem(pem)),
+        JwtAlgorithm::RS512 => (Algorithm::RS512, EncodingKey::from_rsa_pem(pem)),
+        JwtAlgorithm::ES256 => (Algorithm::ES256, EncodingKey::from_ec_pem(pem)),
+        JwtAlgorithm::ES384 => (Algorithm::ES384, EncodingKey::from_ec_pem(pem)),
+        JwtAlgorithm::EdDSA => (Algorithm::EdDSA, EncodingKey::from_ed_pem(pem)),
+    };
+    let key = key.map_err(|e| ProxyError::InvalidInput(format!("invalid JWT signing key: {e}")))?;
+
+    let claims = Claims {
+        iss: issuer.clone(),
+        aud: audience.clone(),
+        iat: now,
+        exp: now.saturating_add(*ttl_secs),
+    };
+    let token = jsonwebtoken::encode(&Header::new(algorithm), &claims, &key)
+        .map_err(|e| ProxyError::InvalidInput(format!("failed to sign JWT: {e}")))?;
+
+    Ok(MintedToken {
+        token,
+        expires_at: claims.exp,
+    })
+}
+
+/// Minted tokens kept per credential and reused until they near expiry.
+/// A token is re-minted early when the credential changes.
+#[derive(Default)]
+pub struct TokenCache {
+    tokens: Mutex<HashMap<Uuid, (u64, MintedToken)>>,
+}
+
+impl TokenCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forget the token for `credential_id`.
+    pub fn invalidate(&self, credential_id: Uuid) {
+        self.tokens.lock().unwrap().remove(&credential_id);
+    }
+
+    /// The cached token for `credential_id`, or a fresh one if there is none,
+    /// it's about to expire at `now`, or the credential changed.
+    pub fn get_or_mint(
+        &self,
+        credential_id: Uuid,
+        secret: &CredentialSecret,
+        now: u64,
+    ) -> Result<String, ProxyError> {
+        let fingerprint = signer_fingerprint(secret);
+        if let Some((cached, minted)) = self.tokens.lock().unwrap().get(&credential_id) {
+            if *cached == fingerprint
+                && now.saturating_add(refresh_margin(secret)) < minted.expires_at
+            {
+                return Ok(minted.token.clone());
+            }
+        }
+
+        let minted = mint(secret, now)?;
+        let token = minted.token.clone();
+        self.tokens
+            .lock()
+            .unwrap()
+            .insert(credential_id, (fingerprint, minted));
+        Ok(token)
+    }
+}
+
+fn refresh_margin(secret: &CredentialSecret) -> u64 {
+    match secret {
+        CredentialSecret::JwtSigner { ttl_secs, .. } => REFRESH_MARGIN_SECS.min(ttl_secs / 2),
+        _ => 0,
+    }
+}
+
+fn signer_fingerprint(secret: &CredentialSecret) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    if let CredentialSecret::JwtSigner {
+        private_key_pem,
+        algorithm,
+        issuer,
+        audience,
+        ttl_secs,
+    } = secret
+    {
+        (private_key_pem, algorithm, issuer, audience, ttl_secs).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{DecodingKey, Validation};
+
+    fn signer(ttl_secs: u64) -> CredentialSecret {
+        CredentialSecret::JwtSigner {
+            private_key_pem: include_str!("../tests/fixtures/jwt_es256.key").to_string(),
+            algorithm: JwtAlgorithm::ES256,
+            issuer: "passman-test".to_string(),
+            audience: "https://api.example.com".to_string(),
+            ttl_secs,
+        }
+    }
+
+    fn verify(token: &str) -> Claims {
+        let key =
+            DecodingKey::from_ec_pem(include_bytes!("../tests/fixtures/jwt_es256.pub")).unwrap();
+        let mut validation = Validation::new(Algorithm::ES256);
+        validation.set_issuer(&["passman-test"]);
+        validation.set_audience(&["https://api.example.com"]);
+        // The tests mint at fixed times in the past
+        validation.validate_exp = false;
+        jsonwebtoken::decode::<Claims>(token, &key, &validation)
+            .unwrap()
+            .claims
+    }
+
+    #[test]
+    fn test_minted_token_claims_and_signature() {
+        let minted = mint(&signer(300), 1_700_000_000).unwrap();
+        assert_eq!(minted.expires_at, 1_700_000_300);
+        assert_eq!(
+            verify(&minted.token),
+            Claims {
+                iss: "passman-test".to_string(),
+                aud: "https://api.example.com".to_string(),
+                iat: 1_700_000_000,
+                exp: 1_700_000_300,
+            }
+        );
+    }
+
+    #[test]
+    fn test_cached_token_is_refreshed_near_expiry() {
+        let cache = TokenCache::new();
+        let id = Uuid::new_v4();
+        let secret = signer(300);
+        let start = 1_700_000_000;
+
+        let first = cache.get_or_mint(id, &secret, start).unwrap();
+        assert_eq!(cache.get_or_mint(id, &secret, start + 60).unwrap(), first);
+
+        // Inside the refresh margin, then past expiry: a new token each time
+        let refreshed = cache.get_or_mint(id, &secret, start + 280).unwrap();
+        assert_ne!(refreshed, first);
+        assert_eq!(verify(&refreshed).iat, start + 280);
+        let expired = cache.get_or_mint(id, &secret, start + 1000).unwrap();
+        assert_eq!(verify(&expired).exp, start + 1300);
+    }
+
+    #[test]
+    fn test_changed_credential_gets_a_new_token() {
+        let cache = TokenCache::new();
+        let id = Uuid::new_v4();
+        let start = 1_700_000_000;
+
+        let first = cache.get_or_mint(id, &signer(300), start).unwrap();
+        let changed = cache.get_or_mint(id, &signer(600), start + 1).unwrap();
+        assert_ne!(changed, first);
+        assert_eq!(verify(&changed).exp, start + 601);
+    }
+
+    #[test]
+    fn test_invalid_key_is_rejected() {
+        let mut secret = signer(300);
+        if let CredentialSecret::JwtSigner {
+            private_key_pem, ..
+        } = &mut secret
+        {
+            *private_key_pem = "not a key".to_string();
+        }
+        assert!(matches!(
+            mint(&secret, 1_700_000_000),
+            Err(ProxyError::InvalidInput(_))
+        ));
+    }
+}
@@ -1,14 +1,61 @@
 pub mod http;
+pub mod jwt;
+#[cfg(feature = "mongo")]
+pub mod mongo;
+pub mod ping;
+pub mod redact;
 pub mod sanitizer;
 pub mod smtp;
 pub mod sql;
 pub mod ssh;
 
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// How long a proxied call took, in milliseconds.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Timing {
+    /// From the start of the call, connecting included, until the result was ready.
+    pub total_ms: u64,
+    /// Until the response status and headers arrived (HTTP only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_byte_ms: Option<u64>,
+}
+
+impl Timing {
+    /// Timing of a call that started at `start` and is finishing now.
+    pub fn since(start: Instant) -> Self {
+        Self {
+            total_ms: millis(start.elapsed()),
+            first_byte_ms: None,
+        }
+    }
+}
+
+pub(crate) fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ProxyError {
     #[error("invalid input: {0}")]
     InvalidInput(String),
 
+    #[error("connection failed: {0}")]
+    Connection(String),
+
+    #[error("authentication failed: {0}")]
+    Auth(String),
+
+    #[error("timed out: {0}")]
+    Timeout(String),
+
+    #[error("TLS error: {0}")]
+    Tls(String),
+
+    #[error("remote returned status {status}: {message}")]
+    Remote { status: u16, message: String },
+
     #[error("protocol error: {0}")]
     Protocol(String),
 
@@ -16,9 +63,14 @@ pub enum ProxyError {
     PolicyDenied(String),
 }
 
-/// Install sqlx any-pool drivers. Call once at startup.
-pub fn install_sql_drivers() {
-    // sqlx 0.8 uses AnyPool::connect which auto-discovers drivers
-    // if the feature flags are enabled. No explicit install needed
-    // for the `any` feature with driver features enabled.
+impl ProxyError {
+    /// Whether repeating the same operation may succeed (transient network
+    /// failures, timeouts, 429 and 5xx responses).
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            ProxyError::Connection(_) | ProxyError::Timeout(_) => true,
+            ProxyError::Remote { status, .. } => *status == 429 || *status >= 500,
+            _ => false,
+        }
+    }
 }
@@ -0,0 +1,87 @@
+use mongodb::bson::{self, Bson, Document};
+use mongodb::Client;
+use passman_types::CredentialSecret;
+use serde::Deserialize;
+
+use crate::sanitizer;
+use crate::ProxyError;
+
+/// Default and maximum number of documents returned by a find.
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct MongoFindInput {
+    pub database: String,
+    pub collection: String,
+    pub filter: Option<serde_json::Value>,
+    pub limit: Option<u32>,
+}
+
+/// Classify a driver failure into the matching `ProxyError` variant.
+fn mongo_error(e: mongodb::error::Error, context: &str) -> ProxyError {
+    use mongodb::error::ErrorKind;
+    match *e.kind {
+        ErrorKind::Authentication { .. } => ProxyError::Auth(format!("{context}: {e}")),
+        ErrorKind::ServerSelection { .. } => ProxyError::Timeout(format!("{context}: {e}")),
+        ErrorKind::Io(_) | ErrorKind::ConnectionPoolCleared { .. } => {
+            ProxyError::Connection(format!("{context}: {e}"))
+        }
+        _ => ProxyError::Protocol(format!("{context}: {e}")),
+    }
+}
+
+/// Run a filtered `find` against a collection using the stored MongoDB credential.
+/// Documents are returned as relaxed extended JSON with secrets sanitized.
+pub async fn find(
+    secret: &CredentialSecret,
+    input: &MongoFindInput,
+) -> Result<Vec<serde_json::Value>, ProxyError> {
+    let uri = match secret {
+        CredentialSecret::MongoDb { uri } => uri,
+        _ => {
+            return Err(ProxyError::InvalidInput(
+                "credential type not supported for MongoDB".to_string(),
+            ));
+        }
+    };
+
+    let filter: Document = match &input.filter {
+        Some(value) => bson::to_document(value)
+            .map_err(|e| ProxyError::InvalidInput(format!("invalid filter document: {e}")))?,
+        None => Document::new(),
+    };
+    let limit = input.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+
+    let client = Client::with_uri_str(uri)
+        .await
+        .map_err(|e| mongo_error(e, "MongoDB connection failed"))?;
+
+    let collection = client
+        .database(&input.database)
+        .collection::<Document>(&input.collection);
+
+    let mut cursor = collection
+        .find(filter)
+        .limit(limit as i64)
+        .await
+        .map_err(|e| mongo_error(e, "MongoDB find failed"))?;
+
+    let secrets = secret.secret_strings();
+    let mut documents = Vec::new();
+    while cursor
+        .advance()
+        .await
+        .map_err(|e| mongo_error(e, "MongoDB cursor failed"))?
+    {
+        let doc = cursor
+            .deserialize_current()
+            .map_err(|e| ProxyError::Protocol(format!("failed to decode document: {e}")))?;
+        let json = Bson::Document(doc).into_relaxed_extjson();
+        documents.push(sanitizer::sanitize_value(json, &secrets));
+    }
+
+    client.shutdown().await;
+
+    Ok(documents)
+}
@@ -0,0 +1,125 @@
+use passman_types::{CredentialSecret, DbDriver};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use crate::ProxyError;
+
+/// Default connect timeout in seconds.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PingOutput {
+    pub host: String,
+    pub port: u16,
+    pub reachable: bool,
+    /// Time to complete the TCP handshake, name resolution included.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Why the connection failed, when unreachable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The host and port a credential connects to, for credential types that
+/// have one (SSH, database servers, SMTP).
+pub fn target(secret: &CredentialSecret) -> Result<(&str, u16), ProxyError> {
+    match secret {
+        CredentialSecret::SshKey { host, port, .. }
+        | CredentialSecret::SshPassword { host, port, .. }
+        | CredentialSecret::SshAgent { host, port, .. }
+        | CredentialSecret::SmtpAccount { host, port, .. } => Ok((host, *port)),
+        CredentialSecret::DatabaseConnection {
+            driver, host, port, ..
+        } if *driver != DbDriver::Sqlite => Ok((host, *port)),
+        _ => Err(ProxyError::InvalidInput(
+            "credential has no host/port to ping".to_string(),
+        )),
+    }
+}
+
+/// Open a TCP connection to the credential's host and close it again,
+/// without speaking the protocol or authenticating. A refused or timed-out
+/// connection is reported as unreachable rather than as an error.
+pub async fn execute(
+    secret: &CredentialSecret,
+    timeout_secs: Option<u64>,
+) -> Result<PingOutput, ProxyError> {
+    let (host, port) = target(secret)?;
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+
+    let start = Instant::now();
+    let result = tokio::time::timeout(timeout, tokio::net::TcpStream::connect((host, port))).await;
+    let elapsed = crate::millis(start.elapsed());
+
+    let error = match result {
+        Ok(Ok(_stream)) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some(format!("no response within {}s", timeout.as_secs())),
+    };
+
+    Ok(PingOutput {
+        host: host.to_string(),
+        port,
+        reachable: error.is_none(),
+        latency_ms: error.is_none().then_some(elapsed),
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use passman_types::SmtpEncryption;
+
+    fn smtp_secret(port: u16) -> CredentialSecret {
+        CredentialSecret::SmtpAccount {
+            host: "127.0.0.1".to_string(),
+            port,
+            username: "mailer@example.com".to_string(),
+            password: "not-used".to_string(),
+            encryption: SmtpEncryption::None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_listening_port_is_reachable() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let output = execute(&smtp_secret(port), Some(2)).await.unwrap();
+        assert!(output.reachable, "{output:?}");
+        assert!(output.latency_ms.is_some());
+        assert!(output.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_closed_port_is_unreachable() {
+        // Bind then drop to get a port nothing listens on
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+
+        let start = Instant::now();
+        let output = execute(&smtp_secret(port), Some(2)).await.unwrap();
+        assert!(start.elapsed() < Duration::from_secs(3));
+        assert!(!output.reachable);
+        assert!(output.latency_ms.is_none());
+        assert!(output.error.is_some());
+    }
+
+    #[test]
+    fn test_target_requires_host() {
+        let sqlite = CredentialSecret::DatabaseConnection {
+            driver: DbDriver::Sqlite,
+            host: String::new(),
+            port: 0,
+            database: "/tmp/app.db".to_string(),
+            username: String::new(),
+            password: String::new(),
+            params: Default::default(),
+        };
+        assert!(matches!(target(&sqlite), Err(ProxyError::InvalidInput(_))));
+        assert_eq!(target(&smtp_secret(25)).unwrap(), ("127.0.0.1", 25));
+    }
+}
@@ -49,11 +49,56 @@ pub fn sanitize(output: &str, secrets: &[String]) -> String {
     result
 }
 
+/// Sanitize every string inside a JSON value, recursing into arrays and objects.
+pub fn sanitize_value(value: serde_json::Value, secrets: &[String]) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(s) => serde_json::Value::String(sanitize(&s, secrets)),
+        serde_json::Value::Array(items) => serde_json::Value::Array(
+            items
+                .into_iter()
+                .map(|v| sanitize_value(v, secrets))
+                .collect(),
+        ),
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.into_iter()
+                .map(|(k, v)| (k, sanitize_value(v, secrets)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Sanitize raw bytes. Valid UTF-8 gets the full treatment from [`sanitize`];
+/// binary data only has raw secret byte sequences replaced.
+pub fn sanitize_bytes(output: &[u8], secrets: &[String]) -> Vec<u8> {
+    if let Ok(text) = std::str::from_utf8(output) {
+        return sanitize(text, secrets).into_bytes();
+    }
+
+    let mut result = output.to_vec();
+    for secret in secrets {
+        let needle = secret.as_bytes();
+        if needle.len() < 4 {
+            continue;
+        }
+        let mut replaced = Vec::with_capacity(result.len());
+        let mut i = 0;
+        while i < result.len() {
+            if result[i..].starts_with(needle) {
+                replaced.extend_from_slice(b"[REDACTED]");
+                i += needle.len();
+            } else {
+                replaced.push(result[i]);
+                i += 1;
+            }
+        }
+        result = replaced;
+    }
+    result
+}
+
 /// Sanitize HTTP headers: remove sensitive header values.
-pub fn sanitize_headers(
-    headers: &[(String, String)],
-    secrets: &[String],
-) -> Vec<(String, String)> {
+pub fn sanitize_headers(headers: &[(String, String)], secrets: &[String]) -> Vec<(String, String)> {
     headers
         .iter()
         .map(|(k, v)| (k.clone(), sanitize(v, secrets)))
@@ -116,15 +161,35 @@ mod tests {
         assert_eq!(result, "Found [REDACTED] and [REDACTED] here");
     }
 
+    #[test]
+    fn test_sanitize_bytes_binary() {
+        let secrets = vec!["mysecret".to_string()];
+        let mut output = vec![0xff, 0xfe];
+        output.extend_from_slice(b"mysecret");
+        output.push(0x00);
+        let mut expected = vec![0xff, 0xfe];
+        expected.extend_from_slice(b"[REDACTED]");
+        expected.push(0x00);
+        assert_eq!(sanitize_bytes(&output, &secrets), expected);
+    }
+
+    #[test]
+    fn test_sanitize_value_nested() {
+        let secrets = vec!["hunter22".to_string()];
+        let value = serde_json::json!({ "a": ["x hunter22", { "b": "hunter22" }], "n": 1 });
+        let sanitized = sanitize_value(value, &secrets);
+        assert_eq!(
+            sanitized,
+            serde_json::json!({ "a": ["x [REDACTED]", { "b": "[REDACTED]" }], "n": 1 })
+        );
+    }
+
     #[test]
     fn test_sanitize_headers() {
         let secrets = vec!["Bearer mytoken123".to_string(), "mytoken123".to_string()];
         let headers = vec![
             ("Content-Type".to_string(), "application/json".to_string()),
-            (
-                "Authorization".to_string(),
-                "Bearer mytoken123".to_string(),
-            ),
+            ("Authorization".to_string(), "Bearer mytoken123".to_string()),
         ];
         let sanitized = sanitize_headers(&headers, &secrets);
         assert_eq!(sanitized[0].1, "application/json");
@@ -1,6 +1,8 @@
+use lettre::address::Envelope;
+use lettre::message::header::{HeaderName, HeaderValue};
 use lettre::message::Mailbox;
 use lettre::transport::smtp::authentication::Credentials;
-use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use lettre::{Address, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
 use passman_types::{CredentialSecret, SmtpEncryption};
 use serde::{Deserialize, Serialize};
 
@@ -14,102 +16,185 @@ pub struct SendEmailInput {
     pub cc: Option<Vec<String>>,
     pub bcc: Option<Vec<String>>,
     pub from: Option<String>,
+    /// SMTP `MAIL FROM` address, when it must differ from the `From` header
+    /// (e.g. a bounce address). Defaults to the `From` address.
+    pub envelope_from: Option<String>,
+    /// Additional headers, e.g. `List-Unsubscribe` or a provider's tracking header.
+    #[serde(default)]
+    pub extra_headers: Vec<(String, String)>,
 }
 
+/// Headers the message builder sets itself; an extra header may not repeat them.
+const RESERVED_HEADERS: [&str; 10] = [
+    "from",
+    "sender",
+    "to",
+    "cc",
+    "bcc",
+    "subject",
+    "date",
+    "message-id",
+    "content-type",
+    "content-transfer-encoding",
+];
+
 #[derive(Debug, Serialize)]
 pub struct SendEmailOutput {
     pub success: bool,
     pub message_id: Option<String>,
 }
 
+/// Classify an SMTP send failure into the matching `ProxyError` variant.
+fn send_error(e: lettre::transport::smtp::Error) -> ProxyError {
+    let status: Option<u16> = e.status().and_then(|code| code.to_string().parse().ok());
+    match status {
+        // 530: authentication required, 535: credentials rejected
+        Some(530 | 535) => ProxyError::Auth(format!("SMTP authentication failed: {e}")),
+        Some(status) => ProxyError::Remote {
+            status,
+            message: e.to_string(),
+        },
+        None if e.is_timeout() => ProxyError::Timeout(format!("SMTP server timed out: {e}")),
+        None if e.is_tls() => ProxyError::Tls(format!("SMTP TLS handshake failed: {e}")),
+        None => ProxyError::Connection(format!("failed to send email: {e}")),
+    }
+}
+
 fn parse_mailbox(addr: &str) -> Result<Mailbox, ProxyError> {
     addr.parse::<Mailbox>()
         .map_err(|e| ProxyError::InvalidInput(format!("invalid email address '{addr}': {e}")))
 }
 
-/// Send an email using the stored SMTP credential.
-pub async fn execute(
-    secret: &CredentialSecret,
-    input: &SendEmailInput,
-) -> Result<SendEmailOutput, ProxyError> {
-    let (host, port, username, password, encryption) = match secret {
-        CredentialSecret::SmtpAccount {
-            host,
-            port,
-            username,
-            password,
-            encryption,
-        } => (
-            host.clone(),
-            *port,
-            username.clone(),
-            password.clone(),
-            *encryption,
-        ),
-        _ => {
-            return Err(ProxyError::InvalidInput(
-                "credential type not supported for SMTP".to_string(),
-            ));
-        }
-    };
+fn parse_header(name: &str, value: &str) -> Result<HeaderValue, ProxyError> {
+    if RESERVED_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+        return Err(ProxyError::InvalidInput(format!(
+            "header '{name}' is set from the other fields and can't be added"
+        )));
+    }
+    if value.contains(['\r', '\n']) {
+        return Err(ProxyError::InvalidInput(format!(
+            "header '{name}' value must not contain line breaks"
+        )));
+    }
+    let name = HeaderName::new_from_ascii(name.to_string())
+        .map_err(|_| ProxyError::InvalidInput(format!("invalid header name '{name}'")))?;
+    Ok(HeaderValue::new(name, value.to_string()))
+}
 
-    let from_addr = if let Some(ref from) = input.from {
-        parse_mailbox(from)?
-    } else {
-        parse_mailbox(&username)?
-    };
+/// Build the message, sent from `input.from` or else the account's `username`.
+fn build_message(input: &SendEmailInput, username: &str) -> Result<Message, ProxyError> {
+    let from_addr = parse_mailbox(input.from.as_deref().unwrap_or(username))?;
+    let envelope_from: Option<Address> = input
+        .envelope_from
+        .as_deref()
+        .map(|addr| parse_mailbox(addr).map(|mailbox| mailbox.email))
+        .transpose()?;
 
     let mut builder = Message::builder().from(from_addr);
+    let mut recipients: Vec<Address> = vec![];
 
     for to in &input.to {
-        builder = builder.to(parse_mailbox(to)?);
+        let mailbox = parse_mailbox(to)?;
+        recipients.push(mailbox.email.clone());
+        builder = builder.to(mailbox);
     }
 
     if let Some(ref cc_list) = input.cc {
         for cc in cc_list {
-            builder = builder.cc(parse_mailbox(cc)?);
+            let mailbox = parse_mailbox(cc)?;
+            recipients.push(mailbox.email.clone());
+            builder = builder.cc(mailbox);
         }
     }
 
     if let Some(ref bcc_list) = input.bcc {
         for bcc in bcc_list {
-            builder = builder.bcc(parse_mailbox(bcc)?);
+            let mailbox = parse_mailbox(bcc)?;
+            recipients.push(mailbox.email.clone());
+            builder = builder.bcc(mailbox);
         }
     }
 
     builder = builder.subject(&input.subject);
 
-    let message = builder
+    for (name, value) in &input.extra_headers {
+        builder = builder.raw_header(parse_header(name, value)?);
+    }
+
+    if let Some(envelope_from) = envelope_from {
+        let envelope = Envelope::new(Some(envelope_from), recipients)
+            .map_err(|e| ProxyError::InvalidInput(format!("invalid envelope: {e}")))?;
+        builder = builder.envelope(envelope);
+    }
+
+    builder
         .body(input.body.clone())
-        .map_err(|e| ProxyError::Protocol(format!("failed to build email message: {e}")))?;
+        .map_err(|e| ProxyError::Protocol(format!("failed to build email message: {e}")))
+}
 
-    let creds = Credentials::new(username, password);
+/// The account's username, and a transport that authenticates with it.
+fn build_transport(
+    secret: &CredentialSecret,
+) -> Result<(String, AsyncSmtpTransport<Tokio1Executor>), ProxyError> {
+    let (host, port, username, password, encryption) = match secret {
+        CredentialSecret::SmtpAccount {
+            host,
+            port,
+            username,
+            password,
+            encryption,
+        } => (host, *port, username, password, *encryption),
+        _ => {
+            return Err(ProxyError::InvalidInput(
+                "credential type not supported for SMTP".to_string(),
+            ));
+        }
+    };
+
+    let creds = Credentials::new(username.clone(), password.clone());
 
     let transport = match encryption {
-        SmtpEncryption::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&host)
-            .map_err(|e| ProxyError::Protocol(format!("SMTP TLS connection failed: {e}")))?
+        SmtpEncryption::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+            .map_err(|e| ProxyError::Tls(format!("SMTP TLS setup failed: {e}")))?
             .port(port)
             .credentials(creds)
             .build(),
-        SmtpEncryption::StartTls => {
-            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)
-                .map_err(|e| {
-                    ProxyError::Protocol(format!("SMTP STARTTLS connection failed: {e}"))
-                })?
-                .port(port)
-                .credentials(creds)
-                .build()
-        }
-        SmtpEncryption::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&host)
+        SmtpEncryption::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .map_err(|e| ProxyError::Tls(format!("SMTP STARTTLS setup failed: {e}")))?
+            .port(port)
+            .credentials(creds)
+            .build(),
+        SmtpEncryption::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
             .port(port)
             .credentials(creds)
             .build(),
     };
 
-    let response = transport
-        .send(message)
-        .await
-        .map_err(|e| ProxyError::Protocol(format!("failed to send email: {e}")))?;
+    Ok((username.clone(), transport))
+}
+
+/// Check an SMTP credential without sending mail: connect (with the
+/// credential's encryption), EHLO, authenticate, NOOP, then QUIT.
+pub async fn verify(secret: &CredentialSecret) -> Result<(), ProxyError> {
+    let (_, transport) = build_transport(secret)?;
+    let connected = transport.test_connection().await.map_err(send_error)?;
+    if !connected {
+        return Err(ProxyError::Protocol(
+            "SMTP server closed the connection after authenticating".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Send an email using the stored SMTP credential.
+pub async fn execute(
+    secret: &CredentialSecret,
+    input: &SendEmailInput,
+) -> Result<SendEmailOutput, ProxyError> {
+    let (username, transport) = build_transport(secret)?;
+    let message = build_message(input, &username)?;
+
+    let response = transport.send(message).await.map_err(send_error)?;
 
     let success = response.is_positive();
     let message_id: Option<String> = response.message().next().map(|s| s.to_string());
@@ -118,3 +203,80 @@ pub async fn execute(
         message_id,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input() -> SendEmailInput {
+        SendEmailInput {
+            to: vec!["dev@example.com".to_string()],
+            subject: "Deploy finished".to_string(),
+            body: "All green.".to_string(),
+            cc: None,
+            bcc: Some(vec!["audit@example.com".to_string()]),
+            from: None,
+            envelope_from: None,
+            extra_headers: vec![],
+        }
+    }
+
+    #[test]
+    fn test_envelope_from_and_extra_headers() {
+        let mut input = input();
+        input.envelope_from = Some("bounces@mail.example.com".to_string());
+        input.extra_headers = vec![
+            ("X-Campaign".to_string(), "spring".to_string()),
+            (
+                "List-Unsubscribe".to_string(),
+                "<mailto:unsub@example.com>".to_string(),
+            ),
+        ];
+
+        let message = build_message(&input, "noreply@example.com").unwrap();
+        let envelope = message.envelope();
+        assert_eq!(
+            envelope.from().map(|a| a.to_string()).as_deref(),
+            Some("bounces@mail.example.com")
+        );
+        let recipients: Vec<String> = envelope.to().iter().map(|a| a.to_string()).collect();
+        assert_eq!(recipients, ["dev@example.com", "audit@example.com"]);
+
+        let formatted = String::from_utf8(message.formatted()).unwrap();
+        assert!(
+            formatted.contains("From: noreply@example.com\r\n"),
+            "{formatted}"
+        );
+        assert!(formatted.contains("X-Campaign: spring\r\n"), "{formatted}");
+        assert!(formatted.contains("List-Unsubscribe: <mailto:unsub@example.com>\r\n"));
+        // Bcc recipients stay in the envelope only
+        assert!(!formatted.contains("audit@example.com"));
+    }
+
+    #[test]
+    fn test_envelope_defaults_to_from() {
+        let message = build_message(&input(), "noreply@example.com").unwrap();
+        assert_eq!(
+            message.envelope().from().map(|a| a.to_string()).as_deref(),
+            Some("noreply@example.com")
+        );
+    }
+
+    #[test]
+    fn test_extra_header_validation() {
+        for (name, value) in [
+            ("Bad Header", "x"),
+            ("X-Ok:", "x"),
+            ("Subject", "override"),
+            ("X-Injected", "a\r\nBcc: evil@example.com"),
+        ] {
+            let mut input = input();
+            input.extra_headers = vec![(name.to_string(), value.to_string())];
+            let err = build_message(&input, "noreply@example.com").unwrap_err();
+            assert!(
+                matches!(err, ProxyError::InvalidInput(_)),
+                "{name}: {err:?}"
+            );
+        }
+    }
+}
@@ -1,26 +1,64 @@
+use base64::Engine;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use futures_util::TryStreamExt;
 use passman_types::{CredentialSecret, DbDriver};
 use serde::{Deserialize, Serialize};
-use sqlx::{AnyPool, Column, Row};
-use sqlx::any::AnyRow;
+use sqlx::mysql::{MySql, MySqlConnection, MySqlQueryResult, MySqlRow};
+use sqlx::postgres::{PgConnection, PgQueryResult, PgRow, Postgres};
+use sqlx::sqlite::{Sqlite, SqliteConnection, SqliteQueryResult, SqliteRow};
+use sqlx::{
+    Column, ColumnIndex, Connection, Database, Either, Executor, IntoArguments, Row, TypeInfo,
+    ValueRef,
+};
 
 use crate::sanitizer;
-use crate::ProxyError;
+use crate::{ProxyError, Timing};
+
+/// Default cap on returned rows when the caller doesn't set one.
+pub const DEFAULT_MAX_ROWS: usize = 1000;
+
+/// Default query timeout in seconds.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// How long to spend asking the server to cancel a timed-out query.
+const CANCEL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
 
 #[derive(Debug, Deserialize)]
 pub struct SqlQueryInput {
     pub query: String,
     pub params: Option<Vec<serde_json::Value>>,
+    /// Stop reading after this many rows (default `DEFAULT_MAX_ROWS`).
+    pub max_rows: Option<usize>,
+    /// Abort the query after this many seconds (default `DEFAULT_TIMEOUT_SECS`).
+    pub timeout_secs: Option<u64>,
+    /// Run the query inside a read-only transaction, so the server rejects
+    /// any write it attempts (e.g. through a function call). SQLite has no
+    /// read-only transactions; there the connection is put in `query_only`
+    /// mode instead.
+    #[serde(default)]
+    pub read_only: bool,
+    /// Allow a script of several statements, run by `execute_multi`.
+    #[serde(default)]
+    pub multi: bool,
 }
 
 #[derive(Debug, Serialize)]
 pub struct SqlQueryOutput {
     pub columns: Vec<String>,
+    /// Row values. `null` always means SQL NULL; a value whose type can't be
+    /// decoded is `{"_unsupported_type": "<database type name>"}` instead.
     pub rows: Vec<Vec<serde_json::Value>>,
+    /// Rows the statement inserted, updated or deleted. SQLite reports the
+    /// count of the connection's last write for a statement that writes
+    /// nothing, e.g. a `SELECT` after an `INSERT` in the same script.
     pub rows_affected: u64,
+    /// True if the result set had more rows than `max_rows`.
+    pub truncated: bool,
+    pub timing: Timing,
 }
 
 /// Build a connection URL from the database credential.
-fn build_connection_url(secret: &CredentialSecret) -> Result<String, ProxyError> {
+fn build_connection_url(secret: &CredentialSecret) -> Result<(DbDriver, String), ProxyError> {
     match secret {
         CredentialSecret::DatabaseConnection {
             driver,
@@ -38,7 +76,7 @@ fn build_connection_url(secret: &CredentialSecret) -> Result<String, ProxyError>
             };
 
             if matches!(driver, DbDriver::Sqlite) {
-                return Ok(format!("sqlite:{database}"));
+                return Ok((*driver, format!("sqlite:{database}")));
             }
 
             let encoded_password = urlencoding::encode(password);
@@ -52,7 +90,7 @@ fn build_connection_url(secret: &CredentialSecret) -> Result<String, ProxyError>
                 url.push_str(&query.join("&"));
             }
 
-            Ok(url)
+            Ok((*driver, url))
         }
         _ => Err(ProxyError::InvalidInput(
             "credential type not supported for SQL".to_string(),
@@ -60,82 +98,534 @@ fn build_connection_url(secret: &CredentialSecret) -> Result<String, ProxyError>
     }
 }
 
-/// Extract a column value from a row as a JSON value.
-fn extract_value(row: &AnyRow, idx: usize) -> serde_json::Value {
+/// JSON for a value whose type we can't decode. Distinct from `null`, which
+/// is only emitted for an actual SQL NULL.
+fn unsupported<R: Row>(row: &R, idx: usize) -> serde_json::Value
+where
+    usize: ColumnIndex<R>,
+{
+    serde_json::json!({ "_unsupported_type": row.column(idx).type_info().name() })
+}
+
+/// Whether the column holds SQL NULL. Checked before decoding, since every
+/// typed `try_get` fails on NULL and would otherwise look unsupported.
+fn is_null<R: Row>(row: &R, idx: usize) -> bool
+where
+    usize: ColumnIndex<R>,
+{
+    row.try_get_raw(idx).map(|v| v.is_null()).unwrap_or(false)
+}
+
+fn timestamp(v: NaiveDateTime) -> serde_json::Value {
+    serde_json::Value::String(v.and_utc().to_rfc3339())
+}
+
+fn bytes(v: Vec<u8>) -> serde_json::Value {
+    serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(v))
+}
+
+/// Extract a Postgres column value as JSON.
+fn pg_value(row: &PgRow, idx: usize) -> serde_json::Value {
+    if is_null(row, idx) {
+        return serde_json::Value::Null;
+    }
+    if let Ok(v) = row.try_get::<bool, _>(idx) {
+        return serde_json::Value::Bool(v);
+    }
+    if let Ok(v) = row.try_get::<i16, _>(idx) {
+        return v.into();
+    }
+    if let Ok(v) = row.try_get::<i32, _>(idx) {
+        return v.into();
+    }
     if let Ok(v) = row.try_get::<i64, _>(idx) {
-        return serde_json::Value::Number(v.into());
+        return v.into();
+    }
+    if let Ok(v) = row.try_get::<f32, _>(idx) {
+        return serde_json::json!(v);
     }
     if let Ok(v) = row.try_get::<f64, _>(idx) {
         return serde_json::json!(v);
     }
+    if let Ok(v) = row.try_get::<String, _>(idx) {
+        return serde_json::Value::String(v);
+    }
+    if let Ok(v) = row.try_get::<DateTime<Utc>, _>(idx) {
+        return serde_json::Value::String(v.to_rfc3339());
+    }
+    if let Ok(v) = row.try_get::<NaiveDateTime, _>(idx) {
+        return timestamp(v);
+    }
+    if let Ok(v) = row.try_get::<NaiveDate, _>(idx) {
+        return serde_json::Value::String(v.to_string());
+    }
+    if let Ok(v) = row.try_get::<uuid::Uuid, _>(idx) {
+        return serde_json::Value::String(v.to_string());
+    }
+    if let Ok(v) = row.try_get::<serde_json::Value, _>(idx) {
+        return v;
+    }
+    if let Ok(v) = row.try_get::<Vec<u8>, _>(idx) {
+        return bytes(v);
+    }
+    unsupported(row, idx)
+}
+
+/// Extract a MySQL column value as JSON.
+fn mysql_value(row: &MySqlRow, idx: usize) -> serde_json::Value {
+    if is_null(row, idx) {
+        return serde_json::Value::Null;
+    }
     if let Ok(v) = row.try_get::<bool, _>(idx) {
         return serde_json::Value::Bool(v);
     }
+    if let Ok(v) = row.try_get::<i64, _>(idx) {
+        return v.into();
+    }
+    if let Ok(v) = row.try_get::<u64, _>(idx) {
+        return v.into();
+    }
+    if let Ok(v) = row.try_get::<f64, _>(idx) {
+        return serde_json::json!(v);
+    }
     if let Ok(v) = row.try_get::<String, _>(idx) {
         return serde_json::Value::String(v);
     }
-    if let Ok(v) = row.try_get::<i32, _>(idx) {
-        return serde_json::Value::Number(v.into());
+    if let Ok(v) = row.try_get::<DateTime<Utc>, _>(idx) {
+        return serde_json::Value::String(v.to_rfc3339());
+    }
+    if let Ok(v) = row.try_get::<NaiveDateTime, _>(idx) {
+        return timestamp(v);
+    }
+    if let Ok(v) = row.try_get::<NaiveDate, _>(idx) {
+        return serde_json::Value::String(v.to_string());
+    }
+    if let Ok(v) = row.try_get::<serde_json::Value, _>(idx) {
+        return v;
+    }
+    if let Ok(v) = row.try_get::<Vec<u8>, _>(idx) {
+        return bytes(v);
+    }
+    unsupported(row, idx)
+}
+
+/// Extract a SQLite column value as JSON. SQLite stores timestamps and JSON
+/// as text, so those come back as strings.
+fn sqlite_value(row: &SqliteRow, idx: usize) -> serde_json::Value {
+    if is_null(row, idx) {
+        return serde_json::Value::Null;
+    }
+    if let Ok(v) = row.try_get::<i64, _>(idx) {
+        return v.into();
+    }
+    if let Ok(v) = row.try_get::<f64, _>(idx) {
+        return serde_json::json!(v);
+    }
+    if let Ok(v) = row.try_get::<String, _>(idx) {
+        return serde_json::Value::String(v);
+    }
+    if let Ok(v) = row.try_get::<Vec<u8>, _>(idx) {
+        return bytes(v);
     }
-    serde_json::Value::Null
+    unsupported(row, idx)
 }
 
-/// Execute a SQL query using the stored credential.
+/// Classify a sqlx failure into the matching `ProxyError` variant. The
+/// message is sanitized, since drivers can echo the connection URL (and the
+/// password in it) when connecting fails.
+fn sql_error(e: sqlx::Error, context: &str, secrets: &[String]) -> ProxyError {
+    let message = sanitizer::sanitize(&format!("{context}: {e}"), secrets);
+    match &e {
+        sqlx::Error::Io(io) if io.kind() == std::io::ErrorKind::TimedOut => {
+            ProxyError::Timeout(message)
+        }
+        sqlx::Error::Io(_) => ProxyError::Connection(message),
+        sqlx::Error::Tls(_) => ProxyError::Tls(message),
+        sqlx::Error::PoolTimedOut => ProxyError::Timeout(message),
+        // 28000/28P01: Postgres auth failures; 1045: MySQL access denied
+        sqlx::Error::Database(db)
+            if matches!(db.code().as_deref(), Some("28000" | "28P01" | "1045")) =>
+        {
+            ProxyError::Auth(message)
+        }
+        _ => ProxyError::Protocol(message),
+    }
+}
+
+/// Execute a SQL query using the stored credential. Rows are streamed and
+/// sanitized one at a time, and reading stops once `max_rows` is reached.
+/// The query is cancelled after `timeout_secs` (on SQLite, abandoned: it
+/// can't be interrupted). A query holding more than one statement is
+/// rejected; see `execute_multi`.
 pub async fn execute(
     secret: &CredentialSecret,
     input: &SqlQueryInput,
 ) -> Result<SqlQueryOutput, ProxyError> {
-    let url = build_connection_url(secret)?;
+    let count = split_statements(&input.query).len();
+    if count > 1 {
+        return Err(ProxyError::InvalidInput(format!(
+            "query holds {count} statements; set multi to run a script"
+        )));
+    }
 
-    let pool: AnyPool = AnyPool::connect(&url)
-        .await
-        .map_err(|e| ProxyError::Protocol(format!("SQL connection failed: {e}")))?;
+    let mut outputs = run_statements(secret, input, &[input.query.as_str()]).await?;
+    Ok(outputs.remove(0))
+}
 
-    let rows: Vec<AnyRow> = sqlx::query(&input.query)
-        .fetch_all(&pool)
-        .await
-        .map_err(|e| ProxyError::Protocol(format!("SQL query failed: {e}")))?;
-
-    let columns: Vec<String> = if let Some(first) = rows.first() {
-        first
-            .columns()
-            .iter()
-            .map(|c| c.name().to_string())
-            .collect()
+/// Execute each statement of a script in turn on one connection, returning
+/// one result per statement. `max_rows` applies to each statement and
+/// `timeout_secs` to the whole script, which stops at the first failing
+/// statement. Without `multi` this accepts a single statement only.
+pub async fn execute_multi(
+    secret: &CredentialSecret,
+    input: &SqlQueryInput,
+) -> Result<Vec<SqlQueryOutput>, ProxyError> {
+    let statements = split_statements(&input.query);
+    if statements.is_empty() {
+        return Err(ProxyError::InvalidInput(
+            "script holds no statements".to_string(),
+        ));
+    }
+    if !input.multi && statements.len() > 1 {
+        return Err(ProxyError::InvalidInput(format!(
+            "query holds {} statements; set multi to run a script",
+            statements.len()
+        )));
+    }
+
+    run_statements(secret, input, &statements).await
+}
+
+/// Split a script into statements at top-level semicolons. Semicolons in
+/// quoted strings and identifiers, dollar-quoted bodies (`$$ ... $$`) and
+/// comments don't count, and statements that are empty or only comments
+/// are dropped.
+pub fn split_statements(script: &str) -> Vec<&str> {
+    let bytes = script.as_bytes();
+    let mut statements = Vec::new();
+    let mut start = 0;
+    let mut has_content = false;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"' | b'`') => {
+                // A doubled quote is an escape; it just reopens the string
+                i = find(bytes, i + 1, &[quote]).map_or(bytes.len(), |end| end + 1);
+                has_content = true;
+                continue;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = find(bytes, i, b"\n").map_or(bytes.len(), |end| end + 1);
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = find(bytes, i + 2, b"*/").map_or(bytes.len(), |end| end + 2);
+                continue;
+            }
+            b'$' => {
+                if let Some(tag) = dollar_tag(&script[i..]) {
+                    let body = i + tag.len();
+                    i = find(bytes, body, tag.as_bytes())
+                        .map_or(bytes.len(), |end| end + tag.len());
+                    has_content = true;
+                    continue;
+                }
+                has_content = true;
+            }
+            b';' => {
+                if has_content {
+                    statements.push(script[start..i].trim());
+                }
+                start = i + 1;
+                has_content = false;
+            }
+            c if !c.is_ascii_whitespace() => has_content = true,
+            _ => {}
+        }
+        i += 1;
+    }
+    if has_content {
+        statements.push(script[start..].trim());
+    }
+
+    statements
+}
+
+/// Index of the first `needle` in `haystack` at or after `from`.
+fn find(haystack: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|pos| from + pos)
+}
+
+/// The `$tag$` opening a Postgres dollar-quoted string at the start of `s`,
+/// if there is one. `$1` and other parameter placeholders aren't tags.
+fn dollar_tag(s: &str) -> Option<&str> {
+    let end = 1 + s[1..].find(|c: char| !(c.is_alphanumeric() || c == '_'))?;
+    let is_tag = s.as_bytes()[end] == b'$' && !s[1..end].starts_with(|c: char| c.is_ascii_digit());
+    is_tag.then(|| &s[..=end])
+}
+
+/// Connect with the credential and run `statements` in order.
+async fn run_statements(
+    secret: &CredentialSecret,
+    input: &SqlQueryInput,
+    statements: &[&str],
+) -> Result<Vec<SqlQueryOutput>, ProxyError> {
+    let start = std::time::Instant::now();
+    let (driver, url) = build_connection_url(secret)?;
+    let secrets = secret.secret_strings();
+
+    match driver {
+        DbDriver::Postgres => {
+            let mut conn = connect::<PgConnection>(&url, input, &secrets).await?;
+            let pid: i32 = sqlx::query_scalar("SELECT pg_backend_pid()")
+                .fetch_one(&mut conn)
+                .await
+                .map_err(|e| sql_error(e, "SQL query failed", &secrets))?;
+            let cancel = Cancel {
+                url: &url,
+                statement: format!("SELECT pg_cancel_backend({pid})"),
+            };
+            run_query::<Postgres>(
+                conn,
+                input,
+                statements,
+                start,
+                &secrets,
+                pg_value,
+                Some(cancel),
+            )
+            .await
+        }
+        DbDriver::Mysql => {
+            let mut conn = connect::<MySqlConnection>(&url, input, &secrets).await?;
+            let id: u64 = sqlx::query_scalar("SELECT CONNECTION_ID()")
+                .fetch_one(&mut conn)
+                .await
+                .map_err(|e| sql_error(e, "SQL query failed", &secrets))?;
+            let cancel = Cancel {
+                url: &url,
+                statement: format!("KILL QUERY {id}"),
+            };
+            run_query::<MySql>(
+                conn,
+                input,
+                statements,
+                start,
+                &secrets,
+                mysql_value,
+                Some(cancel),
+            )
+            .await
+        }
+        DbDriver::Sqlite => {
+            // Runs in-process; there's no server to ask
+            let conn = connect::<SqliteConnection>(&url, input, &secrets).await?;
+            run_query::<Sqlite>(conn, input, statements, start, &secrets, sqlite_value, None).await
+        }
+    }
+}
+
+/// How to stop a query that ran past its timeout: `statement`, run on a
+/// second connection to `url`.
+struct Cancel<'a> {
+    url: &'a str,
+    statement: String,
+}
+
+/// Ask the server to cancel a running query. Best effort: a failure is
+/// logged and the caller reports the timeout either way.
+async fn cancel_query<DB>(cancel: &Cancel<'_>)
+where
+    DB: Database,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+{
+    let send = async {
+        let mut conn = DB::Connection::connect(cancel.url).await?;
+        Executor::execute(&mut conn, cancel.statement.as_str()).await?;
+        conn.close().await
+    };
+    match tokio::time::timeout(CANCEL_TIMEOUT, send).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => tracing::warn!("Failed to cancel timed-out SQL query: {e}"),
+        Err(_) => tracing::warn!("Timed out cancelling SQL query"),
+    }
+}
+
+/// Open a single connection within the input's timeout. Unlike a pool,
+/// which keeps retrying a refused connection until its acquire timeout,
+/// this fails straight away when nothing is listening.
+async fn connect<C: Connection>(
+    url: &str,
+    input: &SqlQueryInput,
+    secrets: &[String],
+) -> Result<C, ProxyError> {
+    let timeout_secs = input.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
+    let timeout = std::time::Duration::from_secs(timeout_secs);
+    match tokio::time::timeout(timeout, C::connect(url)).await {
+        Ok(result) => result.map_err(|e| sql_error(e, "SQL connection failed", secrets)),
+        Err(_) => Err(ProxyError::Timeout(format!(
+            "SQL connection exceeded {timeout_secs}s timeout"
+        ))),
+    }
+}
+
+/// Run the statements on `conn` under the input's timeout, then close it.
+/// With `input.read_only` they run in a `READ ONLY` transaction that is
+/// rolled back afterwards, or on SQLite, which has no such transactions,
+/// with `PRAGMA query_only` on until they finish. Each output's timing runs
+/// from `start` until that statement finished. On timeout the query is
+/// stopped with `cancel`, if given.
+async fn run_query<DB>(
+    mut conn: DB::Connection,
+    input: &SqlQueryInput,
+    statements: &[&str],
+    start: std::time::Instant,
+    secrets: &[String],
+    extract: fn(&DB::Row, usize) -> serde_json::Value,
+    cancel: Option<Cancel<'_>>,
+) -> Result<Vec<SqlQueryOutput>, ProxyError>
+where
+    DB: Database,
+    DB::QueryResult: RowsAffected,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+{
+    let max_rows = input.max_rows.unwrap_or(DEFAULT_MAX_ROWS);
+    let timeout_secs = input.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS);
+    let (begin_read_only, end_read_only) = if DB::NAME == "SQLite" {
+        ("PRAGMA query_only = ON", "PRAGMA query_only = OFF")
     } else {
-        vec![]
+        // Same syntax for Postgres and MySQL
+        ("START TRANSACTION READ ONLY", "ROLLBACK")
+    };
+    let fetch = async {
+        if input.read_only {
+            Executor::execute(&mut conn, begin_read_only)
+                .await
+                .map_err(|e| sql_error(e, "SQL query failed", secrets))?;
+        }
+        let mut outputs = Vec::with_capacity(statements.len());
+        for statement in statements {
+            let mut output = fetch_rows(&mut conn, statement, max_rows, secrets, extract).await?;
+            output.timing = Timing::since(start);
+            outputs.push(output);
+        }
+        if input.read_only {
+            Executor::execute(&mut conn, end_read_only)
+                .await
+                .map_err(|e| sql_error(e, "SQL query failed", secrets))?;
+        }
+        Ok::<_, ProxyError>(outputs)
     };
 
-    let result_rows: Vec<Vec<serde_json::Value>> = rows
-        .iter()
-        .map(|row: &AnyRow| {
-            (0..columns.len())
-                .map(|i| extract_value(row, i))
-                .collect()
-        })
-        .collect();
+    match tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), fetch).await {
+        Ok(result) => {
+            let _ = conn.close().await;
+            result
+        }
+        Err(_) => {
+            // Don't wait for a stuck query to finish. Dropping the
+            // connection alone doesn't stop it: the server only notices
+            // once it has results to send. SQLite has no cancel, so the
+            // query runs on to completion in its worker thread.
+            if let Some(cancel) = &cancel {
+                cancel_query::<DB>(cancel).await;
+            }
+            drop(conn);
+            Err(ProxyError::Timeout(format!(
+                "SQL query exceeded {timeout_secs}s timeout"
+            )))
+        }
+    }
+}
+
+/// Rows a statement changed. Each driver's query result has this as an
+/// inherent method only, so `fetch_rows` reaches it through this trait.
+trait RowsAffected {
+    fn rows_affected(&self) -> u64;
+}
 
-    pool.close().await;
+impl RowsAffected for PgQueryResult {
+    fn rows_affected(&self) -> u64 {
+        PgQueryResult::rows_affected(self)
+    }
+}
 
-    // Sanitize all string values in the results
-    let secrets = secret.secret_strings();
-    let sanitized_rows: Vec<Vec<serde_json::Value>> = result_rows
-        .into_iter()
-        .map(|row: Vec<serde_json::Value>| {
-            row.into_iter()
-                .map(|v| match v {
+impl RowsAffected for MySqlQueryResult {
+    fn rows_affected(&self) -> u64 {
+        MySqlQueryResult::rows_affected(self)
+    }
+}
+
+impl RowsAffected for SqliteQueryResult {
+    fn rows_affected(&self) -> u64 {
+        SqliteQueryResult::rows_affected(self)
+    }
+}
+
+/// Stream rows from the query, sanitizing each as it arrives, and count the
+/// rows it changed.
+async fn fetch_rows<DB>(
+    conn: &mut DB::Connection,
+    query: &str,
+    max_rows: usize,
+    secrets: &[String],
+    extract: fn(&DB::Row, usize) -> serde_json::Value,
+) -> Result<SqlQueryOutput, ProxyError>
+where
+    DB: Database,
+    DB::QueryResult: RowsAffected,
+    for<'q> DB::Arguments<'q>: IntoArguments<'q, DB>,
+    for<'c> &'c mut DB::Connection: Executor<'c, Database = DB>,
+{
+    let mut columns: Vec<String> = vec![];
+    let mut rows: Vec<Vec<serde_json::Value>> = vec![];
+    let mut rows_affected = 0;
+    let mut truncated = false;
+
+    let mut stream = Executor::fetch_many(conn, sqlx::query::<DB>(query));
+    while let Some(item) = stream
+        .try_next()
+        .await
+        .map_err(|e| sql_error(e, "SQL query failed", secrets))?
+    {
+        let row = match item {
+            Either::Left(result) => {
+                rows_affected += result.rows_affected();
+                continue;
+            }
+            Either::Right(row) => row,
+        };
+        if rows.len() == max_rows {
+            truncated = true;
+            break;
+        }
+        if columns.is_empty() {
+            columns = row.columns().iter().map(|c| c.name().to_string()).collect();
+        }
+        rows.push(
+            (0..columns.len())
+                .map(|i| match extract(&row, i) {
                     serde_json::Value::String(s) => {
-                        serde_json::Value::String(sanitizer::sanitize(&s, &secrets))
+                        serde_json::Value::String(sanitizer::sanitize(&s, secrets))
                     }
                     other => other,
                 })
-                .collect()
-        })
-        .collect();
+                .collect(),
+        );
+    }
 
     Ok(SqlQueryOutput {
         columns,
-        rows: sanitized_rows,
-        rows_affected: 0,
+        rows,
+        rows_affected,
+        truncated,
+        timing: Timing::default(),
     })
 }
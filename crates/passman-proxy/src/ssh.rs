@@ -1,20 +1,70 @@
 use passman_types::CredentialSecret;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
 
 use crate::sanitizer;
-use crate::ProxyError;
+use crate::{ProxyError, Timing};
 
 #[derive(Debug, Deserialize)]
 pub struct SshExecInput {
     pub command: String,
+    /// Bytes written to the remote process's stdin, followed by EOF.
+    pub stdin: Option<String>,
+    /// Allocate a pseudo-terminal before exec. The remote side then writes
+    /// stdout and stderr to the same terminal, so they arrive interleaved on stdout.
+    #[serde(default)]
+    pub pty: bool,
+    /// Terminal type requested with the PTY (defaults to `xterm`).
+    pub term: Option<String>,
+    /// Give up after this many seconds without output (defaults to 120).
+    pub inactivity_timeout_secs: Option<u64>,
+    /// Give up after this many seconds in total, even while output is
+    /// still arriving. No cap when unset.
+    pub max_total_secs: Option<u64>,
 }
 
+/// Inactivity timeout used when `SshExecInput` doesn't set one.
+const DEFAULT_INACTIVITY_SECS: u64 = 120;
+
 #[derive(Debug, Serialize)]
 pub struct SshExecOutput {
+    /// Exit status, `128 + signum` when killed by a known signal, or -1 if
+    /// the server reported neither.
     pub exit_code: i32,
     pub stdout: String,
     pub stderr: String,
+    /// Signal that killed the command, e.g. `SIGKILL`.
+    pub signal: Option<String>,
+    pub core_dumped: bool,
+    /// Server's explanation accompanying the signal, if any (sanitized).
+    pub signal_message: Option<String>,
+    pub timing: Timing,
+}
+
+/// Quote `arg` for a POSIX shell so it reaches the program as one literal
+/// word. Plain words are left as they are; anything else is single-quoted.
+pub fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@,+%".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Build a command line running `program` with `args`, quoting each so the
+/// remote shell does no expansion or word splitting on them.
+pub fn shell_command(program: &str, args: &[String]) -> String {
+    std::iter::once(program)
+        .chain(args.iter().map(String::as_str))
+        .map(shell_quote)
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 struct SshClientHandler;
@@ -32,12 +82,24 @@ impl russh::client::Handler for SshClientHandler {
     }
 }
 
-/// Execute an SSH command using the stored credential.
-pub async fn execute(
+/// How `connect` proves the user's identity to the server.
+enum SshAuth {
+    Key {
+        private_key: String,
+        passphrase: Option<String>,
+    },
+    Password(String),
+    /// Sign with the local SSH agent's key matching this OpenSSH public key.
+    Agent {
+        public_key: String,
+    },
+}
+
+/// Open an SSH session and authenticate using the stored credential.
+async fn connect(
     secret: &CredentialSecret,
-    input: &SshExecInput,
-) -> Result<SshExecOutput, ProxyError> {
-    let (username, host, port, key_data, passphrase) = match secret {
+) -> Result<russh::client::Handle<SshClientHandler>, ProxyError> {
+    let (username, host, port, auth) = match secret {
         CredentialSecret::SshKey {
             username,
             host,
@@ -48,20 +110,48 @@ pub async fn execute(
             username.clone(),
             host.clone(),
             *port,
-            Some(private_key.clone()),
-            passphrase.clone(),
+            SshAuth::Key {
+                private_key: private_key.clone(),
+                passphrase: passphrase.clone(),
+            },
         ),
         CredentialSecret::SshPassword {
             username,
             host,
             port,
             password,
-        } => (username.clone(), host.clone(), *port, None, Some(password.clone())),
+        } => (
+            username.clone(),
+            host.clone(),
+            *port,
+            SshAuth::Password(password.clone()),
+        ),
+        CredentialSecret::SshAgent {
+            username,
+            host,
+            port,
+            public_key,
+        } => (
+            username.clone(),
+            host.clone(),
+            *port,
+            SshAuth::Agent {
+                public_key: public_key.clone(),
+            },
+        ),
         CredentialSecret::Password {
-            username, password, url, ..
+            username,
+            password,
+            url,
+            ..
         } => {
             let host = url.as_deref().unwrap_or("localhost").to_string();
-            (username.clone(), host, 22, None, Some(password.clone()))
+            (
+                username.clone(),
+                host,
+                22,
+                SshAuth::Password(password.clone()),
+            )
         }
         _ => {
             return Err(ProxyError::InvalidInput(
@@ -75,40 +165,105 @@ pub async fn execute(
 
     let mut session = russh::client::connect(config, (host.as_str(), port), handler)
         .await
-        .map_err(|e| ProxyError::Protocol(format!("SSH connection failed: {e}")))?;
+        .map_err(connect_error)?;
 
     // Authenticate
-    if let Some(ref key_str) = key_data {
-        let key_pair = if let Some(ref pass) = passphrase {
-            russh_keys::decode_secret_key(key_str, Some(pass))
-                .map_err(|e| ProxyError::Protocol(format!("failed to decode SSH key: {e}")))?
-        } else {
-            russh_keys::decode_secret_key(key_str, None)
-                .map_err(|e| ProxyError::Protocol(format!("failed to decode SSH key: {e}")))?
-        };
-
-        let authenticated = session
-            .authenticate_publickey(&username, Arc::new(key_pair))
-            .await
-            .map_err(|e| ProxyError::Protocol(format!("SSH public key auth failed: {e}")))?;
-
-        if !authenticated {
-            return Err(ProxyError::Protocol(
-                "SSH authentication rejected".to_string(),
-            ));
+    let authenticated = match auth {
+        SshAuth::Key {
+            private_key,
+            passphrase,
+        } => {
+            let key_pair = russh_keys::decode_secret_key(&private_key, passphrase.as_deref())
+                .map_err(|e| ProxyError::Auth(format!("failed to decode SSH key: {e}")))?;
+            session
+                .authenticate_publickey(&username, Arc::new(key_pair))
+                .await
+                .map_err(|e| ProxyError::Auth(format!("SSH public key auth failed: {e}")))?
         }
-    } else if let Some(ref pass) = passphrase {
-        let authenticated = session
-            .authenticate_password(&username, pass)
+        SshAuth::Password(password) => session
+            .authenticate_password(&username, &password)
             .await
-            .map_err(|e| ProxyError::Protocol(format!("SSH password auth failed: {e}")))?;
+            .map_err(|e| ProxyError::Auth(format!("SSH password auth failed: {e}")))?,
+        SshAuth::Agent { public_key } => {
+            authenticate_with_agent(&mut session, &username, &public_key).await?
+        }
+    };
 
-        if !authenticated {
-            return Err(ProxyError::Protocol(
-                "SSH authentication rejected".to_string(),
-            ));
+    if !authenticated {
+        return Err(ProxyError::Auth("SSH authentication rejected".to_string()));
+    }
+
+    Ok(session)
+}
+
+/// Authenticate with the identity in the agent at `$SSH_AUTH_SOCK` whose
+/// public key matches `public_key`. The agent does the signing, so the
+/// private key never leaves it.
+#[cfg(unix)]
+async fn authenticate_with_agent(
+    session: &mut russh::client::Handle<SshClientHandler>,
+    username: &str,
+    public_key: &str,
+) -> Result<bool, ProxyError> {
+    use russh_keys::PublicKeyBase64;
+
+    let mut agent = russh_keys::agent::client::AgentClient::connect_env()
+        .await
+        .map_err(|e| ProxyError::Auth(format!("failed to connect to SSH agent: {e}")))?;
+    let identities = agent
+        .request_identities()
+        .await
+        .map_err(|e| ProxyError::Auth(format!("failed to list SSH agent keys: {e}")))?;
+
+    // Accept a full `type base64 comment` line or just the base64 blob
+    let wanted = public_key
+        .split_whitespace()
+        .find(|part| part.starts_with("AAAA"))
+        .unwrap_or(public_key.trim());
+    let key = identities
+        .into_iter()
+        .find(|key| key.public_key_base64() == wanted)
+        .ok_or_else(|| {
+            ProxyError::Auth(
+                "SSH agent has no key matching the credential's public key".to_string(),
+            )
+        })?;
+
+    let (_, result) = session.authenticate_future(username, key, agent).await;
+    result.map_err(|e| ProxyError::Auth(format!("SSH agent auth failed: {e}")))
+}
+
+#[cfg(not(unix))]
+async fn authenticate_with_agent(
+    _session: &mut russh::client::Handle<SshClientHandler>,
+    _username: &str,
+    _public_key: &str,
+) -> Result<bool, ProxyError> {
+    Err(ProxyError::InvalidInput(
+        "SSH agent authentication is only supported on Unix".to_string(),
+    ))
+}
+
+/// Classify a failure to establish the SSH session.
+fn connect_error(e: russh::Error) -> ProxyError {
+    match e {
+        russh::Error::ConnectionTimeout | russh::Error::InactivityTimeout => {
+            ProxyError::Timeout(format!("SSH connection timed out: {e}"))
         }
+        russh::Error::IO(ref io) if io.kind() == std::io::ErrorKind::TimedOut => {
+            ProxyError::Timeout(format!("SSH connection timed out: {e}"))
+        }
+        _ => ProxyError::Connection(format!("SSH connection failed: {e}")),
     }
+}
+
+/// Execute an SSH command using the stored credential.
+pub async fn execute(
+    secret: &CredentialSecret,
+    input: &SshExecInput,
+) -> Result<SshExecOutput, ProxyError> {
+    let start = Instant::now();
+    let session = connect(secret).await?;
 
     // Execute command
     let mut channel = session
@@ -116,63 +271,345 @@ pub async fn execute(
         .await
         .map_err(|e| ProxyError::Protocol(format!("failed to open SSH channel: {e}")))?;
 
+    if input.pty {
+        let term = input.term.as_deref().unwrap_or("xterm");
+        channel
+            .request_pty(false, term, 80, 24, 0, 0, &[])
+            .await
+            .map_err(|e| ProxyError::Protocol(format!("failed to request SSH PTY: {e}")))?;
+    }
+
     channel
         .exec(true, input.command.as_str())
         .await
         .map_err(|e| ProxyError::Protocol(format!("failed to exec SSH command: {e}")))?;
 
-    let mut stdout_buf = Vec::new();
-    let mut stderr_buf = Vec::new();
-    let mut exit_code: i32 = -1;
+    // Feed stdin, then always send EOF so blocking reads on the remote side return
+    if let Some(ref stdin) = input.stdin {
+        channel
+            .data(stdin.as_bytes())
+            .await
+            .map_err(|e| ProxyError::Protocol(format!("failed to write SSH stdin: {e}")))?;
+        channel
+            .eof()
+            .await
+            .map_err(|e| ProxyError::Protocol(format!("failed to send SSH EOF: {e}")))?;
+    }
 
-    // Inactivity timeout: resets every time we receive data.
-    // Commands that keep producing output can run indefinitely.
-    // Commands that go silent for 120s are considered hung.
-    let inactivity = std::time::Duration::from_secs(120);
-    let mut deadline = tokio::time::Instant::now() + inactivity;
+    let inactivity = Duration::from_secs(
+        input
+            .inactivity_timeout_secs
+            .unwrap_or(DEFAULT_INACTIVITY_SECS),
+    );
+    let max_total = input.max_total_secs.map(Duration::from_secs);
+    let output = read_output(&mut channel, inactivity, max_total).await;
+
+    session
+        .disconnect(russh::Disconnect::ByApplication, "", "en")
+        .await
+        .ok();
+
+    let secrets = secret.secret_strings();
+    let stdout = sanitizer::sanitize(&String::from_utf8_lossy(&output.stdout), &secrets);
+    let stderr = sanitizer::sanitize(&String::from_utf8_lossy(&output.stderr), &secrets);
+
+    let signal_message = output
+        .signal_message
+        .map(|message| sanitizer::sanitize(&message, &secrets));
+
+    Ok(SshExecOutput {
+        exit_code: output.exit_code,
+        stdout,
+        stderr,
+        signal: output.signal,
+        core_dumped: output.core_dumped,
+        signal_message,
+        timing: Timing::since(start.into_std()),
+    })
+}
+
+/// Something `read_output` can pull channel messages from: the SSH channel,
+/// or a fake one in tests.
+#[async_trait::async_trait]
+trait ChannelMessages: Send {
+    async fn next_message(&mut self) -> Option<russh::ChannelMsg>;
+}
+
+#[async_trait::async_trait]
+impl ChannelMessages for russh::Channel<russh::client::Msg> {
+    async fn next_message(&mut self) -> Option<russh::ChannelMsg> {
+        self.wait().await
+    }
+}
+
+/// Raw output collected from an exec channel.
+struct ChannelOutput {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    exit_code: i32,
+    signal: Option<String>,
+    core_dumped: bool,
+    signal_message: Option<String>,
+}
+
+/// Name and number of a signal reported in an `exit-signal` message.
+/// Signals outside the set RFC 4254 names have no portable number.
+fn signal_info(sig: &russh::Sig) -> (String, Option<i32>) {
+    let (name, number) = match sig {
+        russh::Sig::HUP => ("HUP", 1),
+        russh::Sig::INT => ("INT", 2),
+        russh::Sig::QUIT => ("QUIT", 3),
+        russh::Sig::ILL => ("ILL", 4),
+        russh::Sig::ABRT => ("ABRT", 6),
+        russh::Sig::FPE => ("FPE", 8),
+        russh::Sig::KILL => ("KILL", 9),
+        russh::Sig::USR1 => ("USR1", 10),
+        russh::Sig::SEGV => ("SEGV", 11),
+        russh::Sig::PIPE => ("PIPE", 13),
+        russh::Sig::ALRM => ("ALRM", 14),
+        russh::Sig::TERM => ("TERM", 15),
+        russh::Sig::Custom(name) => return (format!("SIG{name}"), None),
+    };
+    (format!("SIG{name}"), Some(number))
+}
+
+/// Collect output until the channel closes, it goes quiet for `inactivity`,
+/// or `max_total` has passed since the start. The inactivity deadline resets
+/// whenever data arrives, so commands that keep producing output run until
+/// the hard cap (if any). Either timeout is noted at the end of stderr.
+async fn read_output<C: ChannelMessages>(
+    channel: &mut C,
+    inactivity: Duration,
+    max_total: Option<Duration>,
+) -> ChannelOutput {
+    let mut output = ChannelOutput {
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+        exit_code: -1,
+        signal: None,
+        core_dumped: false,
+        signal_message: None,
+    };
+
+    let hard_deadline = max_total.map(|cap| Instant::now() + cap);
+    let mut deadline = Instant::now() + inactivity;
 
     loop {
-        let msg = tokio::time::timeout_at(deadline, channel.wait()).await;
-        match msg {
-            Ok(Some(msg)) => match msg {
-                russh::ChannelMsg::Data { ref data } => {
-                    stdout_buf.extend_from_slice(data);
-                    deadline = tokio::time::Instant::now() + inactivity;
-                }
-                russh::ChannelMsg::ExtendedData { ref data, ext } => {
-                    if ext == 1 {
-                        stderr_buf.extend_from_slice(data);
-                    }
-                    deadline = tokio::time::Instant::now() + inactivity;
+        let wake = hard_deadline.map_or(deadline, |hard| deadline.min(hard));
+        match tokio::time::timeout_at(wake, channel.next_message()).await {
+            Ok(Some(russh::ChannelMsg::Data { ref data })) => {
+                output.stdout.extend_from_slice(data);
+                deadline = Instant::now() + inactivity;
+            }
+            Ok(Some(russh::ChannelMsg::ExtendedData { ref data, ext })) => {
+                if ext == 1 {
+                    output.stderr.extend_from_slice(data);
                 }
-                russh::ChannelMsg::ExitStatus { exit_status } => {
-                    exit_code = exit_status as i32;
+                deadline = Instant::now() + inactivity;
+            }
+            Ok(Some(russh::ChannelMsg::ExitStatus { exit_status })) => {
+                output.exit_code = exit_status as i32;
+            }
+            Ok(Some(russh::ChannelMsg::ExitSignal {
+                signal_name,
+                core_dumped,
+                error_message,
+                ..
+            })) => {
+                let (name, number) = signal_info(&signal_name);
+                if let Some(number) = number {
+                    output.exit_code = 128 + number;
                 }
-                _ => {}
-            },
+                output.signal = Some(name);
+                output.core_dumped = core_dumped;
+                output.signal_message = Some(error_message).filter(|m| !m.is_empty());
+            }
+            Ok(Some(_)) => {}
             Ok(None) => break,
             Err(_) => {
-                // No data received for 120s - command is likely hung
-                stderr_buf.extend_from_slice(
-                    b"\n[passman: SSH command timed out - no output for 120s, output may be partial]",
-                );
+                let note = match (hard_deadline, max_total) {
+                    (Some(hard), Some(cap)) if Instant::now() >= hard => format!(
+                        "\n[passman: SSH command stopped after the {cap:?} limit, output may be partial]"
+                    ),
+                    _ => format!(
+                        "\n[passman: SSH command timed out - no output for {inactivity:?}, output may be partial]"
+                    ),
+                };
+                output.stderr.extend_from_slice(note.as_bytes());
                 break;
             }
         }
     }
 
+    output
+}
+
+/// Open a `direct-tcpip` channel through the SSH host to `remote_host:remote_port`,
+/// write `payload`, and return everything the remote service sends back until it
+/// closes the connection or goes quiet for 30s. The response is sanitized.
+pub async fn tunnel_request(
+    secret: &CredentialSecret,
+    remote_host: &str,
+    remote_port: u16,
+    payload: Vec<u8>,
+) -> Result<Vec<u8>, ProxyError> {
+    let session = connect(secret).await?;
+
+    let mut channel = session
+        .channel_open_direct_tcpip(remote_host, remote_port as u32, "127.0.0.1", 0)
+        .await
+        .map_err(|e| ProxyError::Protocol(format!("failed to open SSH tunnel: {e}")))?;
+
+    channel
+        .data(payload.as_slice())
+        .await
+        .map_err(|e| ProxyError::Protocol(format!("failed to write to SSH tunnel: {e}")))?;
+    // Half-close so one-shot services see the end of the request
+    channel
+        .eof()
+        .await
+        .map_err(|e| ProxyError::Protocol(format!("failed to send SSH EOF: {e}")))?;
+
+    let mut response = Vec::new();
+    let inactivity = std::time::Duration::from_secs(30);
+    let mut deadline = tokio::time::Instant::now() + inactivity;
+
+    loop {
+        match tokio::time::timeout_at(deadline, channel.wait()).await {
+            Ok(Some(russh::ChannelMsg::Data { ref data })) => {
+                response.extend_from_slice(data);
+                deadline = tokio::time::Instant::now() + inactivity;
+            }
+            Ok(Some(russh::ChannelMsg::Eof | russh::ChannelMsg::Close)) | Ok(None) => break,
+            Ok(Some(_)) => {}
+            Err(_) => break,
+        }
+    }
+
     session
         .disconnect(russh::Disconnect::ByApplication, "", "en")
         .await
         .ok();
 
-    let secrets = secret.secret_strings();
-    let stdout = sanitizer::sanitize(&String::from_utf8_lossy(&stdout_buf), &secrets);
-    let stderr = sanitizer::sanitize(&String::from_utf8_lossy(&stderr_buf), &secrets);
+    Ok(sanitizer::sanitize_bytes(
+        &response,
+        &secret.secret_strings(),
+    ))
+}
 
-    Ok(SshExecOutput {
-        exit_code,
-        stdout,
-        stderr,
-    })
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_command_quotes_args() {
+        let args = vec![
+            "hello world".to_string(),
+            "it's".to_string(),
+            "$(rm -rf /)".to_string(),
+            "`id`; echo \"hi\"".to_string(),
+            "".to_string(),
+            "--count=3".to_string(),
+        ];
+        assert_eq!(
+            shell_command("echo", &args),
+            r#"echo 'hello world' 'it'\''s' '$(rm -rf /)' '`id`; echo "hi"' '' --count=3"#
+        );
+    }
+
+    #[test]
+    fn test_shell_quote_leaves_plain_words() {
+        assert_eq!(shell_quote("/var/log/app.log"), "/var/log/app.log");
+        assert_eq!(shell_quote("user@host:22"), "user@host:22");
+        assert_eq!(shell_quote("*.log"), "'*.log'");
+        assert_eq!(shell_quote("a\nb"), "'a\nb'");
+    }
+
+    /// Fake channel fed by the test through an mpsc sender.
+    #[async_trait::async_trait]
+    impl ChannelMessages for tokio::sync::mpsc::Receiver<russh::ChannelMsg> {
+        async fn next_message(&mut self) -> Option<russh::ChannelMsg> {
+            self.recv().await
+        }
+    }
+
+    fn data(bytes: &[u8]) -> russh::ChannelMsg {
+        russh::ChannelMsg::Data {
+            data: russh::CryptoVec::from_slice(bytes),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_output_until_close() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        tx.send(data(b"done\n")).await.unwrap();
+        tx.send(russh::ChannelMsg::ExitStatus { exit_status: 3 })
+            .await
+            .unwrap();
+        drop(tx);
+
+        let output = read_output(&mut rx, Duration::from_secs(5), None).await;
+        assert_eq!(output.stdout, b"done\n");
+        assert_eq!(output.exit_code, 3);
+        assert!(output.stderr.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_read_output_exit_signal() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        tx.send(data(b"working")).await.unwrap();
+        tx.send(russh::ChannelMsg::ExitSignal {
+            signal_name: russh::Sig::KILL,
+            core_dumped: true,
+            error_message: "killed by the OOM killer".to_string(),
+            lang_tag: String::new(),
+        })
+        .await
+        .unwrap();
+        drop(tx);
+
+        let output = read_output(&mut rx, Duration::from_secs(5), None).await;
+        assert_eq!(output.exit_code, 137);
+        assert_eq!(output.signal.as_deref(), Some("SIGKILL"));
+        assert!(output.core_dumped);
+        assert_eq!(
+            output.signal_message.as_deref(),
+            Some("killed by the OOM killer")
+        );
+        assert_eq!(output.stdout, b"working");
+    }
+
+    #[tokio::test]
+    async fn test_read_output_inactivity_timeout() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        tx.send(data(b"partial")).await.unwrap();
+
+        // The sender stays open but nothing else arrives
+        let output = read_output(&mut rx, Duration::from_millis(50), None).await;
+        assert_eq!(output.stdout, b"partial");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("no output for 50ms"));
+        drop(tx);
+    }
+
+    #[tokio::test]
+    async fn test_read_output_hard_cap() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        // Keep producing output well inside the inactivity timeout
+        tokio::spawn(async move {
+            while tx.send(data(b".")).await.is_ok() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        });
+
+        let started = Instant::now();
+        let output = read_output(
+            &mut rx,
+            Duration::from_secs(5),
+            Some(Duration::from_millis(100)),
+        )
+        .await;
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!output.stdout.is_empty());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("after the 100ms limit"));
+    }
 }
@@ -0,0 +1,47 @@
+//! Integration test against a local MongoDB. Requires the `mongo` feature
+//! and a reachable server:
+//!
+//!   PASSMAN_TEST_MONGO_URI=mongodb://localhost:27017 \
+//!   cargo test -p passman-proxy --features mongo --test mongo -- --ignored
+
+#![cfg(feature = "mongo")]
+
+use mongodb::bson::doc;
+use passman_proxy::mongo::{self, MongoFindInput};
+use passman_types::CredentialSecret;
+
+#[tokio::test]
+#[ignore = "requires a local MongoDB server"]
+async fn test_filtered_find() {
+    let uri = std::env::var("PASSMAN_TEST_MONGO_URI").expect("PASSMAN_TEST_MONGO_URI must be set");
+
+    let client = mongodb::Client::with_uri_str(&uri).await.unwrap();
+    let collection = client
+        .database("passman_test")
+        .collection::<mongodb::bson::Document>("users");
+    collection.drop().await.ok();
+    collection
+        .insert_many([
+            doc! { "name": "alice", "active": true },
+            doc! { "name": "bob", "active": false },
+            doc! { "name": "carol", "active": true },
+        ])
+        .await
+        .unwrap();
+
+    let input = MongoFindInput {
+        database: "passman_test".into(),
+        collection: "users".into(),
+        filter: Some(serde_json::json!({ "active": true })),
+        limit: None,
+    };
+    let docs = mongo::find(&CredentialSecret::MongoDb { uri }, &input)
+        .await
+        .unwrap();
+
+    let mut names: Vec<&str> = docs.iter().filter_map(|d| d["name"].as_str()).collect();
+    names.sort();
+    assert_eq!(names, vec!["alice", "carol"]);
+
+    collection.drop().await.ok();
+}
@@ -0,0 +1,125 @@
+//! SMTP credential verification, against an in-process mock server speaking
+//! plain SMTP and, for the ignored tests, a real local server.
+//!
+//! Requires the `smtp-tests` feature. The ignored tests need an SMTP server
+//! that checks credentials (encryption is `none`, `start_tls` or `tls`):
+//!
+//!   PASSMAN_TEST_SMTP_HOST=127.0.0.1 PASSMAN_TEST_SMTP_PORT=1025 \
+//!   PASSMAN_TEST_SMTP_USER=test PASSMAN_TEST_SMTP_PASSWORD=test \
+//!   PASSMAN_TEST_SMTP_ENCRYPTION=none \
+//!   cargo test -p passman-proxy --features smtp-tests --test smtp -- --ignored
+
+#![cfg(feature = "smtp-tests")]
+
+use base64::Engine;
+use passman_proxy::smtp;
+use passman_proxy::ProxyError;
+use passman_types::{CredentialSecret, SmtpEncryption};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+fn smtp_secret(
+    host: &str,
+    port: u16,
+    password: &str,
+    encryption: SmtpEncryption,
+) -> CredentialSecret {
+    CredentialSecret::SmtpAccount {
+        host: host.to_string(),
+        port,
+        username: "mailer@example.com".to_string(),
+        password: password.to_string(),
+        encryption,
+    }
+}
+
+/// Serve one SMTP session that accepts `AUTH PLAIN` only for `password`,
+/// and report every command received.
+async fn mock_server(password: &'static str) -> (u16, tokio::task::JoinHandle<Vec<String>>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let handle = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (read, mut write) = stream.into_split();
+        let mut lines = BufReader::new(read).lines();
+        let mut commands = vec![];
+
+        write.write_all(b"220 mock ESMTP\r\n").await.unwrap();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let verb = line.split(' ').next().unwrap_or("").to_uppercase();
+            commands.push(verb.clone());
+            let reply: &[u8] = match verb.as_str() {
+                "EHLO" => b"250-mock\r\n250 AUTH PLAIN\r\n",
+                "AUTH" => {
+                    let encoded = line.rsplit(' ').next().unwrap_or("");
+                    let decoded = base64::engine::general_purpose::STANDARD
+                        .decode(encoded)
+                        .unwrap_or_default();
+                    if decoded == format!("\0mailer@example.com\0{password}").as_bytes() {
+                        b"235 2.7.0 Authentication successful\r\n"
+                    } else {
+                        b"535 5.7.8 Authentication credentials invalid\r\n"
+                    }
+                }
+                "NOOP" => b"250 OK\r\n",
+                "QUIT" => {
+                    write.write_all(b"221 Bye\r\n").await.unwrap();
+                    break;
+                }
+                _ => b"502 Command not implemented\r\n",
+            };
+            write.write_all(reply).await.unwrap();
+        }
+        commands
+    });
+    (port, handle)
+}
+
+#[tokio::test]
+async fn test_verify_with_mock_server() {
+    let (port, server) = mock_server("right-password").await;
+    let secret = smtp_secret("127.0.0.1", port, "right-password", SmtpEncryption::None);
+    smtp::verify(&secret).await.unwrap();
+
+    // Authenticated and quit without starting a message
+    let commands = server.await.unwrap();
+    assert!(commands.contains(&"AUTH".to_string()), "{commands:?}");
+    assert!(!commands.contains(&"MAIL".to_string()), "{commands:?}");
+    assert_eq!(commands.last().map(String::as_str), Some("QUIT"));
+}
+
+#[tokio::test]
+async fn test_verify_rejects_bad_password_with_mock_server() {
+    let (port, _server) = mock_server("right-password").await;
+    let secret = smtp_secret("127.0.0.1", port, "wrong-password", SmtpEncryption::None);
+    let err = smtp::verify(&secret).await.unwrap_err();
+    assert!(matches!(err, ProxyError::Auth(_)), "got {err:?}");
+}
+
+fn local_secret(password: &str) -> CredentialSecret {
+    let var = |k: &str| std::env::var(k).unwrap_or_else(|_| panic!("{k} must be set"));
+    let encryption = match var("PASSMAN_TEST_SMTP_ENCRYPTION").as_str() {
+        "none" => SmtpEncryption::None,
+        "start_tls" => SmtpEncryption::StartTls,
+        "tls" => SmtpEncryption::Tls,
+        other => panic!("unknown PASSMAN_TEST_SMTP_ENCRYPTION: {other}"),
+    };
+    CredentialSecret::SmtpAccount {
+        host: var("PASSMAN_TEST_SMTP_HOST"),
+        port: var("PASSMAN_TEST_SMTP_PORT").parse().unwrap(),
+        username: var("PASSMAN_TEST_SMTP_USER"),
+        password: password.to_string(),
+        encryption,
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires a local SMTP server"]
+async fn test_verify_with_local_server() {
+    let password = std::env::var("PASSMAN_TEST_SMTP_PASSWORD").unwrap();
+    smtp::verify(&local_secret(&password)).await.unwrap();
+
+    let err = smtp::verify(&local_secret("definitely-not-the-password"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ProxyError::Auth(_)), "got {err:?}");
+}
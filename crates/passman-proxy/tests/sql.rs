@@ -0,0 +1,422 @@
+//! Integration tests against a temporary SQLite database, plus ignored
+//! tests for Postgres and MySQL. To run those, point the environment at a
+//! reachable server (prefix `PASSMAN_TEST_POSTGRES` or `PASSMAN_TEST_MYSQL`):
+//!
+//!   PASSMAN_TEST_POSTGRES_HOST=127.0.0.1 PASSMAN_TEST_POSTGRES_PORT=5432 \
+//!   PASSMAN_TEST_POSTGRES_DB=postgres PASSMAN_TEST_POSTGRES_USER=postgres \
+//!   PASSMAN_TEST_POSTGRES_PASSWORD=postgres \
+//!   cargo test -p passman-proxy --test sql -- --ignored
+
+use passman_proxy::sql::{self, SqlQueryInput};
+use passman_types::{CredentialSecret, DbDriver};
+use tempfile::TempDir;
+
+fn sqlite_secret(dir: &TempDir) -> CredentialSecret {
+    CredentialSecret::DatabaseConnection {
+        driver: DbDriver::Sqlite,
+        host: String::new(),
+        port: 0,
+        database: format!("{}?mode=rwc", dir.path().join("test.db").display()),
+        username: String::new(),
+        password: String::new(),
+        params: Default::default(),
+    }
+}
+
+fn server_secret(driver: DbDriver, prefix: &str) -> CredentialSecret {
+    let var = |k: &str| {
+        let key = format!("{prefix}_{k}");
+        std::env::var(&key).unwrap_or_else(|_| panic!("{key} must be set"))
+    };
+    CredentialSecret::DatabaseConnection {
+        driver,
+        host: var("HOST"),
+        port: var("PORT").parse().unwrap(),
+        database: var("DB"),
+        username: var("USER"),
+        password: var("PASSWORD"),
+        params: Default::default(),
+    }
+}
+
+fn query(sql: &str) -> SqlQueryInput {
+    SqlQueryInput {
+        query: sql.to_string(),
+        params: None,
+        max_rows: None,
+        timeout_secs: None,
+        read_only: false,
+        multi: false,
+    }
+}
+
+#[tokio::test]
+async fn test_max_rows_truncates() {
+    let dir = TempDir::new().unwrap();
+    let secret = sqlite_secret(&dir);
+
+    sql::execute(&secret, &query("CREATE TABLE numbers (n INTEGER)"))
+        .await
+        .unwrap();
+    sql::execute(
+        &secret,
+        &query(
+            "WITH RECURSIVE seq(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM seq WHERE n < 5000) \
+             INSERT INTO numbers SELECT n FROM seq",
+        ),
+    )
+    .await
+    .unwrap();
+
+    let output = sql::execute(
+        &secret,
+        &SqlQueryInput {
+            max_rows: Some(250),
+            ..query("SELECT n FROM numbers ORDER BY n")
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(output.rows.len(), 250);
+    assert!(output.truncated);
+    assert_eq!(output.columns, vec!["n"]);
+
+    let output = sql::execute(&secret, &query("SELECT COUNT(*) AS c FROM numbers"))
+        .await
+        .unwrap();
+    assert!(!output.truncated);
+    assert_eq!(output.rows[0][0], serde_json::json!(5000));
+}
+
+#[tokio::test]
+async fn test_output_has_timing() {
+    let dir = TempDir::new().unwrap();
+    let output = sql::execute(&sqlite_secret(&dir), &query("SELECT 1 AS one"))
+        .await
+        .unwrap();
+
+    let json = serde_json::to_value(&output).unwrap();
+    assert!(json["timing"]["total_ms"].as_u64().is_some());
+    assert!(json["timing"].get("first_byte_ms").is_none());
+}
+
+#[tokio::test]
+async fn test_connection_error_hides_password() {
+    // Bind then drop to get a port nothing is listening on
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    drop(listener);
+
+    let password = "Tr0ub4dor&3-leak";
+    let secret = CredentialSecret::DatabaseConnection {
+        driver: DbDriver::Postgres,
+        host: "127.0.0.1".to_string(),
+        port,
+        database: "app".to_string(),
+        username: "app".to_string(),
+        password: password.to_string(),
+        params: Default::default(),
+    };
+
+    let err = sql::execute(&secret, &query("SELECT 1")).await.unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.starts_with("connection failed: SQL connection failed"),
+        "{message}"
+    );
+    assert!(!message.contains(password), "{message}");
+    assert!(
+        !message.contains(&*urlencoding::encode(password)),
+        "{message}"
+    );
+}
+
+#[tokio::test]
+async fn test_slow_query_times_out() {
+    let dir = TempDir::new().unwrap();
+    let secret = sqlite_secret(&dir);
+
+    // Counting a very long recursive sequence keeps SQLite busy for many seconds
+    let started = std::time::Instant::now();
+    let err = sql::execute(
+        &secret,
+        &SqlQueryInput {
+            timeout_secs: Some(1),
+            ..query(
+                "WITH RECURSIVE seq(n) AS (SELECT 1 UNION ALL SELECT n + 1 FROM seq LIMIT 500000000) \
+                 SELECT COUNT(*) FROM seq",
+            )
+        },
+    )
+    .await
+    .unwrap_err();
+
+    assert!(
+        matches!(err, passman_proxy::ProxyError::Timeout(_)),
+        "got {err:?}"
+    );
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_sqlite_typed_columns() {
+    let dir = TempDir::new().unwrap();
+    let secret = sqlite_secret(&dir);
+
+    let output = sql::execute(
+        &secret,
+        &query("SELECT datetime('2024-01-02 03:04:05') AS ts, json_object('a', 1) AS doc, x'cafe' AS raw"),
+    )
+    .await
+    .unwrap();
+
+    // SQLite has no native timestamp or JSON types; both come back as text
+    assert_eq!(output.rows[0][0], serde_json::json!("2024-01-02 03:04:05"));
+    assert_eq!(output.rows[0][1], serde_json::json!("{\"a\":1}"));
+    assert_eq!(output.rows[0][2], serde_json::json!("yv4="));
+}
+
+#[tokio::test]
+#[ignore = "requires a local Postgres server"]
+async fn test_postgres_typed_columns() {
+    let secret = server_secret(DbDriver::Postgres, "PASSMAN_TEST_POSTGRES");
+
+    let output = sql::execute(
+        &secret,
+        &query(
+            "SELECT TIMESTAMPTZ '2024-01-02 03:04:05+00' AS ts, '{\"a\": 1}'::jsonb AS doc, \
+             '6f1c2a7e-0000-4000-8000-000000000001'::uuid AS id",
+        ),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        output.rows[0][0],
+        serde_json::json!("2024-01-02T03:04:05+00:00")
+    );
+    assert_eq!(output.rows[0][1], serde_json::json!({ "a": 1 }));
+    assert_eq!(
+        output.rows[0][2],
+        serde_json::json!("6f1c2a7e-0000-4000-8000-000000000001")
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires a local MySQL server"]
+async fn test_mysql_typed_columns() {
+    let secret = server_secret(DbDriver::Mysql, "PASSMAN_TEST_MYSQL");
+
+    let output = sql::execute(
+        &secret,
+        &query("SELECT CAST('2024-01-02 03:04:05' AS DATETIME) AS ts, JSON_OBJECT('a', 1) AS doc"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        output.rows[0][0],
+        serde_json::json!("2024-01-02T03:04:05+00:00")
+    );
+    assert_eq!(output.rows[0][1], serde_json::json!({ "a": 1 }));
+}
+
+#[tokio::test]
+#[ignore = "requires a local Postgres server"]
+async fn test_postgres_timeout_cancels_query() {
+    let secret = server_secret(DbDriver::Postgres, "PASSMAN_TEST_POSTGRES");
+
+    let err = sql::execute(
+        &secret,
+        &SqlQueryInput {
+            timeout_secs: Some(1),
+            ..query("SELECT pg_sleep(60) AS passman_cancel_probe")
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        matches!(err, passman_proxy::ProxyError::Timeout(_)),
+        "got {err:?}"
+    );
+
+    // The server stopped the sleep rather than leaving it running
+    let output = sql::execute(
+        &secret,
+        &query(
+            "SELECT COUNT(*) FROM pg_stat_activity \
+             WHERE state = 'active' AND query LIKE '%passman_cancel_probe%' \
+             AND pid <> pg_backend_pid()",
+        ),
+    )
+    .await
+    .unwrap();
+    assert_eq!(output.rows[0][0], serde_json::json!(0));
+}
+
+#[tokio::test]
+async fn test_sqlite_null_is_null() {
+    let dir = TempDir::new().unwrap();
+    let secret = sqlite_secret(&dir);
+
+    let output = sql::execute(&secret, &query("SELECT NULL AS empty, 1 AS one"))
+        .await
+        .unwrap();
+    assert_eq!(output.rows[0][0], serde_json::Value::Null);
+    assert_eq!(output.rows[0][1], serde_json::json!(1));
+}
+
+#[tokio::test]
+#[ignore = "requires a local Postgres server"]
+async fn test_postgres_null_vs_unsupported() {
+    let secret = server_secret(DbDriver::Postgres, "PASSMAN_TEST_POSTGRES");
+
+    let output = sql::execute(
+        &secret,
+        &query("SELECT NULL::text AS nothing, POINT(1, 2) AS location"),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(output.rows[0][0], serde_json::Value::Null);
+    assert_eq!(
+        output.rows[0][1],
+        serde_json::json!({ "_unsupported_type": "POINT" })
+    );
+}
+
+#[tokio::test]
+async fn test_sqlite_read_only_rejects_writes() {
+    let dir = TempDir::new().unwrap();
+    let secret = sqlite_secret(&dir);
+    let read_only = |sql: &str| SqlQueryInput {
+        read_only: true,
+        ..query(sql)
+    };
+    sql::execute(&secret, &query("CREATE TABLE t (n INTEGER)"))
+        .await
+        .unwrap();
+
+    let err = sql::execute(&secret, &read_only("INSERT INTO t VALUES (1)"))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("readonly"), "{err}");
+
+    let output = sql::execute(&secret, &read_only("SELECT count(*) FROM t"))
+        .await
+        .unwrap();
+    assert_eq!(output.rows[0][0], serde_json::json!(0));
+
+    // Writes without the flag are unaffected
+    sql::execute(&secret, &query("INSERT INTO t VALUES (1)"))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[ignore = "requires a local Postgres server"]
+async fn test_postgres_read_only_rejects_writes() {
+    let secret = server_secret(DbDriver::Postgres, "PASSMAN_TEST_POSTGRES");
+    let read_only = |sql: &str| SqlQueryInput {
+        read_only: true,
+        ..query(sql)
+    };
+
+    for setup in [
+        "CREATE TABLE IF NOT EXISTS passman_read_only_test (n INT)",
+        "CREATE OR REPLACE FUNCTION passman_read_only_write() RETURNS INT LANGUAGE sql \
+         AS 'INSERT INTO passman_read_only_test VALUES (1) RETURNING n'",
+    ] {
+        sql::execute(&secret, &query(setup)).await.unwrap();
+    }
+
+    // A SELECT that writes through a function is refused by the server
+    let err = sql::execute(&secret, &read_only("SELECT passman_read_only_write()"))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("read-only transaction"), "{err}");
+
+    let output = sql::execute(
+        &secret,
+        &read_only("SELECT count(*) FROM passman_read_only_test"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(output.rows[0][0], serde_json::json!(0));
+
+    for cleanup in [
+        "DROP FUNCTION passman_read_only_write()",
+        "DROP TABLE passman_read_only_test",
+    ] {
+        sql::execute(&secret, &query(cleanup)).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_multi_statement_script() {
+    let dir = TempDir::new().unwrap();
+    let secret = sqlite_secret(&dir);
+    sql::execute(&secret, &query("CREATE TABLE notes (body TEXT)"))
+        .await
+        .unwrap();
+
+    let script = "INSERT INTO notes VALUES ('a; b'), ('c'); -- two rows\n\
+                  DELETE FROM notes WHERE body = 'c';\n\
+                  SELECT body FROM notes;";
+    let outputs = sql::execute_multi(
+        &secret,
+        &SqlQueryInput {
+            multi: true,
+            ..query(script)
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(outputs.len(), 3);
+    assert_eq!(outputs[0].rows_affected, 2);
+    assert_eq!(outputs[1].rows_affected, 1);
+    assert!(outputs[0].rows.is_empty());
+    assert_eq!(outputs[2].columns, vec!["body"]);
+    assert_eq!(outputs[2].rows, vec![vec![serde_json::json!("a; b")]]);
+
+    // Without the flag the same script is refused and nothing runs
+    for err in [
+        sql::execute(&secret, &query(script)).await.unwrap_err(),
+        sql::execute_multi(&secret, &query(script))
+            .await
+            .unwrap_err(),
+    ] {
+        assert!(
+            matches!(err, passman_proxy::ProxyError::InvalidInput(_)),
+            "got {err:?}"
+        );
+    }
+
+    // A single query is unaffected, trailing semicolon included
+    let output = sql::execute(&secret, &query("SELECT count(*) AS n FROM notes;"))
+        .await
+        .unwrap();
+    assert_eq!(output.rows, vec![vec![serde_json::json!(1)]]);
+}
+
+#[test]
+fn test_split_statements() {
+    assert_eq!(
+        sql::split_statements("SELECT 1; SELECT 2;\n"),
+        vec!["SELECT 1", "SELECT 2"]
+    );
+    // Semicolons inside literals, identifiers, comments and bodies don't split
+    assert_eq!(
+        sql::split_statements(
+            "SELECT 'it''s; fine', \"a;b\" /* ; */ FROM t -- ;\n; \
+             CREATE FUNCTION f() RETURNS int AS $body$ SELECT 1; $body$ LANGUAGE sql"
+        )
+        .len(),
+        2
+    );
+    // Comment-only and empty statements are dropped; $1 isn't a dollar quote
+    assert_eq!(
+        sql::split_statements("-- nothing;\n;; SELECT $1;"),
+        vec!["SELECT $1"]
+    );
+}
@@ -0,0 +1,84 @@
+//! Integration tests against a local SSH server.
+//!
+//! These are ignored by default. To run them, point the environment at a
+//! reachable sshd that accepts password auth:
+//!
+//!   PASSMAN_TEST_SSH_HOST=127.0.0.1 PASSMAN_TEST_SSH_PORT=2222 \
+//!   PASSMAN_TEST_SSH_USER=test PASSMAN_TEST_SSH_PASSWORD=test \
+//!   cargo test -p passman-proxy --test ssh -- --ignored
+
+use passman_proxy::ssh::{self, SshExecInput};
+use passman_types::CredentialSecret;
+
+fn local_secret() -> CredentialSecret {
+    let var = |k: &str| std::env::var(k).unwrap_or_else(|_| panic!("{k} must be set"));
+    CredentialSecret::SshPassword {
+        username: var("PASSMAN_TEST_SSH_USER"),
+        host: var("PASSMAN_TEST_SSH_HOST"),
+        port: std::env::var("PASSMAN_TEST_SSH_PORT")
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(22),
+        password: var("PASSMAN_TEST_SSH_PASSWORD"),
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires a local SSH server"]
+async fn test_stdin_is_echoed() {
+    let input = SshExecInput {
+        command: "cat".into(),
+        stdin: Some("hello from stdin\n".into()),
+        pty: false,
+        term: None,
+        inactivity_timeout_secs: None,
+        max_total_secs: None,
+    };
+
+    let output = ssh::execute(&local_secret(), &input).await.unwrap();
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "hello from stdin\n");
+    let json = serde_json::to_value(&output).unwrap();
+    assert!(json["timing"]["total_ms"].as_u64().is_some());
+}
+
+#[tokio::test]
+#[ignore = "requires a local SSH server"]
+async fn test_pty_allocation() {
+    let mut input = SshExecInput {
+        command: "tty".into(),
+        stdin: None,
+        pty: true,
+        term: None,
+        inactivity_timeout_secs: None,
+        max_total_secs: None,
+    };
+
+    let with_pty = ssh::execute(&local_secret(), &input).await.unwrap();
+    assert_eq!(with_pty.exit_code, 0);
+    assert!(with_pty.stdout.trim().starts_with("/dev/"));
+
+    input.pty = false;
+    let without_pty = ssh::execute(&local_secret(), &input).await.unwrap();
+    assert!(without_pty.stdout.contains("not a tty"));
+}
+
+#[tokio::test]
+#[ignore = "requires a local SSH server"]
+async fn test_tunnel_to_echo_server() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = Vec::new();
+        socket.read_to_end(&mut buf).await.unwrap();
+        socket.write_all(&buf).await.unwrap();
+    });
+
+    let response = ssh::tunnel_request(&local_secret(), "127.0.0.1", port, b"ping".to_vec())
+        .await
+        .unwrap();
+    assert_eq!(response, b"ping");
+}
@@ -0,0 +1,79 @@
+//! Agent-based SSH authentication against a local SSH server, with the key
+//! held by an in-process mock agent instead of the user's own.
+//!
+//! Requires the `ssh-agent-tests` feature and an sshd that authorizes the
+//! key at `PASSMAN_TEST_SSH_KEY` (an unencrypted OpenSSH private key):
+//!
+//!   PASSMAN_TEST_SSH_HOST=127.0.0.1 PASSMAN_TEST_SSH_PORT=2222 \
+//!   PASSMAN_TEST_SSH_USER=test PASSMAN_TEST_SSH_KEY=/path/to/id_ed25519 \
+//!   cargo test -p passman-proxy --features ssh-agent-tests --test ssh_agent -- --ignored
+
+#![cfg(all(unix, feature = "ssh-agent-tests"))]
+
+use passman_proxy::ssh::{self, SshExecInput};
+use passman_proxy::ProxyError;
+use passman_types::CredentialSecret;
+use russh_keys::agent::client::AgentClient;
+use russh_keys::PublicKeyBase64;
+
+fn var(k: &str) -> String {
+    std::env::var(k).unwrap_or_else(|_| panic!("{k} must be set"))
+}
+
+fn agent_secret(public_key: String) -> CredentialSecret {
+    CredentialSecret::SshAgent {
+        username: var("PASSMAN_TEST_SSH_USER"),
+        host: var("PASSMAN_TEST_SSH_HOST"),
+        port: std::env::var("PASSMAN_TEST_SSH_PORT")
+            .ok()
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(22),
+        public_key,
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires a local SSH server"]
+async fn test_authenticates_via_mock_agent() {
+    let dir = tempfile::tempdir().unwrap();
+    let socket = dir.path().join("agent.sock");
+
+    // Serve the russh agent implementation on a private socket and load the key into it
+    let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+    tokio::spawn(russh_keys::agent::server::serve(
+        tokio_stream::wrappers::UnixListenerStream::new(listener),
+        (),
+    ));
+    let key = russh_keys::load_secret_key(var("PASSMAN_TEST_SSH_KEY"), None).unwrap();
+    let public = key.clone_public_key().unwrap();
+    let mut agent = AgentClient::connect_uds(&socket).await.unwrap();
+    agent.add_identity(&key, &[]).await.unwrap();
+
+    // Only this test touches the variable, so setting it process-wide is safe
+    std::env::set_var("SSH_AUTH_SOCK", &socket);
+
+    let input = SshExecInput {
+        command: "echo agent-ok".into(),
+        stdin: None,
+        pty: false,
+        term: None,
+        inactivity_timeout_secs: None,
+        max_total_secs: None,
+    };
+    let line = format!(
+        "{} {} test@passman",
+        public.name(),
+        public.public_key_base64()
+    );
+    let output = ssh::execute(&agent_secret(line), &input).await.unwrap();
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout, "agent-ok\n");
+
+    // A public key the agent doesn't hold is refused before anything is signed
+    let other =
+        "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIOtherKeyNotInTheAgentxxxxxxxxxxxxxxxxxxx".to_string();
+    let err = ssh::execute(&agent_secret(other), &input)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ProxyError::Auth(ref m) if m.contains("no key matching")));
+}
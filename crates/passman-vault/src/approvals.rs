@@ -0,0 +1,249 @@
+//! Pending approval queue for policy-gated proxy operations.
+//!
+//! The MCP server enqueues operations and the GUI decides on them. Since
+//! those are separate processes, the queue lives in a small JSON file next
+//! to the vault. Each entry carries an HMAC keyed from the vault key
+//! (see [`crypto::approval_mac_key`]); entries that fail verification are
+//! treated as absent, so editing the file can't approve anything. Expired
+//! and tampered entries are dropped whenever the file is touched.
+
+use chrono::Utc;
+use fd_lock::RwLock;
+use passman_types::{ApprovalStatus, PendingApproval};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::{crypto, VaultError};
+
+/// Approvals file path for a given vault file (same directory).
+pub fn approvals_path(vault_path: &Path) -> PathBuf {
+    vault_path.with_file_name("approvals.json")
+}
+
+/// Hex SHA-256 of a request. `serde_json::Value` keeps object keys sorted,
+/// so equal requests hash the same whatever order their fields arrived in.
+pub fn request_hash(request: &serde_json::Value) -> String {
+    let bytes = serde_json::to_vec(request).unwrap_or_default();
+    hex::encode(Sha256::digest(bytes))
+}
+
+fn sign(key: &[u8; 32], approval: &mut PendingApproval) {
+    approval.mac = hex::encode(crypto::sign(key, &approval.signed_bytes()));
+}
+
+fn verify(key: &[u8; 32], approval: &PendingApproval) -> bool {
+    hex::decode(&approval.mac)
+        .is_ok_and(|tag| crypto::verify_signature(key, &approval.signed_bytes(), &tag))
+}
+
+/// Run `f` over the non-expired, authentic approvals under an exclusive
+/// lock, writing back the result with fresh MACs.
+fn with_approvals<T>(
+    path: &Path,
+    key: &[u8; 32],
+    f: impl FnOnce(&mut Vec<PendingApproval>) -> T,
+) -> Result<T, VaultError> {
+    crate::storage::ensure_vault_dir(path)?;
+
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(|e| VaultError::Io(format!("failed to open approvals file: {e}")))?;
+
+    let mut lock = RwLock::new(file);
+    let mut guard = lock
+        .write()
+        .map_err(|e| VaultError::Io(format!("failed to acquire approvals lock: {e}")))?;
+
+    let mut contents = String::new();
+    guard
+        .read_to_string(&mut contents)
+        .map_err(|e| VaultError::Io(format!("failed to read approvals file: {e}")))?;
+
+    let mut approvals: Vec<PendingApproval> = if contents.trim().is_empty() {
+        vec![]
+    } else {
+        serde_json::from_str(&contents)
+            .map_err(|e| VaultError::Io(format!("failed to parse approvals file: {e}")))?
+    };
+
+    let now = Utc::now();
+    approvals.retain(|a| a.expires_at > now);
+    approvals.retain(|a| {
+        let valid = verify(key, a);
+        if !valid {
+            tracing::warn!("Dropping approval {} with an invalid MAC", a.id);
+        }
+        valid
+    });
+
+    let result = f(&mut approvals);
+    for approval in approvals.iter_mut() {
+        sign(key, approval);
+    }
+
+    let json = serde_json::to_string_pretty(&approvals)
+        .map_err(|e| VaultError::Io(format!("failed to serialize approvals: {e}")))?;
+    guard
+        .set_len(0)
+        .and_then(|_| guard.seek(SeekFrom::Start(0)))
+        .and_then(|_| guard.write_all(json.as_bytes()))
+        .map_err(|e| VaultError::Io(format!("failed to write approvals file: {e}")))?;
+
+    Ok(result)
+}
+
+/// Add a new pending approval.
+pub fn enqueue(path: &Path, key: &[u8; 32], approval: PendingApproval) -> Result<(), VaultError> {
+    with_approvals(path, key, |approvals| approvals.push(approval))
+}
+
+/// List all non-expired approvals, oldest first.
+pub fn list(path: &Path, key: &[u8; 32]) -> Result<Vec<PendingApproval>, VaultError> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    with_approvals(path, key, |approvals| approvals.clone())
+}
+
+/// Look up a single approval by ID.
+pub fn get(path: &Path, key: &[u8; 32], id: Uuid) -> Result<Option<PendingApproval>, VaultError> {
+    Ok(list(path, key)?.into_iter().find(|a| a.id == id))
+}
+
+/// Record a decision on a pending approval. Returns the updated entry, or
+/// `None` if it doesn't exist, has expired, or was already decided.
+pub fn decide(
+    path: &Path,
+    key: &[u8; 32],
+    id: Uuid,
+    status: ApprovalStatus,
+) -> Result<Option<PendingApproval>, VaultError> {
+    with_approvals(path, key, |approvals| {
+        let approval = approvals
+            .iter_mut()
+            .find(|a| a.id == id && a.status == ApprovalStatus::Pending)?;
+        approval.status = status;
+        Some(approval.clone())
+    })
+}
+
+/// Remove an approved entry so it can only release one execution.
+/// Returns the entry if it was approved.
+pub fn consume(
+    path: &Path,
+    key: &[u8; 32],
+    id: Uuid,
+) -> Result<Option<PendingApproval>, VaultError> {
+    with_approvals(path, key, |approvals| {
+        let idx = approvals
+            .iter()
+            .position(|a| a.id == id && a.status == ApprovalStatus::Approved)?;
+        Some(approvals.remove(idx))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_approval(ttl_secs: i64) -> PendingApproval {
+        let now = Utc::now();
+        PendingApproval {
+            id: Uuid::new_v4(),
+            credential_id: Uuid::new_v4(),
+            credential_name: None,
+            tool: "sql_query".to_string(),
+            summary: "DELETE FROM users".to_string(),
+            request: serde_json::json!({ "query": "DELETE FROM users" }),
+            request_hash: String::new(),
+            status: ApprovalStatus::Pending,
+            created_at: now,
+            expires_at: now + chrono::Duration::seconds(ttl_secs),
+            mac: String::new(),
+        }
+    }
+
+    const KEY: [u8; 32] = [9u8; 32];
+
+    #[test]
+    fn test_decide_and_consume() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("approvals.json");
+
+        let approval = test_approval(300);
+        let id = approval.id;
+        enqueue(&path, &KEY, approval).unwrap();
+        assert_eq!(list(&path, &KEY).unwrap().len(), 1);
+
+        // Not approved yet, so nothing to consume
+        assert!(consume(&path, &KEY, id).unwrap().is_none());
+
+        let decided = decide(&path, &KEY, id, ApprovalStatus::Approved)
+            .unwrap()
+            .unwrap();
+        assert_eq!(decided.status, ApprovalStatus::Approved);
+        // Already decided
+        assert!(decide(&path, &KEY, id, ApprovalStatus::Denied)
+            .unwrap()
+            .is_none());
+
+        assert!(consume(&path, &KEY, id).unwrap().is_some());
+        assert!(consume(&path, &KEY, id).unwrap().is_none());
+        assert!(list(&path, &KEY).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_expired_entries_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("approvals.json");
+
+        enqueue(&path, &KEY, test_approval(-1)).unwrap();
+        enqueue(&path, &KEY, test_approval(300)).unwrap();
+        assert_eq!(list(&path, &KEY).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_edited_entry_is_not_approved() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("approvals.json");
+
+        let approval = test_approval(300);
+        let id = approval.id;
+        enqueue(&path, &KEY, approval).unwrap();
+
+        // Another local process flips the status without the vault key
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::write(&path, contents.replace("\"pending\"", "\"approved\"")).unwrap();
+
+        assert!(get(&path, &KEY, id).unwrap().is_none());
+        assert!(consume(&path, &KEY, id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_wrong_key_sees_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("approvals.json");
+
+        enqueue(&path, &KEY, test_approval(300)).unwrap();
+        assert!(list(&path, &[1u8; 32]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_request_hash_ignores_key_order() {
+        let a = serde_json::json!({ "query": "SELECT 1", "params": [1] });
+        let b: serde_json::Value =
+            serde_json::from_str(r#"{"params":[1],"query":"SELECT 1"}"#).unwrap();
+        assert_eq!(request_hash(&a), request_hash(&b));
+        assert_ne!(
+            request_hash(&a),
+            request_hash(&serde_json::json!({ "query": "SELECT 1", "params": [2] }))
+        );
+    }
+}
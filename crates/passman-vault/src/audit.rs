@@ -1,38 +1,99 @@
-use passman_types::AuditEntry;
+use chrono::{DateTime, Utc};
+use fd_lock::RwLock;
+use passman_types::{ActionCount, AuditAction, AuditEntry, AuditSummary, CredentialUsage};
+use std::collections::HashMap;
 use std::fs::{self, OpenOptions};
-use std::io::Write;
+use std::io::{BufRead, BufReader, Write};
 use std::path::Path;
+use uuid::Uuid;
 
-use crate::VaultError;
+use crate::{storage, VaultError};
+
+/// Which entries `read_entries` returns. Unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub credential_id: Option<Uuid>,
+    pub action: Option<AuditAction>,
+    pub tool: Option<String>,
+    pub success: Option<bool>,
+    /// Only entries at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Only entries at or before this time.
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl AuditFilter {
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        self.credential_id
+            .is_none_or(|id| entry.credential_id == Some(id))
+            && self.action.as_ref().is_none_or(|a| entry.action == *a)
+            && self.tool.as_ref().is_none_or(|t| entry.tool == *t)
+            && self.success.is_none_or(|s| entry.success == s)
+            && self.since.is_none_or(|s| entry.timestamp >= s)
+            && self.until.is_none_or(|u| entry.timestamp <= u)
+    }
+
+    /// Strings a line must contain to possibly match, so most non-matching
+    /// lines are skipped without being deserialized.
+    fn needles(&self) -> Vec<String> {
+        let mut needles = vec![];
+        if let Some(id) = self.credential_id {
+            needles.push(id.to_string());
+        }
+        if let Some(action) = &self.action {
+            needles.extend(serde_json::to_string(action).ok());
+        }
+        if let Some(tool) = &self.tool {
+            needles.extend(serde_json::to_string(tool).ok());
+        }
+        if let Some(success) = self.success {
+            needles.push(success.to_string());
+        }
+        needles
+    }
+}
 
 /// Append an audit entry to the JSONL audit log file.
 pub fn append_entry(path: &Path, entry: &AuditEntry) -> Result<(), VaultError> {
+    append_entries(path, std::slice::from_ref(entry))
+}
+
+/// Append several audit entries with a single write.
+pub fn append_entries(path: &Path, entries: &[AuditEntry]) -> Result<(), VaultError> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)
             .map_err(|e| VaultError::Io(format!("failed to create audit dir: {e}")))?;
     }
 
+    let mut lock = log_lock(path)?;
+    let _guard = lock
+        .write()
+        .map_err(|e| VaultError::Io(format!("failed to acquire audit log lock: {e}")))?;
     let mut file = OpenOptions::new()
         .create(true)
         .append(true)
         .open(path)
         .map_err(|e| VaultError::Io(format!("failed to open audit log: {e}")))?;
 
-    let line = serde_json::to_string(entry)
-        .map_err(|e| VaultError::Io(format!("failed to serialize audit entry: {e}")))?;
+    let mut lines = String::new();
+    for entry in entries {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| VaultError::Io(format!("failed to serialize audit entry: {e}")))?;
+        lines.push_str(&line);
+        lines.push('\n');
+    }
 
-    writeln!(file, "{line}")
+    file.write_all(lines.as_bytes())
         .map_err(|e| VaultError::Io(format!("failed to write audit entry: {e}")))?;
 
     Ok(())
 }
 
-/// Read audit entries from the JSONL log, with optional filters.
+/// Read audit entries from the JSONL log that match `filter`.
 pub fn read_entries(
     path: &Path,
-    credential_id: Option<uuid::Uuid>,
+    filter: &AuditFilter,
     limit: Option<usize>,
-    since: Option<chrono::DateTime<chrono::Utc>>,
 ) -> Result<Vec<AuditEntry>, VaultError> {
     if !path.exists() {
         return Ok(vec![]);
@@ -41,27 +102,17 @@ pub fn read_entries(
     let contents = fs::read_to_string(path)
         .map_err(|e| VaultError::Io(format!("failed to read audit log: {e}")))?;
 
+    let needles = filter.needles();
     let mut entries: Vec<AuditEntry> = contents
         .lines()
         .filter(|line| !line.trim().is_empty())
+        .filter(|line| needles.iter().all(|n| line.contains(n.as_str())))
         .filter_map(|line| serde_json::from_str(line).ok())
-        .filter(|entry: &AuditEntry| {
-            if let Some(cid) = credential_id {
-                if entry.credential_id != Some(cid) {
-                    return false;
-                }
-            }
-            if let Some(ref s) = since {
-                if entry.timestamp < *s {
-                    return false;
-                }
-            }
-            true
-        })
+        .filter(|entry: &AuditEntry| filter.matches(entry))
         .collect();
 
     // Most recent first
-    entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
 
     if let Some(lim) = limit {
         entries.truncate(lim);
@@ -70,12 +121,155 @@ pub fn read_entries(
     Ok(entries)
 }
 
+/// Lock file serializing appends with `clear_entries`, shared by every
+/// process writing the log. The log itself is replaced by rename when
+/// cleared, so it can't hold the lock: an append to the old file would be
+/// lost.
+fn log_lock(path: &Path) -> Result<RwLock<fs::File>, VaultError> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(storage::lock_path(path))
+        .map(RwLock::new)
+        .map_err(|e| VaultError::Io(format!("failed to open audit log lock file: {e}")))
+}
+
+/// Drop entries older than `before` from the log, or every entry when
+/// `before` is `None`. Lines that don't parse are kept unless the whole log
+/// is cleared. The log is rewritten to a temp file and renamed into place,
+/// so a crash part-way leaves the old log intact. Appends wait on the log's
+/// lock file until it's done. Returns the number of entries removed.
+pub fn clear_entries(path: &Path, before: Option<DateTime<Utc>>) -> Result<usize, VaultError> {
+    if !path.exists() {
+        return Ok(0);
+    }
+
+    let mut lock = log_lock(path)?;
+    let _guard = lock
+        .write()
+        .map_err(|e| VaultError::Io(format!("failed to acquire audit log lock: {e}")))?;
+    let contents = fs::read_to_string(path)
+        .map_err(|e| VaultError::Io(format!("failed to read audit log: {e}")))?;
+
+    let mut kept = String::new();
+    let mut removed = 0;
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        let old = match (before, serde_json::from_str::<AuditEntry>(line)) {
+            (None, _) => true,
+            (Some(before), Ok(entry)) => entry.timestamp < before,
+            (Some(_), Err(_)) => false,
+        };
+        if old {
+            removed += 1;
+        } else {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+    }
+
+    if removed > 0 {
+        storage::write_atomic(path, kept.as_bytes())?;
+    }
+    Ok(removed)
+}
+
+/// Number of credentials listed in `AuditSummary::top_credentials`.
+pub const SUMMARY_TOP_CREDENTIALS: usize = 10;
+
+/// Count entries at or after `since`, streaming the log in a single pass.
+pub fn summarize(path: &Path, since: Option<DateTime<Utc>>) -> Result<AuditSummary, VaultError> {
+    summarize_matching(path, since, |_| true)
+}
+
+/// `summarize`, counting only the entries `keep` accepts.
+pub fn summarize_matching(
+    path: &Path,
+    since: Option<DateTime<Utc>>,
+    keep: impl Fn(&AuditEntry) -> bool,
+) -> Result<AuditSummary, VaultError> {
+    let mut summary = AuditSummary::default();
+    if !path.exists() {
+        return Ok(summary);
+    }
+
+    let file = fs::File::open(path)
+        .map_err(|e| VaultError::Io(format!("failed to read audit log: {e}")))?;
+
+    // action -> (total, failed); credential -> (latest name, count)
+    let mut actions: HashMap<AuditAction, (u64, u64)> = HashMap::new();
+    let mut credentials: HashMap<Uuid, (Option<String>, u64)> = HashMap::new();
+
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| VaultError::Io(format!("failed to read audit log: {e}")))?;
+        let Ok(entry) = serde_json::from_str::<AuditEntry>(&line) else {
+            continue;
+        };
+        if since.is_some_and(|s| entry.timestamp < s) || !keep(&entry) {
+            continue;
+        }
+
+        summary.total += 1;
+        let counts = actions.entry(entry.action).or_default();
+        counts.0 += 1;
+        if entry.success {
+            summary.succeeded += 1;
+        } else {
+            summary.failed += 1;
+            counts.1 += 1;
+        }
+
+        if let Some(id) = entry.credential_id {
+            let usage = credentials.entry(id).or_default();
+            usage.1 += 1;
+            if entry.credential_name.is_some() {
+                usage.0 = entry.credential_name;
+            }
+        }
+
+        let at = entry.timestamp;
+        summary.first_entry = Some(summary.first_entry.map_or(at, |t| t.min(at)));
+        summary.last_entry = Some(summary.last_entry.map_or(at, |t| t.max(at)));
+    }
+
+    summary.by_action = actions
+        .into_iter()
+        .map(|(action, (total, failed))| ActionCount {
+            action,
+            total,
+            failed,
+        })
+        .collect();
+    summary.by_action.sort_by(|a, b| {
+        b.total
+            .cmp(&a.total)
+            .then_with(|| format!("{:?}", a.action).cmp(&format!("{:?}", b.action)))
+    });
+
+    summary.top_credentials = credentials
+        .into_iter()
+        .map(
+            |(credential_id, (credential_name, count))| CredentialUsage {
+                credential_id,
+                credential_name,
+                count,
+            },
+        )
+        .collect();
+    summary.top_credentials.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.credential_id.cmp(&b.credential_id))
+    });
+    summary.top_credentials.truncate(SUMMARY_TOP_CREDENTIALS);
+
+    Ok(summary)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
-    use passman_types::AuditAction;
-    use uuid::Uuid;
 
     fn test_entry(cred_id: Option<Uuid>) -> AuditEntry {
         AuditEntry {
@@ -98,16 +292,29 @@ mod tests {
         append_entry(&path, &test_entry(Some(id))).unwrap();
         append_entry(&path, &test_entry(None)).unwrap();
 
-        let all = read_entries(&path, None, None, None).unwrap();
+        let all = read_entries(&path, &AuditFilter::default(), None).unwrap();
         assert_eq!(all.len(), 2);
 
-        let filtered = read_entries(&path, Some(id), None, None).unwrap();
+        let filtered = read_entries(
+            &path,
+            &AuditFilter {
+                credential_id: Some(id),
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
         assert_eq!(filtered.len(), 1);
     }
 
     #[test]
     fn test_read_nonexistent() {
-        let entries = read_entries(Path::new("/nonexistent/audit.jsonl"), None, None, None).unwrap();
+        let entries = read_entries(
+            Path::new("/nonexistent/audit.jsonl"),
+            &AuditFilter::default(),
+            None,
+        )
+        .unwrap();
         assert!(entries.is_empty());
     }
 
@@ -120,7 +327,207 @@ mod tests {
             append_entry(&path, &test_entry(None)).unwrap();
         }
 
-        let limited = read_entries(&path, None, Some(3), None).unwrap();
+        let limited = read_entries(&path, &AuditFilter::default(), Some(3)).unwrap();
         assert_eq!(limited.len(), 3);
     }
+
+    fn entry(action: AuditAction, tool: &str, success: bool) -> AuditEntry {
+        AuditEntry {
+            action,
+            tool: tool.to_string(),
+            success,
+            ..test_entry(None)
+        }
+    }
+
+    fn mixed_log(path: &Path) {
+        append_entries(
+            path,
+            &[
+                entry(AuditAction::SqlQuery, "sql_query", true),
+                entry(AuditAction::SqlQuery, "sql_query", false),
+                entry(AuditAction::HttpRequest, "http_request", false),
+                entry(AuditAction::SshExec, "ssh_exec", true),
+                entry(AuditAction::SqlQuery, "sql_query", false),
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_filter_by_action() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        mixed_log(&path);
+
+        let filter = AuditFilter {
+            action: Some(AuditAction::SqlQuery),
+            ..Default::default()
+        };
+        let sql = read_entries(&path, &filter, None).unwrap();
+        assert_eq!(sql.len(), 3);
+        assert!(sql.iter().all(|e| e.action == AuditAction::SqlQuery));
+
+        let filter = AuditFilter {
+            tool: Some("ssh_exec".into()),
+            ..Default::default()
+        };
+        assert_eq!(read_entries(&path, &filter, None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_filter_by_success() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        mixed_log(&path);
+
+        let failed = AuditFilter {
+            success: Some(false),
+            ..Default::default()
+        };
+        let entries = read_entries(&path, &failed, None).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|e| !e.success));
+
+        let failed_sql = AuditFilter {
+            action: Some(AuditAction::SqlQuery),
+            ..failed
+        };
+        assert_eq!(read_entries(&path, &failed_sql, None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_filter_by_time_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let now = Utc::now();
+        for hours_ago in [3, 2, 1] {
+            append_entry(
+                &path,
+                &AuditEntry {
+                    timestamp: now - chrono::Duration::hours(hours_ago),
+                    ..test_entry(None)
+                },
+            )
+            .unwrap();
+        }
+
+        let filter = AuditFilter {
+            since: Some(now - chrono::Duration::minutes(150)),
+            until: Some(now - chrono::Duration::minutes(90)),
+            ..Default::default()
+        };
+        assert_eq!(read_entries(&path, &filter, None).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_clear_entries_keeps_recent_and_unparseable_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let now = Utc::now();
+        for days_ago in [40, 20, 2, 0] {
+            append_entry(
+                &path,
+                &AuditEntry {
+                    timestamp: now - chrono::Duration::days(days_ago),
+                    ..test_entry(None)
+                },
+            )
+            .unwrap();
+        }
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        let cutoff = now - chrono::Duration::days(7);
+        assert_eq!(clear_entries(&path, Some(cutoff)).unwrap(), 2);
+        assert_eq!(clear_entries(&path, Some(cutoff)).unwrap(), 0);
+
+        let entries = read_entries(&path, &AuditFilter::default(), None).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.timestamp >= cutoff));
+        assert!(fs::read_to_string(&path).unwrap().ends_with("not json\n"));
+        assert!(!dir.path().join("audit.jsonl.tmp").exists());
+
+        assert_eq!(clear_entries(&path, None).unwrap(), 3);
+        assert!(read_entries(&path, &AuditFilter::default(), None)
+            .unwrap()
+            .is_empty());
+        let missing = dir.path().join("missing.jsonl");
+        assert_eq!(clear_entries(&missing, None).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_clear_entries_loses_no_concurrent_appends() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let cutoff = Utc::now();
+        for _ in 0..200 {
+            append_entry(
+                &path,
+                &AuditEntry {
+                    timestamp: cutoff - chrono::Duration::days(1),
+                    ..test_entry(None)
+                },
+            )
+            .unwrap();
+        }
+
+        // Another process appends while this one prunes
+        let appender = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                for _ in 0..200 {
+                    append_entry(&path, &test_entry(None)).unwrap();
+                }
+            })
+        };
+        for _ in 0..20 {
+            clear_entries(&path, Some(cutoff)).unwrap();
+        }
+        appender.join().unwrap();
+        clear_entries(&path, Some(cutoff)).unwrap();
+
+        let entries = read_entries(&path, &AuditFilter::default(), None).unwrap();
+        assert_eq!(entries.len(), 200);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        mixed_log(&path);
+        let id = Uuid::new_v4();
+        append_entry(&path, &test_entry(Some(id))).unwrap();
+        append_entry(&path, &test_entry(Some(id))).unwrap();
+
+        let summary = summarize(&path, None).unwrap();
+        assert_eq!(summary.total, 7);
+        assert_eq!(summary.succeeded, 4);
+        assert_eq!(summary.failed, 3);
+
+        let count = |action: AuditAction| {
+            summary
+                .by_action
+                .iter()
+                .find(|c| c.action == action)
+                .map(|c| (c.total, c.failed))
+        };
+        assert_eq!(count(AuditAction::SqlQuery), Some((3, 2)));
+        assert_eq!(count(AuditAction::HttpRequest), Some((3, 1)));
+        assert_eq!(count(AuditAction::SshExec), Some((1, 0)));
+        assert_eq!(count(AuditAction::VaultLock), None);
+
+        assert_eq!(summary.top_credentials.len(), 1);
+        assert_eq!(summary.top_credentials[0].credential_id, id);
+        assert_eq!(summary.top_credentials[0].count, 2);
+        assert!(summary.first_entry <= summary.last_entry);
+
+        let future = summarize(&path, Some(Utc::now() + chrono::Duration::hours(1))).unwrap();
+        assert_eq!(future.total, 0);
+        assert!(future.first_entry.is_none());
+    }
 }
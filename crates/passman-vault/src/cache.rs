@@ -0,0 +1,130 @@
+//! Bounded LRU cache of decrypted secrets.
+//!
+//! Proxy tools decrypt the same few credentials over and over; caching the
+//! most recent ones avoids repeated AES-GCM work without keeping every secret
+//! of a large vault decrypted. Entries are keyed by credential ID and the
+//! blob's nonce: re-encrypting a secret (update, password change) gives it a
+//! new nonce, so a stale entry can never be returned.
+
+use passman_types::CredentialSecret;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+/// Default number of decrypted secrets kept in memory.
+pub const DEFAULT_SECRET_CACHE_SIZE: usize = 64;
+
+/// The secret is wiped when the entry is evicted or cleared.
+struct Entry {
+    id: Uuid,
+    nonce: Vec<u8>,
+    secret: Zeroizing<CredentialSecret>,
+}
+
+/// Least-recently-used cache; the front of the queue is evicted first.
+pub struct SecretCache {
+    capacity: usize,
+    entries: Mutex<VecDeque<Entry>>,
+}
+
+impl SecretCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Cached secret for `id`, if it was decrypted from a blob with `nonce`.
+    pub fn get(&self, id: Uuid, nonce: &[u8]) -> Option<Zeroizing<CredentialSecret>> {
+        let mut entries = self.entries.lock().unwrap();
+        let pos = entries.iter().position(|e| e.id == id)?;
+        let entry = entries.remove(pos)?;
+        if entry.nonce != nonce {
+            return None;
+        }
+        let secret = entry.secret.clone();
+        entries.push_back(entry);
+        Some(secret)
+    }
+
+    /// Cache a freshly decrypted secret, evicting the least recently used
+    /// entry if the cache is full.
+    pub fn insert(&self, id: Uuid, nonce: &[u8], secret: CredentialSecret) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|e| e.id != id);
+        while entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(Entry {
+            id,
+            nonce: nonce.to_vec(),
+            secret: Zeroizing::new(secret),
+        });
+    }
+
+    /// Drop every cached secret (on lock).
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret(token: &str) -> CredentialSecret {
+        CredentialSecret::ApiToken {
+            token: token.to_string(),
+            header_name: None,
+            prefix: None,
+        }
+    }
+
+    fn token(secret: Zeroizing<CredentialSecret>) -> String {
+        match &*secret {
+            CredentialSecret::ApiToken { token, .. } => token.clone(),
+            _ => panic!("wrong variant"),
+        }
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = SecretCache::new(2);
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        cache.insert(a, b"n1", secret("a"));
+        cache.insert(b, b"n1", secret("b"));
+        // Touch `a` so `b` becomes the eviction candidate
+        assert!(cache.get(a, b"n1").is_some());
+        cache.insert(c, b"n1", secret("c"));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(b, b"n1").is_none());
+        assert_eq!(token(cache.get(a, b"n1").unwrap()), "a");
+        assert_eq!(token(cache.get(c, b"n1").unwrap()), "c");
+    }
+
+    #[test]
+    fn test_new_nonce_misses() {
+        let cache = SecretCache::new(4);
+        let id = Uuid::new_v4();
+        cache.insert(id, b"old", secret("old"));
+
+        assert!(cache.get(id, b"new").is_none());
+        // The stale entry is dropped on the miss
+        assert!(cache.is_empty());
+    }
+}
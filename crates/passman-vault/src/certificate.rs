@@ -0,0 +1,60 @@
+//! Checks on certificate credentials, so a mismatched certificate and key is
+//! caught when stored instead of at the first mTLS handshake.
+
+use rustls::crypto::ring::sign::any_supported_type;
+use rustls::pki_types::CertificateDer;
+use rustls::sign::CertifiedKey;
+use rustls::{Error, InconsistentKeys};
+
+/// Check that `key_pem` is the private key for the first certificate in
+/// `cert_pem`.
+pub fn validate(cert_pem: &str, key_pem: &str) -> Result<(), String> {
+    let certs = rustls_pemfile::certs(&mut cert_pem.as_bytes())
+        .collect::<Result<Vec<CertificateDer<'static>>, _>>()
+        .map_err(|e| format!("invalid cert_pem: {e}"))?;
+    if certs.is_empty() {
+        return Err("cert_pem contains no certificate".to_string());
+    }
+
+    let key = rustls_pemfile::private_key(&mut key_pem.as_bytes())
+        .map_err(|e| format!("invalid key_pem: {e}"))?
+        .ok_or("key_pem contains no private key")?;
+    let signing_key =
+        any_supported_type(&key).map_err(|e| format!("unsupported private key: {e}"))?;
+
+    match CertifiedKey::new(certs, signing_key).keys_match() {
+        Ok(()) => Ok(()),
+        Err(Error::InconsistentKeys(InconsistentKeys::KeyMismatch)) => {
+            Err("key_pem does not match the certificate's public key".to_string())
+        }
+        // The key type can't report its public key; nothing to compare
+        Err(Error::InconsistentKeys(InconsistentKeys::Unknown)) => Ok(()),
+        Err(e) => Err(format!("invalid cert_pem: {e}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT_PEM: &str = include_str!("../../passman-proxy/tests/fixtures/client.pem");
+    const CLIENT_KEY: &str = include_str!("../../passman-proxy/tests/fixtures/client.key");
+    const SERVER_KEY: &str = include_str!("../../passman-proxy/tests/fixtures/server.key");
+
+    #[test]
+    fn test_matching_pair() {
+        assert!(validate(CLIENT_PEM, CLIENT_KEY).is_ok());
+    }
+
+    #[test]
+    fn test_mismatched_pair() {
+        let err = validate(CLIENT_PEM, SERVER_KEY).unwrap_err();
+        assert!(err.contains("does not match"), "{err}");
+    }
+
+    #[test]
+    fn test_missing_pem_blocks() {
+        assert!(validate("", CLIENT_KEY).is_err());
+        assert!(validate(CLIENT_PEM, "not a key").is_err());
+    }
+}
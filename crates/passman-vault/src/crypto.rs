@@ -1,59 +1,108 @@
 use aes_gcm::{
-    aead::{Aead, KeyInit},
-    Aes256Gcm, Nonce,
+    aead::{Aead, KeyInit, Nonce},
+    Aes256Gcm,
 };
 use argon2::Argon2;
-use passman_types::{EncryptedBlob, KdfParams};
+use chacha20poly1305::XChaCha20Poly1305;
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use passman_types::{Cipher, EncryptedBlob, KdfParams, VaultFile};
 use rand::RngCore;
-use zeroize::Zeroize;
+use sha2::Sha256;
+use std::collections::HashSet;
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::VaultError;
 
+/// Name of the key derivation function, as reported in vault status.
+pub const KDF_ALGORITHM: &str = "argon2id";
+
 /// Derive a 256-bit encryption key from a master password using Argon2id.
-pub fn derive_key(password: &str, salt: &[u8], params: &KdfParams) -> Result<[u8; 32], VaultError> {
+/// The key is wiped when the returned handle drops.
+pub fn derive_key(
+    password: &str,
+    salt: &[u8],
+    params: &KdfParams,
+) -> Result<Zeroizing<[u8; 32]>, VaultError> {
     let argon2 = Argon2::new(
         argon2::Algorithm::Argon2id,
         argon2::Version::V0x13,
-        argon2::Params::new(params.memory_kib, params.iterations, params.parallelism, Some(32))
-            .map_err(|e| VaultError::Crypto(format!("invalid KDF params: {e}")))?,
+        argon2::Params::new(
+            params.memory_kib,
+            params.iterations,
+            params.parallelism,
+            Some(32),
+        )
+        .map_err(|e| VaultError::Crypto(format!("invalid KDF params: {e}")))?,
     );
 
-    let mut key = [0u8; 32];
+    let mut key = Zeroizing::new([0u8; 32]);
     argon2
-        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .hash_password_into(password.as_bytes(), salt, &mut *key)
         .map_err(|e| VaultError::Crypto(format!("key derivation failed: {e}")))?;
 
     Ok(key)
 }
 
-/// Encrypt plaintext with AES-256-GCM using a unique random nonce.
-pub fn encrypt(key: &[u8; 32], plaintext: &[u8]) -> Result<EncryptedBlob, VaultError> {
-    let cipher = Aes256Gcm::new_from_slice(key)
+/// Encrypt plaintext with `cipher` using a unique random nonce.
+pub fn encrypt(
+    key: &[u8; 32],
+    cipher: Cipher,
+    plaintext: &[u8],
+) -> Result<EncryptedBlob, VaultError> {
+    match cipher {
+        Cipher::Aes256Gcm => seal::<Aes256Gcm>(key, plaintext),
+        Cipher::XChaCha20Poly1305 => seal::<XChaCha20Poly1305>(key, plaintext),
+    }
+}
+
+/// Decrypt a blob encrypted with `cipher`.
+pub fn decrypt(
+    key: &[u8; 32],
+    cipher: Cipher,
+    blob: &EncryptedBlob,
+) -> Result<Vec<u8>, VaultError> {
+    match cipher {
+        Cipher::Aes256Gcm => open::<Aes256Gcm>(key, blob),
+        Cipher::XChaCha20Poly1305 => open::<XChaCha20Poly1305>(key, blob),
+    }
+}
+
+fn seal<C: Aead + KeyInit>(key: &[u8; 32], plaintext: &[u8]) -> Result<EncryptedBlob, VaultError> {
+    let cipher = C::new_from_slice(key)
         .map_err(|e| VaultError::Crypto(format!("cipher init failed: {e}")))?;
 
-    let mut nonce_bytes = [0u8; 12];
-    rand::thread_rng().fill_bytes(&mut nonce_bytes);
-    let nonce = Nonce::from_slice(&nonce_bytes);
+    let mut nonce = Nonce::<C>::default();
+    rand::thread_rng().fill_bytes(&mut nonce);
 
     let ciphertext = cipher
-        .encrypt(nonce, plaintext)
+        .encrypt(&nonce, plaintext)
         .map_err(|e| VaultError::Crypto(format!("encryption failed: {e}")))?;
 
     Ok(EncryptedBlob {
-        nonce: nonce_bytes.to_vec(),
+        nonce: nonce.to_vec(),
         ciphertext,
     })
 }
 
-/// Decrypt an AES-256-GCM encrypted blob.
-pub fn decrypt(key: &[u8; 32], blob: &EncryptedBlob) -> Result<Vec<u8>, VaultError> {
-    let cipher = Aes256Gcm::new_from_slice(key)
+fn open<C: Aead + KeyInit>(key: &[u8; 32], blob: &EncryptedBlob) -> Result<Vec<u8>, VaultError> {
+    let cipher = C::new_from_slice(key)
         .map_err(|e| VaultError::Crypto(format!("cipher init failed: {e}")))?;
 
-    let nonce = Nonce::from_slice(&blob.nonce);
+    // A nonce of the wrong size means the blob was made by the other
+    // cipher, or the file was damaged
+    let mut nonce = Nonce::<C>::default();
+    if blob.nonce.len() != nonce.len() {
+        return Err(VaultError::Crypto(format!(
+            "decryption failed: {}-byte nonce, expected {}",
+            blob.nonce.len(),
+            nonce.len()
+        )));
+    }
+    nonce.copy_from_slice(&blob.nonce);
 
     cipher
-        .decrypt(nonce, blob.ciphertext.as_ref())
+        .decrypt(&nonce, blob.ciphertext.as_ref())
         .map_err(|e| VaultError::Crypto(format!("decryption failed: {e}")))
 }
 
@@ -64,78 +113,262 @@ pub fn generate_salt() -> [u8; 32] {
     salt
 }
 
-/// A wrapper that holds the derived key and zeroizes it on drop.
+/// A wrapper that holds the derived key and zeroizes it on drop. With the
+/// `mlock` feature the key's page is also pinned in RAM so it can't be
+/// written to swap.
 pub struct DerivedKey {
-    key: [u8; 32],
+    /// Boxed so the pinned memory stays put when the handle moves.
+    key: Box<[u8; 32]>,
+    cipher: Cipher,
+    /// Whether `mlock` succeeded and `drop` has to undo it.
+    locked: bool,
 }
 
 impl DerivedKey {
-    pub fn new(key: [u8; 32]) -> Self {
-        Self { key }
+    pub fn new(mut key: [u8; 32], cipher: Cipher) -> Self {
+        let boxed = Box::new(key);
+        key.zeroize();
+        let locked = lock_memory(&boxed[..]);
+        Self {
+            key: boxed,
+            cipher,
+            locked,
+        }
     }
 
     pub fn as_bytes(&self) -> &[u8; 32] {
         &self.key
     }
 
+    /// The same key, used with `cipher`. The bytes stay where they are.
+    pub fn with_cipher(mut self, cipher: Cipher) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
     pub fn encrypt(&self, plaintext: &[u8]) -> Result<EncryptedBlob, VaultError> {
-        encrypt(&self.key, plaintext)
+        encrypt(&self.key, self.cipher, plaintext)
     }
 
     pub fn decrypt(&self, blob: &EncryptedBlob) -> Result<Vec<u8>, VaultError> {
-        decrypt(&self.key, blob)
+        decrypt(&self.key, self.cipher, blob)
     }
 }
 
 impl Drop for DerivedKey {
     fn drop(&mut self) {
+        // Wipe while still pinned, so the key can't reach swap in between
         self.key.zeroize();
+        if self.locked {
+            unlock_memory(&self.key[..]);
+        }
     }
 }
 
+/// Live keys on each pinned page, by page address. `munlock` works on whole
+/// pages and doesn't nest, so two keys sharing a page must not unpin it
+/// until the second one is dropped.
+#[cfg(feature = "mlock")]
+static PINNED_PAGES: std::sync::Mutex<std::collections::BTreeMap<usize, usize>> =
+    std::sync::Mutex::new(std::collections::BTreeMap::new());
+
+/// Addresses of the pages `bytes` spans.
+#[cfg(feature = "mlock")]
+fn pages(bytes: &[u8]) -> impl Iterator<Item = usize> {
+    let start = region::page::floor(bytes.as_ptr()) as usize;
+    let end = bytes.as_ptr() as usize + bytes.len();
+    (start..end).step_by(region::page::size())
+}
+
+/// Pin `bytes` in RAM. Best effort: a refusal (say, a low `RLIMIT_MEMLOCK`)
+/// is logged and the key stays usable, just swappable.
+#[cfg(feature = "mlock")]
+fn lock_memory(bytes: &[u8]) -> bool {
+    let mut pinned = PINNED_PAGES.lock().unwrap_or_else(|e| e.into_inner());
+    let fresh: Vec<usize> = pages(bytes)
+        .filter(|page| !pinned.contains_key(page))
+        .collect();
+    for (i, &page) in fresh.iter().enumerate() {
+        match region::lock(page as *const u8, region::page::size()) {
+            // `unlock_memory` unlocks by hand; the guard would panic in debug
+            // builds if unlocking failed
+            Ok(guard) => std::mem::forget(guard),
+            Err(e) => {
+                for &done in &fresh[..i] {
+                    let _ = region::unlock(done as *const u8, region::page::size());
+                }
+                tracing::warn!("Could not lock key memory, it may be swapped to disk: {e}");
+                return false;
+            }
+        }
+    }
+    for page in pages(bytes) {
+        *pinned.entry(page).or_default() += 1;
+    }
+    true
+}
+
+#[cfg(not(feature = "mlock"))]
+fn lock_memory(_bytes: &[u8]) -> bool {
+    false
+}
+
+/// Release `bytes` pinned by `lock_memory`, unlocking each page it spans
+/// once no other key is on it.
+#[cfg(feature = "mlock")]
+fn unlock_memory(bytes: &[u8]) {
+    let mut pinned = PINNED_PAGES.lock().unwrap_or_else(|e| e.into_inner());
+    for page in pages(bytes) {
+        let Some(count) = pinned.get_mut(&page) else {
+            continue;
+        };
+        *count -= 1;
+        if *count > 0 {
+            continue;
+        }
+        pinned.remove(&page);
+        if let Err(e) = region::unlock(page as *const u8, region::page::size()) {
+            tracing::warn!("Could not unlock key memory: {e}");
+        }
+    }
+}
+
+#[cfg(not(feature = "mlock"))]
+fn unlock_memory(_bytes: &[u8]) {}
+
 /// Known plaintext used to verify the master password on unlock.
 const VERIFICATION_PLAINTEXT: &[u8] = b"passman-vault-verification-v1";
 
 /// Create a verification blob that can later be used to check the master password.
-pub fn create_verification(key: &[u8; 32]) -> Result<EncryptedBlob, VaultError> {
-    encrypt(key, VERIFICATION_PLAINTEXT)
+pub fn create_verification(key: &[u8; 32], cipher: Cipher) -> Result<EncryptedBlob, VaultError> {
+    encrypt(key, cipher, VERIFICATION_PLAINTEXT)
 }
 
 /// Verify a master password by attempting to decrypt the verification blob.
-pub fn verify_password(key: &[u8; 32], blob: &EncryptedBlob) -> Result<bool, VaultError> {
-    match decrypt(key, blob) {
+pub fn verify_password(
+    key: &[u8; 32],
+    cipher: Cipher,
+    blob: &EncryptedBlob,
+) -> Result<bool, VaultError> {
+    match decrypt(key, cipher, blob) {
         Ok(plaintext) => Ok(plaintext == VERIFICATION_PLAINTEXT),
         Err(_) => Ok(false),
     }
 }
 
+/// Fail if two blobs under the vault key share a nonce. `encrypt` picks a
+/// fresh random one each time, so a repeat means the file was damaged or
+/// crafted. The duress verification is under another key and is skipped.
+pub fn check_nonce_reuse(vault: &VaultFile) -> Result<(), VaultError> {
+    let mut seen = HashSet::new();
+    let blobs = std::iter::once(&vault.verification)
+        .chain(vault.credentials.iter().map(|cred| &cred.secret));
+    for blob in blobs {
+        if !seen.insert(blob.nonce.as_slice()) {
+            return Err(VaultError::Crypto("nonce reuse detected".to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// HKDF label for the audit-signing key, keeping it distinct from the
+/// encryption key it is derived from.
+const AUDIT_SIGNING_INFO: &[u8] = b"passman-audit-signing-v1";
+
+/// HKDF label for the key that authenticates queued approvals.
+const APPROVAL_MAC_INFO: &[u8] = b"passman-approval-mac-v1";
+
+/// Derive the key used to sign audit exports from the master key.
+pub fn audit_signing_key(master: &[u8; 32]) -> [u8; 32] {
+    derive_subkey(master, AUDIT_SIGNING_INFO)
+}
+
+/// Derive the key used to MAC pending approvals from the master key.
+pub fn approval_mac_key(master: &[u8; 32]) -> [u8; 32] {
+    derive_subkey(master, APPROVAL_MAC_INFO)
+}
+
+fn derive_subkey(master: &[u8; 32], info: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(None, master)
+        .expand(info, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+/// HMAC-SHA256 of `data`.
+pub fn sign(key: &[u8; 32], data: &[u8]) -> Vec<u8> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Check an HMAC-SHA256 tag in constant time.
+pub fn verify_signature(key: &[u8; 32], data: &[u8], tag: &[u8]) -> bool {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(data);
+    mac.verify_slice(tag).is_ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const CIPHERS: [Cipher; 2] = [Cipher::Aes256Gcm, Cipher::XChaCha20Poly1305];
+
     #[test]
     fn test_encrypt_decrypt_roundtrip() {
         let key = [42u8; 32];
         let plaintext = b"hello, world!";
-        let blob = encrypt(&key, plaintext).unwrap();
-        let decrypted = decrypt(&key, &blob).unwrap();
-        assert_eq!(decrypted, plaintext);
+        for cipher in CIPHERS {
+            let blob = encrypt(&key, cipher, plaintext).unwrap();
+            let decrypted = decrypt(&key, cipher, &blob).unwrap();
+            assert_eq!(decrypted, plaintext);
+        }
+    }
+
+    #[test]
+    fn test_nonce_size_follows_cipher() {
+        let key = [42u8; 32];
+        let gcm = encrypt(&key, Cipher::Aes256Gcm, b"data").unwrap();
+        let xchacha = encrypt(&key, Cipher::XChaCha20Poly1305, b"data").unwrap();
+        assert_eq!(gcm.nonce.len(), 12);
+        assert_eq!(xchacha.nonce.len(), 24);
+
+        // Reading a blob with the other cipher fails instead of panicking
+        assert!(decrypt(&key, Cipher::XChaCha20Poly1305, &gcm).is_err());
+        assert!(decrypt(&key, Cipher::Aes256Gcm, &xchacha).is_err());
     }
 
     #[test]
     fn test_wrong_key_fails() {
         let key1 = [1u8; 32];
         let key2 = [2u8; 32];
-        let blob = encrypt(&key1, b"secret").unwrap();
-        let result = decrypt(&key2, &blob);
-        assert!(result.is_err());
+        for cipher in CIPHERS {
+            let blob = encrypt(&key1, cipher, b"secret").unwrap();
+            let result = decrypt(&key2, cipher, &blob);
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn test_audit_signing_key_differs_from_master() {
+        let master = [7u8; 32];
+        let signing = audit_signing_key(&master);
+        assert_ne!(signing, master);
+        assert_eq!(signing, audit_signing_key(&master));
+
+        let tag = sign(&signing, b"entries");
+        assert!(verify_signature(&signing, b"entries", &tag));
+        assert!(!verify_signature(&signing, b"entries!", &tag));
+        assert!(!verify_signature(&master, b"entries", &tag));
     }
 
     #[test]
     fn test_unique_nonces() {
         let key = [42u8; 32];
-        let b1 = encrypt(&key, b"data").unwrap();
-        let b2 = encrypt(&key, b"data").unwrap();
+        let b1 = encrypt(&key, Cipher::Aes256Gcm, b"data").unwrap();
+        let b2 = encrypt(&key, Cipher::Aes256Gcm, b"data").unwrap();
         assert_ne!(b1.nonce, b2.nonce);
     }
 
@@ -168,22 +401,65 @@ mod tests {
     #[test]
     fn test_verification_roundtrip() {
         let key = [42u8; 32];
-        let blob = create_verification(&key).unwrap();
-        assert!(verify_password(&key, &blob).unwrap());
+        for cipher in CIPHERS {
+            let blob = create_verification(&key, cipher).unwrap();
+            assert!(verify_password(&key, cipher, &blob).unwrap());
+        }
     }
 
     #[test]
     fn test_verification_wrong_password() {
         let key1 = [1u8; 32];
         let key2 = [2u8; 32];
-        let blob = create_verification(&key1).unwrap();
-        assert!(!verify_password(&key2, &blob).unwrap());
+        let blob = create_verification(&key1, Cipher::Aes256Gcm).unwrap();
+        assert!(!verify_password(&key2, Cipher::Aes256Gcm, &blob).unwrap());
     }
 
     #[test]
     fn test_derived_key_zeroize_on_drop() {
-        let key = DerivedKey::new([42u8; 32]);
+        let key = DerivedKey::new([42u8; 32], Cipher::Aes256Gcm);
         assert_eq!(key.as_bytes(), &[42u8; 32]);
         // key is zeroized when dropped
     }
+
+    #[cfg(all(unix, feature = "mlock"))]
+    #[test]
+    fn test_locked_key_survives_moves_and_failed_unlock() {
+        let key = DerivedKey::new([42u8; 32], Cipher::Aes256Gcm);
+        let blob = key.encrypt(b"pinned").unwrap();
+
+        // Moving the handle doesn't move the pinned bytes
+        let moved = vec![key];
+        assert_eq!(moved[0].as_bytes(), &[42u8; 32]);
+        assert_eq!(moved[0].decrypt(&blob).unwrap(), b"pinned");
+
+        // Unlocking early makes the unlock in drop redundant; whatever the
+        // OS says about it, drop must not panic
+        let key = moved.into_iter().next().unwrap();
+        let _ = region::unlock(key.as_bytes().as_ptr(), 32);
+        drop(key);
+
+        // A key whose lock was refused still works and drops cleanly
+        let mut unpinned = DerivedKey::new([7u8; 32], Cipher::XChaCha20Poly1305);
+        unlock_memory(&unpinned.key[..]);
+        unpinned.locked = false;
+        assert_eq!(unpinned.as_bytes(), &[7u8; 32]);
+    }
+
+    #[cfg(all(unix, feature = "mlock"))]
+    #[test]
+    fn test_shared_page_stays_pinned_until_last_key_drops() {
+        let buf = [0u8; 64];
+        let (first, second) = buf.split_at(32);
+        if !lock_memory(first) {
+            // RLIMIT_MEMLOCK too low to say anything
+            return;
+        }
+        assert!(lock_memory(second));
+
+        unlock_memory(first);
+        let page = region::page::floor(second.as_ptr()) as usize;
+        assert!(PINNED_PAGES.lock().unwrap().contains_key(&page));
+        unlock_memory(second);
+    }
 }
@@ -0,0 +1,85 @@
+//! Keeping the derived key in an OS secret store for unattended unlock.
+//!
+//! With a `KeyStore` configured (`Vault::with_key_store`), every successful
+//! password unlock saves the derived key under the vault's profile, and
+//! `Vault::unlock_from_keyring` opens the vault with it later, without the
+//! master password. The OS-backed store (macOS Keychain, Windows Credential
+//! Manager, Secret Service) needs the `keyring` feature.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::VaultError;
+
+/// Somewhere derived keys can be kept, one per profile.
+pub trait KeyStore: Send + Sync {
+    fn get(&self, account: &str) -> Result<Option<Vec<u8>>, VaultError>;
+    fn set(&self, account: &str, key: &[u8]) -> Result<(), VaultError>;
+    fn delete(&self, account: &str) -> Result<(), VaultError>;
+}
+
+/// Service name entries are stored under in the OS secret store.
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "passman";
+
+/// The platform secret store, via the `keyring` crate.
+#[cfg(feature = "keyring")]
+#[derive(Debug, Default)]
+pub struct OsKeyring;
+
+#[cfg(feature = "keyring")]
+impl OsKeyring {
+    fn entry(account: &str) -> Result<keyring::Entry, VaultError> {
+        keyring::Entry::new(KEYRING_SERVICE, account)
+            .map_err(|e| VaultError::Keyring(e.to_string()))
+    }
+}
+
+#[cfg(feature = "keyring")]
+impl KeyStore for OsKeyring {
+    fn get(&self, account: &str) -> Result<Option<Vec<u8>>, VaultError> {
+        match Self::entry(account)?.get_secret() {
+            Ok(key) => Ok(Some(key)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(VaultError::Keyring(e.to_string())),
+        }
+    }
+
+    fn set(&self, account: &str, key: &[u8]) -> Result<(), VaultError> {
+        Self::entry(account)?
+            .set_secret(key)
+            .map_err(|e| VaultError::Keyring(e.to_string()))
+    }
+
+    fn delete(&self, account: &str) -> Result<(), VaultError> {
+        match Self::entry(account)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(VaultError::Keyring(e.to_string())),
+        }
+    }
+}
+
+/// In-process store, for tests and as a stand-in where no OS store exists.
+#[derive(Debug, Default)]
+pub struct MemoryKeyStore {
+    keys: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl KeyStore for MemoryKeyStore {
+    fn get(&self, account: &str) -> Result<Option<Vec<u8>>, VaultError> {
+        Ok(self.keys.lock().unwrap().get(account).cloned())
+    }
+
+    fn set(&self, account: &str, key: &[u8]) -> Result<(), VaultError> {
+        self.keys
+            .lock()
+            .unwrap()
+            .insert(account.to_string(), key.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, account: &str) -> Result<(), VaultError> {
+        self.keys.lock().unwrap().remove(account);
+        Ok(())
+    }
+}
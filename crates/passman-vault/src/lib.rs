@@ -1,17 +1,41 @@
+pub mod approvals;
 pub mod audit;
+pub mod cache;
+pub mod certificate;
 pub mod credential;
 pub mod crypto;
+pub mod keystore;
+pub mod notifications;
+pub mod profiles;
+pub mod qr;
+pub mod recovery;
+pub mod sink;
+pub mod ssh_config;
 pub mod storage;
+pub mod strength;
+pub mod usage;
 pub mod watcher;
 
+pub use audit::AuditFilter;
+pub use credential::NewCredential;
+pub use keystore::KeyStore;
+pub use profiles::ProfileRegistry;
+pub use sink::AuditSink;
+pub use usage::USAGE_FLUSH_INTERVAL;
+
 use passman_types::{
-    AuditAction, AuditEntry, CredentialKind, CredentialMeta, CredentialSecret, Environment,
-    PolicyRule, VaultFile,
+    ApprovalStatus, AuditAction, AuditEntry, AuditSummary, Category, CredentialKind,
+    CredentialMeta, CredentialSecret, DuressAction, DuressConfig, Environment, LinkedCredential,
+    PendingApproval, PolicyRule, PolicyTemplate, ReencryptProgress, SignedAudit, StorageFormat,
+    VaultFile, VaultProfile, VaultSecurityInfo, VaultStats, WebhookRule,
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use uuid::Uuid;
+use zeroize::Zeroize;
 
 // ── Errors ───────────────────────────────────────────────────────
 
@@ -26,6 +50,60 @@ pub enum VaultError {
     #[error("invalid master password")]
     InvalidPassword,
 
+    #[error("vault password was changed elsewhere; unlock again with the new password")]
+    ReauthRequired,
+
+    #[error("vault was modified by another process; reload and retry")]
+    Conflict,
+
+    #[error("vault handle is read-only")]
+    ReadOnly,
+
+    #[error("master password is too weak: {}", .0.join("; "))]
+    WeakPassword(Vec<String>),
+
+    #[error("{0}")]
+    UnknownEnvironment(String),
+
+    #[error("{0}")]
+    InvalidLink(String),
+
+    #[error("credentials link to it: {}; delete with force to unlink them", .0.join(", "))]
+    HasDependents(Vec<String>),
+
+    #[error("a credential named '{name}' already exists in {environment}")]
+    DuplicateName { name: String, environment: String },
+
+    #[error("unknown category: {0}")]
+    UnknownCategory(String),
+
+    #[error("{0}")]
+    InvalidWebhook(String),
+
+    #[error("invalid certificate: {0}")]
+    InvalidCertificate(String),
+
+    #[error("invalid secret: {0}")]
+    InvalidSecret(String),
+
+    #[error("secret field '{0}' can't be searched")]
+    UnsearchableField(String),
+
+    #[error("unknown profile: {0}")]
+    UnknownProfile(String),
+
+    #[error("{0}")]
+    InvalidProfile(String),
+
+    #[error("{0}")]
+    Recovery(String),
+
+    #[error("{0}")]
+    InvalidDuress(String),
+
+    #[error("keyring error: {0}")]
+    Keyring(String),
+
     #[error("credential not found: {0}")]
     NotFound(Uuid),
 
@@ -34,6 +112,9 @@ pub enum VaultError {
 
     #[error("I/O error: {0}")]
     Io(String),
+
+    #[error("vault file was unreadable ({reason}); recovered from {path}")]
+    RecoveredFromBackup { path: PathBuf, reason: String },
 }
 
 // ── Vault (thread-safe handle) ───────────────────────────────────
@@ -42,37 +123,357 @@ pub enum VaultError {
 #[derive(Clone)]
 pub struct Vault {
     inner: Arc<RwLock<VaultInner>>,
+    /// When this handle last wrote the vault file, so the watcher can skip
+    /// reloading our own writes.
+    last_write: Arc<std::sync::Mutex<Option<Instant>>>,
+    /// Number of successful vault file writes through this handle.
+    saves: Arc<AtomicUsize>,
+    /// Lowest `strength::estimate` score accepted for a master password.
+    min_password_score: u8,
+    /// Argon2 cost used when creating a vault.
+    kdf_params: passman_types::KdfParams,
+    /// AEAD used when creating a vault.
+    cipher: passman_types::Cipher,
+    /// Recently decrypted secrets, cleared on lock.
+    secret_cache: Arc<cache::SecretCache>,
+    /// Credential uses not yet written to disk.
+    pending_usage: Arc<usage::PendingUsage>,
+    /// Set on handles from `read_only_view`; every mutation fails.
+    read_only: bool,
+    /// Where derived keys are remembered for `unlock_from_keyring`.
+    key_store: Option<Arc<dyn KeyStore>>,
+    /// Where audit entries go. The JSONL file sink always comes first.
+    audit_sinks: Vec<Arc<dyn AuditSink>>,
+    /// Also in `audit_sinks`; kept here to repoint it on profile switch.
+    audit_file: Arc<sink::FileSink>,
+    /// Also in `audit_sinks`; kept here to refresh its rules on change.
+    webhooks: Arc<notifications::WebhookSink>,
+    /// Set while unlocked with the duress password: saves stay in memory
+    /// and the audit log and approvals read back empty.
+    decoy: Arc<AtomicBool>,
+    /// Lock epoch on disk when this handle last unlocked or locked. A higher
+    /// one means another process locked the vault since.
+    lock_epoch: Arc<AtomicU64>,
+    /// Set when loading fell back to a backup copy, until
+    /// `take_recovery_warning` collects it.
+    recovery_warning: Arc<std::sync::Mutex<Option<VaultError>>>,
 }
 
+/// Re-encrypt every secret in `data` under a key derived from `password`
+/// with a fresh salt, calling `progress` after each one. Returns the new key
+/// and the updated copy.
+fn rekey(
+    key: &crypto::DerivedKey,
+    data: &VaultFile,
+    password: &str,
+    progress: &dyn Fn(ReencryptProgress),
+) -> Result<(crypto::DerivedKey, VaultFile), VaultError> {
+    let salt = crypto::generate_salt();
+    let new_key = crypto::DerivedKey::new(
+        *crypto::derive_key(password, &salt, &data.kdf_params)?,
+        data.cipher,
+    );
+
+    let mut updated = data.clone();
+    let total = updated.credentials.len();
+    for (i, cred) in updated.credentials.iter_mut().enumerate() {
+        let plaintext = zeroize::Zeroizing::new(key.decrypt(&cred.secret)?);
+        cred.secret = new_key.encrypt(&plaintext)?;
+        progress(ReencryptProgress {
+            processed: i + 1,
+            total,
+        });
+    }
+    updated.salt = salt.to_vec();
+    updated.verification = crypto::create_verification(new_key.as_bytes(), data.cipher)?;
+    // Checked with the old salt, so it can't match any more
+    updated.duress = None;
+    Ok((new_key, updated))
+}
+
+/// Default for `Vault::with_min_password_score`: rejects common passwords,
+/// short ones and simple patterns.
+pub const DEFAULT_MIN_PASSWORD_SCORE: u8 = 2;
+
 struct VaultInner {
     vault_path: PathBuf,
     audit_path: PathBuf,
+    /// Profile name, when opened through one; names the keyring entry.
+    profile: Option<String>,
     state: VaultState,
 }
 
+impl VaultInner {
+    /// Key that authenticates entries in the approvals file.
+    fn approval_key(&self) -> Result<zeroize::Zeroizing<[u8; 32]>, VaultError> {
+        match &self.state {
+            VaultState::Locked => Err(VaultError::Locked),
+            VaultState::Unlocked { key, .. } => Ok(zeroize::Zeroizing::new(
+                crypto::approval_mac_key(key.as_bytes()),
+            )),
+        }
+    }
+
+    /// Keyring entry name: the profile, or the vault path outside profiles.
+    fn key_store_account(&self) -> String {
+        match &self.profile {
+            Some(name) => name.clone(),
+            None => self.vault_path.display().to_string(),
+        }
+    }
+}
+
 enum VaultState {
     Locked,
     Unlocked {
         key: crypto::DerivedKey,
-        data: VaultFile,
+        /// Boxed: a `VaultFile` is large next to the empty `Locked`.
+        data: Box<VaultFile>,
     },
 }
 
 impl Vault {
     /// Create a new Vault handle pointing at the given paths.
     pub fn new(vault_path: PathBuf, audit_path: PathBuf) -> Self {
+        Self::with_paths(vault_path, audit_path, None)
+    }
+
+    fn with_paths(vault_path: PathBuf, audit_path: PathBuf, profile: Option<String>) -> Self {
+        let audit_file = Arc::new(sink::FileSink::new(audit_path.clone()));
+        let webhooks = Arc::new(notifications::WebhookSink::default());
         Self {
+            audit_sinks: vec![audit_file.clone(), webhooks.clone()],
+            audit_file,
+            webhooks,
             inner: Arc::new(RwLock::new(VaultInner {
                 vault_path,
                 audit_path,
+                profile,
                 state: VaultState::Locked,
             })),
+            last_write: Arc::new(std::sync::Mutex::new(None)),
+            saves: Arc::new(AtomicUsize::new(0)),
+            min_password_score: DEFAULT_MIN_PASSWORD_SCORE,
+            kdf_params: passman_types::KdfParams::default(),
+            cipher: passman_types::Cipher::default(),
+            secret_cache: Arc::new(cache::SecretCache::new(cache::DEFAULT_SECRET_CACHE_SIZE)),
+            pending_usage: Arc::default(),
+            read_only: false,
+            decoy: Arc::default(),
+            lock_epoch: Arc::default(),
+            recovery_warning: Arc::default(),
+            key_store: None,
+        }
+    }
+
+    /// A handle sharing this vault's state that can read metadata, decrypt
+    /// secrets for proxy tools and write audit entries, but fails every
+    /// mutation with `VaultError::ReadOnly`. Locking still works.
+    pub fn read_only_view(&self) -> Self {
+        Self {
+            read_only: true,
+            ..self.clone()
+        }
+    }
+
+    /// Whether this handle came from `read_only_view`.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn check_writable(&self) -> Result<(), VaultError> {
+        if self.read_only {
+            return Err(VaultError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// Also send every audit entry to `sink`, after the built-in ones.
+    /// `read_audit` still reads only the JSONL file. Add sinks before
+    /// cloning the handle; clones made earlier won't see the new sink.
+    pub fn add_audit_sink(&mut self, sink: Arc<dyn AuditSink>) {
+        self.audit_sinks.push(sink);
+    }
+
+    /// Set how many decrypted secrets are cached (0 disables the cache).
+    pub fn with_secret_cache_size(mut self, size: usize) -> Self {
+        self.secret_cache = Arc::new(cache::SecretCache::new(size));
+        self
+    }
+
+    /// Number of decrypted secrets currently cached.
+    pub fn cached_secret_count(&self) -> usize {
+        self.secret_cache.len()
+    }
+
+    /// Remember the derived key in `store` after each password unlock, so
+    /// `unlock_from_keyring` can open this profile unattended.
+    pub fn with_key_store(mut self, store: Arc<dyn KeyStore>) -> Self {
+        self.key_store = Some(store);
+        self
+    }
+
+    /// Set the minimum strength score (0–4) a new master password must reach.
+    pub fn with_min_password_score(mut self, score: u8) -> Self {
+        self.min_password_score = score;
+        self
+    }
+
+    /// Set the Argon2 parameters used by `create`. Existing vaults keep the
+    /// parameters stored in their file.
+    pub fn with_kdf_params(mut self, params: passman_types::KdfParams) -> Self {
+        self.kdf_params = params;
+        self
+    }
+
+    /// Set the cipher used by `create`. Existing vaults keep the cipher
+    /// stored in their file.
+    pub fn with_cipher(mut self, cipher: passman_types::Cipher) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    /// Reject a master password below the minimum strength, unless `allow_weak`.
+    fn check_password_strength(&self, password: &str, allow_weak: bool) -> Result<(), VaultError> {
+        if allow_weak {
+            return Ok(());
+        }
+        let report = strength::estimate(password);
+        if report.score < self.min_password_score {
+            let mut reasons = report.warnings;
+            if reasons.is_empty() {
+                reasons.push("too easy to guess".to_string());
+            }
+            return Err(VaultError::WeakPassword(reasons));
         }
+        Ok(())
     }
 
     /// Create a Vault with default paths (~/.passman/).
     pub fn with_defaults() -> Self {
-        Self::new(storage::default_vault_path(), storage::default_audit_path())
+        Self::with_paths(
+            storage::default_vault_path(),
+            storage::default_audit_path(),
+            Some(profiles::DEFAULT_PROFILE.to_string()),
+        )
+    }
+
+    /// Create a Vault for a profile's paths.
+    pub fn from_profile(profile: &VaultProfile) -> Self {
+        Self::with_paths(
+            profile.vault_path.clone(),
+            profile.audit_path.clone(),
+            Some(profile.name.clone()),
+        )
+    }
+
+    /// Name of the profile this handle has open, if it came from one.
+    pub async fn profile(&self) -> Option<String> {
+        self.inner.read().await.profile.clone()
+    }
+
+    /// Create a Vault for a profile in the registry at ~/.passman/profiles.json.
+    pub fn open_profile(name: &str) -> Result<Self, VaultError> {
+        let profile = ProfileRegistry::open_default()?.get(name)?;
+        Ok(Self::from_profile(&profile))
+    }
+
+    /// Lock this vault and point the handle (and every clone of it) at
+    /// another profile's files, so only one profile is ever unlocked.
+    /// Watchers started for the old path keep watching it.
+    pub async fn switch_profile(&self, profile: &VaultProfile) {
+        let _ = self.flush_usage().await;
+        // Lock and repoint under one write lock so nothing unlocks in between
+        let mut inner = self.inner.write().await;
+        inner.state = VaultState::Locked;
+        self.decoy.store(false, Ordering::Relaxed);
+        self.secret_cache.clear();
+        self.pending_usage.clear();
+        self.webhooks.refresh(None);
+        inner.vault_path = profile.vault_path.clone();
+        inner.audit_path = profile.audit_path.clone();
+        inner.profile = Some(profile.name.clone());
+        self.audit_file.set_path(profile.audit_path.clone());
+        *self.last_write.lock().unwrap() = None;
+    }
+
+    /// Write the vault file and record the write time.
+    ///
+    /// Fails with `Conflict` if another process saved since `data` was
+    /// loaded. In that case `data` is replaced with the on-disk copy (when it
+    /// is still readable with our key) so the unsaved change is dropped
+    /// rather than left diverging in memory. Unsaved credential uses are
+    /// carried over to the reloaded copy.
+    fn save(&self, path: &Path, data: &mut VaultFile) -> Result<(), VaultError> {
+        if self.is_decoy() {
+            return Ok(());
+        }
+        match storage::save_vault_checked(path, data) {
+            Ok(()) => {
+                *self.last_write.lock().unwrap() = Some(Instant::now());
+                self.saves.fetch_add(1, Ordering::Relaxed);
+                self.pending_usage.clear();
+                self.webhooks.refresh(Some(data));
+                Ok(())
+            }
+            Err(VaultError::Conflict) => {
+                let on_disk = storage::load_vault(path)?;
+                if on_disk.salt == data.salt && on_disk.kdf_params == data.kdf_params {
+                    *data = on_disk;
+                    self.pending_usage.apply(data);
+                    self.webhooks.refresh(Some(data));
+                }
+                Err(VaultError::Conflict)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Load the vault file, falling back to a backup copy if it's corrupt.
+    /// A recovery is kept for `take_recovery_warning`.
+    fn load_vault_file(&self, path: &Path) -> Result<VaultFile, VaultError> {
+        let (vault_file, warning) = storage::load_vault_or_backup(path)?;
+        if let Some(warning) = warning {
+            tracing::warn!("{warning}");
+            *self.recovery_warning.lock().unwrap() = Some(warning);
+        }
+        Ok(vault_file)
+    }
+
+    /// The `RecoveredFromBackup` error from the last load that found the
+    /// vault file corrupt and restored a backup, if not yet taken. The
+    /// unlock itself succeeds, so callers check this afterwards to tell the
+    /// user that recent changes may be missing.
+    pub fn take_recovery_warning(&self) -> Option<VaultError> {
+        self.recovery_warning.lock().unwrap().take()
+    }
+
+    /// Whether this handle wrote the vault file within the last `window`.
+    pub fn wrote_within(&self, window: Duration) -> bool {
+        self.last_write
+            .lock()
+            .unwrap()
+            .is_some_and(|at| at.elapsed() < window)
+    }
+
+    /// Number of times this handle has written the vault file.
+    pub fn save_count(&self) -> usize {
+        self.saves.load(Ordering::Relaxed)
+    }
+
+    /// Hand entries to every audit sink. All sinks are tried; the first
+    /// failure is returned.
+    async fn write_audit(&self, entries: &[AuditEntry]) -> Result<(), VaultError> {
+        let mut result = Ok(());
+        for sink in &self.audit_sinks {
+            if let Err(e) = sink.write_all(entries).await {
+                tracing::warn!("Audit sink failed: {e}");
+                if result.is_ok() {
+                    result = Err(e);
+                }
+            }
+        }
+        result
     }
 
     /// Get the vault file path.
@@ -80,34 +481,55 @@ impl Vault {
         self.inner.read().await.vault_path.clone()
     }
 
-    /// Create a new vault file with the given master password.
-    pub async fn create(&self, password: &str) -> Result<(), VaultError> {
+    /// Get the audit log path.
+    pub async fn audit_path(&self) -> PathBuf {
+        self.inner.read().await.audit_path.clone()
+    }
+
+    /// Create a new vault file with the given master password. Weak
+    /// passwords are rejected unless `allow_weak` is set.
+    pub async fn create(&self, password: &str, allow_weak: bool) -> Result<(), VaultError> {
+        self.check_writable()?;
         let inner = self.inner.read().await;
         if storage::vault_exists(&inner.vault_path) {
             return Err(VaultError::AlreadyExists(inner.vault_path.clone()));
         }
         drop(inner);
+        self.check_password_strength(password, allow_weak)?;
 
         let salt = crypto::generate_salt();
-        let params = passman_types::KdfParams::default();
+        let params = self.kdf_params.clone();
         let key_bytes = crypto::derive_key(password, &salt, &params)?;
-        let verification = crypto::create_verification(&key_bytes)?;
+        let verification = crypto::create_verification(&key_bytes, self.cipher)?;
 
-        let vault_file = VaultFile {
+        let mut vault_file = VaultFile {
             version: 1,
+            revision: 0,
             kdf_params: params,
             salt: salt.to_vec(),
+            cipher: self.cipher,
             verification,
             credentials: vec![],
             categories: vec![],
             policies: vec![],
+            policy_templates: vec![],
+            environments: vec![],
+            strict_environments: false,
+            unique_names: false,
+            storage_format: Default::default(),
+            compressed: false,
+            webhooks: vec![],
+            duress: None,
         };
 
         let mut inner = self.inner.write().await;
-        storage::save_vault(&inner.vault_path, &vault_file)?;
+        self.decoy.store(false, Ordering::Relaxed);
+        self.save(&inner.vault_path, &mut vault_file)?;
+        self.remember_key(&inner.key_store_account(), &key_bytes);
+        self.note_lock_epoch(&inner.vault_path);
         inner.state = VaultState::Unlocked {
-            key: crypto::DerivedKey::new(key_bytes),
-            data: vault_file,
+            key: crypto::DerivedKey::new(*key_bytes, self.cipher),
+            data: Box::new(vault_file),
         };
 
         Ok(())
@@ -116,29 +538,384 @@ impl Vault {
     /// Unlock the vault with the master password.
     pub async fn unlock(&self, password: &str) -> Result<usize, VaultError> {
         let inner = self.inner.read().await;
-        let vault_file = storage::load_vault(&inner.vault_path)?;
+        let vault_file = self.load_vault_file(&inner.vault_path)?;
         drop(inner);
 
         let key_bytes = crypto::derive_key(password, &vault_file.salt, &vault_file.kdf_params)?;
 
-        if !crypto::verify_password(&key_bytes, &vault_file.verification)? {
+        // Always check both blobs so timing doesn't show whether a duress
+        // password is set
+        let duress_blob = vault_file
+            .duress
+            .as_ref()
+            .map_or(&vault_file.verification, |d| &d.verification);
+        let cipher = vault_file.cipher;
+        let is_master = crypto::verify_password(&key_bytes, cipher, &vault_file.verification)?;
+        let is_duress = crypto::verify_password(&key_bytes, cipher, duress_blob)?
+            && vault_file.duress.is_some();
+        if is_duress {
+            return Ok(self.unlock_duress(&key_bytes, vault_file).await);
+        }
+        if !is_master {
+            return Err(VaultError::InvalidPassword);
+        }
+        crypto::check_nonce_reuse(&vault_file)?;
+
+        let count = self.set_unlocked(&key_bytes, vault_file).await;
+        self.remember_key(&self.key_store_account().await, &key_bytes);
+        Ok(count)
+    }
+
+    /// Unlock with the key saved by an earlier password unlock of this
+    /// profile (see `with_key_store`), without the master password.
+    pub async fn unlock_from_keyring(&self) -> Result<usize, VaultError> {
+        let store = self
+            .key_store
+            .clone()
+            .ok_or_else(|| VaultError::Keyring("no key store configured".to_string()))?;
+        let account = self.key_store_account().await;
+        let inner = self.inner.read().await;
+        let vault_file = self.load_vault_file(&inner.vault_path)?;
+        drop(inner);
+
+        let mut stored = store
+            .get(&account)?
+            .ok_or_else(|| VaultError::Keyring(format!("no key saved for '{account}'")))?;
+        let key_bytes = <[u8; 32]>::try_from(stored.as_slice()).map(zeroize::Zeroizing::new);
+        stored.zeroize();
+        let key_bytes = key_bytes
+            .map_err(|_| VaultError::Keyring(format!("saved key for '{account}' is malformed")))?;
+        // A key saved before a password change no longer opens the vault
+        if !crypto::verify_password(&key_bytes, vault_file.cipher, &vault_file.verification)? {
             return Err(VaultError::InvalidPassword);
         }
+        crypto::check_nonce_reuse(&vault_file)?;
+
+        let count = self.set_unlocked(&key_bytes, vault_file).await;
+        let _ = self
+            .write_audit(&[AuditEntry {
+                timestamp: chrono::Utc::now(),
+                credential_id: None,
+                credential_name: None,
+                action: AuditAction::KeyringUnlock,
+                tool: "keyring".to_string(),
+                success: true,
+                details: Some(format!("unlocked with the key saved for '{account}'")),
+            }])
+            .await;
+        Ok(count)
+    }
+
+    /// Remove this profile's saved key, if any.
+    pub async fn forget_keyring_key(&self) -> Result<(), VaultError> {
+        match &self.key_store {
+            Some(store) => store.delete(&self.key_store_account().await),
+            None => Ok(()),
+        }
+    }
+
+    async fn key_store_account(&self) -> String {
+        self.inner.read().await.key_store_account()
+    }
+
+    /// Save `key` to the key store, if one is configured. Failures are
+    /// logged; they never fail the unlock. The decoy's key is never saved.
+    fn remember_key(&self, account: &str, key: &[u8; 32]) {
+        let Some(store) = &self.key_store else {
+            return;
+        };
+        if self.is_decoy() {
+            return;
+        }
+        if let Err(e) = store.set(account, key) {
+            tracing::warn!("Failed to save key for '{account}' to the keyring: {e}");
+        }
+    }
 
+    /// Install `vault_file` as the unlocked state. Returns its credential count.
+    async fn set_unlocked(&self, key_bytes: &[u8; 32], vault_file: VaultFile) -> usize {
         let count = vault_file.credentials.len();
+        self.webhooks.refresh(Some(&vault_file));
+        let mut inner = self.inner.write().await;
+        self.decoy.store(false, Ordering::Relaxed);
+        self.note_lock_epoch(&inner.vault_path);
+        inner.state = VaultState::Unlocked {
+            key: crypto::DerivedKey::new(*key_bytes, vault_file.cipher),
+            data: Box::new(vault_file),
+        };
+        count
+    }
+
+    /// Remember the current lock epoch, so only locks after this point
+    /// lock this handle. Called with the state write lock held.
+    fn note_lock_epoch(&self, vault_path: &Path) {
+        self.lock_epoch
+            .store(storage::read_lock_epoch(vault_path), Ordering::Relaxed);
+    }
+
+    /// Open an empty decoy in place of the real vault (shredding the real
+    /// file first if configured) and quietly log `DuressTriggered`. Looks
+    /// like a successful unlock of an empty vault.
+    async fn unlock_duress(&self, key_bytes: &[u8; 32], mut real: VaultFile) -> usize {
+        let Some(duress) = real.duress.take() else {
+            return 0;
+        };
+        let decoy = VaultFile {
+            verification: duress.verification,
+            credentials: vec![],
+            categories: vec![],
+            policies: vec![],
+            policy_templates: vec![],
+            environments: vec![],
+            webhooks: vec![],
+            ..real
+        };
+
+        let mut inner = self.inner.write().await;
+        if duress.action == DuressAction::Wipe {
+            let _ = storage::shred_vault(&inner.vault_path);
+        }
+        self.decoy.store(true, Ordering::Relaxed);
+        self.secret_cache.clear();
+        self.pending_usage.clear();
+        self.webhooks.refresh(None);
+        self.note_lock_epoch(&inner.vault_path);
+        inner.state = VaultState::Unlocked {
+            key: crypto::DerivedKey::new(*key_bytes, decoy.cipher),
+            data: Box::new(decoy),
+        };
+        drop(inner);
+
+        let details = match duress.action {
+            DuressAction::Decoy => "decoy vault opened",
+            DuressAction::Wipe => "vault file shredded",
+        };
+        let _ = self
+            .write_audit(&[AuditEntry {
+                timestamp: chrono::Utc::now(),
+                credential_id: None,
+                credential_name: None,
+                action: AuditAction::DuressTriggered,
+                tool: "vault_unlock".to_string(),
+                success: true,
+                details: Some(details.to_string()),
+            }])
+            .await;
+        0
+    }
+
+    /// Whether this vault was unlocked with the duress password.
+    fn is_decoy(&self) -> bool {
+        self.decoy.load(Ordering::Relaxed)
+    }
+
+    /// Set a duress password: unlocking with it opens an empty decoy vault
+    /// (or shreds the real one first, per `action`) instead of failing.
+    /// Changing the master password clears it.
+    pub async fn set_duress_password(
+        &self,
+        password: &str,
+        action: DuressAction,
+    ) -> Result<(), VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let VaultState::Unlocked { data, .. } = &mut inner.state else {
+            return Err(VaultError::Locked);
+        };
+
+        let key = crypto::derive_key(password, &data.salt, &data.kdf_params)?;
+        let result = if crypto::verify_password(&key, data.cipher, &data.verification)? {
+            Err(VaultError::InvalidDuress(
+                "duress password must differ from the master password".to_string(),
+            ))
+        } else {
+            crypto::create_verification(&key, data.cipher)
+        };
+        data.duress = Some(DuressConfig {
+            verification: result?,
+            action,
+        });
+        self.save(&vault_path, data)
+    }
+
+    /// Remove the duress password.
+    pub async fn clear_duress_password(&self) -> Result<(), VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let VaultState::Unlocked { data, .. } = &mut inner.state else {
+            return Err(VaultError::Locked);
+        };
+        data.duress = None;
+        self.save(&vault_path, data)
+    }
+
+    /// Change the master password. Every credential is re-encrypted under a
+    /// key derived from the new password and a fresh salt. A weak new
+    /// password is rejected unless `allow_weak` is set.
+    pub async fn change_password(
+        &self,
+        current: &str,
+        new: &str,
+        allow_weak: bool,
+    ) -> Result<(), VaultError> {
+        self.change_password_with_progress(current, new, allow_weak, |_| {})
+            .await
+    }
+
+    /// `change_password`, calling `progress` after each secret is
+    /// re-encrypted so a UI can show how far a large vault has got.
+    pub async fn change_password_with_progress(
+        &self,
+        current: &str,
+        new: &str,
+        allow_weak: bool,
+        progress: impl Fn(ReencryptProgress) + Send + Sync,
+    ) -> Result<(), VaultError> {
+        self.check_writable()?;
+        self.check_password_strength(new, allow_weak)?;
         let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let VaultState::Unlocked { key, data } = &inner.state else {
+            return Err(VaultError::Locked);
+        };
+
+        let current_key = crypto::derive_key(current, &data.salt, &data.kdf_params)?;
+        if !crypto::verify_password(&current_key, data.cipher, &data.verification)? {
+            return Err(VaultError::InvalidPassword);
+        }
+
+        let (new_key, mut updated) = rekey(key, data, new, &progress)?;
+        self.save(&vault_path, &mut updated)?;
+        self.remember_key(&inner.key_store_account(), new_key.as_bytes());
         inner.state = VaultState::Unlocked {
-            key: crypto::DerivedKey::new(key_bytes),
-            data: vault_file,
+            key: new_key,
+            data: Box::new(updated),
+        };
+        // Every nonce changed, so nothing cached can be hit again
+        self.secret_cache.clear();
+
+        Ok(())
+    }
+
+    /// Split the master key into `shares` recovery strings, any `threshold`
+    /// of which can later reset the master password with
+    /// `recover_with_shares`. Requires the current password. Changing the
+    /// master password invalidates shares made before it.
+    pub async fn generate_recovery_shares(
+        &self,
+        password: &str,
+        threshold: u8,
+        shares: u8,
+    ) -> Result<Vec<String>, VaultError> {
+        let inner = self.inner.read().await;
+        let VaultState::Unlocked { data, .. } = &inner.state else {
+            return Err(VaultError::Locked);
         };
+        let key = crypto::derive_key(password, &data.salt, &data.kdf_params)?;
+        if !crypto::verify_password(&key, data.cipher, &data.verification)? {
+            return Err(VaultError::InvalidPassword);
+        }
+        recovery::split(&key, &data.salt, threshold, shares)
+    }
+
+    /// Rebuild the master key from recovery shares, re-encrypt the vault
+    /// under `new_password` and leave it unlocked. Works while locked, which
+    /// is the point. Old shares stop working once this succeeds.
+    pub async fn recover_with_shares(
+        &self,
+        shares: &[String],
+        new_password: &str,
+        allow_weak: bool,
+    ) -> Result<usize, VaultError> {
+        self.check_writable()?;
+        self.check_password_strength(new_password, allow_weak)?;
+        let _ = self.flush_usage().await;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let data = self.load_vault_file(&vault_path)?;
+
+        let key = crypto::DerivedKey::new(recovery::combine(shares, &data.salt)?, data.cipher);
+        if !crypto::verify_password(key.as_bytes(), data.cipher, &data.verification)? {
+            return Err(VaultError::Recovery(
+                "shares do not reconstruct this vault's key".to_string(),
+            ));
+        }
 
+        // Leave any decoy opened by a duress unlock: saves there never reach
+        // the real file
+        self.clear_unlocked(&mut inner);
+        let (new_key, mut updated) = rekey(&key, &data, new_password, &|_| {})?;
+        self.save(&vault_path, &mut updated)?;
+        self.remember_key(&inner.key_store_account(), new_key.as_bytes());
+        let count = updated.credentials.len();
+        self.note_lock_epoch(&vault_path);
+        inner.state = VaultState::Unlocked {
+            key: new_key,
+            data: Box::new(updated),
+        };
+        self.secret_cache.clear();
         Ok(count)
     }
 
-    /// Lock the vault, zeroing the key from memory.
+    /// Lock the vault, zeroing the key from memory. Pending credential uses
+    /// are flushed first; if that fails they are dropped. If this handle was
+    /// unlocked, other processes with the vault open are told to lock too.
     pub async fn lock(&self) {
+        let _ = self.flush_usage().await;
+        let mut inner = self.inner.write().await;
+        let was_unlocked = matches!(inner.state, VaultState::Unlocked { .. });
+        self.clear_unlocked(&mut inner);
+        if !was_unlocked {
+            return;
+        }
+        // Tell other processes with this vault open to lock too
+        match storage::bump_lock_epoch(&inner.vault_path) {
+            Ok(epoch) => self.lock_epoch.store(epoch, Ordering::Relaxed),
+            Err(e) => tracing::warn!("Failed to signal vault lock to other processes: {e}"),
+        }
+    }
+
+    /// Like `lock`, but only for this handle and its clones: other
+    /// processes are not told. For a process that is exiting, where the
+    /// user hasn't asked for the vault to be locked everywhere.
+    pub async fn lock_local(&self) {
+        let _ = self.flush_usage().await;
+        let mut inner = self.inner.write().await;
+        self.clear_unlocked(&mut inner);
+    }
+
+    /// Lock this handle if another process has locked the vault since it
+    /// was unlocked. The watcher calls this when the lock epoch file
+    /// changes. Returns true if it locked.
+    pub async fn sync_lock_epoch(&self) -> bool {
+        if !self.locked_elsewhere(&*self.inner.read().await) {
+            return false;
+        }
+        let _ = self.flush_usage().await;
         let mut inner = self.inner.write().await;
+        // Checked again: an unlock may have read the new epoch meanwhile
+        if !self.locked_elsewhere(&inner) {
+            return false;
+        }
+        self.clear_unlocked(&mut inner);
+        true
+    }
+
+    /// Whether this handle is unlocked but the vault was locked elsewhere
+    /// after it last read the lock epoch.
+    fn locked_elsewhere(&self, inner: &VaultInner) -> bool {
+        matches!(inner.state, VaultState::Unlocked { .. })
+            && storage::read_lock_epoch(&inner.vault_path) > self.lock_epoch.load(Ordering::Relaxed)
+    }
+
+    fn clear_unlocked(&self, inner: &mut VaultInner) {
         inner.state = VaultState::Locked;
+        self.decoy.store(false, Ordering::Relaxed);
+        self.secret_cache.clear();
+        self.pending_usage.clear();
+        self.webhooks.refresh(None);
     }
 
     /// Check if the vault is currently unlocked.
@@ -162,6 +939,57 @@ impl Vault {
         }
     }
 
+    /// Format version, KDF settings and per-kind credential counts.
+    pub async fn security_info(&self) -> Result<VaultSecurityInfo, VaultError> {
+        let inner = self.inner.read().await;
+        let VaultState::Unlocked { data, .. } = &inner.state else {
+            return Err(VaultError::Locked);
+        };
+        let mut credentials_by_kind = std::collections::HashMap::new();
+        for cred in &data.credentials {
+            *credentials_by_kind.entry(cred.meta.kind).or_insert(0) += 1;
+        }
+        Ok(VaultSecurityInfo {
+            format_version: data.version,
+            kdf_algorithm: crypto::KDF_ALGORITHM.to_string(),
+            kdf_params: data.kdf_params.clone(),
+            cipher: data.cipher,
+            storage_format: data.storage_format,
+            compressed: data.compressed,
+            credentials_by_kind,
+        })
+    }
+
+    /// Credential counts by kind and environment, plus tag and policy totals.
+    /// Reads metadata only; nothing is decrypted.
+    pub async fn stats(&self) -> Result<VaultStats, VaultError> {
+        let inner = self.inner.read().await;
+        let VaultState::Unlocked { data, .. } = &inner.state else {
+            return Err(VaultError::Locked);
+        };
+        let with_policy: std::collections::HashSet<Uuid> =
+            data.policies.iter().map(|p| p.credential_id).collect();
+        let mut tags = std::collections::HashSet::new();
+        let mut stats = VaultStats {
+            total: data.credentials.len(),
+            ..Default::default()
+        };
+        for cred in &data.credentials {
+            let meta = &cred.meta;
+            *stats.by_kind.entry(meta.kind).or_insert(0) += 1;
+            *stats
+                .by_environment
+                .entry(meta.environment.to_string())
+                .or_insert(0) += 1;
+            tags.extend(meta.tags.iter().map(String::as_str));
+            if with_policy.contains(&meta.id) {
+                stats.with_policy += 1;
+            }
+        }
+        stats.total_tags = tags.len();
+        Ok(stats)
+    }
+
     /// Store a new credential. Returns the new credential ID.
     pub async fn store_credential(
         &self,
@@ -172,26 +1000,34 @@ impl Vault {
         notes: Option<String>,
         secret: &CredentialSecret,
     ) -> Result<Uuid, VaultError> {
+        self.check_writable()?;
         let mut inner = self.inner.write().await;
         let vault_path = inner.vault_path.clone();
-        let audit_path = inner.audit_path.clone();
 
         let (key, data) = match &mut inner.state {
             VaultState::Locked => return Err(VaultError::Locked),
             VaultState::Unlocked { key, data } => (key, data),
         };
 
-        let id = credential::add_credential(data, key, name, kind, environment, tags, notes, secret)?;
+        credential::check_environment(data, &environment)?;
+        credential::validate_secret(secret)?;
+        let fields = credential::CredentialFields {
+            name,
+            kind,
+            environment,
+            tags,
+            notes,
+        };
+        let id = credential::add_credential(data, key, fields, secret)?;
         let cred_name = data
             .credentials
             .iter()
             .find(|c| c.meta.id == id)
             .map(|c| c.meta.name.clone());
-        storage::save_vault(&vault_path, data)?;
+        self.save(&vault_path, data)?;
 
-        let _ = audit::append_entry(
-            &audit_path,
-            &AuditEntry {
+        let _ = self
+            .write_audit(&[AuditEntry {
                 timestamp: chrono::Utc::now(),
                 credential_id: Some(id),
                 credential_name: cred_name,
@@ -199,12 +1035,97 @@ impl Vault {
                 tool: "credential_store".to_string(),
                 success: true,
                 details: None,
-            },
-        );
+            }])
+            .await;
 
         Ok(id)
     }
 
+    /// Store many credentials with one vault write and one audit append.
+    /// Returns the new IDs in input order. Nothing is kept if any entry fails
+    /// or the save fails.
+    pub async fn store_credentials_batch(
+        &self,
+        items: Vec<NewCredential>,
+    ) -> Result<Vec<Uuid>, VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+
+        let (key, data) = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { key, data } => (key, data),
+        };
+
+        for item in &items {
+            credential::check_environment(data, &item.environment)?;
+            credential::validate_secret(&item.secret)?;
+        }
+
+        let mut ids = Vec::with_capacity(items.len());
+        let mut entries = Vec::with_capacity(items.len());
+        let mut result = Ok(());
+        for item in items {
+            let fields = credential::CredentialFields {
+                name: item.name.clone(),
+                kind: item.kind,
+                environment: item.environment,
+                tags: item.tags,
+                notes: item.notes,
+            };
+            match credential::add_credential(data, key, fields, &item.secret) {
+                Ok(id) => {
+                    ids.push(id);
+                    entries.push(AuditEntry {
+                        timestamp: chrono::Utc::now(),
+                        credential_id: Some(id),
+                        credential_name: Some(item.name),
+                        action: AuditAction::CredentialStore,
+                        tool: "credential_store".to_string(),
+                        success: true,
+                        details: Some("batch".to_string()),
+                    });
+                }
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        if result.is_ok() && !ids.is_empty() {
+            result = self.save(&vault_path, data);
+        }
+        if let Err(e) = result {
+            data.credentials.retain(|c| !ids.contains(&c.meta.id));
+            return Err(e);
+        }
+
+        let _ = self.write_audit(&entries).await;
+        Ok(ids)
+    }
+
+    /// Import every host in an OpenSSH client config as an `SshKey`
+    /// credential named after its alias, reading private keys from
+    /// `keys_dir` unless the config names another path. Hosts with no
+    /// readable key are skipped. Stored as one batch; returns the new IDs.
+    pub async fn import_ssh_config(
+        &self,
+        config_path: &Path,
+        keys_dir: &Path,
+    ) -> Result<Vec<Uuid>, VaultError> {
+        let contents = std::fs::read_to_string(config_path).map_err(|e| {
+            VaultError::Io(format!("failed to read {}: {e}", config_path.display()))
+        })?;
+        let hosts = ssh_config::parse(&contents);
+        let items = ssh_config::to_credentials(
+            &hosts,
+            keys_dir,
+            &storage::dirs_home(),
+            &ssh_config::local_user(),
+        );
+        self.store_credentials_batch(items).await
+    }
+
     /// Update an existing credential's metadata and/or secret. Returns the credential ID.
     pub async fn update_credential(
         &self,
@@ -215,25 +1136,25 @@ impl Vault {
         notes: Option<Option<String>>,
         secret: Option<&CredentialSecret>,
     ) -> Result<Uuid, VaultError> {
+        self.check_writable()?;
         let mut inner = self.inner.write().await;
         let vault_path = inner.vault_path.clone();
-        let audit_path = inner.audit_path.clone();
 
         let (key, data) = match &mut inner.state {
             VaultState::Locked => return Err(VaultError::Locked),
             VaultState::Unlocked { key, data } => (key, data),
         };
 
+        if let Some(ref environment) = environment {
+            credential::check_environment(data, environment)?;
+        }
+        if let Some(secret) = secret {
+            credential::validate_secret(secret)?;
+        }
+
         // Update metadata if any fields provided
         if name.is_some() || environment.is_some() || tags.is_some() || notes.is_some() {
-            credential::update_credential_meta(
-                data,
-                id,
-                name,
-                environment,
-                tags,
-                notes,
-            )?;
+            credential::update_credential_meta(data, id, name, environment, tags, notes)?;
         }
 
         // Update secret if provided
@@ -247,11 +1168,10 @@ impl Vault {
             .find(|c| c.meta.id == id)
             .map(|c| c.meta.name.clone());
 
-        storage::save_vault(&vault_path, data)?;
+        self.save(&vault_path, data)?;
 
-        let _ = audit::append_entry(
-            &audit_path,
-            &AuditEntry {
+        let _ = self
+            .write_audit(&[AuditEntry {
                 timestamp: chrono::Utc::now(),
                 credential_id: Some(id),
                 credential_name: cred_name,
@@ -259,60 +1179,215 @@ impl Vault {
                 tool: "credential_update".to_string(),
                 success: true,
                 details: None,
-            },
-        );
+            }])
+            .await;
 
         Ok(id)
     }
 
-    /// Get credential metadata by ID.
-    pub async fn get_credential_meta(&self, id: Uuid) -> Result<CredentialMeta, VaultError> {
-        let inner = self.inner.read().await;
-        match &inner.state {
-            VaultState::Locked => Err(VaultError::Locked),
-            VaultState::Unlocked { data, .. } => {
-                credential::get_credential_meta(data, id)
-                    .cloned()
-                    .ok_or(VaultError::NotFound(id))
-            }
+    /// Set one field of a `Custom` credential, or remove it when `value` is
+    /// `None`, without resending the others. Errors for any other kind.
+    pub async fn update_custom_field(
+        &self,
+        id: Uuid,
+        field: &str,
+        value: Option<String>,
+    ) -> Result<(), VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+
+        let (key, data) = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { key, data } => (key, data),
+        };
+
+        let removed = value.is_none();
+        credential::update_custom_field(data, key, id, field, value)?;
+        let cred_name = credential::get_credential_meta(data, id).map(|m| m.name.clone());
+        self.save(&vault_path, data)?;
+
+        let _ = self
+            .write_audit(&[AuditEntry {
+                timestamp: chrono::Utc::now(),
+                credential_id: Some(id),
+                credential_name: cred_name,
+                action: AuditAction::CredentialUpdate,
+                tool: "credential_update_field".to_string(),
+                success: true,
+                details: Some(if removed {
+                    format!("removed field '{field}'")
+                } else {
+                    format!("set field '{field}'")
+                }),
+            }])
+            .await;
+
+        Ok(())
+    }
+
+    /// Duplicate a credential under a new name, optionally in another
+    /// environment. The source's policy is not copied. Returns the new ID.
+    pub async fn clone_credential(
+        &self,
+        id: Uuid,
+        new_name: String,
+        new_environment: Option<Environment>,
+    ) -> Result<Uuid, VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+
+        let (key, data) = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { key, data } => (key, data),
+        };
+
+        if let Some(ref environment) = new_environment {
+            credential::check_environment(data, environment)?;
         }
+        let new_id =
+            credential::clone_credential(data, key, id, new_name.clone(), new_environment)?;
+        self.save(&vault_path, data)?;
+
+        let _ = self
+            .write_audit(&[AuditEntry {
+                timestamp: chrono::Utc::now(),
+                credential_id: Some(new_id),
+                credential_name: Some(new_name),
+                action: AuditAction::CredentialStore,
+                tool: "credential_clone".to_string(),
+                success: true,
+                details: Some(format!("cloned from {id}")),
+            }])
+            .await;
+
+        Ok(new_id)
     }
 
-    /// Get a credential's decrypted secret.
+    /// Get credential metadata by ID.
+    pub async fn get_credential_meta(&self, id: Uuid) -> Result<CredentialMeta, VaultError> {
+        let inner = self.inner.read().await;
+        match &inner.state {
+            VaultState::Locked => Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => credential::get_credential_meta(data, id)
+                .cloned()
+                .ok_or(VaultError::NotFound(id)),
+        }
+    }
+
+    /// Get a credential's decrypted secret, wiped when the caller drops it.
     pub async fn get_credential_secret(
         &self,
         id: Uuid,
-    ) -> Result<CredentialSecret, VaultError> {
+    ) -> Result<zeroize::Zeroizing<CredentialSecret>, VaultError> {
         let inner = self.inner.read().await;
         match &inner.state {
             VaultState::Locked => Err(VaultError::Locked),
             VaultState::Unlocked { key, data } => {
-                credential::get_credential_secret(data, key, id)
+                let nonce = &data
+                    .credentials
+                    .iter()
+                    .find(|c| c.meta.id == id)
+                    .ok_or(VaultError::NotFound(id))?
+                    .secret
+                    .nonce;
+                if let Some(secret) = self.secret_cache.get(id, nonce) {
+                    return Ok(secret);
+                }
+                let secret =
+                    zeroize::Zeroizing::new(credential::get_credential_secret(data, key, id)?);
+                self.secret_cache.insert(id, nonce, (*secret).clone());
+                Ok(secret)
             }
         }
     }
 
+    /// Count a successful use of a credential by a proxy tool. The change is
+    /// made in memory only; `flush_usage` writes it out.
+    pub async fn record_usage(&self, id: Uuid) -> Result<(), VaultError> {
+        let mut inner = self.inner.write().await;
+        let VaultState::Unlocked { data, .. } = &mut inner.state else {
+            return Err(VaultError::Locked);
+        };
+        let cred = data
+            .credentials
+            .iter_mut()
+            .find(|c| c.meta.id == id)
+            .ok_or(VaultError::NotFound(id))?;
+        let now = chrono::Utc::now();
+        cred.meta.use_count += 1;
+        cred.meta.last_used_at = Some(now);
+        self.pending_usage.record(id, now);
+        Ok(())
+    }
+
+    /// Write recorded credential uses to disk, if there are any. Usage
+    /// counters are bookkeeping rather than credential changes, so this
+    /// also works through a read-only handle.
+    pub async fn flush_usage(&self) -> Result<(), VaultError> {
+        if self.pending_usage.is_empty() {
+            return Ok(());
+        }
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        match &mut inner.state {
+            VaultState::Locked => Ok(()),
+            VaultState::Unlocked { data, .. } => self.save(&vault_path, data),
+        }
+    }
+
+    /// Decrypt a credential's secret for display to a human, recording a
+    /// `SecretReveal` audit entry. Proxy tools use `get_credential_secret`
+    /// and log their own action instead. Wiped when the caller drops it.
+    pub async fn reveal_credential_secret(
+        &self,
+        id: Uuid,
+        tool: &str,
+    ) -> Result<zeroize::Zeroizing<CredentialSecret>, VaultError> {
+        let inner = self.inner.read().await;
+        let (key, data) = match &inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { key, data } => (key, data),
+        };
+
+        let secret = zeroize::Zeroizing::new(credential::get_credential_secret(data, key, id)?);
+        let cred_name = credential::get_credential_meta(data, id).map(|m| m.name.clone());
+        let _ = self
+            .write_audit(&[AuditEntry {
+                timestamp: chrono::Utc::now(),
+                credential_id: Some(id),
+                credential_name: cred_name,
+                action: AuditAction::SecretReveal,
+                tool: tool.to_string(),
+                success: true,
+                details: None,
+            }])
+            .await;
+        Ok(secret)
+    }
+
     /// List credentials with optional filters.
     pub async fn list_credentials(
         &self,
         kind: Option<CredentialKind>,
         environment: Option<Environment>,
         tag: Option<String>,
+        category: Option<String>,
     ) -> Result<Vec<CredentialMeta>, VaultError> {
         let inner = self.inner.read().await;
         match &inner.state {
             VaultState::Locked => Err(VaultError::Locked),
-            VaultState::Unlocked { data, .. } => {
-                Ok(credential::list_credentials(
-                    data,
-                    kind,
-                    environment.as_ref(),
-                    tag.as_deref(),
-                )
-                .into_iter()
-                .cloned()
-                .collect())
-            }
+            VaultState::Unlocked { data, .. } => Ok(credential::list_credentials(
+                data,
+                kind,
+                environment.as_ref(),
+                tag.as_deref(),
+                category.as_deref(),
+            )
+            .into_iter()
+            .cloned()
+            .collect()),
         }
     }
 
@@ -328,38 +1403,301 @@ impl Vault {
         }
     }
 
-    /// Delete a credential by ID.
-    pub async fn delete_credential(&self, id: Uuid) -> Result<bool, VaultError> {
+    /// IDs of credentials whose non-sensitive secret fields (see
+    /// `credential::SEARCHABLE_SECRET_FIELDS`) contain `query`. This
+    /// decrypts every secret, so it's opt-in and records a `SecretSearch`
+    /// audit entry; the query itself isn't logged.
+    pub async fn search_secrets(
+        &self,
+        query: &str,
+        fields: &[&str],
+    ) -> Result<Vec<Uuid>, VaultError> {
+        let inner = self.inner.read().await;
+        let (key, data) = match &inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { key, data } => (key, data),
+        };
+
+        let ids = credential::search_secrets(data, key, query, fields)?;
+        let _ = self
+            .write_audit(&[AuditEntry {
+                timestamp: chrono::Utc::now(),
+                credential_id: None,
+                credential_name: None,
+                action: AuditAction::SecretSearch,
+                tool: "search_secrets".to_string(),
+                success: true,
+                details: Some(format!(
+                    "fields: {}; {} match(es)",
+                    if fields.is_empty() {
+                        credential::SEARCHABLE_SECRET_FIELDS.join(", ")
+                    } else {
+                        fields.join(", ")
+                    },
+                    ids.len()
+                )),
+            }])
+            .await;
+        Ok(ids)
+    }
+
+    /// Credentials with exactly this name (ignoring case), optionally only
+    /// in `environment`.
+    pub async fn find_by_name(
+        &self,
+        name: &str,
+        environment: Option<&Environment>,
+    ) -> Result<Vec<CredentialMeta>, VaultError> {
+        let inner = self.inner.read().await;
+        match &inner.state {
+            VaultState::Locked => Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => {
+                Ok(credential::find_by_name(data, name, environment)
+                    .into_iter()
+                    .cloned()
+                    .collect())
+            }
+        }
+    }
+
+    /// Delete a credential by ID. If other credentials link to it, this
+    /// fails with `HasDependents` naming them unless `force` is set, in
+    /// which case their links are removed with it.
+    pub async fn delete_credential(&self, id: Uuid, force: bool) -> Result<bool, VaultError> {
+        self.check_writable()?;
         let mut inner = self.inner.write().await;
         let vault_path = inner.vault_path.clone();
-        let audit_path = inner.audit_path.clone();
 
         let data = match &mut inner.state {
             VaultState::Locked => return Err(VaultError::Locked),
             VaultState::Unlocked { data, .. } => data,
         };
 
+        let dependents = credential::dependents(data, id);
+        if !dependents.is_empty() && !force {
+            return Err(VaultError::HasDependents(dependents));
+        }
+
+        let cred_name = credential::get_credential_meta(data, id).map(|m| m.name.clone());
         let deleted = credential::delete_credential(data, id);
         if deleted {
-            storage::save_vault(&vault_path, data)?;
+            // Drop the credential's policy and links to it in the same save
+            // so they can't go stale
+            let policies_before = data.policies.len();
+            data.policies.retain(|p| p.credential_id != id);
+            let policy_removed = data.policies.len() < policies_before;
+            let dependents = credential::remove_links_to(data, id);
+
+            self.save(&vault_path, data)?;
+
+            let mut details = Vec::new();
+            if policy_removed {
+                details.push("policy removed".to_string());
+            }
+            if !dependents.is_empty() {
+                details.push(format!("unlinked from {}", dependents.join(", ")));
+            }
 
-            let _ = audit::append_entry(
-                &audit_path,
-                &AuditEntry {
+            let _ = self
+                .write_audit(&[AuditEntry {
                     timestamp: chrono::Utc::now(),
                     credential_id: Some(id),
-                    credential_name: None,
+                    credential_name: cred_name,
                     action: AuditAction::CredentialDelete,
                     tool: "credential_delete".to_string(),
                     success: true,
-                    details: None,
-                },
-            );
+                    details: (!details.is_empty()).then(|| details.join("; ")),
+                }])
+                .await;
         }
 
         Ok(deleted)
     }
 
+    /// Set or clear a credential's rotation interval in days.
+    pub async fn set_rotation_interval(
+        &self,
+        id: Uuid,
+        days: Option<u32>,
+    ) -> Result<(), VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        credential::set_rotation_interval(data, id, days)?;
+        self.save(&vault_path, data)
+    }
+
+    /// Credentials past their rotation interval, most overdue first.
+    pub async fn rotation_due(&self) -> Result<Vec<CredentialMeta>, VaultError> {
+        let inner = self.inner.read().await;
+        match &inner.state {
+            VaultState::Locked => Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => {
+                Ok(credential::rotation_due(data, chrono::Utc::now())
+                    .into_iter()
+                    .cloned()
+                    .collect())
+            }
+        }
+    }
+
+    /// Record that `source` depends on `target`, e.g. with relation
+    /// `depends_on`. Returns false if the same link already exists.
+    pub async fn link_credentials(
+        &self,
+        source: Uuid,
+        target: Uuid,
+        relation: &str,
+    ) -> Result<bool, VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        let added = credential::link_credentials(data, source, target, relation)?;
+        if added {
+            self.save(&vault_path, data)?;
+        }
+        Ok(added)
+    }
+
+    /// Remove the links from `source` to `target`. Returns true if there were any.
+    pub async fn unlink_credentials(&self, source: Uuid, target: Uuid) -> Result<bool, VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        let removed = credential::unlink_credentials(data, source, target)?;
+        if removed {
+            self.save(&vault_path, data)?;
+        }
+        Ok(removed)
+    }
+
+    /// Credentials linked to `id` in either direction. Incoming links are
+    /// the credentials that deleting or rotating `id` would affect.
+    pub async fn get_linked(&self, id: Uuid) -> Result<Vec<LinkedCredential>, VaultError> {
+        let inner = self.inner.read().await;
+        match &inner.state {
+            VaultState::Locked => Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => credential::get_linked(data, id),
+        }
+    }
+
+    /// Add a tag to the given credentials. Returns the number of credentials changed.
+    pub async fn bulk_add_tag(&self, ids: &[Uuid], tag: &str) -> Result<usize, VaultError> {
+        self.modify_tags(|data| credential::add_tag(data, ids, tag))
+            .await
+    }
+
+    /// Remove a tag from the given credentials. Returns the number of credentials changed.
+    pub async fn bulk_remove_tag(&self, ids: &[Uuid], tag: &str) -> Result<usize, VaultError> {
+        self.modify_tags(|data| credential::remove_tag(data, ids, tag))
+            .await
+    }
+
+    /// Rename a tag across every credential that carries it. Returns the number changed.
+    pub async fn rename_tag(&self, old: &str, new: &str) -> Result<usize, VaultError> {
+        self.modify_tags(|data| credential::rename_tag(data, old, new))
+            .await
+    }
+
+    /// List the vault's categories.
+    pub async fn list_categories(&self) -> Result<Vec<Category>, VaultError> {
+        let inner = self.inner.read().await;
+        match &inner.state {
+            VaultState::Locked => Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => Ok(data.categories.clone()),
+        }
+    }
+
+    /// Add a category, or update its description if it already exists.
+    pub async fn add_category(
+        &self,
+        name: &str,
+        description: Option<String>,
+    ) -> Result<(), VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        credential::add_category(data, name, description);
+        self.save(&vault_path, data)
+    }
+
+    /// Delete a category. Credentials in it become uncategorized.
+    /// Returns true if the category existed.
+    pub async fn delete_category(&self, name: &str) -> Result<bool, VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        let removed = credential::delete_category(data, name);
+        if removed {
+            self.save(&vault_path, data)?;
+        }
+        Ok(removed)
+    }
+
+    /// Assign a credential to an existing category, or clear it with `None`.
+    pub async fn set_credential_category(
+        &self,
+        id: Uuid,
+        category: Option<String>,
+    ) -> Result<(), VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        credential::set_category(data, id, category)?;
+        self.save(&vault_path, data)
+    }
+
+    /// Apply a tag edit to the unlocked vault and save once if anything changed.
+    async fn modify_tags(
+        &self,
+        edit: impl FnOnce(&mut VaultFile) -> usize,
+    ) -> Result<usize, VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        let affected = edit(data);
+        if affected > 0 {
+            self.save(&vault_path, data)?;
+        }
+        Ok(affected)
+    }
+
     /// Get the policy for a credential, if any.
     pub async fn get_policy(&self, credential_id: Uuid) -> Result<Option<PolicyRule>, VaultError> {
         let inner = self.inner.read().await;
@@ -373,8 +1711,41 @@ impl Vault {
         }
     }
 
+    /// Get the policy that applies to a credential: its own policy if set,
+    /// otherwise the first matching policy template.
+    pub async fn get_effective_policy(
+        &self,
+        credential_id: Uuid,
+    ) -> Result<Option<PolicyRule>, VaultError> {
+        let inner = self.inner.read().await;
+        match &inner.state {
+            VaultState::Locked => Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => {
+                if let Some(policy) = data
+                    .policies
+                    .iter()
+                    .find(|p| p.credential_id == credential_id)
+                {
+                    return Ok(Some(policy.clone()));
+                }
+                let Some(meta) = credential::get_credential_meta(data, credential_id) else {
+                    return Ok(None);
+                };
+                Ok(data
+                    .policy_templates
+                    .iter()
+                    .find(|t| t.matches(meta))
+                    .map(|t| PolicyRule {
+                        credential_id,
+                        ..t.rule.clone()
+                    }))
+            }
+        }
+    }
+
     /// Save (create or update) a policy for a credential.
     pub async fn save_policy(&self, policy: PolicyRule) -> Result<(), VaultError> {
+        self.check_writable()?;
         let mut inner = self.inner.write().await;
         let vault_path = inner.vault_path.clone();
 
@@ -384,19 +1755,25 @@ impl Vault {
         };
 
         // Verify the credential exists
-        if !data.credentials.iter().any(|c| c.meta.id == policy.credential_id) {
+        if !data
+            .credentials
+            .iter()
+            .any(|c| c.meta.id == policy.credential_id)
+        {
             return Err(VaultError::NotFound(policy.credential_id));
         }
 
         // Upsert: remove old policy for this credential, then add new one
-        data.policies.retain(|p| p.credential_id != policy.credential_id);
+        data.policies
+            .retain(|p| p.credential_id != policy.credential_id);
         data.policies.push(policy);
-        storage::save_vault(&vault_path, data)?;
+        self.save(&vault_path, data)?;
         Ok(())
     }
 
     /// Delete the policy for a credential.
     pub async fn delete_policy(&self, credential_id: Uuid) -> Result<bool, VaultError> {
+        self.check_writable()?;
         let mut inner = self.inner.write().await;
         let vault_path = inner.vault_path.clone();
 
@@ -409,7 +1786,131 @@ impl Vault {
         data.policies.retain(|p| p.credential_id != credential_id);
         let removed = data.policies.len() < before;
         if removed {
-            storage::save_vault(&vault_path, data)?;
+            self.save(&vault_path, data)?;
+        }
+        Ok(removed)
+    }
+
+    /// Remove all policies whose credential no longer exists. Returns the number removed.
+    pub async fn prune_orphaned_policies(&self) -> Result<usize, VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        let before = data.policies.len();
+        let credentials = &data.credentials;
+        data.policies
+            .retain(|p| credentials.iter().any(|c| c.meta.id == p.credential_id));
+        let removed = before - data.policies.len();
+        if removed > 0 {
+            self.save(&vault_path, data)?;
+        }
+        Ok(removed)
+    }
+
+    /// List policy templates in precedence order.
+    pub async fn list_policy_templates(&self) -> Result<Vec<PolicyTemplate>, VaultError> {
+        let inner = self.inner.read().await;
+        match &inner.state {
+            VaultState::Locked => Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => Ok(data.policy_templates.clone()),
+        }
+    }
+
+    /// Save a policy template. Replaces a template with the same name in place,
+    /// otherwise appends it (lowest precedence).
+    pub async fn save_policy_template(&self, template: PolicyTemplate) -> Result<(), VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        match data
+            .policy_templates
+            .iter_mut()
+            .find(|t| t.name == template.name)
+        {
+            Some(existing) => *existing = template,
+            None => data.policy_templates.push(template),
+        }
+        self.save(&vault_path, data)?;
+        Ok(())
+    }
+
+    /// Delete a policy template by name.
+    pub async fn delete_policy_template(&self, name: &str) -> Result<bool, VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        let before = data.policy_templates.len();
+        data.policy_templates.retain(|t| t.name != name);
+        let removed = data.policy_templates.len() < before;
+        if removed {
+            self.save(&vault_path, data)?;
+        }
+        Ok(removed)
+    }
+
+    /// List webhook notification rules.
+    pub async fn list_webhooks(&self) -> Result<Vec<WebhookRule>, VaultError> {
+        let inner = self.inner.read().await;
+        match &inner.state {
+            VaultState::Locked => Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => Ok(data.webhooks.clone()),
+        }
+    }
+
+    /// Save a webhook rule, replacing any rule with the same name.
+    pub async fn save_webhook(&self, rule: WebhookRule) -> Result<(), VaultError> {
+        self.check_writable()?;
+        notifications::validate_url(&rule.url).map_err(VaultError::InvalidWebhook)?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        match data.webhooks.iter_mut().find(|w| w.name == rule.name) {
+            Some(existing) => *existing = rule,
+            None => data.webhooks.push(rule),
+        }
+        self.save(&vault_path, data)?;
+        Ok(())
+    }
+
+    /// Delete a webhook rule by name.
+    pub async fn delete_webhook(&self, name: &str) -> Result<bool, VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        let before = data.webhooks.len();
+        data.webhooks.retain(|w| w.name != name);
+        let removed = data.webhooks.len() < before;
+        if removed {
+            self.save(&vault_path, data)?;
         }
         Ok(removed)
     }
@@ -441,41 +1942,417 @@ impl Vault {
         }
     }
 
+    /// Built-in environments plus every custom environment the vault knows.
+    pub async fn known_environments(&self) -> Result<Vec<Environment>, VaultError> {
+        let inner = self.inner.read().await;
+        match &inner.state {
+            VaultState::Locked => Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => Ok(credential::known_environments(data)),
+        }
+    }
+
+    /// Register a custom environment so strict mode accepts it.
+    pub async fn register_environment(&self, name: &str) -> Result<(), VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        let env = Environment::Custom(name.to_string());
+        if !credential::known_environments(data).contains(&env) {
+            data.environments.push(name.to_string());
+            self.save(&vault_path, data)?;
+        }
+        Ok(())
+    }
+
+    /// Turn strict environment checking on or off.
+    pub async fn set_strict_environments(&self, strict: bool) -> Result<(), VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        data.strict_environments = strict;
+        self.save(&vault_path, data)
+    }
+
+    /// Turn per-environment name uniqueness on or off. Turning it on fails
+    /// with `DuplicateName` if the vault already has duplicates.
+    pub async fn set_unique_names(&self, unique: bool) -> Result<(), VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        if unique {
+            let mut seen = std::collections::HashSet::new();
+            for meta in data.credentials.iter().map(|c| &c.meta) {
+                let name = meta.name.trim().to_lowercase();
+                if !seen.insert((name, meta.environment.to_string())) {
+                    return Err(VaultError::DuplicateName {
+                        name: meta.name.clone(),
+                        environment: meta.environment.to_string(),
+                    });
+                }
+            }
+        }
+        data.unique_names = unique;
+        self.save(&vault_path, data)
+    }
+
+    /// Switch the on-disk layout. The next save migrates the vault, writing
+    /// every record (to the directory format) or removing `vault.d/` (back to
+    /// a single file).
+    pub async fn set_storage_format(&self, format: StorageFormat) -> Result<(), VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        data.storage_format = format;
+        self.save(&vault_path, data)
+    }
+
+    /// Turn gzip compression of the vault file on or off.
+    pub async fn set_compression(&self, compressed: bool) -> Result<(), VaultError> {
+        self.check_writable()?;
+        let mut inner = self.inner.write().await;
+        let vault_path = inner.vault_path.clone();
+        let data = match &mut inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => data,
+        };
+
+        data.compressed = compressed;
+        self.save(&vault_path, data)
+    }
+
+    /// Queue an operation for human approval. The entry expires after `ttl`.
+    /// `request` is the full operation as it will run; only a later request
+    /// that hashes the same can consume the approval.
+    pub async fn request_approval(
+        &self,
+        credential_id: Uuid,
+        tool: &str,
+        summary: String,
+        request: serde_json::Value,
+        ttl: chrono::Duration,
+    ) -> Result<PendingApproval, VaultError> {
+        let inner = self.inner.read().await;
+        let approval_key = inner.approval_key()?;
+        let credential_name = match &inner.state {
+            VaultState::Locked => return Err(VaultError::Locked),
+            VaultState::Unlocked { data, .. } => {
+                credential::get_credential_meta(data, credential_id).map(|m| m.name.clone())
+            }
+        };
+
+        let now = chrono::Utc::now();
+        let approval = PendingApproval {
+            id: Uuid::new_v4(),
+            credential_id,
+            credential_name: credential_name.clone(),
+            tool: tool.to_string(),
+            summary,
+            request_hash: approvals::request_hash(&request),
+            request,
+            status: ApprovalStatus::Pending,
+            created_at: now,
+            expires_at: now + ttl,
+            mac: String::new(),
+        };
+        approvals::enqueue(
+            &approvals::approvals_path(&inner.vault_path),
+            &approval_key,
+            approval.clone(),
+        )?;
+
+        let _ = self
+            .write_audit(&[AuditEntry {
+                timestamp: now,
+                credential_id: Some(credential_id),
+                credential_name,
+                action: AuditAction::ApprovalRequest,
+                tool: tool.to_string(),
+                success: true,
+                details: Some(format!("approval {}: {}", approval.id, approval.summary)),
+            }])
+            .await;
+
+        Ok(approval)
+    }
+
+    /// List pending and decided (not yet consumed) approvals.
+    pub async fn list_approvals(&self) -> Result<Vec<PendingApproval>, VaultError> {
+        if self.is_decoy() {
+            return Ok(vec![]);
+        }
+        let inner = self.inner.read().await;
+        let approval_key = inner.approval_key()?;
+        approvals::list(&approvals::approvals_path(&inner.vault_path), &approval_key)
+    }
+
+    /// Get a single approval by ID.
+    pub async fn get_approval(&self, id: Uuid) -> Result<Option<PendingApproval>, VaultError> {
+        if self.is_decoy() {
+            return Ok(None);
+        }
+        let inner = self.inner.read().await;
+        let approval_key = inner.approval_key()?;
+        approvals::get(
+            &approvals::approvals_path(&inner.vault_path),
+            &approval_key,
+            id,
+        )
+    }
+
+    /// Approve a pending operation. Returns false if it's unknown, expired, or already decided.
+    pub async fn approve(&self, id: Uuid) -> Result<bool, VaultError> {
+        self.decide_approval(id, ApprovalStatus::Approved).await
+    }
+
+    /// Deny a pending operation. Returns false if it's unknown, expired, or already decided.
+    pub async fn deny(&self, id: Uuid) -> Result<bool, VaultError> {
+        self.decide_approval(id, ApprovalStatus::Denied).await
+    }
+
+    async fn decide_approval(&self, id: Uuid, status: ApprovalStatus) -> Result<bool, VaultError> {
+        let inner = self.inner.read().await;
+        let approval_key = inner.approval_key()?;
+
+        let decided = approvals::decide(
+            &approvals::approvals_path(&inner.vault_path),
+            &approval_key,
+            id,
+            status,
+        )?;
+        if let Some(ref approval) = decided {
+            let _ = self
+                .write_audit(&[AuditEntry {
+                    timestamp: chrono::Utc::now(),
+                    credential_id: Some(approval.credential_id),
+                    credential_name: approval.credential_name.clone(),
+                    action: AuditAction::ApprovalDecision,
+                    tool: approval.tool.clone(),
+                    success: status == ApprovalStatus::Approved,
+                    details: Some(format!("approval {id}: {status:?}")),
+                }])
+                .await;
+        }
+        Ok(decided.is_some())
+    }
+
+    /// Consume an approved operation so it can only run once.
+    /// Returns the approval if it was approved and not yet consumed.
+    pub async fn consume_approval(&self, id: Uuid) -> Result<Option<PendingApproval>, VaultError> {
+        let inner = self.inner.read().await;
+        let approval_key = inner.approval_key()?;
+        approvals::consume(
+            &approvals::approvals_path(&inner.vault_path),
+            &approval_key,
+            id,
+        )
+    }
+
     /// Append an audit entry.
     pub async fn log_audit(&self, entry: &AuditEntry) -> Result<(), VaultError> {
+        self.write_audit(std::slice::from_ref(entry)).await
+    }
+
+    /// Per-action and per-credential counts over entries since `since`
+    /// (the whole log if `None`).
+    pub async fn audit_summary(
+        &self,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<AuditSummary, VaultError> {
+        if self.is_decoy() {
+            return Ok(AuditSummary::default());
+        }
+        let inner = self.inner.read().await;
+        audit::summarize(&inner.audit_path, since)
+    }
+
+    /// `audit_summary` over only the entries `keep` accepts, e.g. those a
+    /// scoped MCP session may see.
+    pub async fn audit_summary_matching(
+        &self,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        keep: impl Fn(&AuditEntry) -> bool,
+    ) -> Result<AuditSummary, VaultError> {
+        if self.is_decoy() {
+            return Ok(AuditSummary::default());
+        }
+        let inner = self.inner.read().await;
+        audit::summarize_matching(&inner.audit_path, since, keep)
+    }
+
+    /// Export audit entries since `since` (all when `None`), oldest first,
+    /// signed with a key derived from the master key.
+    pub async fn export_signed_audit(
+        &self,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<SignedAudit, VaultError> {
+        let inner = self.inner.read().await;
+        let VaultState::Unlocked { key, .. } = &inner.state else {
+            return Err(VaultError::Locked);
+        };
+        let filter = AuditFilter {
+            since,
+            ..Default::default()
+        };
+        let mut entries = if self.is_decoy() {
+            vec![]
+        } else {
+            audit::read_entries(&inner.audit_path, &filter, None)?
+        };
+        entries.reverse();
+
+        let mut export = SignedAudit {
+            exported_at: chrono::Utc::now(),
+            since,
+            entries,
+            signature: String::new(),
+        };
+        let mut signing_key = crypto::audit_signing_key(key.as_bytes());
+        export.signature = hex::encode(crypto::sign(&signing_key, &export.signed_bytes()));
+        signing_key.zeroize();
+        Ok(export)
+    }
+
+    /// Check a serialized `SignedAudit` against this vault's master key.
+    /// Returns `Ok(false)` if it was altered or signed by a different key;
+    /// exports made before a master password change no longer verify.
+    pub async fn verify_signed_audit(&self, bytes: &[u8]) -> Result<bool, VaultError> {
+        let export: SignedAudit = serde_json::from_slice(bytes)
+            .map_err(|e| VaultError::Crypto(format!("malformed audit export: {e}")))?;
+        let Ok(tag) = hex::decode(&export.signature) else {
+            return Ok(false);
+        };
         let inner = self.inner.read().await;
-        audit::append_entry(&inner.audit_path, entry)
+        let VaultState::Unlocked { key, .. } = &inner.state else {
+            return Err(VaultError::Locked);
+        };
+        let mut signing_key = crypto::audit_signing_key(key.as_bytes());
+        let valid = crypto::verify_signature(&signing_key, &export.signed_bytes(), &tag);
+        signing_key.zeroize();
+        Ok(valid)
     }
 
-    /// Read audit entries with optional filters.
+    /// Read audit entries matching `filter`, most recent first.
     pub async fn read_audit(
         &self,
-        credential_id: Option<Uuid>,
+        filter: &AuditFilter,
         limit: Option<usize>,
-        since: Option<chrono::DateTime<chrono::Utc>>,
     ) -> Result<Vec<AuditEntry>, VaultError> {
+        if self.is_decoy() {
+            return Ok(vec![]);
+        }
         let inner = self.inner.read().await;
-        audit::read_entries(&inner.audit_path, credential_id, limit, since)
+        audit::read_entries(&inner.audit_path, filter, limit)
+    }
+
+    /// Remove audit entries older than `before`, or all of them when `None`.
+    /// Requires an unlocked vault. The clear itself is logged afterwards.
+    /// Returns the number of entries removed.
+    pub async fn clear_audit(
+        &self,
+        before: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<usize, VaultError> {
+        self.check_writable()?;
+        let inner = self.inner.read().await;
+        if matches!(inner.state, VaultState::Locked) {
+            return Err(VaultError::Locked);
+        }
+        // The decoy must not touch the real log
+        if self.is_decoy() {
+            return Ok(0);
+        }
+        let removed = audit::clear_entries(&inner.audit_path, before)?;
+        drop(inner);
+
+        let _ = self
+            .write_audit(&[AuditEntry {
+                timestamp: chrono::Utc::now(),
+                credential_id: None,
+                credential_name: None,
+                action: AuditAction::AuditClear,
+                tool: "audit_clear".to_string(),
+                success: true,
+                details: Some(match before {
+                    Some(before) => format!("removed {removed} entries before {before}"),
+                    None => format!("removed all {removed} entries"),
+                }),
+            }])
+            .await;
+        Ok(removed)
+    }
+
+    /// Remove audit entries older than `before`, keeping the rest. See
+    /// `clear_audit`.
+    pub async fn prune_audit(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+    ) -> Result<usize, VaultError> {
+        self.clear_audit(Some(before)).await
     }
 
     /// Reload vault data from disk (used when another process writes the file).
     pub async fn reload(&self) -> Result<(), VaultError> {
+        // The decoy never reads the real file back
+        if self.is_decoy() {
+            return Ok(());
+        }
         let mut inner = self.inner.write().await;
         match &inner.state {
             VaultState::Locked => Ok(()),
-            VaultState::Unlocked { key, .. } => {
-                let vault_file = storage::load_vault(&inner.vault_path)?;
+            VaultState::Unlocked { key, data } => {
+                let mut vault_file = self.load_vault_file(&inner.vault_path)?;
+                // A new salt or KDF params means the password was changed
+                // elsewhere; our key can't open the file any more
+                if vault_file.salt != data.salt || vault_file.kdf_params != data.kdf_params {
+                    inner.state = VaultState::Locked;
+                    self.secret_cache.clear();
+                    self.pending_usage.clear();
+                    self.webhooks.refresh(None);
+                    return Err(VaultError::ReauthRequired);
+                }
                 // Verify the key still works
-                if !crypto::verify_password(key.as_bytes(), &vault_file.verification)? {
+                if !crypto::verify_password(
+                    key.as_bytes(),
+                    vault_file.cipher,
+                    &vault_file.verification,
+                )? {
                     inner.state = VaultState::Locked;
+                    self.secret_cache.clear();
+                    self.pending_usage.clear();
+                    self.webhooks.refresh(None);
                     return Err(VaultError::InvalidPassword);
                 }
-                // Re-derive the key reference — the key stays the same
-                let key_bytes = *key.as_bytes();
-                inner.state = VaultState::Unlocked {
-                    key: crypto::DerivedKey::new(key_bytes),
-                    data: vault_file,
-                };
+                crypto::check_nonce_reuse(&vault_file)?;
+                self.pending_usage.apply(&mut vault_file);
+                self.webhooks.refresh(Some(&vault_file));
+                // The key stays the same; move it over rather than copying it
+                if let VaultState::Unlocked { key, .. } =
+                    std::mem::replace(&mut inner.state, VaultState::Locked)
+                {
+                    inner.state = VaultState::Unlocked {
+                        key: key.with_cipher(vault_file.cipher),
+                        data: Box::new(vault_file),
+                    };
+                }
                 Ok(())
             }
         }
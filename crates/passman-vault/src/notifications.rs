@@ -0,0 +1,184 @@
+//! Webhook notifications for audit events.
+//!
+//! Rules live in the vault file (`VaultFile.webhooks`). When an audit entry
+//! matches a rule, a small JSON payload is POSTed to the rule's URL in the
+//! background. Delivery failures are logged and never fail the operation
+//! being audited.
+
+use passman_types::{AuditEntry, Environment, VaultFile, WebhookRule};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::sink::AuditSink;
+use crate::VaultError;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// Check that a webhook URL is an absolute http(s) URL.
+pub fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("invalid webhook URL: {e}"))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(()),
+        other => Err(format!("webhook URL must use http or https, not {other}")),
+    }
+}
+
+/// The body sent for `entry`. Deliberately omits `details`, which can hold
+/// queries, commands or recipients. The `text` field makes it render as a
+/// message in Slack-compatible incoming webhooks.
+pub fn payload(entry: &AuditEntry, environment: Option<&Environment>) -> serde_json::Value {
+    let action = serde_json::to_value(&entry.action).unwrap_or_default();
+    let action_name = action.as_str().unwrap_or("unknown");
+    let mut text = format!("Passman: {action_name}");
+    if let Some(name) = &entry.credential_name {
+        text.push_str(&format!(" on \"{name}\""));
+    }
+    if let Some(env) = environment {
+        text.push_str(&format!(" ({env})"));
+    }
+    if !entry.success {
+        text.push_str(" [failed]");
+    }
+
+    serde_json::json!({
+        "text": text,
+        "action": action,
+        "tool": entry.tool,
+        "success": entry.success,
+        "credential_id": entry.credential_id.map(|id| id.to_string()),
+        "credential_name": entry.credential_name,
+        "environment": environment.map(|e| e.to_string()),
+        "timestamp": entry.timestamp.to_rfc3339(),
+    })
+}
+
+/// Audit sink that fires matching webhooks.
+///
+/// Sinks only see the entry, so this keeps its own copy of the rules and of
+/// each credential's environment, refreshed by the vault whenever its
+/// contents change. While the vault is locked no webhooks fire.
+#[derive(Default)]
+pub struct WebhookSink {
+    state: RwLock<WebhookState>,
+}
+
+#[derive(Default)]
+struct WebhookState {
+    rules: Vec<WebhookRule>,
+    environments: HashMap<Uuid, Environment>,
+}
+
+impl WebhookSink {
+    /// Take the rules and credential environments from `data` (`None` when locked).
+    pub fn refresh(&self, data: Option<&VaultFile>) {
+        let mut state = self.state.write().unwrap();
+        *state = match data {
+            Some(data) if !data.webhooks.is_empty() => WebhookState {
+                rules: data.webhooks.clone(),
+                environments: data
+                    .credentials
+                    .iter()
+                    .map(|c| (c.meta.id, c.meta.environment.clone()))
+                    .collect(),
+            },
+            _ => WebhookState::default(),
+        };
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSink for WebhookSink {
+    /// Requests run in the background so a slow endpoint never delays the
+    /// operation being audited.
+    async fn write(&self, entry: &AuditEntry) -> Result<(), VaultError> {
+        let state = self.state.read().unwrap();
+        let environment = entry
+            .credential_id
+            .and_then(|id| state.environments.get(&id));
+        for rule in state.rules.iter().filter(|r| r.matches(entry, environment)) {
+            let body = payload(entry, environment);
+            let rule = rule.clone();
+            tokio::spawn(async move { send(&rule, &body).await });
+        }
+        Ok(())
+    }
+}
+
+async fn send(rule: &WebhookRule, body: &serde_json::Value) {
+    let result = client()
+        .post(&rule.url)
+        .json(body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+    if let Err(e) = result {
+        tracing::warn!("Webhook '{}' failed: {e}", rule.name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use passman_types::AuditAction;
+
+    fn entry(action: AuditAction) -> AuditEntry {
+        AuditEntry {
+            timestamp: chrono::Utc::now(),
+            credential_id: Some(uuid::Uuid::new_v4()),
+            credential_name: Some("Prod DB".to_string()),
+            action,
+            tool: "sql_query".to_string(),
+            success: true,
+            details: Some("SELECT * FROM users".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_rule_matching() {
+        let rule = WebhookRule {
+            name: "prod".into(),
+            url: "https://hooks.example.com/x".into(),
+            actions: vec![AuditAction::SqlQuery],
+            environments: vec![Environment::Production],
+        };
+        let prod = Environment::Production;
+        assert!(rule.matches(&entry(AuditAction::SqlQuery), Some(&prod)));
+        assert!(!rule.matches(&entry(AuditAction::SqlQuery), Some(&Environment::Staging)));
+        assert!(!rule.matches(&entry(AuditAction::SqlQuery), None));
+        assert!(!rule.matches(&entry(AuditAction::CredentialList), Some(&prod)));
+    }
+
+    #[test]
+    fn test_payload_omits_details() {
+        let body = payload(
+            &entry(AuditAction::SqlQuery),
+            Some(&Environment::Production),
+        );
+        assert_eq!(body["action"], "sql_query");
+        assert_eq!(body["environment"], "production");
+        assert!(!body.to_string().contains("SELECT"));
+        assert_eq!(
+            body["text"],
+            "Passman: sql_query on \"Prod DB\" (production)"
+        );
+    }
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://hooks.slack.com/services/T/B/x").is_ok());
+        assert!(validate_url("file:///etc/passwd").is_err());
+        assert!(validate_url("not a url").is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! Named vault profiles.
+//!
+//! `~/.passman/profiles.json` maps profile names to vault and audit log
+//! paths so separate vaults (work, personal) can be kept side by side. The
+//! built-in `default` profile is the vault next to the registry
+//! (`~/.passman/vault.json`) and is always listed; profiles created here
+//! get their own directory under `~/.passman/profiles/<name>/`.
+
+use passman_types::VaultProfile;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{storage, VaultError};
+
+/// Name of the profile for the vault at the default paths.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Registry path: ~/.passman/profiles.json
+pub fn default_registry_path() -> PathBuf {
+    storage::default_vault_dir().join("profiles.json")
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    /// Profile the app opens on start; `default` when unset.
+    #[serde(default)]
+    active: Option<String>,
+    #[serde(default)]
+    profiles: Vec<VaultProfile>,
+}
+
+/// The profile registry, loaded from its JSON file.
+pub struct ProfileRegistry {
+    path: PathBuf,
+    file: RegistryFile,
+}
+
+impl ProfileRegistry {
+    /// Load the registry at `path`. A missing file is an empty registry.
+    pub fn open(path: PathBuf) -> Result<Self, VaultError> {
+        let file = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .map_err(|e| VaultError::Io(format!("failed to parse profiles file: {e}")))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => RegistryFile::default(),
+            Err(e) => return Err(VaultError::Io(format!("failed to read profiles file: {e}"))),
+        };
+        Ok(Self { path, file })
+    }
+
+    /// Load the registry at the default path.
+    pub fn open_default() -> Result<Self, VaultError> {
+        Self::open(default_registry_path())
+    }
+
+    fn dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new("."))
+    }
+
+    fn save(&self) -> Result<(), VaultError> {
+        storage::ensure_vault_dir(&self.path)?;
+        let json = serde_json::to_string_pretty(&self.file)
+            .map_err(|e| VaultError::Io(format!("failed to serialize profiles: {e}")))?;
+        fs::write(&self.path, json)
+            .map_err(|e| VaultError::Io(format!("failed to write profiles file: {e}")))
+    }
+
+    fn default_profile(&self) -> VaultProfile {
+        VaultProfile {
+            name: DEFAULT_PROFILE.to_string(),
+            vault_path: self.dir().join("vault.json"),
+            audit_path: self.dir().join("audit.jsonl"),
+        }
+    }
+
+    /// Every profile, `default` first.
+    pub fn list(&self) -> Vec<VaultProfile> {
+        let mut profiles = vec![self.default_profile()];
+        profiles.extend(self.file.profiles.iter().cloned());
+        profiles
+    }
+
+    pub fn get(&self, name: &str) -> Result<VaultProfile, VaultError> {
+        self.list()
+            .into_iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| VaultError::UnknownProfile(name.to_string()))
+    }
+
+    /// Register a new profile with its own directory and save the registry.
+    /// The vault itself is created on first unlock, as with the default one.
+    pub fn create(&mut self, name: &str) -> Result<VaultProfile, VaultError> {
+        validate_name(name)?;
+        if self.get(name).is_ok() {
+            return Err(VaultError::InvalidProfile(format!(
+                "profile '{name}' already exists"
+            )));
+        }
+        let dir = self.dir().join("profiles").join(name);
+        let profile = VaultProfile {
+            name: name.to_string(),
+            vault_path: dir.join("vault.json"),
+            audit_path: dir.join("audit.jsonl"),
+        };
+        self.file.profiles.push(profile.clone());
+        self.save()?;
+        Ok(profile)
+    }
+
+    /// The profile to open on start.
+    pub fn active(&self) -> Result<VaultProfile, VaultError> {
+        self.get(self.file.active.as_deref().unwrap_or(DEFAULT_PROFILE))
+    }
+
+    /// Remember `name` as the profile to open on start.
+    pub fn set_active(&mut self, name: &str) -> Result<VaultProfile, VaultError> {
+        let profile = self.get(name)?;
+        self.file.active = Some(profile.name.clone());
+        self.save()?;
+        Ok(profile)
+    }
+}
+
+/// Profile names become directory names: letters, digits, `-` and `_` only.
+fn validate_name(name: &str) -> Result<(), VaultError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(VaultError::InvalidProfile(format!(
+            "invalid profile name '{name}': use 1-64 letters, digits, '-' or '_'"
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_and_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles.json");
+
+        let mut registry = ProfileRegistry::open(path.clone()).unwrap();
+        assert_eq!(registry.active().unwrap().name, DEFAULT_PROFILE);
+        let work = registry.create("work").unwrap();
+        assert_eq!(work.vault_path, dir.path().join("profiles/work/vault.json"));
+        registry.set_active("work").unwrap();
+
+        let reopened = ProfileRegistry::open(path).unwrap();
+        let names: Vec<_> = reopened.list().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["default", "work"]);
+        assert_eq!(reopened.active().unwrap(), work);
+    }
+
+    #[test]
+    fn test_rejects_bad_and_duplicate_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut registry = ProfileRegistry::open(dir.path().join("profiles.json")).unwrap();
+        assert!(registry.create("../escape").is_err());
+        assert!(registry.create("").is_err());
+        assert!(registry.create("default").is_err());
+        registry.create("personal").unwrap();
+        assert!(registry.create("personal").is_err());
+        assert!(matches!(
+            registry.set_active("missing"),
+            Err(VaultError::UnknownProfile(_))
+        ));
+    }
+}
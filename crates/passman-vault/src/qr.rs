@@ -0,0 +1,134 @@
+//! QR codes for moving a secret to a phone, e.g. a TOTP seed into an
+//! authenticator app.
+//!
+//! There is no TOTP credential kind; a `Custom` credential with a
+//! `totp_secret` field (base32, as shown by most "can't scan?" links) is
+//! exported as an `otpauth://totp/...` URI. The encoded text is a secret:
+//! nothing here logs it, and errors never include it.
+
+use image::{ImageFormat, Luma};
+use passman_types::CredentialSecret;
+use qrcode::QrCode;
+use std::io::Cursor;
+
+use crate::VaultError;
+
+/// `Custom` field holding a base32 TOTP seed.
+pub const TOTP_SECRET_FIELD: &str = "totp_secret";
+
+/// Optional `Custom` field naming the TOTP issuer shown in the app.
+pub const TOTP_ISSUER_FIELD: &str = "totp_issuer";
+
+/// Issuer used when the credential doesn't name one.
+const DEFAULT_ISSUER: &str = "Passman";
+
+/// Render `text` as a QR code and return the PNG bytes.
+pub fn encode(text: &str) -> Result<Vec<u8>, VaultError> {
+    let code = QrCode::new(text.as_bytes())
+        .map_err(|e| VaultError::InvalidSecret(format!("can't encode as a QR code: {e}")))?;
+    let image = code.render::<Luma<u8>>().min_dimensions(256, 256).build();
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(|e| VaultError::Io(format!("failed to write QR code PNG: {e}")))?;
+    Ok(png)
+}
+
+/// `otpauth://` URI for a `Custom` credential holding a TOTP seed, labelled
+/// with the credential's name. `None` for anything else.
+pub fn totp_uri(name: &str, secret: &CredentialSecret) -> Option<String> {
+    let CredentialSecret::Custom { fields } = secret else {
+        return None;
+    };
+    let seed = fields.get(TOTP_SECRET_FIELD)?;
+    let issuer = fields
+        .get(TOTP_ISSUER_FIELD)
+        .map_or(DEFAULT_ISSUER, String::as_str);
+    Some(otpauth_uri(issuer, name, seed))
+}
+
+/// Key URI in the format authenticator apps scan:
+/// `otpauth://totp/Issuer:account?secret=SEED&issuer=Issuer`.
+pub fn otpauth_uri(issuer: &str, account: &str, seed: &str) -> String {
+    // Seeds are often shown grouped ("JBSW Y3DP") and in lower case
+    let seed: String = seed
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    format!(
+        "otpauth://totp/{}:{}?secret={seed}&issuer={}",
+        percent_encode(issuer),
+        percent_encode(account),
+        percent_encode(issuer)
+    )
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters.
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decode the single QR code in a PNG.
+    fn decode(png: &[u8]) -> String {
+        let image = image::load_from_memory(png).unwrap().to_luma8();
+        let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(
+            image.width() as usize,
+            image.height() as usize,
+            |x, y| image.get_pixel(x as u32, y as u32).0[0],
+        );
+        let grids = prepared.detect_grids();
+        assert_eq!(grids.len(), 1);
+        grids[0].decode().unwrap().1
+    }
+
+    #[test]
+    fn test_totp_qr_round_trips_otpauth_uri() {
+        let secret = CredentialSecret::Custom {
+            fields: [
+                (
+                    TOTP_SECRET_FIELD.to_string(),
+                    "jbsw y3dp ehpk 3pxp".to_string(),
+                ),
+                (TOTP_ISSUER_FIELD.to_string(), "Acme Corp".to_string()),
+            ]
+            .into(),
+        };
+        let uri = totp_uri("ops@acme.io", &secret).unwrap();
+        assert_eq!(
+            uri,
+            "otpauth://totp/Acme%20Corp:ops%40acme.io?secret=JBSWY3DPEHPK3PXP&issuer=Acme%20Corp"
+        );
+
+        let png = encode(&uri).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        assert_eq!(decode(&png), uri);
+    }
+
+    #[test]
+    fn test_only_custom_totp_credentials_get_a_uri() {
+        let password = CredentialSecret::Password {
+            username: "user".into(),
+            password: "pass".into(),
+            url: None,
+        };
+        assert_eq!(totp_uri("Login", &password), None);
+
+        let custom = CredentialSecret::Custom {
+            fields: [("api_key".to_string(), "sk_123".to_string())].into(),
+        };
+        assert_eq!(totp_uri("Service", &custom), None);
+    }
+}
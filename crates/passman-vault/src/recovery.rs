@@ -0,0 +1,142 @@
+//! Master-key recovery with Shamir's Secret Sharing.
+//!
+//! The key derived from the master password is split into `shares` pieces,
+//! any `threshold` of which rebuild it. Each share is a self-describing
+//! string:
+//!
+//! ```text
+//! passman-recovery:v1:<threshold>:<vault fingerprint>:<hex share>
+//! ```
+//!
+//! The fingerprint is a hash of the vault's salt, so shares from another
+//! vault, or from before a master password change, are rejected up front.
+
+use sha2::{Digest, Sha256};
+use sharks::{Share, Sharks};
+use zeroize::Zeroize;
+
+use crate::VaultError;
+
+const SHARE_PREFIX: &str = "passman-recovery";
+const SHARE_VERSION: &str = "v1";
+
+/// Short hex digest identifying the salt (and so the key) shares belong to.
+fn fingerprint(salt: &[u8]) -> String {
+    hex::encode(&Sha256::digest(salt)[..4])
+}
+
+/// Split `key` into `shares` strings, any `threshold` of which recover it.
+pub fn split(
+    key: &[u8; 32],
+    salt: &[u8],
+    threshold: u8,
+    shares: u8,
+) -> Result<Vec<String>, VaultError> {
+    if threshold < 2 || shares < threshold {
+        return Err(VaultError::Recovery(format!(
+            "need 2 <= threshold <= shares, got threshold {threshold} of {shares}"
+        )));
+    }
+    let fingerprint = fingerprint(salt);
+    Ok(Sharks(threshold)
+        .dealer(key)
+        .take(shares as usize)
+        .map(|share| {
+            let mut bytes = Vec::from(&share);
+            let encoded = format!(
+                "{SHARE_PREFIX}:{SHARE_VERSION}:{threshold}:{fingerprint}:{}",
+                hex::encode(&bytes)
+            );
+            bytes.zeroize();
+            encoded
+        })
+        .collect())
+}
+
+/// Rebuild the key from `shares`, which must belong to the vault with `salt`.
+/// The result still has to be checked against the vault's verification blob.
+pub fn combine(shares: &[String], salt: &[u8]) -> Result<[u8; 32], VaultError> {
+    let expected = fingerprint(salt);
+    let mut threshold = None;
+    let mut parsed: Vec<Share> = Vec::with_capacity(shares.len());
+    let mut seen_x = Vec::with_capacity(shares.len());
+
+    for share in shares {
+        let invalid = || VaultError::Recovery("malformed recovery share".to_string());
+        let parts: Vec<&str> = share.trim().split(':').collect();
+        let [SHARE_PREFIX, SHARE_VERSION, t, fp, data] = parts[..] else {
+            return Err(invalid());
+        };
+        if fp != expected {
+            return Err(VaultError::Recovery(
+                "share belongs to a different vault or an earlier master password".to_string(),
+            ));
+        }
+        let t: u8 = t.parse().ok().filter(|t| *t >= 2).ok_or_else(invalid)?;
+        if *threshold.get_or_insert(t) != t {
+            return Err(VaultError::Recovery(
+                "shares come from different splits".to_string(),
+            ));
+        }
+        let mut bytes = hex::decode(data).map_err(|_| invalid())?;
+        // The first byte is the share's x coordinate; the same share given
+        // twice doesn't count towards the threshold
+        let x = bytes.first().copied();
+        let share = Share::try_from(bytes.as_slice()).map_err(|_| invalid());
+        bytes.zeroize();
+        let share = share?;
+        if !seen_x.contains(&x) {
+            seen_x.push(x);
+            parsed.push(share);
+        }
+    }
+
+    let threshold = threshold.unwrap_or(2);
+    if parsed.len() < threshold as usize {
+        return Err(VaultError::Recovery(format!(
+            "need {threshold} distinct shares, got {}",
+            parsed.len()
+        )));
+    }
+    let mut secret = Sharks(threshold)
+        .recover(parsed.iter())
+        .map_err(|e| VaultError::Recovery(format!("failed to combine shares: {e}")))?;
+    let key = <[u8; 32]>::try_from(secret.as_slice())
+        .map_err(|_| VaultError::Recovery("recovered key has the wrong length".to_string()));
+    secret.zeroize();
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_any_threshold_subset_recovers() {
+        let key = [9u8; 32];
+        let salt = [1u8; 32];
+        let shares = split(&key, &salt, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+        assert!(shares[0].starts_with("passman-recovery:v1:3:"));
+
+        assert_eq!(combine(&shares[..3], &salt).unwrap(), key);
+        assert_eq!(combine(&shares[2..], &salt).unwrap(), key);
+        let picked = [shares[0].clone(), shares[2].clone(), shares[4].clone()];
+        assert_eq!(combine(&picked, &salt).unwrap(), key);
+    }
+
+    #[test]
+    fn test_too_few_or_foreign_shares_fail() {
+        let key = [9u8; 32];
+        let salt = [1u8; 32];
+        let shares = split(&key, &salt, 3, 5).unwrap();
+
+        assert!(combine(&shares[..2], &salt).is_err());
+        let repeated = [shares[0].clone(), shares[0].clone(), shares[1].clone()];
+        assert!(combine(&repeated, &salt).is_err());
+        assert!(combine(&shares[..3], &[2u8; 32]).is_err());
+        assert!(combine(&["not a share".to_string()], &salt).is_err());
+        assert!(split(&key, &salt, 1, 5).is_err());
+        assert!(split(&key, &salt, 4, 3).is_err());
+    }
+}
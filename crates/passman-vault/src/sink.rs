@@ -0,0 +1,131 @@
+//! Destinations for audit entries.
+//!
+//! Every entry the vault logs is handed to each configured `AuditSink` in
+//! turn: the JSONL file (always first, and the one `read_audit` reads back),
+//! webhooks, and optionally syslog or anything a caller registers with
+//! `Vault::add_audit_sink` — a database or cloud logger, say.
+
+use passman_types::AuditEntry;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use crate::{audit, VaultError};
+
+/// Somewhere audit entries are written. Implement with
+/// `#[async_trait::async_trait]`.
+///
+/// Sinks should return promptly: the vault may be holding its lock while
+/// they run, so slow deliveries belong in a spawned task.
+#[async_trait::async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn write(&self, entry: &AuditEntry) -> Result<(), VaultError>;
+
+    /// Record several entries in order. Sinks that can batch override this.
+    async fn write_all(&self, entries: &[AuditEntry]) -> Result<(), VaultError> {
+        for entry in entries {
+            self.write(entry).await?;
+        }
+        Ok(())
+    }
+}
+
+/// The JSONL audit log.
+pub struct FileSink {
+    path: RwLock<PathBuf>,
+}
+
+impl FileSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path: RwLock::new(path),
+        }
+    }
+
+    /// Write to `path` from now on (used when switching profiles).
+    pub fn set_path(&self, path: PathBuf) {
+        *self.path.write().unwrap() = path;
+    }
+
+    fn path(&self) -> PathBuf {
+        self.path.read().unwrap().clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSink for FileSink {
+    async fn write(&self, entry: &AuditEntry) -> Result<(), VaultError> {
+        audit::append_entry(&self.path(), entry)
+    }
+
+    /// One append for the whole batch.
+    async fn write_all(&self, entries: &[AuditEntry]) -> Result<(), VaultError> {
+        audit::append_entries(&self.path(), entries)
+    }
+}
+
+/// Mirrors entries to the local syslog daemon in RFC 5424 format, with the
+/// entry's fields as structured data.
+#[cfg(unix)]
+pub struct SyslogSink {
+    logger: std::sync::Mutex<syslog::Logger<syslog::LoggerBackend, syslog::Formatter5424>>,
+}
+
+/// Private enterprise number used for the structured-data ID. 32473 is
+/// reserved for documentation and examples (RFC 5612).
+#[cfg(unix)]
+const SYSLOG_SD_ID: &str = "passman@32473";
+
+#[cfg(unix)]
+impl SyslogSink {
+    /// Connect to the local syslog socket, logging under the `auth` facility.
+    pub fn connect() -> Result<Self, VaultError> {
+        let formatter = syslog::Formatter5424 {
+            facility: syslog::Facility::LOG_AUTH,
+            hostname: None,
+            process: "passman".into(),
+            pid: std::process::id(),
+        };
+        let logger = syslog::unix(formatter)
+            .map_err(|e| VaultError::Io(format!("failed to connect to syslog: {e}")))?;
+        Ok(Self {
+            logger: std::sync::Mutex::new(logger),
+        })
+    }
+}
+
+#[cfg(unix)]
+#[async_trait::async_trait]
+impl AuditSink for SyslogSink {
+    async fn write(&self, entry: &AuditEntry) -> Result<(), VaultError> {
+        let action = serde_json::to_value(&entry.action)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+
+        let mut params = std::collections::BTreeMap::new();
+        params.insert("action".to_string(), action.clone());
+        params.insert("tool".to_string(), entry.tool.clone());
+        params.insert("success".to_string(), entry.success.to_string());
+        if let Some(id) = entry.credential_id {
+            params.insert("credential_id".to_string(), id.to_string());
+        }
+        if let Some(name) = &entry.credential_name {
+            params.insert("credential_name".to_string(), name.clone());
+        }
+        let data = std::collections::BTreeMap::from([(SYSLOG_SD_ID.to_string(), params)]);
+
+        let mut message = format!("{action} via {}", entry.tool);
+        if let Some(details) = &entry.details {
+            message.push_str(": ");
+            message.push_str(details);
+        }
+
+        let mut logger = self.logger.lock().unwrap();
+        let result = if entry.success {
+            logger.info((0, data, message))
+        } else {
+            logger.warning((0, data, message))
+        };
+        result.map_err(|e| VaultError::Io(format!("failed to write to syslog: {e}")))
+    }
+}
@@ -0,0 +1,346 @@
+//! Import hosts from an OpenSSH client config (`~/.ssh/config`).
+//!
+//! Only the keywords needed for an `SshKey` credential are read: `HostName`,
+//! `User`, `Port` and `IdentityFile`. As in `ssh`, every block whose
+//! patterns match a host applies, and the first value seen for a keyword
+//! wins, so `Host *` at the end of the file fills in defaults. Wildcard and
+//! negated patterns are never imported as hosts, and `Match` blocks are
+//! skipped.
+
+use passman_types::{CredentialKind, CredentialSecret, Environment};
+use std::path::{Path, PathBuf};
+
+use crate::NewCredential;
+
+/// Port used when neither the host nor `Host *` sets one.
+pub const DEFAULT_PORT: u16 = 22;
+
+/// Keys `ssh` tries, in order, when a host has no `IdentityFile`.
+pub const DEFAULT_IDENTITY_FILES: &[&str] = &["id_rsa", "id_ecdsa", "id_ed25519"];
+
+/// Tag put on every imported credential.
+pub const IMPORT_TAG: &str = "ssh-config";
+
+/// Settings for one `Host` alias.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SshHost {
+    pub alias: String,
+    pub host_name: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub identity_files: Vec<String>,
+}
+
+impl SshHost {
+    fn set(&mut self, keyword: &str, value: &str) {
+        match keyword {
+            "hostname" if self.host_name.is_none() => self.host_name = Some(value.to_string()),
+            "user" if self.user.is_none() => self.user = Some(value.to_string()),
+            "port" if self.port.is_none() => self.port = value.parse().ok(),
+            "identityfile" => self.identity_files.push(value.to_string()),
+            _ => {}
+        }
+    }
+}
+
+/// A `Host` block (or the settings before the first one, which apply to
+/// every host), with its lowercased keywords and values in file order.
+struct Block<'a> {
+    patterns: Option<Vec<&'a str>>,
+    settings: Vec<(String, &'a str)>,
+}
+
+impl Block<'_> {
+    fn matches(&self, alias: &str) -> bool {
+        let Some(patterns) = &self.patterns else {
+            return true;
+        };
+        let mut matched = false;
+        for pattern in patterns {
+            match pattern.strip_prefix('!') {
+                Some(negated) if glob_match(negated, alias) => return false,
+                Some(_) => {}
+                None => matched |= glob_match(pattern, alias),
+            }
+        }
+        matched
+    }
+}
+
+/// Parse the concrete (non-wildcard) hosts out of an ssh config, in file
+/// order, with every matching block applied.
+pub fn parse(contents: &str) -> Vec<SshHost> {
+    let mut blocks = vec![Block {
+        patterns: None,
+        settings: Vec::new(),
+    }];
+    let mut in_match = false;
+    for line in contents.lines() {
+        let Some((keyword, value)) = split_line(line) else {
+            continue;
+        };
+        match keyword.as_str() {
+            "host" => {
+                in_match = false;
+                blocks.push(Block {
+                    patterns: Some(value.split_whitespace().collect()),
+                    settings: Vec::new(),
+                });
+            }
+            "match" => in_match = true,
+            _ if in_match => {}
+            _ => blocks
+                .last_mut()
+                .expect("preamble block")
+                .settings
+                .push((keyword, value)),
+        }
+    }
+
+    let mut aliases: Vec<&str> = Vec::new();
+    for block in &blocks {
+        for pattern in block.patterns.iter().flatten() {
+            if !pattern.contains(['*', '?', '!']) && !aliases.contains(pattern) {
+                aliases.push(pattern);
+            }
+        }
+    }
+
+    aliases
+        .into_iter()
+        .map(|alias| {
+            let mut host = SshHost {
+                alias: alias.to_string(),
+                ..SshHost::default()
+            };
+            for block in blocks.iter().filter(|b| b.matches(alias)) {
+                for (keyword, value) in &block.settings {
+                    host.set(keyword, value);
+                }
+            }
+            host
+        })
+        .collect()
+}
+
+/// Match `text` against an ssh pattern, where `*` is any run of characters
+/// and `?` is any single character.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Split `Keyword value` or `Keyword=value` into a lowercased keyword and
+/// its value, unquoted. `None` for blank lines and comments.
+fn split_line(line: &str) -> Option<(String, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let split = line.find(|c: char| c.is_whitespace() || c == '=')?;
+    let keyword = line[..split].to_ascii_lowercase();
+    let value = line[split..]
+        .trim_start_matches(|c: char| c.is_whitespace())
+        .trim_start_matches('=')
+        .trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value);
+    Some((keyword, value))
+}
+
+/// Expand `~`, `%d` (home), `%u` (local user), `%h` (host name) and `%r`
+/// (remote user) in an `IdentityFile` path. Relative paths are taken
+/// relative to `keys_dir`.
+fn expand_identity_file(
+    path: &str,
+    host: &str,
+    user: &str,
+    local_user: &str,
+    home: &Path,
+    keys_dir: &Path,
+) -> PathBuf {
+    let home_str = home.to_string_lossy();
+    let mut expanded = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('d') => expanded.push_str(&home_str),
+            Some('u') => expanded.push_str(local_user),
+            Some('h') => expanded.push_str(host),
+            Some('r') => expanded.push_str(user),
+            Some('%') => expanded.push('%'),
+            Some(other) => {
+                expanded.push('%');
+                expanded.push(other);
+            }
+            None => expanded.push('%'),
+        }
+    }
+
+    let path = if expanded == "~" {
+        home.to_path_buf()
+    } else if let Some(rest) = expanded.strip_prefix("~/") {
+        home.join(rest)
+    } else {
+        PathBuf::from(expanded)
+    };
+    if path.is_relative() {
+        keys_dir.join(path)
+    } else {
+        path
+    }
+}
+
+/// Build an `SshKey` credential for each host whose private key can be
+/// read, named after the host alias. Hosts without a readable key are
+/// skipped. `local_user` is the default login, as for `ssh`.
+pub fn to_credentials(
+    hosts: &[SshHost],
+    keys_dir: &Path,
+    home: &Path,
+    local_user: &str,
+) -> Vec<NewCredential> {
+    hosts
+        .iter()
+        .filter_map(|host| {
+            let host_name = host.host_name.as_deref().unwrap_or(&host.alias);
+            let user = host.user.as_deref().unwrap_or(local_user);
+            let candidates: Vec<PathBuf> = if host.identity_files.is_empty() {
+                DEFAULT_IDENTITY_FILES
+                    .iter()
+                    .map(|name| keys_dir.join(name))
+                    .collect()
+            } else {
+                host.identity_files
+                    .iter()
+                    .map(|f| expand_identity_file(f, host_name, user, local_user, home, keys_dir))
+                    .collect()
+            };
+            let private_key = candidates
+                .iter()
+                .find_map(|path| std::fs::read_to_string(path).ok())?;
+
+            Some(NewCredential {
+                name: host.alias.clone(),
+                kind: CredentialKind::SshKey,
+                environment: Environment::Development,
+                tags: vec![IMPORT_TAG.to_string()],
+                notes: None,
+                secret: CredentialSecret::SshKey {
+                    username: user.to_string(),
+                    host: host_name.to_string(),
+                    port: host.port.unwrap_or(DEFAULT_PORT),
+                    private_key,
+                    passphrase: None,
+                },
+            })
+        })
+        .collect()
+}
+
+/// The login `ssh` uses when a host sets no `User`.
+pub fn local_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "root".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\
+# Personal hosts
+Host web web-alias
+    HostName web.example.com
+    User deploy
+    Port 2222
+    IdentityFile ~/.ssh/web_key
+
+Host db
+    HostName=10.0.0.5
+    IdentityFile \"db key\"
+
+Host *.internal !bastion
+    User nobody
+
+Host *
+    User admin
+    Port 2200
+    ForwardAgent yes
+
+Match host db
+    User ignored
+";
+
+    #[test]
+    fn test_parse_hosts_and_apply_wildcard_defaults() {
+        let hosts = parse(CONFIG);
+        let aliases: Vec<_> = hosts.iter().map(|h| h.alias.as_str()).collect();
+        assert_eq!(aliases, ["web", "web-alias", "db"]);
+
+        assert_eq!(hosts[0].host_name.as_deref(), Some("web.example.com"));
+        assert_eq!(hosts[0].user.as_deref(), Some("deploy"));
+        assert_eq!(hosts[0].port, Some(2222));
+        assert_eq!(hosts[0].identity_files, ["~/.ssh/web_key"]);
+        assert_eq!(
+            hosts[1],
+            SshHost {
+                alias: "web-alias".into(),
+                ..hosts[0].clone()
+            }
+        );
+
+        assert_eq!(hosts[2].host_name.as_deref(), Some("10.0.0.5"));
+        assert_eq!(hosts[2].user.as_deref(), Some("admin"));
+        assert_eq!(hosts[2].port, Some(2200));
+        assert_eq!(hosts[2].identity_files, ["db key"]);
+    }
+
+    #[test]
+    fn test_first_value_wins_and_keywords_are_case_insensitive() {
+        let hosts = parse("HOST a\n  user first\n  USER second\n  hostname=a.example\n");
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].user.as_deref(), Some("first"));
+        assert_eq!(hosts[0].host_name.as_deref(), Some("a.example"));
+        assert_eq!(hosts[0].port, None);
+    }
+
+    #[test]
+    fn test_identity_file_expansion() {
+        let home = Path::new("/home/me");
+        let keys = Path::new("/keys");
+        let expand = |p| expand_identity_file(p, "db.example", "deploy", "me", home, keys);
+        assert_eq!(expand("~/.ssh/id"), PathBuf::from("/home/me/.ssh/id"));
+        assert_eq!(
+            expand("%d/.ssh/%r@%h"),
+            PathBuf::from("/home/me/.ssh/deploy@db.example")
+        );
+        assert_eq!(expand("id_%u"), PathBuf::from("/keys/id_me"));
+        assert_eq!(expand("/abs/key"), PathBuf::from("/abs/key"));
+    }
+}
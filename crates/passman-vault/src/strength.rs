@@ -0,0 +1,318 @@
+//! Offline password strength estimation.
+//!
+//! A small zxcvbn-style heuristic: the password is split into the cheapest
+//! sequence of patterns an attacker would try (common passwords, dictionary
+//! words, keyboard/alphabet sequences, repeats, then brute force), and the
+//! guess counts of those patterns are multiplied together.
+
+use serde::Serialize;
+
+/// Result of [`estimate`].
+#[derive(Debug, Clone, Serialize)]
+pub struct StrengthReport {
+    /// 0 (trivially guessable) to 4 (very strong).
+    pub score: u8,
+    /// Estimated number of guesses needed to find the password.
+    pub guesses: f64,
+    /// Human-readable reasons the password is weaker than it looks.
+    pub warnings: Vec<String>,
+}
+
+const MIN_LENGTH: usize = 8;
+
+/// Passwords attackers try first, most common first.
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "password",
+    "12345678",
+    "qwerty",
+    "123456789",
+    "12345",
+    "1234",
+    "111111",
+    "1234567",
+    "dragon",
+    "123123",
+    "baseball",
+    "abc123",
+    "football",
+    "monkey",
+    "letmein",
+    "696969",
+    "shadow",
+    "master",
+    "666666",
+    "qwertyuiop",
+    "123321",
+    "mustang",
+    "1234567890",
+    "michael",
+    "654321",
+    "superman",
+    "1qaz2wsx",
+    "7777777",
+    "121212",
+    "000000",
+    "qazwsx",
+    "123qwe",
+    "killer",
+    "trustno1",
+    "jordan",
+    "jennifer",
+    "zxcvbnm",
+    "asdfgh",
+    "hunter",
+    "buster",
+    "soccer",
+    "harley",
+    "batman",
+    "andrew",
+    "tigger",
+    "sunshine",
+    "iloveyou",
+    "charlie",
+    "robert",
+    "thomas",
+    "hockey",
+    "ranger",
+    "daniel",
+    "starwars",
+    "klaster",
+    "112233",
+    "george",
+    "computer",
+    "michelle",
+    "jessica",
+    "pepper",
+    "passw0rd",
+    "admin",
+    "welcome",
+    "login",
+    "princess",
+    "qwerty123",
+    "password1",
+    "password123",
+    "changeme",
+];
+
+/// Common English words and password fragments.
+const DICTIONARY: &[&str] = &[
+    "password", "secret", "admin", "welcome", "login", "letmein", "dragon", "monkey", "master",
+    "shadow", "sunshine", "princess", "football", "baseball", "soccer", "hockey", "summer",
+    "winter", "spring", "autumn", "love", "hello", "money", "freedom", "flower", "computer",
+    "internet", "server", "database", "access", "secure", "company", "office", "house", "family",
+    "friend", "orange", "banana", "apple", "cheese", "coffee", "chicken", "purple", "yellow",
+    "silver", "golden", "diamond", "tiger", "eagle", "falcon", "killer", "angel", "heaven",
+    "thunder", "ginger", "pepper", "cookie", "matrix", "ninja", "pirate", "rocket", "wizard",
+    "magic", "music", "guitar", "player", "gamer", "hunter", "user", "test", "guest", "root",
+    "default", "system", "manager", "account", "pass", "word", "qwerty", "change", "please",
+    "trust", "nobody", "batman", "superman",
+];
+
+/// Rows and runs that people type as "random" characters.
+const SEQUENCES: &[&str] = &[
+    "abcdefghijklmnopqrstuvwxyz",
+    "0123456789",
+    "qwertyuiop",
+    "asdfghjkl",
+    "zxcvbnm",
+    "1qaz2wsx3edc",
+];
+
+/// Estimate how hard `password` is to guess. Pure and offline.
+pub fn estimate(password: &str) -> StrengthReport {
+    let chars: Vec<char> = password.chars().collect();
+    let mut warnings = Vec::new();
+
+    if chars.is_empty() {
+        return StrengthReport {
+            score: 0,
+            guesses: 1.0,
+            warnings: vec!["password is empty".to_string()],
+        };
+    }
+    if chars.len() < MIN_LENGTH {
+        warnings.push(format!("too short; use at least {MIN_LENGTH} characters"));
+    }
+
+    let lower = password.to_lowercase();
+    let normalized = normalize(password);
+    let common = COMMON_PASSWORDS
+        .iter()
+        .position(|p| *p == lower || *p == normalized);
+    let log10_guesses = if let Some(rank) = common {
+        warnings.push("this is a very common password".to_string());
+        ((rank + 1) as f64).log10()
+    } else {
+        cheapest_split(
+            &chars,
+            &normalized.chars().collect::<Vec<_>>(),
+            &mut warnings,
+        )
+    };
+
+    StrengthReport {
+        score: score_for(log10_guesses),
+        guesses: 10f64.powf(log10_guesses),
+        warnings,
+    }
+}
+
+/// Lowercase and undo common character substitutions (`p@ssw0rd`).
+fn normalize(password: &str) -> String {
+    password
+        .chars()
+        .map(|c| match c {
+            '@' | '4' => 'a',
+            '3' => 'e',
+            '1' | '!' => 'i',
+            '0' => 'o',
+            '$' | '5' => 's',
+            '7' => 't',
+            c => c.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+/// Minimum log10(guesses) over all ways of splitting the password into
+/// patterns, by dynamic programming over prefix positions.
+fn cheapest_split(chars: &[char], normalized: &[char], warnings: &mut Vec<String>) -> f64 {
+    let n = chars.len();
+    let pool = (charset_size(chars) as f64).log10();
+
+    // best[i] = cheapest cost of chars[..i], where the last pattern started,
+    // and the warning that pattern carries
+    let mut best: Vec<(f64, usize, Option<&'static str>)> = vec![(f64::INFINITY, 0, None); n + 1];
+    best[0] = (0.0, 0, None);
+
+    for start in 0..n {
+        let base = best[start].0;
+        let mut relax = |end: usize, cost: f64, warning: Option<&'static str>| {
+            if base + cost < best[end].0 {
+                best[end] = (base + cost, start, warning);
+            }
+        };
+
+        // Brute force one character
+        relax(start + 1, pool, None);
+
+        for end in (start + 3)..=n {
+            let segment: String = normalized[start..end].iter().collect();
+            let raw = &chars[start..end];
+
+            if DICTIONARY.contains(&segment.as_str()) {
+                let mut cost = (DICTIONARY.len() as f64).log10();
+                if raw.iter().any(|c| c.is_uppercase()) {
+                    cost += 1.0;
+                }
+                relax(end, cost, Some("contains a common word"));
+            }
+            if is_sequence(raw) {
+                relax(
+                    end,
+                    ((end - start) as f64 * 10.0).log10(),
+                    Some("contains a predictable sequence like 'abc' or '123'"),
+                );
+            }
+            if raw.iter().all(|c| *c == raw[0]) {
+                relax(
+                    end,
+                    pool + ((end - start) as f64).log10(),
+                    Some("contains repeated characters"),
+                );
+            }
+            if is_year(raw) {
+                relax(end, 200f64.log10(), Some("contains a year"));
+            }
+        }
+    }
+
+    // Walk back through the cheapest split to report the patterns it used
+    let mut i = n;
+    while i > 0 {
+        let (_, start, warning) = best[i];
+        if let Some(w) = warning {
+            if !warnings.iter().any(|existing| existing == w) {
+                warnings.push(w.to_string());
+            }
+        }
+        i = start;
+    }
+
+    best[n].0
+}
+
+/// Four digits between 1900 and 2099.
+fn is_year(segment: &[char]) -> bool {
+    segment.len() == 4
+        && segment.iter().all(|c| c.is_ascii_digit())
+        && matches!(segment[..2], ['1', '9'] | ['2', '0'])
+}
+
+/// Forward or backward run of `SEQUENCES`, e.g. `abc`, `321`, `qwer`.
+fn is_sequence(segment: &[char]) -> bool {
+    let s: String = segment.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let reversed: String = s.chars().rev().collect();
+    SEQUENCES
+        .iter()
+        .any(|seq| seq.contains(&s) || seq.contains(&reversed))
+}
+
+fn charset_size(chars: &[char]) -> usize {
+    let mut size = 0;
+    if chars.iter().any(|c| c.is_ascii_lowercase()) {
+        size += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_uppercase()) {
+        size += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_digit()) {
+        size += 10;
+    }
+    if chars.iter().any(|c| c.is_ascii_punctuation() || *c == ' ') {
+        size += 33;
+    }
+    if chars.iter().any(|c| !c.is_ascii()) {
+        size += 100;
+    }
+    size.max(10)
+}
+
+fn score_for(log10_guesses: f64) -> u8 {
+    match log10_guesses {
+        g if g < 3.0 => 0,
+        g if g < 6.0 => 1,
+        g if g < 8.0 => 2,
+        g if g < 10.0 => 3,
+        _ => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_common_password_scores_low() {
+        let report = estimate("password123");
+        assert!(report.score <= 1, "score {}", report.score);
+        assert!(!report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_patterns_are_cheap() {
+        let report = estimate("Sunshine2024abc");
+        assert!(report.score <= 2, "score {}", report.score);
+        assert!(report.warnings.iter().any(|w| w.contains("common word")));
+
+        assert_eq!(estimate("").score, 0);
+        assert!(estimate("aaaaaaaaaaaa").score <= 1);
+    }
+
+    #[test]
+    fn test_random_string_scores_high() {
+        let report = estimate("x7#Qp9!mZr2@Lw4$");
+        assert_eq!(report.score, 4);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+    }
+}
@@ -0,0 +1,55 @@
+//! Per-credential usage counters.
+//!
+//! Proxy tools bump a credential's `use_count` and `last_used_at` on every
+//! successful call. Saving the vault each time would mean a full file write
+//! per request, so uses are applied to the in-memory vault straight away and
+//! only written out by `Vault::flush_usage` (periodically, and on lock).
+//! Until then they are also kept here, so they survive a reload from disk or
+//! a save conflict.
+
+use chrono::{DateTime, Utc};
+use passman_types::VaultFile;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How often a long-running process should call `Vault::flush_usage`.
+pub const USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Uses recorded since the last successful save.
+#[derive(Default)]
+pub struct PendingUsage {
+    uses: Mutex<HashMap<Uuid, (u64, DateTime<Utc>)>>,
+}
+
+impl PendingUsage {
+    pub fn record(&self, id: Uuid, at: DateTime<Utc>) {
+        let mut uses = self.uses.lock().unwrap();
+        let entry = uses.entry(id).or_insert((0, at));
+        entry.0 += 1;
+        entry.1 = at;
+    }
+
+    /// Re-apply unsaved uses to a copy of the vault freshly read from disk.
+    pub fn apply(&self, data: &mut VaultFile) {
+        let uses = self.uses.lock().unwrap();
+        for cred in &mut data.credentials {
+            if let Some((count, at)) = uses.get(&cred.meta.id) {
+                cred.meta.use_count += count;
+                if cred.meta.last_used_at.is_none_or(|last| last < *at) {
+                    cred.meta.last_used_at = Some(*at);
+                }
+            }
+        }
+    }
+
+    /// Forget pending uses once they are on disk (or can't be written).
+    pub fn clear(&self) {
+        self.uses.lock().unwrap().clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.uses.lock().unwrap().is_empty()
+    }
+}
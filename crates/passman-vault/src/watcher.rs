@@ -1,29 +1,64 @@
 //! File watcher for vault auto-reload.
 //!
 //! Watches the vault file and triggers a reload when another process
-//! (GUI or MCP server) writes changes.
+//! (GUI or MCP server) writes changes. Bursts of events (the temp-file write
+//! plus the rename in `save_vault`) are coalesced into one reload, and events
+//! caused by this handle's own writes are ignored. Also watches the lock
+//! epoch file, so locking the vault in one process locks it in the others.
 
-use crate::Vault;
+use crate::{storage, Vault};
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
+/// Quiet period after the last event before reloading.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Events this soon after our own write are assumed to be that write.
+const SELF_WRITE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Which watched file changed.
+enum Change {
+    Vault,
+    LockEpoch,
+}
+
 /// Spawn a background task that watches the vault file and calls `vault.reload()`
-/// whenever it detects a modification. Returns a handle to stop the watcher.
+/// whenever it detects a modification, and locks `vault` when another
+/// process locks the same vault. Returns a handle to stop the watcher.
 pub fn watch_vault(vault: Vault, vault_path: PathBuf) -> WatchHandle {
     let (stop_tx, mut stop_rx) = mpsc::channel::<()>(1);
+    let reloads = Arc::new(AtomicUsize::new(0));
+    let reload_count = reloads.clone();
 
     tokio::spawn(async move {
         let (tx, mut rx) = mpsc::channel(16);
 
+        let file_name = vault_path.file_name().map(|n| n.to_os_string());
+        let epoch_name = storage::lock_epoch_path(&vault_path)
+            .file_name()
+            .map(|n| n.to_os_string());
         let mut watcher = match RecommendedWatcher::new(
             move |res: Result<Event, notify::Error>| {
                 if let Ok(event) = res {
-                    match event.kind {
-                        EventKind::Modify(_) | EventKind::Create(_) => {
-                            let _ = tx.blocking_send(());
-                        }
-                        _ => {}
+                    if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                        return;
+                    }
+                    // The directory also holds the audit log and temp files
+                    let touches = |name: &Option<std::ffi::OsString>| {
+                        event
+                            .paths
+                            .iter()
+                            .any(|p| p.file_name().map(|n| n.to_os_string()) == *name)
+                    };
+                    if touches(&epoch_name) {
+                        let _ = tx.blocking_send(Change::LockEpoch);
+                    }
+                    if touches(&file_name) {
+                        let _ = tx.blocking_send(Change::Vault);
                     }
                 }
             },
@@ -47,13 +82,37 @@ pub fn watch_vault(vault: Vault, vault_path: PathBuf) -> WatchHandle {
 
         loop {
             tokio::select! {
-                Some(()) = rx.recv() => {
-                    // Debounce: drain any queued events
-                    while rx.try_recv().is_ok() {}
+                Some(change) = rx.recv() => {
+                    let mut lock_changed = matches!(change, Change::LockEpoch);
+                    let vault_changed = matches!(change, Change::Vault);
+                    // Debounce: wait until events stop arriving for DEBOUNCE.
+                    // Lock changes are acted on first and without waiting.
+                    loop {
+                        if lock_changed {
+                            lock_changed = false;
+                            if vault.sync_lock_epoch().await {
+                                tracing::info!("Vault locked by another process");
+                            }
+                        }
+                        if !vault_changed {
+                            break;
+                        }
+                        match tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+                            Ok(Some(Change::LockEpoch)) => lock_changed = true,
+                            Ok(Some(Change::Vault)) => {}
+                            _ => break,
+                        }
+                    }
+                    if !vault_changed {
+                        continue;
+                    }
 
-                    // Small delay to let the writing process finish
-                    tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+                    if vault.wrote_within(SELF_WRITE_WINDOW) {
+                        tracing::debug!("Ignoring vault change from our own write");
+                        continue;
+                    }
 
+                    reload_count.fetch_add(1, Ordering::Relaxed);
                     match vault.reload().await {
                         Ok(()) => tracing::info!("Vault reloaded from disk"),
                         Err(e) => tracing::warn!("Vault reload failed: {e}"),
@@ -67,17 +126,105 @@ pub fn watch_vault(vault: Vault, vault_path: PathBuf) -> WatchHandle {
         }
     });
 
-    WatchHandle { stop_tx }
+    WatchHandle { stop_tx, reloads }
 }
 
 /// Handle to stop the vault file watcher.
 pub struct WatchHandle {
     stop_tx: mpsc::Sender<()>,
+    reloads: Arc<AtomicUsize>,
 }
 
 impl WatchHandle {
+    /// Number of reloads the watcher has triggered so far.
+    pub fn reload_count(&self) -> usize {
+        self.reloads.load(Ordering::Relaxed)
+    }
+
     /// Stop the watcher.
     pub async fn stop(self) {
         let _ = self.stop_tx.send(()).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use passman_types::{CredentialKind, CredentialSecret, Environment};
+
+    async fn store(vault: &Vault, name: &str) {
+        vault
+            .store_credential(
+                name.into(),
+                CredentialKind::Password,
+                Environment::Local,
+                vec![],
+                None,
+                &CredentialSecret::Password {
+                    username: "user".into(),
+                    password: "watcher-test-secret".into(),
+                    url: None,
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rapid_saves_reload_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.json");
+        let audit_path = dir.path().join("audit.jsonl");
+
+        // Writer simulates another process; the watched handle is separate
+        let writer = Vault::new(vault_path.clone(), audit_path.clone());
+        writer.create("watcher-test-pw", false).await.unwrap();
+        let watched = Vault::new(vault_path.clone(), audit_path);
+        watched.unlock("watcher-test-pw").await.unwrap();
+
+        let handle = watch_vault(watched.clone(), vault_path);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        for i in 0..5 {
+            store(&writer, &format!("cred-{i}")).await;
+        }
+        tokio::time::sleep(DEBOUNCE * 4).await;
+
+        assert_eq!(handle.reload_count(), 1);
+        assert_eq!(watched.credential_count().await.unwrap(), 5);
+
+        // Our own writes don't trigger a reload
+        store(&watched, "own-write").await;
+        tokio::time::sleep(DEBOUNCE * 4).await;
+        assert_eq!(handle.reload_count(), 1);
+
+        handle.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_lock_in_one_process_locks_the_other() {
+        let dir = tempfile::tempdir().unwrap();
+        let vault_path = dir.path().join("vault.json");
+        let audit_path = dir.path().join("audit.jsonl");
+
+        let gui = Vault::new(vault_path.clone(), audit_path.clone());
+        gui.create("watcher-test-pw", false).await.unwrap();
+        let server = Vault::new(vault_path.clone(), audit_path);
+        server.unlock("watcher-test-pw").await.unwrap();
+
+        let handle = watch_vault(server.clone(), vault_path);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        gui.lock().await;
+        tokio::time::sleep(DEBOUNCE * 2).await;
+        assert!(!server.is_unlocked().await);
+
+        // Unlocking again after the lock sticks
+        server.unlock("watcher-test-pw").await.unwrap();
+        tokio::time::sleep(DEBOUNCE * 2).await;
+        assert!(server.is_unlocked().await);
+        assert_eq!(handle.reload_count(), 0);
+
+        handle.stop().await;
+    }
+}